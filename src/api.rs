@@ -0,0 +1,561 @@
+//! Typed, reader/writer-generic entry points to qsv commands.
+//!
+//! Each function takes the command's options as a struct, reads CSV from any
+//! `io::Read` and writes the command's output to any `io::Write`, e.g.
+//!
+//! ```no_run
+//! use qsv::api::{self, SelectOptions};
+//!
+//! let input = "name,age,city\nAlice,30,Paris\nBob,25,Oslo\n".as_bytes();
+//! let mut output = Vec::new();
+//! api::select(input, &mut output, &SelectOptions::new("name,city"))?;
+//! # Ok::<(), qsv::CliError>(())
+//! ```
+//!
+//! The commands run in the calling process with the same code as the qsv binary,
+//! so calls are serialized by a global lock as the commands keep per-run state.
+//! The input is streamed to the command & its output to the writer through bounded
+//! in-process pipes, except for `stats` & `validate`: they scan their input more than
+//! once & write their cache/report files next to it, so their input is first copied
+//! to a temporary file, as the qsv binary does when they read from stdin.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    thread,
+};
+
+use crate::{
+    CliResult,
+    clitypes::{self, ErrorCode},
+    cmd, config,
+    config::{PipeReader, PipeWriter},
+    util,
+};
+
+// the commands keep per-run state in statics, so only one command can run at a time
+static API_LOCK: Mutex<()> = Mutex::new(());
+
+// the number of chunks buffered by each of the stdin & stdout pipes of a command
+const PIPE_CAPACITY: usize = 16;
+
+type RunFn = fn(&[&str]) -> CliResult<()>;
+
+/// Options common to all the commands reading CSV
+#[derive(Clone, Debug, Default)]
+pub struct CsvOptions {
+    /// the field delimiter of the input. Defaults to a comma.
+    pub delimiter:  Option<char>,
+    /// the first row of the input is not a header row
+    pub no_headers: bool,
+}
+
+impl CsvOptions {
+    fn push_args(&self, args: &mut Vec<String>) {
+        if let Some(delimiter) = self.delimiter {
+            args.push("--delimiter".to_string());
+            args.push(delimiter.to_string());
+        }
+        push_flag(args, "--no-headers", self.no_headers);
+    }
+}
+
+/// Options of `qsv stats`
+#[derive(Clone, Debug, Default)]
+pub struct StatsOptions {
+    pub csv:             CsvOptions,
+    /// the columns to compute stats for, in `qsv select` syntax
+    pub select:          Option<String>,
+    /// compute all the statistics available, except the dataset stats
+    pub everything:      bool,
+    /// infer data types only
+    pub typesonly:       bool,
+    pub infer_boolean:   bool,
+    pub mode:            bool,
+    pub cardinality:     bool,
+    pub median:          bool,
+    pub mad:             bool,
+    pub quartiles:       bool,
+    /// include NULLs in the population size for computing mean & stddev
+    pub nulls:           bool,
+    pub infer_dates:     bool,
+    /// the column name patterns to infer dates for. Defaults to the qsv default.
+    pub dates_whitelist: Option<String>,
+    pub prefer_dmy:      bool,
+    /// round statistics to this many decimal places
+    pub round:           Option<u32>,
+    /// the number of jobs to run in parallel
+    pub jobs:            Option<usize>,
+}
+
+impl StatsOptions {
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        push_opt(&mut args, "--select", self.select.as_ref());
+        push_flag(&mut args, "--everything", self.everything);
+        push_flag(&mut args, "--typesonly", self.typesonly);
+        push_flag(&mut args, "--infer-boolean", self.infer_boolean);
+        push_flag(&mut args, "--mode", self.mode);
+        push_flag(&mut args, "--cardinality", self.cardinality);
+        push_flag(&mut args, "--median", self.median);
+        push_flag(&mut args, "--mad", self.mad);
+        push_flag(&mut args, "--quartiles", self.quartiles);
+        push_flag(&mut args, "--nulls", self.nulls);
+        push_flag(&mut args, "--infer-dates", self.infer_dates);
+        push_opt(
+            &mut args,
+            "--dates-whitelist",
+            self.dates_whitelist.as_ref(),
+        );
+        push_flag(&mut args, "--prefer-dmy", self.prefer_dmy);
+        push_opt(&mut args, "--round", self.round.as_ref());
+        push_opt(&mut args, "--jobs", self.jobs.as_ref());
+        // the input is a temporary file, so don't bother caching its stats
+        push_opt(&mut args, "--cache-threshold", Some(&0));
+        self.csv.push_args(&mut args);
+        args
+    }
+}
+
+/// Options of `qsv frequency`
+#[derive(Clone, Debug, Default)]
+pub struct FrequencyOptions {
+    pub csv:         CsvOptions,
+    /// the columns to compute frequencies for, in `qsv select` syntax
+    pub select:      Option<String>,
+    /// limit the frequency tables to the N most common values. 0 for no limit.
+    pub limit:       Option<usize>,
+    /// the limit for columns with all unique values
+    pub unq_limit:   Option<usize>,
+    /// sort the frequency tables in ascending order by count
+    pub asc:         bool,
+    pub no_nulls:    bool,
+    pub ignore_case: bool,
+}
+
+impl FrequencyOptions {
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        push_opt(&mut args, "--select", self.select.as_ref());
+        push_opt(&mut args, "--limit", self.limit.as_ref());
+        push_opt(&mut args, "--unq-limit", self.unq_limit.as_ref());
+        push_flag(&mut args, "--asc", self.asc);
+        push_flag(&mut args, "--no-nulls", self.no_nulls);
+        push_flag(&mut args, "--ignore-case", self.ignore_case);
+        self.csv.push_args(&mut args);
+        args
+    }
+}
+
+/// Options of `qsv select`
+#[derive(Clone, Debug, Default)]
+pub struct SelectOptions {
+    pub csv:       CsvOptions,
+    /// the columns to select, in `qsv select` syntax
+    pub selection: String,
+    /// sort the selected columns lexicographically
+    pub sort:      bool,
+}
+
+impl SelectOptions {
+    pub fn new(selection: &str) -> Self {
+        Self {
+            selection: selection.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        push_flag(&mut args, "--sort", self.sort);
+        self.csv.push_args(&mut args);
+        args.push("--".to_string());
+        args.push(self.selection.clone());
+        args
+    }
+}
+
+/// Options of `qsv search`
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    pub csv:          CsvOptions,
+    pub regex:        String,
+    /// treat the regex as a literal string
+    pub literal:      bool,
+    /// the columns to search, in `qsv select` syntax
+    pub select:       Option<String>,
+    pub ignore_case:  bool,
+    pub invert_match: bool,
+    pub unicode:      bool,
+    /// instead of filtering, add a column with this name flagging the matching rows
+    pub flag:         Option<String>,
+}
+
+impl SearchOptions {
+    pub fn new(regex: &str) -> Self {
+        Self {
+            regex: regex.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn to_args(&self) -> Vec<String> {
+        // no matches is not an error for the API, it's just an empty result
+        let mut args = vec!["--quiet".to_string(), "--not-one".to_string()];
+        push_flag(&mut args, "--literal", self.literal);
+        push_opt(&mut args, "--select", self.select.as_ref());
+        push_flag(&mut args, "--ignore-case", self.ignore_case);
+        push_flag(&mut args, "--invert-match", self.invert_match);
+        push_flag(&mut args, "--unicode", self.unicode);
+        push_opt(&mut args, "--flag", self.flag.as_ref());
+        self.csv.push_args(&mut args);
+        args.push(self.regex.clone());
+        args
+    }
+}
+
+/// Options of `qsv dedup`
+#[derive(Clone, Debug, Default)]
+pub struct DedupOptions {
+    pub csv:         CsvOptions,
+    /// the columns to dedup on, in `qsv select` syntax. Defaults to all columns.
+    pub select:      Option<String>,
+    pub numeric:     bool,
+    pub ignore_case: bool,
+    /// the input is already sorted, so it can be streamed
+    pub sorted:      bool,
+}
+
+impl DedupOptions {
+    fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["--quiet".to_string()];
+        push_opt(&mut args, "--select", self.select.as_ref());
+        push_flag(&mut args, "--numeric", self.numeric);
+        push_flag(&mut args, "--ignore-case", self.ignore_case);
+        push_flag(&mut args, "--sorted", self.sorted);
+        self.csv.push_args(&mut args);
+        args
+    }
+}
+
+/// Options of `qsv sort`
+#[derive(Clone, Debug, Default)]
+pub struct SortOptions {
+    pub csv:         CsvOptions,
+    /// the columns to sort on, in `qsv select` syntax. Defaults to all columns.
+    pub select:      Option<String>,
+    pub numeric:     bool,
+    pub reverse:     bool,
+    pub ignore_case: bool,
+    /// drop identical consecutive rows
+    pub unique:      bool,
+    /// the number of jobs to run in parallel
+    pub jobs:        Option<usize>,
+}
+
+impl SortOptions {
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        push_opt(&mut args, "--select", self.select.as_ref());
+        push_flag(&mut args, "--numeric", self.numeric);
+        push_flag(&mut args, "--reverse", self.reverse);
+        push_flag(&mut args, "--ignore-case", self.ignore_case);
+        push_flag(&mut args, "--unique", self.unique);
+        push_opt(&mut args, "--jobs", self.jobs.as_ref());
+        self.csv.push_args(&mut args);
+        args
+    }
+}
+
+/// Options of `qsv validate`
+#[derive(Clone, Debug, Default)]
+pub struct ValidateOptions {
+    pub csv:       CsvOptions,
+    /// the JSON Schema (a file or URL) to validate against.
+    /// If not set, the input is only checked for RFC 4180 compliance & UTF-8 encoding.
+    pub schema:    Option<String>,
+    /// trim leading and trailing whitespace from fields before validating
+    pub trim:      bool,
+    /// stop on the first error
+    pub fail_fast: bool,
+    /// the number of jobs to run in parallel
+    pub jobs:      Option<usize>,
+}
+
+impl ValidateOptions {
+    fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["--quiet".to_string()];
+        push_flag(&mut args, "--trim", self.trim);
+        push_flag(&mut args, "--fail-fast", self.fail_fast);
+        push_opt(&mut args, "--jobs", self.jobs.as_ref());
+        self.csv.push_args(&mut args);
+        args
+    }
+}
+
+/// A row that failed validation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationFailure {
+    /// the 1-based data row number, if known
+    pub row_number: Option<u64>,
+    /// the field that failed validation, or `<RECORD>` if it's the whole record
+    pub field:      String,
+    pub error:      String,
+}
+
+/// The result of `validate`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub valid:   bool,
+    /// the validation summary message, if the input is invalid
+    pub message: Option<String>,
+    pub errors:  Vec<ValidationFailure>,
+}
+
+/// Compute summary statistics of the input, writing the stats CSV to `output`.
+/// The input is first copied to a temporary file, as `stats` scans it more than once.
+pub fn stats<R: Read, W: Write + Send>(
+    input: R,
+    output: W,
+    options: &StatsOptions,
+) -> CliResult<()> {
+    let workdir = tempfile::tempdir()?;
+    let input_path = spool_input(input, workdir.path())?;
+    let mut args = options.to_args();
+    args.push(input_path.display().to_string());
+    run_command("stats", cmd::stats::run, &args, io::empty(), output)
+}
+
+/// Compute frequency tables of the input, writing them as CSV to `output`.
+///
+/// The input is read once, so the stats cache is not used to detect all-unique columns.
+pub fn frequency<R: Read + Send, W: Write + Send>(
+    input: R,
+    output: W,
+    options: &FrequencyOptions,
+) -> CliResult<()> {
+    run_command(
+        "frequency",
+        cmd::frequency::run,
+        &options.to_args(),
+        input,
+        output,
+    )
+}
+
+/// Select columns of the input.
+pub fn select<R: Read + Send, W: Write + Send>(
+    input: R,
+    output: W,
+    options: &SelectOptions,
+) -> CliResult<()> {
+    run_command(
+        "select",
+        cmd::select::run,
+        &options.to_args(),
+        input,
+        output,
+    )
+}
+
+/// Keep the rows of the input matching a regex. No matches is not an error.
+pub fn search<R: Read + Send, W: Write + Send>(
+    input: R,
+    output: W,
+    options: &SearchOptions,
+) -> CliResult<()> {
+    run_command(
+        "search",
+        cmd::search::run,
+        &options.to_args(),
+        input,
+        output,
+    )
+}
+
+/// Remove the duplicate rows of the input.
+pub fn dedup<R: Read + Send, W: Write + Send>(
+    input: R,
+    output: W,
+    options: &DedupOptions,
+) -> CliResult<()> {
+    run_command("dedup", cmd::dedup::run, &options.to_args(), input, output)
+}
+
+/// Sort the rows of the input.
+pub fn sort<R: Read + Send, W: Write + Send>(
+    input: R,
+    output: W,
+    options: &SortOptions,
+) -> CliResult<()> {
+    run_command("sort", cmd::sort::run, &options.to_args(), input, output)
+}
+
+/// Validate the input against a JSON Schema, or check that it's RFC 4180 compliant
+/// & UTF-8 encoded if no schema is given.
+///
+/// The input is first copied to a temporary file, next to which `validate` writes its
+/// error report.
+///
+/// An invalid input is not an error: the failures are returned in the report.
+/// Only errors preventing validation (e.g. an invalid schema) are returned as `Err`.
+pub fn validate<R: Read>(input: R, options: &ValidateOptions) -> CliResult<ValidationReport> {
+    let workdir = tempfile::tempdir()?;
+    let input_path = spool_input(input, workdir.path())?;
+    let mut args = options.to_args();
+    args.push(input_path.display().to_string());
+    if let Some(schema) = &options.schema {
+        args.push(schema.clone());
+    }
+
+    let _lock = API_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let result = run_locked(
+        "validate",
+        cmd::validate::run,
+        &args,
+        io::empty(),
+        io::sink(),
+    );
+    let Err(err) = result else {
+        return Ok(ValidationReport {
+            valid: true,
+            ..Default::default()
+        });
+    };
+
+    let errors = match clitypes::error_context_code() {
+        Some(ErrorCode::SchemaValidationFailed) => {
+            let mut report = input_path.into_os_string();
+            report.push(".validation-errors.tsv");
+            read_validation_errors(Path::new(&report))?
+        },
+        Some(ErrorCode::Rfc4180ValidationFailed | ErrorCode::Utf8ValidationFailed) => {
+            vec![ValidationFailure {
+                row_number: clitypes::error_context_row(),
+                field:      "<RECORD>".to_string(),
+                error:      err.to_string(),
+            }]
+        },
+        _ => return Err(err),
+    };
+    Ok(ValidationReport {
+        valid: false,
+        message: Some(err.to_string()),
+        errors,
+    })
+}
+
+/// read the errors of the `<input>.validation-errors.tsv` report
+fn read_validation_errors(path: &Path) -> CliResult<Vec<ValidationFailure>> {
+    let report = BufReader::new(fs::File::open(path)?);
+    let mut errors = Vec::new();
+    // skip the "row_number<TAB>field<TAB>error" header
+    for line in report.lines().skip(1) {
+        let line = line?;
+        let mut parts = line.splitn(3, '\t');
+        let row_number = parts.next().and_then(|row| row.parse().ok());
+        errors.push(ValidationFailure {
+            row_number,
+            field: parts.next().unwrap_or_default().to_string(),
+            error: parts.next().unwrap_or_default().to_string(),
+        });
+    }
+    Ok(errors)
+}
+
+/// run a command with `input` as its stdin, streaming its stdout to `output`
+fn run_command<R: Read + Send, W: Write + Send>(
+    name: &str,
+    run: RunFn,
+    args: &[String],
+    input: R,
+    output: W,
+) -> CliResult<()> {
+    let _lock = API_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    run_locked(name, run, args, input, output)
+}
+
+/// run a command with its stdio redirected to pipes, copied from `input` & to `output`
+/// by scoped threads, so neither is held in memory. API_LOCK must be held.
+fn run_locked<R: Read + Send, W: Write + Send>(
+    name: &str,
+    run: RunFn,
+    args: &[String],
+    mut input: R,
+    mut output: W,
+) -> CliResult<()> {
+    let mut argv = vec!["qsv", name];
+    argv.extend(args.iter().map(String::as_str));
+
+    util::reset_run_state();
+    let (mut stdin_writer, stdin_reader) = config::pipe(PIPE_CAPACITY);
+    let (stdout_writer, mut stdout_reader) = config::pipe(PIPE_CAPACITY);
+    thread::scope(|scope| {
+        let input_copier = scope.spawn(move || io::copy(&mut input, &mut stdin_writer));
+        let output_copier = scope.spawn(move || {
+            io::copy(&mut stdout_reader, &mut output)?;
+            output.flush()
+        });
+
+        let result = {
+            let _pipes = StdioPipes::set(stdin_reader, stdout_writer);
+            run(&argv)
+        };
+
+        // the command's ends of the pipes are dropped, so both copiers are done.
+        // An output error fails the command with a broken pipe, so report it instead.
+        output_copier
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+        match input_copier
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        {
+            // the command doesn't have to read all of its input
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {},
+        }
+        result
+    })
+}
+
+/// the stdio pipes of the current thread, cleared when dropped - even if the command panics,
+/// so the copier threads are not left waiting on the pipes
+struct StdioPipes;
+
+impl StdioPipes {
+    fn set(stdin: PipeReader, stdout: PipeWriter) -> Self {
+        config::set_stdio_pipes(stdin, stdout);
+        Self
+    }
+}
+
+impl Drop for StdioPipes {
+    fn drop(&mut self) {
+        config::clear_stdio_pipes();
+    }
+}
+
+/// copy the input to a file in `dir`, for the commands that need a seekable input
+fn spool_input<R: Read>(mut input: R, dir: &Path) -> CliResult<PathBuf> {
+    let path = dir.join("input.csv");
+    let mut file = io::BufWriter::new(fs::File::create(&path)?);
+    io::copy(&mut input, &mut file)?;
+    file.flush()?;
+    Ok(path)
+}
+
+fn push_flag(args: &mut Vec<String>, flag: &str, set: bool) {
+    if set {
+        args.push(flag.to_string());
+    }
+}
+
+fn push_opt<T: ToString>(args: &mut Vec<String>, opt: &str, value: Option<&T>) {
+    if let Some(value) = value {
+        args.push(opt.to_string());
+        args.push(value.to_string());
+    }
+}
@@ -2,9 +2,11 @@
 use std::{
     fmt, io,
     process::{ExitCode, Termination},
-    sync::OnceLock,
+    sync::{Mutex, OnceLock},
 };
 
+use serde::Serialize;
+
 /// write to stdout
 macro_rules! wout {
     ($($arg:tt)*) => ({
@@ -142,6 +144,115 @@ impl Termination for QsvExitCode {
 
 pub type CliResult<T> = Result<T, CliError>;
 
+/// The stable error code catalog used by the JSON error format (QSV_ERROR_FORMAT=json).
+/// Codes are never renamed or reused. New codes may be added.
+/// See https://github.com/dathere/qsv/blob/master/docs/ERROR_CODES.md
+// not all the binary variants have the commands that use the command-specific codes
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // usage errors
+    InvalidArguments,
+    IncorrectUsage,
+    // CSV parsing/writing errors
+    CsvUnequalLengths,
+    CsvInvalidUtf8,
+    CsvDeserialize,
+    CsvSerialize,
+    CsvSeek,
+    CsvError,
+    // I/O errors
+    FileNotFound,
+    PermissionDenied,
+    BrokenPipe,
+    IoError,
+    // other error kinds
+    NetworkError,
+    OutOfMemory,
+    EncodingError,
+    NoMatch,
+    Error,
+    // command-specific errors
+    Rfc4180ValidationFailed,
+    Utf8ValidationFailed,
+    SchemaValidationFailed,
+    JoinKeyError,
+}
+
+/// Additional details about the error being returned, set by commands & readers where
+/// known, and reported by the JSON error format.
+#[derive(Debug, Default, Clone)]
+struct ErrorContext {
+    code:   Option<ErrorCode>,
+    input:  Option<String>,
+    /// 1-based data row number (the header row is not counted)
+    row:    Option<u64>,
+    /// 1-based line number
+    line:   Option<u64>,
+    column: Option<String>,
+}
+
+static ERROR_CONTEXT: Mutex<ErrorContext> = Mutex::new(ErrorContext {
+    code:   None,
+    input:  None,
+    row:    None,
+    line:   None,
+    column: None,
+});
+
+/// record the input file currently being read, for error reporting
+pub fn set_error_input(input: &str) {
+    if let Ok(mut ctx) = ERROR_CONTEXT.lock() {
+        ctx.input = Some(input.to_string());
+    }
+}
+
+/// record the code & position of the error about to be returned, for error reporting
+#[allow(dead_code)]
+pub fn set_error_context(
+    code: ErrorCode,
+    row: Option<u64>,
+    line: Option<u64>,
+    column: Option<String>,
+) {
+    if let Ok(mut ctx) = ERROR_CONTEXT.lock() {
+        ctx.code = Some(code);
+        ctx.row = row;
+        ctx.line = line;
+        ctx.column = column;
+    }
+}
+
+/// clear the error context of the previous command run
+#[allow(dead_code)]
+pub fn reset_error_context() {
+    if let Ok(mut ctx) = ERROR_CONTEXT.lock() {
+        *ctx = ErrorContext::default();
+    }
+}
+
+/// the error code set by the command, if any
+#[allow(dead_code)]
+pub fn error_context_code() -> Option<ErrorCode> {
+    ERROR_CONTEXT.lock().ok().and_then(|ctx| ctx.code)
+}
+
+/// the data row number of the error set by the command, if any
+#[allow(dead_code)]
+pub fn error_context_row() -> Option<u64> {
+    ERROR_CONTEXT.lock().ok().and_then(|ctx| ctx.row)
+}
+
+/// are errors to be reported as JSON? (QSV_ERROR_FORMAT=json)
+#[cfg_attr(feature = "lib", allow(dead_code))]
+pub fn json_error_format() -> bool {
+    static JSON_ERROR_FORMAT: OnceLock<bool> = OnceLock::new();
+    *JSON_ERROR_FORMAT.get_or_init(|| {
+        std::env::var("QSV_ERROR_FORMAT").is_ok_and(|fmt| fmt.eq_ignore_ascii_case("json"))
+    })
+}
+
 #[derive(Debug)]
 pub enum CliError {
     Flag(docopt::Error),
@@ -173,6 +284,108 @@ impl fmt::Display for CliError {
     }
 }
 
+impl CliError {
+    /// the error kind, as reported by the JSON error format
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            CliError::Flag(_) | CliError::IncorrectUsage(_) => "usage",
+            CliError::Help(_) => "help",
+            CliError::Csv(_) => "csv",
+            CliError::Io(_) => "io",
+            CliError::NoMatch() => "no_match",
+            CliError::Network(_) => "network",
+            CliError::OutOfMemory(_) => "out_of_memory",
+            CliError::Encoding(_) => "encoding",
+            CliError::Other(_) => "other",
+        }
+    }
+
+    /// the error code from the error code catalog, based on the error's kind
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CliError::Flag(_) | CliError::Help(_) => ErrorCode::InvalidArguments,
+            CliError::IncorrectUsage(_) => ErrorCode::IncorrectUsage,
+            CliError::Csv(err) => match err.kind() {
+                csv::ErrorKind::UnequalLengths { .. } => ErrorCode::CsvUnequalLengths,
+                csv::ErrorKind::Utf8 { .. } => ErrorCode::CsvInvalidUtf8,
+                csv::ErrorKind::Deserialize { .. } => ErrorCode::CsvDeserialize,
+                csv::ErrorKind::Serialize(_) => ErrorCode::CsvSerialize,
+                csv::ErrorKind::Seek => ErrorCode::CsvSeek,
+                _ => ErrorCode::CsvError,
+            },
+            CliError::Io(err) => match err.kind() {
+                io::ErrorKind::NotFound => ErrorCode::FileNotFound,
+                io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+                io::ErrorKind::BrokenPipe => ErrorCode::BrokenPipe,
+                _ => ErrorCode::IoError,
+            },
+            CliError::NoMatch() => ErrorCode::NoMatch,
+            CliError::Network(_) => ErrorCode::NetworkError,
+            CliError::OutOfMemory(_) => ErrorCode::OutOfMemory,
+            CliError::Encoding(_) => ErrorCode::EncodingError,
+            CliError::Other(_) => ErrorCode::Error,
+        }
+    }
+
+    /// the process exit code for the error
+    pub fn exit_code(&self) -> QsvExitCode {
+        match self {
+            CliError::Help(_) => QsvExitCode::Good,
+            CliError::Flag(_) | CliError::IncorrectUsage(_) => QsvExitCode::IncorrectUsage,
+            CliError::Network(_) => QsvExitCode::NetworkError,
+            CliError::OutOfMemory(_) => QsvExitCode::OutOfMemory,
+            CliError::Encoding(_) => QsvExitCode::EncodingError,
+            CliError::Io(err) if matches!(err.kind(), io::ErrorKind::BrokenPipe) => {
+                QsvExitCode::Warning
+            },
+            CliError::Csv(_) | CliError::Io(_) | CliError::NoMatch() | CliError::Other(_) => {
+                QsvExitCode::Bad
+            },
+        }
+    }
+
+    /// the error as a JSON object, with the error context recorded by the command, if any
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut ctx = ERROR_CONTEXT
+            .lock()
+            .map(|ctx| ctx.clone())
+            .unwrap_or_default();
+        // a code set by the command is more specific than the one derived from the error kind
+        let code = ctx.code.unwrap_or_else(|| self.code());
+        let mut record = None;
+        let mut byte = None;
+        if let CliError::Csv(err) = self {
+            if let Some(pos) = err.position() {
+                ctx.line = ctx.line.or(Some(pos.line()));
+                record = Some(pos.record());
+                byte = Some(pos.byte());
+            }
+            if let csv::ErrorKind::Utf8 { err: utf8_err, .. } = err.kind() {
+                // report the 1-based column number
+                ctx.column = ctx
+                    .column
+                    .or_else(|| Some((utf8_err.field() + 1).to_string()));
+            }
+        }
+
+        serde_json::json!({
+            "error": {
+                "code": code,
+                "kind": self.kind(),
+                "command": CURRENT_COMMAND.get(),
+                "message": self.to_string(),
+                "input": ctx.input,
+                "row": ctx.row,
+                "line": ctx.line,
+                "record": record,
+                "byte": byte,
+                "column": ctx.column,
+                "exit_code": self.exit_code() as u8,
+            }
+        })
+    }
+}
+
 impl From<docopt::Error> for CliError {
     fn from(err: docopt::Error) -> CliError {
         if let docopt::Error::WithProgramUsage(ref errtype, ref usage_text) = err {
@@ -33,7 +33,13 @@ Usage:
 cat arguments:
     <input>...              The CSV file(s) to read. Use '-' for standard input.
                             If input is a directory, all files in the directory will
-                            be read as input.
+                            be read as input. Set QSV_INPUT_RECURSIVE to also read the
+                            files in its subdirectories.
+                            If input is a glob pattern (e.g. 'data/**/*.csv' - quote it
+                            so it is not expanded by the shell), all matching files
+                            will be read as input, sorted by path.
+                            QSV_INPUT_INCLUDE/QSV_INPUT_EXCLUDE file name patterns filter
+                            the files found in directories and by glob patterns.
                             If the input is a file with a '.infile-list' extension,
                             the file will be read as a list of input files.
                             If the input are snappy-compressed files(s), it will be
@@ -50,15 +56,16 @@ cat options:
                              that the input and output CSVs have the same number of columns.
                              This is faster, but may result in invalid CSV data.
 
-                             ROWSKEY OPTIONS:
-    -g, --group <grpkind>    When concatenating with rowskey, you can specify a grouping value
-                             which will be used as the first column in the output. This is useful
-                             when you want to know which file a row came from. Valid values are
+                             ROWS/ROWSKEY OPTIONS:
+    -g, --group <grpkind>    When concatenating with rows or rowskey, you can specify a grouping
+                             value which will be used as the first column in the output. This is
+                             useful when you want to know which file a row came from
+                             (e.g. --group fname to add a source file column). Valid values are
                              'fullpath', 'parentdirfname', 'parentdirfstem', 'fname', 'fstem' and 'none'.
                              A new column will be added to the beginning of each row using --group-name.
                              If 'none' is specified, no grouping column will be added.
                              [default: none]
-    -N, --group-name <arg>   When concatenating with rowskey, this flag provides the name
+    -N, --group-name <arg>   When concatenating with rows or rowskey, this flag provides the name
                              for the new grouping column. [default: file]
                              
 Common options:
@@ -126,6 +133,20 @@ fn get_parentdir_and_file(path: &Path, stem_only: bool) -> String {
     parent_dir.join(file_info).to_string_lossy().into_owned()
 }
 
+/// the grouping value of a file, i.e. the value of the --group-name column
+/// safety: we know that this is a valid file path and if the file path
+/// is not utf8, we convert it to lossy utf8
+fn get_grouping_value(group_kind: &GroupKind, path: &Path) -> String {
+    match group_kind {
+        GroupKind::FullPath => path.canonicalize().unwrap().to_string_lossy().into_owned(),
+        GroupKind::ParentDirFName => get_parentdir_and_file(path, false),
+        GroupKind::ParentDirFStem => get_parentdir_and_file(path, true),
+        GroupKind::FName => path.file_name().unwrap().to_string_lossy().into_owned(),
+        GroupKind::FStem => path.file_stem().unwrap().to_string_lossy().into_owned(),
+        GroupKind::None => String::new(),
+    }
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut args: Args = util::get_args(USAGE, argv)?;
 
@@ -157,7 +178,23 @@ impl Args {
         .map_err(From::from)
     }
 
+    fn group_kind(&self) -> CliResult<GroupKind> {
+        let Ok(group_kind) = GroupKind::from_str(&self.flag_group) else {
+            return fail_incorrectusage_clierror!(
+                "Invalid grouping value `{}`. Valid values are 'fullpath', 'parentdirfname', \
+                 'parentdirfstem', 'fname', 'fstem' and 'none'.",
+                self.flag_group
+            );
+        };
+        Ok(group_kind)
+    }
+
     fn cat_rows(&self) -> CliResult<()> {
+        let group_kind = self.group_kind()?;
+        if group_kind != GroupKind::None {
+            return self.cat_rows_grouped(&group_kind);
+        }
+
         let mut row = csv::ByteRecord::new();
         let mut wtr = Config::new(self.flag_output.as_ref())
             .flexible(self.flag_flexible)
@@ -189,6 +226,37 @@ impl Args {
         Ok(wtr.flush()?)
     }
 
+    // same as cat_rows, but with the --group column added as the first column
+    fn cat_rows_grouped(&self, group_kind: &GroupKind) -> CliResult<()> {
+        let mut row = csv::ByteRecord::new();
+        let mut new_row = csv::ByteRecord::new();
+        let mut wtr = Config::new(self.flag_output.as_ref())
+            .flexible(self.flag_flexible)
+            .writer()?;
+
+        for (i, conf) in self.configs()?.into_iter().enumerate() {
+            let mut rdr = conf.reader()?;
+            // only the headers of the first file are written
+            if i == 0 && !self.flag_no_headers {
+                new_row.clear();
+                new_row.push_field(self.flag_group_name.as_bytes());
+                new_row.extend(rdr.byte_headers()?);
+                wtr.write_byte_record(&new_row)?;
+            }
+
+            // safety: stdin input was copied to a file by process_input
+            let grouping_value = get_grouping_value(group_kind, conf.path.as_ref().unwrap());
+            while rdr.read_byte_record(&mut row)? {
+                new_row.clear();
+                new_row.push_field(grouping_value.as_bytes());
+                new_row.extend(&row);
+                wtr.write_byte_record(&new_row)?;
+            }
+        }
+
+        Ok(wtr.flush()?)
+    }
+
     // this algorithm is largely inspired by https://github.com/vi/csvcatrow by @vi
     // https://github.com/dathere/qsv/issues/527
     fn cat_rowskey(&self) -> CliResult<()> {
@@ -196,13 +264,7 @@ impl Args {
         type FhashIndexSet<T> = IndexSet<T, foldhash::fast::RandomState>;
         type FhashIndexMap<T, T2> = IndexMap<T, T2, foldhash::fast::RandomState>;
 
-        let Ok(group_kind) = GroupKind::from_str(&self.flag_group) else {
-            return fail_incorrectusage_clierror!(
-                "Invalid grouping value `{}`. Valid values are 'fullpath', 'parentdirfname', \
-                 'parentdirfstem', 'fname', 'fstem' and 'none'.",
-                self.flag_group
-            );
-        };
+        let group_kind = self.group_kind()?;
 
         let mut columns_global: FhashIndexSet<Box<[u8]>> = FhashIndexSet::default();
 
@@ -269,7 +331,6 @@ impl Args {
         }
 
         // amortize allocations
-        let mut grouping_value = String::new();
         let mut conf_path;
         let mut rdr;
         let mut header: &csv::ByteRecord;
@@ -311,30 +372,7 @@ impl Args {
             let conf_pathbuf = conf_path.unwrap();
 
             // set grouping_value
-            // safety: we know that this is a valid file path and if the file path
-            // is not utf8, we convert it to lossy utf8
-            match group_kind {
-                GroupKind::FullPath => {
-                    grouping_value.clear();
-                    grouping_value
-                        .push_str(&conf_pathbuf.canonicalize().unwrap().to_string_lossy());
-                },
-                GroupKind::ParentDirFName => {
-                    grouping_value = get_parentdir_and_file(&conf_pathbuf, false);
-                },
-                GroupKind::ParentDirFStem => {
-                    grouping_value = get_parentdir_and_file(&conf_pathbuf, true);
-                },
-                GroupKind::FName => {
-                    grouping_value.clear();
-                    grouping_value.push_str(&conf_pathbuf.file_name().unwrap().to_string_lossy());
-                },
-                GroupKind::FStem => {
-                    grouping_value.clear();
-                    grouping_value.push_str(&conf_pathbuf.file_stem().unwrap().to_string_lossy());
-                },
-                GroupKind::None => {},
-            }
+            let grouping_value = get_grouping_value(&group_kind, &conf_pathbuf);
 
             let group_flag = group_kind != GroupKind::None;
             let grouping_value_bytes = grouping_value.as_bytes();
@@ -0,0 +1,831 @@
+static USAGE: &str = r#"
+Generate shell completion scripts for qsv.
+
+The completions are generated from the usage text of the commands enabled in this
+qsv binary. They complete the commands, their subcommands (e.g. `apply operations`,
+`geocode suggest`), their options and the valid values of options that only accept
+a fixed set of values (e.g. `sqlp --format`).
+
+To install the completions:
+
+  bash:
+    $ qsv completions bash > ~/.local/share/bash-completion/completions/qsv
+  zsh (to a directory in your $fpath):
+    $ qsv completions zsh > ~/.zfunc/_qsv
+  fish:
+    $ qsv completions fish > ~/.config/fish/completions/qsv.fish
+  powershell:
+    PS> qsv completions powershell >> $PROFILE
+
+For qsvlite & qsvdp, run the command with that binary instead, e.g.
+    $ qsvlite completions bash > ~/.local/share/bash-completion/completions/qsvlite
+
+Usage:
+    qsv completions [options] <shell>
+    qsv completions --help
+
+completions arguments:
+    <shell>                The shell to generate completions for.
+                           Valid values are "bash", "zsh", "fish" and "powershell".
+
+completions options:
+    --bin-name <name>      The name of the qsv binary to complete. Defaults to the
+                           name this binary was invoked with (e.g. qsv, qsvlite or qsvdp).
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+"#;
+
+use std::{fmt::Write as _, io::Write, path::Path};
+
+use serde::Deserialize;
+
+use crate::{CliError, CliResult, Command, config::Config, util};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_shell:     String,
+    flag_bin_name: Option<String>,
+    flag_output:   Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct OptionSpec {
+    short:       Option<char>,
+    long:        Option<String>,
+    takes_value: bool,
+    /// the valid values, if the option only accepts a fixed set of values
+    values:      Vec<String>,
+    help:        String,
+}
+
+#[derive(Debug)]
+struct CommandSpec {
+    name:        String,
+    about:       String,
+    subcommands: Vec<String>,
+    options:     Vec<OptionSpec>,
+}
+
+pub fn run(argv: &[&str], command_list: &str) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let bin_name = args.flag_bin_name.unwrap_or_else(|| {
+        Path::new(argv[0]).file_stem().map_or_else(
+            || "qsv".to_string(),
+            |stem| stem.to_string_lossy().to_string(),
+        )
+    });
+
+    let commands = command_specs(&bin_name, command_list);
+    let global_options = global_options();
+    let script = match args.arg_shell.to_lowercase().as_str() {
+        "bash" => bash_completions(&bin_name, &commands, &global_options),
+        "zsh" => zsh_completions(&bin_name, &commands, &global_options),
+        "fish" => fish_completions(&bin_name, &commands, &global_options),
+        "powershell" => powershell_completions(&bin_name, &commands, &global_options),
+        shell => {
+            return fail_incorrectusage_clierror!(
+                "Unsupported shell \"{shell}\". Valid values are bash, zsh, fish and powershell."
+            );
+        },
+    };
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).io_writer()?;
+    wtr.write_all(script.as_bytes())?;
+    Ok(wtr.flush()?)
+}
+
+/// the options of the qsv binary itself
+fn global_options() -> Vec<OptionSpec> {
+    [
+        (None, "list", "List all commands available."),
+        (
+            None,
+            "envlist",
+            "List all qsv-relevant environment variables.",
+        ),
+        (
+            Some('u'),
+            "update",
+            "Update qsv to the latest release from GitHub.",
+        ),
+        (
+            Some('U'),
+            "updatenow",
+            "Update qsv to the latest release from GitHub without confirming.",
+        ),
+        (Some('h'), "help", "Display this message"),
+        (Some('v'), "version", "Print version info then exit"),
+    ]
+    .into_iter()
+    .map(|(short, long, help)| OptionSpec {
+        short,
+        long: Some(long.to_string()),
+        help: help.to_string(),
+        ..Default::default()
+    })
+    .collect()
+}
+
+/// get the subcommands & options of the commands in the command list
+/// from their usage text
+fn command_specs(bin_name: &str, command_list: &str) -> Vec<CommandSpec> {
+    command_list
+        .lines()
+        .filter_map(|line| {
+            let (name, about) = line.trim().split_once(char::is_whitespace)?;
+            if !name.chars().all(|c| c.is_ascii_lowercase()) {
+                return None;
+            }
+            let (subcommands, options) = match command_usage(bin_name, name) {
+                Some(usage) => parse_usage(name, &usage),
+                None => (Vec::new(), Vec::new()),
+            };
+            Some(CommandSpec {
+                name: name.to_string(),
+                about: about.trim().to_string(),
+                subcommands,
+                options,
+            })
+        })
+        .collect()
+}
+
+/// get the usage text of a command, by asking the command for its help
+fn command_usage(bin_name: &str, name: &str) -> Option<String> {
+    // help shows the qsv usage & completions is this command
+    if name == "help" || name == "completions" {
+        return None;
+    }
+    match Command::from_name(name)?.run_with_argv(&[bin_name, name, "--help"]) {
+        Err(CliError::Help(usage)) => Some(usage),
+        _ => None,
+    }
+}
+
+/// parse the subcommands & options of a command from its docopt usage text
+fn parse_usage(command: &str, usage: &str) -> (Vec<String>, Vec<OptionSpec>) {
+    let mut subcommands: Vec<String> = Vec::new();
+    let mut options: Vec<OptionSpec> = Vec::new();
+    // the description lines of the current option
+    let mut description: Vec<&str> = Vec::new();
+    let mut in_description = false;
+    let mut in_usage = false;
+    let mut seen_usage = false;
+
+    for line in usage.lines() {
+        let trimmed = line.trim();
+        if !seen_usage {
+            if let Some(pattern) = trimmed.strip_prefix("Usage:") {
+                seen_usage = true;
+                in_usage = true;
+                add_subcommand(command, pattern, &mut subcommands);
+            }
+            continue;
+        }
+        if in_usage {
+            if trimmed.is_empty() {
+                in_usage = false;
+            } else {
+                add_subcommand(command, trimmed, &mut subcommands);
+            }
+            continue;
+        }
+
+        if let Some(option) = parse_option_line(line) {
+            if let Some(last) = options.last_mut() {
+                last.values = option_values(&last.help, &description);
+            }
+            description.clear();
+            options.push(option);
+            in_description = true;
+        } else if trimmed.is_empty() {
+            in_description = false;
+        } else if in_description {
+            description.push(line);
+        }
+    }
+    if let Some(last) = options.last_mut() {
+        last.values = option_values(&last.help, &description);
+    }
+
+    (subcommands, options)
+}
+
+/// add the subcommand of a usage pattern (e.g. `qsv apply operations <operations> ...`)
+fn add_subcommand(command: &str, pattern: &str, subcommands: &mut Vec<String>) {
+    let mut words = pattern.split_whitespace().skip(1);
+    if words.next() != Some(command) {
+        return;
+    }
+    let Some(word) = words.next() else {
+        return;
+    };
+    if word != command
+        && word.starts_with(|c: char| c.is_ascii_lowercase())
+        && is_value_word(word)
+        && !subcommands.iter().any(|sub| sub == word)
+    {
+        subcommands.push(word.to_string());
+    }
+}
+
+/// parse an option line, e.g. `    -s, --select <arg>      Select the columns...`
+fn parse_option_line(line: &str) -> Option<OptionSpec> {
+    let trimmed = line.trim_start();
+    // option descriptions are indented deeper than the options themselves
+    if line.len() - trimmed.len() > 8 || !trimmed.starts_with('-') {
+        return None;
+    }
+
+    // the option & its description are separated by at least two spaces
+    let (flags, help) = match trimmed.find("  ") {
+        Some(idx) => (&trimmed[..idx], trimmed[idx..].trim()),
+        None => (trimmed.trim_end(), ""),
+    };
+
+    let mut option = OptionSpec {
+        help: help.to_string(),
+        ..Default::default()
+    };
+    for flag in flags.split(", ") {
+        let (flag, arg) = match flag.split_once([' ', '=']) {
+            Some((flag, arg)) => (flag, Some(arg)),
+            None => (flag, None),
+        };
+        if let Some(long) = flag.strip_prefix("--") {
+            if long.is_empty() || !is_value_word(long) {
+                return None;
+            }
+            option.long = Some(long.to_string());
+        } else if let Some(short) = flag.strip_prefix('-') {
+            let mut chars = short.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphanumeric() => option.short = Some(c),
+                _ => return None,
+            }
+        } else {
+            return None;
+        }
+        if arg.is_some_and(|arg| arg.starts_with('<')) {
+            option.takes_value = true;
+        }
+    }
+    Some(option)
+}
+
+/// a word that can be a subcommand, option name or option value
+fn is_value_word(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// find the valid values of an option in its description, e.g.
+/// `Valid values are "sec", "milli", "micro", "nano".`, `Possible values: all, never.`
+/// or a list of values, one per line, following `Valid values are:`
+fn option_values(help: &str, description: &[&str]) -> Vec<String> {
+    let lines: Vec<&str> = std::iter::once(help)
+        .chain(description.iter().copied())
+        .collect();
+
+    let mut values = values_after_marker(&lines).unwrap_or_else(|| dashed_values(&lines));
+    if values.is_empty() {
+        return values;
+    }
+
+    // make sure the default value is offered too
+    let text = lines.join(" ");
+    if let Some(default) = text
+        .split_once("[default: ")
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(default, _)| default.trim())
+        .filter(|default| is_value_word(default) && !values.iter().any(|value| value == default))
+    {
+        values.push(default.to_string());
+    }
+    values
+}
+
+fn values_after_marker(lines: &[&str]) -> Option<Vec<String>> {
+    const MARKERS: [&str; 3] = ["valid values are", "valid values:", "possible values"];
+
+    let (line_idx, rest) = lines.iter().enumerate().find_map(|(idx, line)| {
+        let lower = line.to_ascii_lowercase();
+        MARKERS.iter().find_map(|marker| {
+            lower.find(marker).map(|pos| {
+                let rest = &line[pos + marker.len()..];
+                (idx, rest.trim_start_matches(':').trim())
+            })
+        })
+    })?;
+    let following = &lines[line_idx + 1..];
+
+    // quoted values, up to the end of the sentence
+    let mut sentence = rest.to_string();
+    for line in following {
+        if sentence.trim_end().ends_with('.') {
+            break;
+        }
+        sentence.push(' ');
+        sentence.push_str(line.trim());
+    }
+    let sentence = sentence.split(". ").next().unwrap_or_default();
+    let quoted: Vec<String> = sentence
+        .split(['"', '\''])
+        .skip(1)
+        .step_by(2)
+        .filter(|value| is_value_word(value))
+        .map(str::to_string)
+        .collect();
+    if !quoted.is_empty() {
+        return Some(quoted);
+    }
+
+    // a list of values on the marker line or on the next line, e.g. `all, necessary and never.`
+    let inline = if rest.is_empty() {
+        following
+            .first()
+            .map(|line| line.trim())
+            .filter(|line| line.contains(','))
+            .unwrap_or_default()
+    } else {
+        rest
+    };
+    if !inline.is_empty() {
+        let inline = inline
+            .trim_end_matches('.')
+            .replace(" and ", ",")
+            .replace(" or ", ",");
+        let values: Vec<String> = inline
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect();
+        if !values.is_empty() && values.iter().all(|value| is_value_word(value)) {
+            return Some(values);
+        }
+    }
+
+    // one value per line, followed by its description, e.g. `csv  Comma-separated values`
+    let mut values = Vec::new();
+    let mut list_indent = None;
+    for line in following {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('[') {
+            break;
+        }
+        match list_indent {
+            None => list_indent = Some(indent),
+            // a value description spanning several lines
+            Some(list_indent) if indent > list_indent => continue,
+            Some(list_indent) if indent < list_indent => break,
+            Some(_) => {},
+        }
+        let value = trimmed
+            .trim_start_matches("- ")
+            .split(|c: char| c.is_whitespace() || c == ':')
+            .next()
+            .unwrap_or_default();
+        if !is_value_word(value) {
+            break;
+        }
+        values.push(value.to_string());
+    }
+    (!values.is_empty()).then_some(values)
+}
+
+/// values listed as `- value: description` lines, e.g. `sort --rng`
+fn dashed_values(lines: &[&str]) -> Vec<String> {
+    let values: Vec<String> = lines
+        .iter()
+        .filter_map(|line| {
+            let (value, _) = line.trim().strip_prefix("- ")?.split_once(':')?;
+            is_value_word(value).then(|| value.to_string())
+        })
+        .collect();
+    if values.len() > 1 { values } else { Vec::new() }
+}
+
+/// all the flags of an option, e.g. `-s --select`
+fn option_flags(option: &OptionSpec) -> Vec<String> {
+    option
+        .short
+        .map(|short| format!("-{short}"))
+        .into_iter()
+        .chain(option.long.as_ref().map(|long| format!("--{long}")))
+        .collect()
+}
+
+fn bash_completions(bin_name: &str, commands: &[CommandSpec], global: &[OptionSpec]) -> String {
+    let func = format!("_{}", bin_name.replace(['-', '.'], "_"));
+    let command_names = commands
+        .iter()
+        .map(|command| command.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let global_flags = global
+        .iter()
+        .flat_map(option_flags)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut script = String::new();
+    let _ = write!(
+        script,
+        r#"# bash completions for {bin_name}, generated by `{bin_name} completions bash`
+
+{func}() {{
+    local cur prev cmd
+    COMPREPLY=()
+    cur="${{COMP_WORDS[COMP_CWORD]}}"
+    prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+
+    if [[ ${{COMP_CWORD}} -eq 1 ]]; then
+        COMPREPLY=( $(compgen -W "{command_names} {global_flags}" -- "${{cur}}") )
+        return 0
+    fi
+
+    cmd="${{COMP_WORDS[1]}}"
+    case "${{cmd}}" in
+"#
+    );
+
+    for command in commands {
+        let _ = writeln!(script, "        {})", command.name);
+        if command
+            .options
+            .iter()
+            .any(|option| !option.values.is_empty())
+        {
+            script.push_str("            case \"${prev}\" in\n");
+            for option in command
+                .options
+                .iter()
+                .filter(|option| !option.values.is_empty())
+            {
+                let _ = write!(
+                    script,
+                    r#"                {})
+                    COMPREPLY=( $(compgen -W "{}" -- "${{cur}}") )
+                    return 0
+                    ;;
+"#,
+                    option_flags(option).join("|"),
+                    option.values.join(" ")
+                );
+            }
+            script.push_str("            esac\n");
+        }
+        if !command.options.is_empty() {
+            let _ = write!(
+                script,
+                r#"            if [[ ${{cur}} == -* ]]; then
+                COMPREPLY=( $(compgen -W "{}" -- "${{cur}}") )
+                return 0
+            fi
+"#,
+                command
+                    .options
+                    .iter()
+                    .flat_map(option_flags)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+        }
+        if !command.subcommands.is_empty() {
+            let _ = write!(
+                script,
+                r#"            if [[ ${{COMP_CWORD}} -eq 2 ]]; then
+                COMPREPLY=( $(compgen -W "{}" -- "${{cur}}") $(compgen -f -- "${{cur}}") )
+                return 0
+            fi
+"#,
+                command.subcommands.join(" ")
+            );
+        }
+        script.push_str("            ;;\n");
+    }
+
+    let _ = write!(
+        script,
+        r#"    esac
+
+    COMPREPLY=( $(compgen -f -- "${{cur}}") )
+    return 0
+}}
+
+complete -o filenames -o bashdefault -F {func} {bin_name}
+"#
+    );
+    script
+}
+
+/// escape a description for a single-quoted zsh string
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', r"'\''")
+        .replace('[', "(")
+        .replace(']', ")")
+}
+
+fn zsh_option_spec(option: &OptionSpec) -> String {
+    let help = zsh_escape(&option.help);
+    let value = if option.takes_value {
+        if option.values.is_empty() {
+            ":value:_files".to_string()
+        } else {
+            format!(":value:({})", option.values.join(" "))
+        }
+    } else {
+        String::new()
+    };
+    let equals = if option.takes_value { "=" } else { "" };
+    match (option.short, &option.long) {
+        (Some(short), Some(long)) => {
+            format!("'(-{short} --{long})'{{-{short},--{long}{equals}}}'[{help}]{value}'")
+        },
+        (None, Some(long)) => format!("'--{long}{equals}[{help}]{value}'"),
+        (Some(short), None) => format!("'-{short}[{help}]{value}'"),
+        (None, None) => String::new(),
+    }
+}
+
+fn zsh_completions(bin_name: &str, commands: &[CommandSpec], global: &[OptionSpec]) -> String {
+    let func = format!("_{}", bin_name.replace(['-', '.'], "_"));
+
+    let mut script = String::new();
+    let _ = writeln!(script, "#compdef {bin_name}\n");
+    let _ = writeln!(
+        script,
+        "# zsh completions for {bin_name}, generated by `{bin_name} completions zsh`\n"
+    );
+    let _ = writeln!(script, "{func}() {{");
+    script.push_str(
+        "    local context state state_descr line\n    typeset -A opt_args\n\n    _arguments -C \
+         \\\n",
+    );
+    for option in global {
+        let _ = writeln!(script, "        {} \\", zsh_option_spec(option));
+    }
+    let _ = write!(
+        script,
+        r#"        '1: :{func}_commands' \
+        '*:: :->args'
+
+    case $state in
+        args)
+            case $words[1] in
+"#
+    );
+
+    for command in commands {
+        let _ = writeln!(script, "                {})", command.name);
+        script.push_str("                    _arguments -s \\\n");
+        for option in &command.options {
+            let _ = writeln!(
+                script,
+                "                        {} \\",
+                zsh_option_spec(option)
+            );
+        }
+        if !command.subcommands.is_empty() {
+            let _ = writeln!(
+                script,
+                "                        '1: :{{_alternative \"subcommands:subcommand:({})\" \
+                 \"files:file:_files\"}}' \\",
+                command.subcommands.join(" ")
+            );
+        }
+        script.push_str("                        '*:file:_files'\n                    ;;\n");
+    }
+
+    let _ = write!(
+        script,
+        r#"            esac
+            ;;
+    esac
+}}
+
+{func}_commands() {{
+    local -a commands
+    commands=(
+"#
+    );
+    for command in commands {
+        let _ = writeln!(
+            script,
+            "        '{}:{}'",
+            command.name,
+            command.about.replace('\'', r"'\''")
+        );
+    }
+    let _ = write!(
+        script,
+        r#"    )
+    _describe -t commands '{bin_name} command' commands
+}}
+
+if [ "$funcstack[1]" = "{func}" ]; then
+    {func} "$@"
+else
+    compdef {func} {bin_name}
+fi
+"#
+    );
+    script
+}
+
+fn fish_option_spec(option: &OptionSpec) -> String {
+    let mut spec = String::new();
+    if let Some(short) = option.short {
+        let _ = write!(spec, " -s {short}");
+    }
+    if let Some(long) = &option.long {
+        let _ = write!(spec, " -l {long}");
+    }
+    if !option.values.is_empty() {
+        let _ = write!(spec, " -x -a \"{}\"", option.values.join(" "));
+    } else if option.takes_value {
+        spec.push_str(" -r");
+    }
+    if !option.help.is_empty() {
+        let _ = write!(spec, " -d '{}'", option.help.replace('\'', r"\'"));
+    }
+    spec
+}
+
+fn fish_completions(bin_name: &str, commands: &[CommandSpec], global: &[OptionSpec]) -> String {
+    let mut script = String::new();
+    let _ = writeln!(
+        script,
+        "# fish completions for {bin_name}, generated by `{bin_name} completions fish`\n"
+    );
+    for command in commands {
+        let _ = writeln!(
+            script,
+            "complete -c {bin_name} -n \"__fish_use_subcommand\" -f -a {} -d '{}'",
+            command.name,
+            command.about.replace('\'', r"\'")
+        );
+    }
+    for option in global {
+        let _ = writeln!(
+            script,
+            "complete -c {bin_name} -n \"__fish_use_subcommand\"{}",
+            fish_option_spec(option)
+        );
+    }
+
+    for command in commands {
+        let _ = writeln!(script, "\n# {}", command.name);
+        let condition = format!("__fish_seen_subcommand_from {}", command.name);
+        if !command.subcommands.is_empty() {
+            let subcommands = command.subcommands.join(" ");
+            let _ = writeln!(
+                script,
+                "complete -c {bin_name} -n \"{condition}; and not __fish_seen_subcommand_from \
+                 {subcommands}\" -a \"{subcommands}\""
+            );
+        }
+        for option in &command.options {
+            let _ = writeln!(
+                script,
+                "complete -c {bin_name} -n \"{condition}\"{}",
+                fish_option_spec(option)
+            );
+        }
+    }
+    script
+}
+
+/// quote a string for PowerShell
+fn ps_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn ps_list(items: &[String]) -> String {
+    format!(
+        "@({})",
+        items
+            .iter()
+            .map(|item| ps_quote(item))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn powershell_completions(
+    bin_name: &str,
+    commands: &[CommandSpec],
+    global: &[OptionSpec],
+) -> String {
+    let mut script = String::new();
+    let _ = write!(
+        script,
+        r#"# PowerShell completions for {bin_name}, generated by `{bin_name} completions powershell`
+
+using namespace System.Management.Automation
+
+Register-ArgumentCompleter -Native -CommandName '{bin_name}' -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $commands = [ordered]@{{
+"#
+    );
+    for command in commands {
+        let _ = writeln!(
+            script,
+            "        {} = {}",
+            ps_quote(&command.name),
+            ps_quote(&command.about)
+        );
+    }
+    let global_flags: Vec<String> = global.iter().flat_map(option_flags).collect();
+    let _ = write!(
+        script,
+        "    }}\n    $globalOptions = {}\n    $options = @{{\n",
+        ps_list(&global_flags)
+    );
+    for command in commands
+        .iter()
+        .filter(|command| !command.options.is_empty())
+    {
+        let flags: Vec<String> = command.options.iter().flat_map(option_flags).collect();
+        let _ = writeln!(
+            script,
+            "        {} = {}",
+            ps_quote(&command.name),
+            ps_list(&flags)
+        );
+    }
+    script.push_str("    }\n    $subcommands = @{\n");
+    for command in commands
+        .iter()
+        .filter(|command| !command.subcommands.is_empty())
+    {
+        let _ = writeln!(
+            script,
+            "        {} = {}",
+            ps_quote(&command.name),
+            ps_list(&command.subcommands)
+        );
+    }
+    script.push_str("    }\n    $values = @{\n");
+    for command in commands {
+        for option in command
+            .options
+            .iter()
+            .filter(|option| !option.values.is_empty())
+        {
+            for flag in option_flags(option) {
+                let _ = writeln!(
+                    script,
+                    "        {} = {}",
+                    ps_quote(&format!("{} {flag}", command.name)),
+                    ps_list(&option.values)
+                );
+            }
+        }
+    }
+    script.push_str(
+        r#"    }
+
+    # the words before the one being completed, without the command name
+    $words = @($commandAst.CommandElements |
+        Where-Object { $_.Extent.EndOffset -lt $cursorPosition } |
+        Select-Object -Skip 1 |
+        ForEach-Object { $_.ToString() })
+
+    $candidates = @()
+    if ($words.Count -eq 0) {
+        if ($wordToComplete -like '-*') {
+            $candidates = $globalOptions
+        } else {
+            $candidates = $commands.Keys
+        }
+    } else {
+        $cmd = $words[0]
+        $valueKey = "$cmd $($words[-1])"
+        if ($values.ContainsKey($valueKey)) {
+            $candidates = $values[$valueKey]
+        } elseif ($wordToComplete -like '-*') {
+            $candidates = $options[$cmd]
+        } elseif ($words.Count -eq 1 -and $subcommands.ContainsKey($cmd)) {
+            $candidates = $subcommands[$cmd]
+        }
+    }
+
+    $candidates | Where-Object { $_ -like "$wordToComplete*" } | ForEach-Object {
+        $tooltip = if ($words.Count -eq 0 -and $commands.Contains($_)) { $commands[$_] } else { $_ }
+        [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $tooltip)
+    }
+}
+"#,
+    );
+    script
+}
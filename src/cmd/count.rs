@@ -1,4 +1,5 @@
 #![allow(clippy::cast_precision_loss)] // we're not worried about precision loss here
+#[cfg_attr(feature = "lib", allow(dead_code))]
 static USAGE: &str = r#"
 Returns a count of the number of records in the CSV data.
 
@@ -91,6 +92,7 @@ struct Args {
     flag_delimiter:       Option<Delimiter>,
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[derive(Copy, Clone, PartialEq)]
 enum CountDelimsMode {
     IncludeDelims,
@@ -98,6 +100,7 @@ enum CountDelimsMode {
     NotRequired,
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[derive(Default)]
 struct WidthStats {
     max:      usize,
@@ -109,6 +112,7 @@ struct WidthStats {
     mad:      f64,
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let conf = Config::new(args.arg_input.as_ref())
@@ -152,9 +156,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     (idx.count(), empty_record_stats)
                 },
                 None => {
-                    // if --no-polars or its a snappy compressed file, use the regular CSV reader
+                    // if --no-polars, its a snappy compressed file or its a non-CSV input
+                    // format that needs to be converted first, use the regular CSV reader
                     #[cfg(feature = "polars")]
-                    if args.flag_no_polars || conf.is_snappy() {
+                    if args.flag_no_polars
+                        || conf.is_snappy()
+                        || conf.input_format() != crate::config::InputFormat::Csv
+                    {
                         count_input(&conf, count_delims_mode)?
                     } else {
                         let count = polars_count_input(&conf, args.flag_low_memory)?;
@@ -252,6 +260,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 /// Returns error if:
 /// - Unable to read from the CSV file
 /// - Out of memory when allocating vectors for statistics
+#[cfg_attr(feature = "lib", allow(dead_code))]
 fn count_input(conf: &Config, count_delims_mode: CountDelimsMode) -> CliResult<(u64, WidthStats)> {
     use rayon::{
         iter::{IntoParallelRefIterator, ParallelIterator},
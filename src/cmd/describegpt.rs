@@ -681,6 +681,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         &tmpdir,
         "",
     )?;
+    // a directory, glob pattern or .infile-list input has to resolve to a single file
+    // as describegpt describes one dataset at a time
+    if work_input.len() > 1 {
+        return fail_incorrectusage_clierror!(
+            "describegpt describes one file at a time, but the input resolved to {} files.",
+            work_input.len()
+        );
+    }
     // safety: we just checked that there is at least one input file
     let input_path = work_input[0]
         .canonicalize()?
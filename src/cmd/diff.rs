@@ -330,6 +330,8 @@ fn check_stats_cache(args: &Args) -> Result<bool, CliError> {
         flag_strict_dates:    false,
         flag_pattern_columns: SelectColumns::parse("")?,
         flag_stdout:          false,
+        flag_format:          None,
+        flag_detect_formats:  false,
     };
 
     // Set stats config for right file using same args
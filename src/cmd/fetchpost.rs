@@ -666,7 +666,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     // prep progress bars
     let show_progress =
-        (args.flag_progressbar || util::get_envvar_flag("QSV_PROGRESSBAR")) && !rconfig.is_stdin();
+        (args.flag_progressbar || util::progress_envvar_enabled()) && !rconfig.is_stdin();
 
     // create multi_progress to stderr with a maximum refresh of 5 per second
     let multi_progress = MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(5));
@@ -692,7 +692,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     if show_progress {
         record_count = util::count_rows(&rconfig)?;
         util::prep_progress(&progress, record_count);
-    } else {
+    }
+    // NDJSON progress events are emitted instead of drawing the progress bars
+    if !show_progress || crate::events::enabled() {
         multi_progress.set_draw_target(ProgressDrawTarget::hidden());
     }
 
@@ -0,0 +1,528 @@
+static USAGE: &str = r#"
+Convert fixed-width text files (e.g. mainframe & census extracts) to CSV.
+
+Each field of a fixed-width record is found at the same character position in every
+record. The positions can be given in a layout file (--layout) or inferred from the
+whitespace alignment of the data (--auto).
+
+The layout file can be a CSV (or TSV) with a header row, or a JSON file.
+It lists the fields in the order they should be written, with these columns/keys:
+  name         the name of the output column
+  start        the position of the first character of the field (1-based)
+  length       the number of characters in the field
+  record_type  (optional) the record type the field belongs to
+
+e.g. as a CSV:
+  name,start,length
+  id,1,5
+  state,6,2
+  population,8,10
+
+or as JSON - either an array of fields, or an object with a "fields" array:
+  [{"name": "id", "start": 1, "length": 5}, {"name": "state", "start": 6, "length": 2}]
+
+MULTI-RECORD-TYPE LAYOUTS:
+Some extracts interleave several kinds of records (e.g. headers, details & trailers),
+each with its own layout. Tag the fields of each kind with a record_type value and use
+--record-type to set where the record type code is found in each record.
+Fields without a record_type are common to all record types.
+
+The output has a "record_type" column, followed by the union of the fields of all
+record types. Fields that do not apply to a record are left empty. Fields with the
+same name in different record types share the same column.
+Use --type to only convert the records of a given record type.
+Records with a record type that's not in the layout are skipped with a warning.
+
+AUTO-DETECT:
+With --auto, the first --sample records are used to find the character positions that
+are blank in every record. A new column starts after each run of blank positions.
+Unless --no-headers is set, the first record is used for the column names.
+As the layout is inferred from a sample, review it with --print-layout and save it to
+a layout file if needed.
+
+EBCDIC:
+With --ebcdic, the input is decoded from EBCDIC (code page 037) to UTF-8.
+EBCDIC records are separated by NL (0x15) characters, unless --record-length is set.
+Otherwise, the input is read as UTF-8, with invalid UTF-8 sequences replaced with �.
+
+Positions are counted in characters, and records are separated by newlines (LF or CRLF),
+unless --record-length is set. Empty records are skipped.
+
+Examples:
+
+Convert a fixed-width file using a layout file:
+  $ qsv fixedwidth --layout layout.csv census.txt --output census.csv
+
+Infer the layout from the data:
+  $ qsv fixedwidth --auto report.txt
+
+Only show the inferred layout:
+  $ qsv fixedwidth --auto --print-layout report.txt
+
+Convert an EBCDIC extract with 80-byte records & a 2-character record type code:
+  $ qsv fixedwidth --layout layout.json --ebcdic --record-length 80 --record-type 1:2 extract.dat
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_fixedwidth.rs.
+
+Usage:
+    qsv fixedwidth [options] [<input>]
+    qsv fixedwidth --help
+
+fixedwidth options:
+    --layout <file>          The layout file (CSV or JSON) with the name, start & length
+                             of each field.
+    --auto                   Infer the layout from the whitespace alignment of the data.
+    --sample <arg>           The number of records to sample with --auto.
+                             [default: 1000]
+    --print-layout           Write the layout as a CSV instead of converting the data.
+    --no-trim                Do not trim leading & trailing whitespace from field values.
+    --record-type <pos>      The position of the record type code in each record,
+                             as <start>:<length> (e.g. 1:2).
+                             Required if the layout has record types.
+    --type <value>           Only convert the records of this record type.
+    --ebcdic                 Decode the input from EBCDIC (code page 037).
+    --record-length <arg>    Read records of exactly <arg> bytes, instead of
+                             newline-separated records.
+    --skip-lines <arg>       The number of preamble lines to skip.
+
+Common options:
+    -h, --help               Display this message
+    -o, --output <file>      Write output to <file> instead of stdout.
+    -n, --no-headers         When set with --auto, the first record will not be
+                             used for the column names.
+"#;
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, Read},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    CliResult,
+    config::{Config, DEFAULT_RDR_BUFFER_CAPACITY},
+    util,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:          Option<String>,
+    flag_layout:        Option<String>,
+    flag_auto:          bool,
+    flag_sample:        usize,
+    flag_print_layout:  bool,
+    flag_no_trim:       bool,
+    flag_record_type:   Option<String>,
+    flag_type:          Option<String>,
+    flag_ebcdic:        bool,
+    flag_record_length: Option<usize>,
+    flag_skip_lines:    Option<u64>,
+    flag_output:        Option<String>,
+    flag_no_headers:    bool,
+}
+
+#[derive(Deserialize, Clone)]
+struct FieldSpec {
+    name:        String,
+    start:       usize,
+    length:      usize,
+    #[serde(default)]
+    record_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonLayout {
+    Fields(Vec<FieldSpec>),
+    Object { fields: Vec<FieldSpec> },
+}
+
+/// the output column, 0-based start & length of each field of a record type
+type Plan = Vec<(usize, usize, usize)>;
+
+/// the NL character, used as the record terminator in EBCDIC files
+const EBCDIC_NL: u8 = 0x15;
+
+/// EBCDIC code page 037 to Unicode
+#[rustfmt::skip]
+static EBCDIC_CP037: [char; 256] = [
+    '\u{00}', '\u{01}', '\u{02}', '\u{03}', '\u{9C}', '\u{09}', '\u{86}', '\u{7F}',
+    '\u{97}', '\u{8D}', '\u{8E}', '\u{0B}', '\u{0C}', '\u{0D}', '\u{0E}', '\u{0F}',
+    '\u{10}', '\u{11}', '\u{12}', '\u{13}', '\u{9D}', '\u{85}', '\u{08}', '\u{87}',
+    '\u{18}', '\u{19}', '\u{92}', '\u{8F}', '\u{1C}', '\u{1D}', '\u{1E}', '\u{1F}',
+    '\u{80}', '\u{81}', '\u{82}', '\u{83}', '\u{84}', '\u{0A}', '\u{17}', '\u{1B}',
+    '\u{88}', '\u{89}', '\u{8A}', '\u{8B}', '\u{8C}', '\u{05}', '\u{06}', '\u{07}',
+    '\u{90}', '\u{91}', '\u{16}', '\u{93}', '\u{94}', '\u{95}', '\u{96}', '\u{04}',
+    '\u{98}', '\u{99}', '\u{9A}', '\u{9B}', '\u{14}', '\u{15}', '\u{9E}', '\u{1A}',
+    '\u{20}', '\u{A0}', '\u{E2}', '\u{E4}', '\u{E0}', '\u{E1}', '\u{E3}', '\u{E5}',
+    '\u{E7}', '\u{F1}', '\u{A2}', '\u{2E}', '\u{3C}', '\u{28}', '\u{2B}', '\u{7C}',
+    '\u{26}', '\u{E9}', '\u{EA}', '\u{EB}', '\u{E8}', '\u{ED}', '\u{EE}', '\u{EF}',
+    '\u{EC}', '\u{DF}', '\u{21}', '\u{24}', '\u{2A}', '\u{29}', '\u{3B}', '\u{AC}',
+    '\u{2D}', '\u{2F}', '\u{C2}', '\u{C4}', '\u{C0}', '\u{C1}', '\u{C3}', '\u{C5}',
+    '\u{C7}', '\u{D1}', '\u{A6}', '\u{2C}', '\u{25}', '\u{5F}', '\u{3E}', '\u{3F}',
+    '\u{F8}', '\u{C9}', '\u{CA}', '\u{CB}', '\u{C8}', '\u{CD}', '\u{CE}', '\u{CF}',
+    '\u{CC}', '\u{60}', '\u{3A}', '\u{23}', '\u{40}', '\u{27}', '\u{3D}', '\u{22}',
+    '\u{D8}', '\u{61}', '\u{62}', '\u{63}', '\u{64}', '\u{65}', '\u{66}', '\u{67}',
+    '\u{68}', '\u{69}', '\u{AB}', '\u{BB}', '\u{F0}', '\u{FD}', '\u{FE}', '\u{B1}',
+    '\u{B0}', '\u{6A}', '\u{6B}', '\u{6C}', '\u{6D}', '\u{6E}', '\u{6F}', '\u{70}',
+    '\u{71}', '\u{72}', '\u{AA}', '\u{BA}', '\u{E6}', '\u{B8}', '\u{C6}', '\u{A4}',
+    '\u{B5}', '\u{7E}', '\u{73}', '\u{74}', '\u{75}', '\u{76}', '\u{77}', '\u{78}',
+    '\u{79}', '\u{7A}', '\u{A1}', '\u{BF}', '\u{D0}', '\u{DD}', '\u{DE}', '\u{AE}',
+    '\u{5E}', '\u{A3}', '\u{A5}', '\u{B7}', '\u{A9}', '\u{A7}', '\u{B6}', '\u{BC}',
+    '\u{BD}', '\u{BE}', '\u{5B}', '\u{5D}', '\u{AF}', '\u{A8}', '\u{B4}', '\u{D7}',
+    '\u{7B}', '\u{41}', '\u{42}', '\u{43}', '\u{44}', '\u{45}', '\u{46}', '\u{47}',
+    '\u{48}', '\u{49}', '\u{AD}', '\u{F4}', '\u{F6}', '\u{F2}', '\u{F3}', '\u{F5}',
+    '\u{7D}', '\u{4A}', '\u{4B}', '\u{4C}', '\u{4D}', '\u{4E}', '\u{4F}', '\u{50}',
+    '\u{51}', '\u{52}', '\u{B9}', '\u{FB}', '\u{FC}', '\u{F9}', '\u{FA}', '\u{FF}',
+    '\u{5C}', '\u{F7}', '\u{53}', '\u{54}', '\u{55}', '\u{56}', '\u{57}', '\u{58}',
+    '\u{59}', '\u{5A}', '\u{B2}', '\u{D4}', '\u{D6}', '\u{D2}', '\u{D3}', '\u{D5}',
+    '\u{30}', '\u{31}', '\u{32}', '\u{33}', '\u{34}', '\u{35}', '\u{36}', '\u{37}',
+    '\u{38}', '\u{39}', '\u{B3}', '\u{DB}', '\u{DC}', '\u{D9}', '\u{DA}', '\u{9F}',
+];
+
+/// reads newline-separated or fixed-length records
+struct RecordReader {
+    rdr:           io::BufReader<Box<dyn io::Read + Send + 'static>>,
+    record_length: Option<usize>,
+    ebcdic:        bool,
+}
+
+impl RecordReader {
+    /// reads the next non-empty record into buf, without its terminator.
+    /// Returns false at EOF.
+    fn read_record(&mut self, buf: &mut Vec<u8>) -> io::Result<bool> {
+        loop {
+            buf.clear();
+            let bytes_read = if let Some(record_length) = self.record_length {
+                (&mut self.rdr)
+                    .take(record_length as u64)
+                    .read_to_end(buf)?
+            } else {
+                let terminator = if self.ebcdic { EBCDIC_NL } else { b'\n' };
+                let bytes_read = self.rdr.read_until(terminator, buf)?;
+                if buf.last() == Some(&terminator) {
+                    buf.pop();
+                }
+                if !self.ebcdic && buf.last() == Some(&b'\r') {
+                    buf.pop();
+                }
+                bytes_read
+            };
+            if bytes_read == 0 {
+                return Ok(false);
+            }
+            if !buf.is_empty() {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+fn decode_record(raw: &[u8], ebcdic: bool, chars: &mut Vec<char>) {
+    chars.clear();
+    if ebcdic {
+        chars.extend(raw.iter().map(|&b| EBCDIC_CP037[b as usize]));
+    } else {
+        chars.extend(String::from_utf8_lossy(raw).chars());
+    }
+}
+
+/// copies the field at the 0-based start & length of the record into out
+fn extract_field(chars: &[char], start: usize, length: usize, trim: bool, out: &mut String) {
+    let end = start.saturating_add(length).min(chars.len());
+    let mut field = &chars[start.min(end)..end];
+    if trim {
+        let begin = field
+            .iter()
+            .position(|c| !c.is_whitespace())
+            .unwrap_or(field.len());
+        let end = field
+            .iter()
+            .rposition(|c| !c.is_whitespace())
+            .map_or(begin, |i| i + 1);
+        field = &field[begin..end];
+    }
+    out.clear();
+    out.extend(field);
+}
+
+/// parses a <start>:<length> position, returning a 0-based start
+fn parse_position(pos: &str) -> CliResult<(usize, usize)> {
+    if let Some((start, length)) = pos.split_once(':') {
+        if let (Ok(start), Ok(length)) = (
+            start.trim().parse::<usize>(),
+            length.trim().parse::<usize>(),
+        ) {
+            if start > 0 && length > 0 {
+                return Ok((start - 1, length));
+            }
+        }
+    }
+    fail_incorrectusage_clierror!(
+        "Invalid --record-type position: {pos}. It must be <start>:<length>, e.g. 1:2."
+    )
+}
+
+fn load_layout(path: &str) -> CliResult<Vec<FieldSpec>> {
+    let layout: Vec<FieldSpec> = if Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+    {
+        let file =
+            fs::File::open(path).map_err(|e| format!("Cannot open layout file {path}: {e}"))?;
+        match serde_json::from_reader(io::BufReader::new(file)) {
+            Ok(JsonLayout::Fields(fields) | JsonLayout::Object { fields }) => fields,
+            Err(e) => return fail_clierror!("Invalid JSON layout file {path}: {e}"),
+        }
+    } else {
+        let mut rdr = Config::new(Some(&path.to_string())).reader()?;
+        match rdr.deserialize().collect::<Result<Vec<FieldSpec>, _>>() {
+            Ok(fields) => fields,
+            Err(e) => return fail_clierror!("Invalid layout file {path}: {e}"),
+        }
+    };
+
+    if layout.is_empty() {
+        return fail_clierror!("The layout file {path} has no fields.");
+    }
+    for field in &layout {
+        if field.name.is_empty() || field.start == 0 || field.length == 0 {
+            return fail_clierror!(
+                "Invalid field in layout file {path}: \"{}\". Fields must have a name, a 1-based \
+                 start & a length greater than 0.",
+                field.name
+            );
+        }
+    }
+    Ok(layout)
+}
+
+/// infers the layout from the runs of positions that are blank in every sampled record
+fn detect_layout(sample: &[Vec<char>], no_headers: bool) -> Vec<FieldSpec> {
+    let width = sample.iter().map(Vec::len).max().unwrap_or_default();
+    let mut blank = vec![true; width];
+    for record in sample {
+        for (i, c) in record.iter().enumerate() {
+            if !c.is_whitespace() {
+                blank[i] = false;
+            }
+        }
+    }
+
+    let starts: Vec<usize> = (0..width)
+        .filter(|&i| !blank[i] && (i == 0 || blank[i - 1]))
+        .collect();
+
+    let mut name = String::new();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let length = starts.get(n + 1).copied().unwrap_or(width) - start;
+            if no_headers {
+                name.clear();
+            } else {
+                extract_field(&sample[0], start, length, true, &mut name);
+            }
+            FieldSpec {
+                name: if name.is_empty() {
+                    format!("column_{}", n + 1)
+                } else {
+                    name.clone()
+                },
+                start: start + 1,
+                length,
+                record_type: None,
+            }
+        })
+        .collect()
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_auto == args.flag_layout.is_some() {
+        return fail_incorrectusage_clierror!("Either --layout or --auto must be specified.");
+    }
+    if args.flag_record_length == Some(0) {
+        return fail_incorrectusage_clierror!("--record-length must be greater than 0.");
+    }
+    if args.flag_auto && args.flag_sample == 0 {
+        return fail_incorrectusage_clierror!("--sample must be greater than 0.");
+    }
+    let record_type_pos = args
+        .flag_record_type
+        .as_deref()
+        .map(parse_position)
+        .transpose()?;
+
+    let rconfig = Config::new(args.arg_input.as_ref());
+    let mut rdr = RecordReader {
+        rdr:           io::BufReader::with_capacity(
+            DEFAULT_RDR_BUFFER_CAPACITY,
+            rconfig.io_reader()?,
+        ),
+        record_length: args.flag_record_length,
+        ebcdic:        args.flag_ebcdic,
+    };
+    let mut raw: Vec<u8> = Vec::with_capacity(1024);
+
+    for _ in 0..args.flag_skip_lines.unwrap_or_default() {
+        if !rdr.read_record(&mut raw)? {
+            break;
+        }
+    }
+
+    // with --auto, the sampled records are also converted
+    let mut sample: Vec<Vec<char>> = Vec::new();
+    let layout = if let Some(ref layout_path) = args.flag_layout {
+        load_layout(layout_path)?
+    } else {
+        while sample.len() < args.flag_sample && rdr.read_record(&mut raw)? {
+            let mut chars = Vec::with_capacity(raw.len());
+            decode_record(&raw, args.flag_ebcdic, &mut chars);
+            sample.push(chars);
+        }
+        if sample.is_empty() {
+            return fail_clierror!("No records to infer the layout from.");
+        }
+        let layout = detect_layout(&sample, args.flag_no_headers);
+        if !args.flag_no_headers {
+            sample.remove(0);
+        }
+        layout
+    };
+
+    let multi_record = layout.iter().any(|field| field.record_type.is_some());
+    if multi_record && record_type_pos.is_none() {
+        return fail_incorrectusage_clierror!(
+            "The layout has record types. Use --record-type to set the position of the record \
+             type code."
+        );
+    }
+    if !multi_record && (record_type_pos.is_some() || args.flag_type.is_some()) {
+        return fail_incorrectusage_clierror!(
+            "--record-type & --type require a layout with record types."
+        );
+    }
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+
+    if args.flag_print_layout {
+        if multi_record {
+            wtr.write_record(["name", "start", "length", "record_type"])?;
+        } else {
+            wtr.write_record(["name", "start", "length"])?;
+        }
+        let mut start_buf = itoa::Buffer::new();
+        let mut length_buf = itoa::Buffer::new();
+        for field in &layout {
+            let mut row = vec![
+                field.name.as_str(),
+                start_buf.format(field.start),
+                length_buf.format(field.length),
+            ];
+            if multi_record {
+                row.push(field.record_type.as_deref().unwrap_or_default());
+            }
+            wtr.write_record(row)?;
+        }
+        return Ok(wtr.flush()?);
+    }
+
+    // the record_type column is only written when converting all record types
+    let write_record_type = multi_record && args.flag_type.is_none();
+    let mut headers: Vec<String> = Vec::with_capacity(layout.len() + 1);
+    if write_record_type {
+        headers.push("record_type".to_string());
+    }
+    let mut common_plan = Plan::new();
+    let mut type_plans: HashMap<String, Plan> = HashMap::new();
+    for field in &layout {
+        if let (Some(wanted), Some(record_type)) = (&args.flag_type, &field.record_type) {
+            if wanted != record_type {
+                continue;
+            }
+        }
+        let col = if let Some(col) = headers.iter().position(|h| *h == field.name) {
+            col
+        } else {
+            headers.push(field.name.clone());
+            headers.len() - 1
+        };
+        let plan = match field.record_type {
+            Some(ref record_type) => type_plans.entry(record_type.clone()).or_default(),
+            None => &mut common_plan,
+        };
+        if plan.iter().any(|&(c, ..)| c == col) {
+            return fail_clierror!("Duplicate field name in layout: {}", field.name);
+        }
+        plan.push((col, field.start - 1, field.length));
+    }
+    for plan in type_plans.values_mut() {
+        for &(col, start, length) in &common_plan {
+            if plan.iter().any(|&(c, ..)| c == col) {
+                return fail_clierror!("Duplicate field name in layout: {}", headers[col]);
+            }
+            plan.push((col, start, length));
+        }
+    }
+    if let Some(ref wanted) = args.flag_type {
+        if !type_plans.contains_key(wanted) {
+            return fail_incorrectusage_clierror!("Record type \"{wanted}\" is not in the layout.");
+        }
+    }
+
+    wtr.write_record(&headers)?;
+
+    let trim = !args.flag_no_trim;
+    let mut row: Vec<String> = vec![String::new(); headers.len()];
+    let mut record_type = String::new();
+    let mut chars: Vec<char> = Vec::with_capacity(1024);
+    let mut sample_iter = sample.into_iter();
+    let mut unknown_types = 0_u64;
+
+    loop {
+        if let Some(sampled) = sample_iter.next() {
+            chars = sampled;
+        } else if rdr.read_record(&mut raw)? {
+            decode_record(&raw, args.flag_ebcdic, &mut chars);
+        } else {
+            break;
+        }
+
+        let plan = if let Some((start, length)) = record_type_pos {
+            extract_field(&chars, start, length, true, &mut record_type);
+            if let Some(plan) = type_plans.get(&record_type) {
+                plan
+            } else {
+                if args.flag_type.is_none() {
+                    unknown_types += 1;
+                }
+                continue;
+            }
+        } else {
+            &common_plan
+        };
+
+        for value in &mut row {
+            value.clear();
+        }
+        if write_record_type {
+            row[0].push_str(&record_type);
+        }
+        for &(col, start, length) in plan {
+            extract_field(&chars, start, length, trim, &mut row[col]);
+        }
+        wtr.write_record(&row)?;
+    }
+
+    if unknown_types > 0 {
+        wwarn!("Skipped {unknown_types} records with a record type that's not in the layout.");
+    }
+
+    Ok(wtr.flush()?)
+}
@@ -181,7 +181,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     // prep progress bar
     #[cfg(feature = "feature_capable")]
     let show_progress =
-        (args.flag_progressbar || util::get_envvar_flag("QSV_PROGRESSBAR")) && !rconfig.is_stdin();
+        (args.flag_progressbar || util::progress_envvar_enabled()) && !rconfig.is_stdin();
     #[cfg(feature = "feature_capable")]
     let progress = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr_with_hz(5));
     #[cfg(feature = "feature_capable")]
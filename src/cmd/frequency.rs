@@ -116,7 +116,13 @@ Common options:
                            CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::{fs, io, sync::OnceLock};
+use std::{
+    fs, io,
+    sync::{
+        RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use crossbeam_channel;
 use indicatif::HumanCount;
@@ -161,11 +167,13 @@ pub struct Args {
 const NULL_VAL: &[u8] = b"(NULL)";
 const NON_UTF8_ERR: &str = "<Non-UTF8 ERROR>";
 
-static UNIQUE_COLUMNS: OnceLock<Vec<usize>> = OnceLock::new();
-static FREQ_ROW_COUNT: OnceLock<u64> = OnceLock::new();
+// reset on every run so frequency can be run more than once in the same process
+static UNIQUE_COLUMNS: RwLock<Vec<usize>> = RwLock::new(Vec::new());
+static FREQ_ROW_COUNT: AtomicU64 = AtomicU64::new(0);
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
+    FREQ_ROW_COUNT.store(0, Ordering::Relaxed);
     let rconfig = args.rconfig();
 
     // we're loading the entire file into memory, we need to check avail mem
@@ -193,13 +201,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut row: Vec<&[u8]>;
     let mut all_unique_header: bool;
 
-    // safety: we know that UNIQUE_COLUMNS has been previously set when compiling frequencies
-    // by sel_headers fn
-    let all_unique_headers = UNIQUE_COLUMNS.get().unwrap();
+    // UNIQUE_COLUMNS has been previously set when compiling frequencies by sel_headers fn
+    let all_unique_headers = UNIQUE_COLUMNS.read().unwrap().clone();
 
     wtr.write_record(vec!["field", "value", "count", "percentage"])?;
     let head_ftables = headers.iter().zip(tables);
-    let row_count = *FREQ_ROW_COUNT.get().unwrap_or(&0);
+    let row_count = FREQ_ROW_COUNT.load(Ordering::Relaxed);
 
     let all_unique_text = args.flag_all_unique_text.as_bytes();
 
@@ -428,7 +435,7 @@ impl Args {
         let mut field_buffer: Vec<u8> = Vec::with_capacity(1024);
         let mut row_buffer: csv::ByteRecord = csv::ByteRecord::with_capacity(200, nsel_len);
 
-        let all_unique_headers = UNIQUE_COLUMNS.get().unwrap();
+        let all_unique_headers = UNIQUE_COLUMNS.read().unwrap().clone();
 
         // assign flags to local variables for faster access
         let flag_no_nulls = self.flag_no_nulls;
@@ -515,6 +522,8 @@ impl Args {
             flag_prefer_dmy:      false,
             flag_force:           false,
             flag_stdout:          false,
+            flag_format:          None,
+            flag_detect_formats:  false,
             flag_jobs:            Some(util::njobs(self.flag_jobs)),
             flag_no_headers:      self.flag_no_headers,
             flag_delimiter:       self.flag_delimiter,
@@ -563,7 +572,7 @@ impl Args {
             .get("qsv__rowcount")
             .and_then(|count| count.parse::<u64>().ok())
             .unwrap_or_else(|| util::count_rows(&self.rconfig()).unwrap_or_default());
-        FREQ_ROW_COUNT.set(row_count).unwrap();
+        FREQ_ROW_COUNT.store(row_count, Ordering::Relaxed);
 
         // Most datasets have relatively few columns with all unique values (e.g. ID columns)
         // so pre-allocate space for 5 as a reasonable default capacity
@@ -588,9 +597,9 @@ impl Args {
         let headers = rdr.byte_headers()?;
         let all_unique_headers_vec = self.get_unique_headers(headers)?;
 
-        UNIQUE_COLUMNS
-            .set(all_unique_headers_vec)
-            .map_err(|_| "Cannot set UNIQUE_COLUMNS")?;
+        *UNIQUE_COLUMNS
+            .write()
+            .map_err(|_| "Cannot set UNIQUE_COLUMNS")? = all_unique_headers_vec;
 
         let sel = self.rconfig().selection(headers)?;
         Ok((sel.select(headers).map(<[u8]>::to_vec).collect(), sel))
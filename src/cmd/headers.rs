@@ -16,7 +16,11 @@ Usage:
 headers arguments:
     <input>...             The CSV file(s) to read. Use '-' for standard input.
                            If input is a directory, all files in the directory will
-                           be read as input.
+                           be read as input. Set QSV_INPUT_RECURSIVE to also read the
+                           files in its subdirectories.
+                           If input is a glob pattern (e.g. 'data/**/*.csv' - quote it
+                           so it is not expanded by the shell), all matching files
+                           will be read as input, sorted by path.
                            If the input is a file with a '.infile-list' extension,
                            the file will be read as a list of input files.
                            If the input are snappy-compressed files(s), it will be
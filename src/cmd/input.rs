@@ -11,12 +11,17 @@ options. Similarly, --skip-lastlines allows epilogue lines to be skipped.
 
 Finally, non UTF-8 encoded files are "lossy" saved to UTF-8 by default, replacing all
 invalid UTF-8 sequences with �. Note though that this is not true transcoding.
+You can change this behavior with the --encoding-errors option.
 
-If you need to properly transcode non UTF-8 files, you'll need to use a tool like `iconv`
-before processing it with qsv - e.g. to convert an ISO-8859-1 encoded file to UTF-8:
-    `iconv -f ISO-8859-1 -t UTF-8 input.csv -o utf8_output.csv`.
+Files with multi-character field delimiters (e.g. "||", "~|~" or two tabs) can be read with
+the --multi-delimiter option, or with --regex-delimiter for delimiters that vary. Custom record
+terminators (e.g. "|$|" or the ASCII record separator) can be set with --record-terminator.
+These files are normalized to a standard CSV with a single-byte delimiter. Note that quoting
+is not supported when using these options - quote characters are treated as regular data.
 
-You can change this behavior with the --encoding-errors option.
+To properly transcode non UTF-8 files (e.g. Windows-1252, Latin-1, Shift_JIS or UTF-16),
+use the --encoding option with the encoding's label or "auto" to detect it:
+    `qsv input --encoding windows-1252 input.csv -o utf8_output.csv`.
 
 See https://github.com/dathere/qsv#utf-8-encoding for more details.
 
@@ -53,6 +58,25 @@ input options:
     --trim-fields            Trim leading & trailing whitespace from field values.
     --comment <char>         The comment character to use. When set, lines
                              starting with this character will be skipped.
+    --multi-delimiter <arg>  A multi-character field delimiter (e.g. "||" or "~|~").
+                             The escapes \t, \n, \r, \\ & \xHH are supported,
+                             e.g. "\t\t" for two tabs.
+    --regex-delimiter <arg>  A regular expression that matches the field delimiter,
+                             e.g. "\s*\|\s*" or "\t+".
+    --record-terminator <arg>
+                             A custom record terminator (e.g. "|$|" or "\x1e").
+                             Supports the same escapes as --multi-delimiter.
+                             When not set, records are terminated by LF or CRLF.
+                             If set without --multi-delimiter or --regex-delimiter,
+                             fields are delimited by --delimiter.
+    --encoding <label>       Transcode the input from this character encoding to UTF-8.
+                             Any WHATWG encoding label is supported (e.g. windows-1252,
+                             latin1, shift_jis, utf-16le, gbk, euc-kr). See
+                             https://encoding.spec.whatwg.org/#names-and-labels
+                             Use "auto" to detect the encoding from the start of the input
+                             (see `qsv sniff`). A BOM, if present, takes precedence.
+                             Malformed sequences are replaced with �, so
+                             --encoding-errors does not apply when this is set.
     --encoding-errors <arg>  How to handle UTF-8 encoding errors.
                              Possible values: replace, skip, strict.
                                replace: Replace invalid UTF-8 sequences with �.
@@ -67,7 +91,11 @@ Common options:
                              Must be a single character. (default: ,)
 "#;
 
-use std::{env, str::FromStr};
+use std::{
+    env,
+    io::{self, BufRead, Read},
+    str::FromStr,
+};
 
 use log::{debug, info, warn};
 use serde::Deserialize;
@@ -75,10 +103,180 @@ use strum_macros::EnumString;
 
 use crate::{
     CliResult,
-    config::{Config, Delimiter},
+    config::{Config, DEFAULT_RDR_BUFFER_CAPACITY, Delimiter},
     util,
 };
 
+/// how fields are delimited when not reading standard CSV
+enum FieldDelimiter {
+    Literal(Vec<u8>),
+    Regex(regex::bytes::Regex),
+}
+
+/// reads records with multi-character/regex field delimiters & custom record terminators,
+/// which the csv crate doesn't support. Quoting is not supported.
+struct SplitReader {
+    rdr:        io::BufReader<Box<dyn io::Read + Send + 'static>>,
+    delimiter:  FieldDelimiter,
+    terminator: Option<Vec<u8>>,
+    comment:    Option<u8>,
+    trim:       bool,
+    buf:        Vec<u8>,
+    /// a CR read after a record terminator that wasn't followed by LF,
+    /// which belongs to the next record
+    pending_cr: bool,
+}
+
+impl SplitReader {
+    /// reads the next record into buf, without its terminator. Returns false at EOF.
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.buf.clear();
+        if self.pending_cr {
+            self.buf.push(b'\r');
+            self.pending_cr = false;
+        }
+        let Some(ref terminator) = self.terminator else {
+            if self.rdr.read_until(b'\n', &mut self.buf)? == 0 {
+                return Ok(false);
+            }
+            if self.buf.last() == Some(&b'\n') {
+                self.buf.pop();
+                if self.buf.last() == Some(&b'\r') {
+                    self.buf.pop();
+                }
+            }
+            return Ok(true);
+        };
+
+        // safety: the terminator is validated to be non-empty
+        let last_byte = *terminator.last().unwrap();
+        while self.rdr.read_until(last_byte, &mut self.buf)? > 0 {
+            if self.buf.ends_with(terminator) {
+                self.buf.truncate(self.buf.len() - terminator.len());
+                self.skip_newline()?;
+                return Ok(true);
+            }
+        }
+        Ok(!self.buf.is_empty())
+    }
+
+    /// skips a single LF or CRLF directly after a record terminator, as records with
+    /// custom terminators are often also put on separate lines (e.g. "1||2|$|\n")
+    fn skip_newline(&mut self) -> io::Result<()> {
+        let next = self.rdr.fill_buf()?;
+        // the CRLF may be split across reads
+        let split_crlf = next == b"\r";
+        let newline_len = match next {
+            [b'\n', ..] | [b'\r'] => 1,
+            [b'\r', b'\n', ..] => 2,
+            _ => 0,
+        };
+        self.rdr.consume(newline_len);
+        if split_crlf {
+            if self.rdr.fill_buf()?.first() == Some(&b'\n') {
+                self.rdr.consume(1);
+            } else {
+                self.pending_cr = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_byte_record(&mut self, record: &mut csv::ByteRecord) -> io::Result<bool> {
+        loop {
+            if !self.read_line()? {
+                return Ok(false);
+            }
+            // skip empty records, e.g. blank lines between custom record terminators
+            if self.buf.iter().all(|&b| b == b'\n' || b == b'\r') {
+                continue;
+            }
+            if self.comment.is_some() && self.buf.first() == self.comment.as_ref() {
+                continue;
+            }
+
+            record.clear();
+            match self.delimiter {
+                FieldDelimiter::Literal(ref delimiter) => {
+                    let mut rest = self.buf.as_slice();
+                    while let Some(pos) = rest
+                        .windows(delimiter.len())
+                        .position(|window| window == delimiter.as_slice())
+                    {
+                        record.push_field(&rest[..pos]);
+                        rest = &rest[pos + delimiter.len()..];
+                    }
+                    record.push_field(rest);
+                },
+                FieldDelimiter::Regex(ref re) => {
+                    for field in re.split(&self.buf) {
+                        record.push_field(field);
+                    }
+                },
+            }
+            if self.trim {
+                record.trim();
+            }
+            return Ok(true);
+        }
+    }
+}
+
+enum RecordReader {
+    Csv(csv::Reader<Box<dyn io::Read + Send + 'static>>),
+    Split(SplitReader),
+}
+
+impl RecordReader {
+    fn read_byte_record(&mut self, record: &mut csv::ByteRecord) -> CliResult<bool> {
+        Ok(match self {
+            RecordReader::Csv(rdr) => rdr.read_byte_record(record)?,
+            RecordReader::Split(rdr) => rdr.read_byte_record(record)?,
+        })
+    }
+}
+
+/// decodes the \t, \n, \r, \\ & \xHH escapes of a delimiter or record terminator
+fn unescape(arg: &str, option: &str) -> CliResult<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(arg.len());
+    let mut bytes = arg.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'\\' {
+            unescaped.push(b);
+            continue;
+        }
+        match bytes.next() {
+            Some(b't') => unescaped.push(b'\t'),
+            Some(b'n') => unescaped.push(b'\n'),
+            Some(b'r') => unescaped.push(b'\r'),
+            Some(b'\\') => unescaped.push(b'\\'),
+            Some(b'x') => {
+                let hex = [bytes.next().unwrap_or(b' '), bytes.next().unwrap_or(b' ')];
+                let Some(byte) = std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                else {
+                    return fail_incorrectusage_clierror!(
+                        "Invalid \\x escape in {option}: {arg}. It must be followed by two hex \
+                         digits."
+                    );
+                };
+                unescaped.push(byte);
+            },
+            _ => {
+                return fail_incorrectusage_clierror!(
+                    "Invalid escape in {option}: {arg}. Valid escapes are \\t, \\n, \\r, \\\\ & \
+                     \\xHH."
+                );
+            },
+        }
+    }
+    if unescaped.is_empty() {
+        return fail_incorrectusage_clierror!("{option} cannot be empty.");
+    }
+    Ok(unescaped)
+}
+
 #[derive(EnumString, Clone, Copy)]
 #[strum(ascii_case_insensitive)]
 #[allow(non_camel_case_types)]
@@ -90,20 +288,24 @@ enum EncodingHandling {
 
 #[derive(Deserialize)]
 struct Args {
-    arg_input:            Option<String>,
-    flag_output:          Option<String>,
-    flag_delimiter:       Option<Delimiter>,
-    flag_quote:           Delimiter,
-    flag_escape:          Option<Delimiter>,
-    flag_no_quoting:      bool,
-    flag_quote_style:     String,
-    flag_skip_lines:      Option<u64>,
-    flag_skip_lastlines:  Option<u64>,
-    flag_auto_skip:       bool,
-    flag_trim_headers:    bool,
-    flag_trim_fields:     bool,
-    flag_comment:         Option<char>,
-    flag_encoding_errors: String,
+    arg_input:              Option<String>,
+    flag_output:            Option<String>,
+    flag_delimiter:         Option<Delimiter>,
+    flag_quote:             Delimiter,
+    flag_escape:            Option<Delimiter>,
+    flag_no_quoting:        bool,
+    flag_quote_style:       String,
+    flag_skip_lines:        Option<u64>,
+    flag_skip_lastlines:    Option<u64>,
+    flag_auto_skip:         bool,
+    flag_trim_headers:      bool,
+    flag_trim_fields:       bool,
+    flag_comment:           Option<char>,
+    flag_multi_delimiter:   Option<String>,
+    flag_regex_delimiter:   Option<String>,
+    flag_record_terminator: Option<String>,
+    flag_encoding:          Option<String>,
+    flag_encoding_errors:   String,
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -123,6 +325,57 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         );
     };
 
+    let field_delimiter = match (&args.flag_multi_delimiter, &args.flag_regex_delimiter) {
+        (Some(_), Some(_)) => {
+            return fail_incorrectusage_clierror!(
+                "--multi-delimiter and --regex-delimiter cannot be used together."
+            );
+        },
+        (Some(delimiter), None) => Some(FieldDelimiter::Literal(unescape(
+            delimiter,
+            "--multi-delimiter",
+        )?)),
+        (None, Some(pattern)) => match regex::bytes::Regex::new(pattern) {
+            Ok(re) => Some(FieldDelimiter::Regex(re)),
+            Err(e) => return fail_incorrectusage_clierror!("Invalid --regex-delimiter: {e}"),
+        },
+        (None, None) if args.flag_record_terminator.is_some() => {
+            Some(FieldDelimiter::Literal(vec![
+                args.flag_delimiter.map_or(b',', Delimiter::as_byte),
+            ]))
+        },
+        (None, None) => None,
+    };
+    let record_terminator = args
+        .flag_record_terminator
+        .as_deref()
+        .map(|terminator| unescape(terminator, "--record-terminator"))
+        .transpose()?;
+    if field_delimiter.is_some() && (args.flag_auto_skip || args.flag_skip_lastlines.is_some()) {
+        return fail_incorrectusage_clierror!(
+            "--auto-skip and --skip-lastlines cannot be used with --multi-delimiter, \
+             --regex-delimiter or --record-terminator."
+        );
+    }
+
+    let transcode_from = match args.flag_encoding.as_deref() {
+        None => None,
+        Some(label) if label.eq_ignore_ascii_case("auto") => None,
+        Some(label) => match encoding_rs::Encoding::for_label(label.trim().as_bytes()) {
+            Some(encoding) => Some(encoding),
+            None => {
+                return fail_incorrectusage_clierror!(
+                    "Invalid --encoding: {label}. See \
+                     https://encoding.spec.whatwg.org/#names-and-labels for valid labels."
+                );
+            },
+        },
+    };
+    let auto_encoding = args
+        .flag_encoding
+        .as_deref()
+        .is_some_and(|label| label.eq_ignore_ascii_case("auto"));
+
     if args.flag_auto_skip {
         // safety: we are in single-threaded code.
         unsafe { std::env::set_var("QSV_SNIFF_PREAMBLE", "1") };
@@ -184,7 +437,41 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         total_lines = row_count.saturating_sub(skip_llines);
     }
 
-    let mut rdr = rconfig.reader()?;
+    let transcoding = transcode_from.is_some() || auto_encoding;
+    let mut rdr = if transcoding || field_delimiter.is_some() {
+        let mut io_rdr = rconfig.io_reader()?;
+        if transcoding {
+            let encoding = if let Some(encoding) = transcode_from {
+                encoding
+            } else {
+                let mut sample = Vec::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY);
+                io_rdr
+                    .by_ref()
+                    .take(DEFAULT_RDR_BUFFER_CAPACITY as u64)
+                    .read_to_end(&mut sample)?;
+                let encoding = util::detect_encoding(&sample);
+                info!("detected encoding: {}", encoding.name());
+                io_rdr = Box::new(io::Cursor::new(sample).chain(io_rdr));
+                encoding
+            };
+            io_rdr = Box::new(util::TranscodingReader::new(io_rdr, encoding));
+        }
+        if let Some(delimiter) = field_delimiter {
+            RecordReader::Split(SplitReader {
+                rdr: io::BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, io_rdr),
+                delimiter,
+                terminator: record_terminator,
+                comment: comment_char,
+                trim: args.flag_trim_fields,
+                buf: Vec::with_capacity(1024),
+                pending_cr: false,
+            })
+        } else {
+            RecordReader::Csv(rconfig.from_reader(io_rdr))
+        }
+    } else {
+        RecordReader::Csv(rconfig.reader()?)
+    };
     let mut wtr = wconfig.writer()?;
     let mut row = csv::ByteRecord::new();
     let mut str_row = csv::StringRecord::new();
@@ -98,6 +98,7 @@ use serde::Deserialize;
 
 use crate::{
     CliResult,
+    clitypes::{self, ErrorCode},
     config::{Config, Delimiter, SeekRead},
     index::Indexed,
     select::{SelectColumns, Selection},
@@ -438,7 +439,6 @@ impl Args {
         })
     }
 
-    #[allow(clippy::unused_self)]
     fn get_selections<R: io::Read>(
         &self,
         rconf1: &Config,
@@ -448,9 +448,26 @@ impl Args {
     ) -> CliResult<(Selection, Selection)> {
         let headers1 = rdr1.byte_headers()?;
         let headers2 = rdr2.byte_headers()?;
-        let select1 = rconf1.selection(headers1)?;
-        let select2 = rconf2.selection(headers2)?;
+        let select1 = rconf1.selection(headers1).inspect_err(|_| {
+            clitypes::set_error_input(&self.arg_input1);
+            clitypes::set_error_context(
+                ErrorCode::JoinKeyError,
+                None,
+                None,
+                Some(self.arg_columns1.raw().to_string()),
+            );
+        })?;
+        let select2 = rconf2.selection(headers2).inspect_err(|_| {
+            clitypes::set_error_input(&self.arg_input2);
+            clitypes::set_error_context(
+                ErrorCode::JoinKeyError,
+                None,
+                None,
+                Some(self.arg_columns2.raw().to_string()),
+            );
+        })?;
         if select1.len() != select2.len() {
+            clitypes::set_error_context(ErrorCode::JoinKeyError, None, None, None);
             return fail_incorrectusage_clierror!(
                 "Column selections must have the same number of columns, but found column \
                  selections with {} and {} columns.",
@@ -879,6 +879,8 @@ impl Args {
                 flag_prefer_dmy:      false,
                 flag_force:           false,
                 flag_stdout:          false,
+                flag_format:          None,
+                flag_detect_formats:  false,
                 flag_jobs:            Some(util::njobs(None)),
                 flag_no_headers:      false,
                 flag_delimiter:       args.flag_delimiter,
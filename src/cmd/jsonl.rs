@@ -1,3 +1,4 @@
+#[cfg_attr(feature = "lib", allow(dead_code))]
 static USAGE: &str = r#"
 Convert newline-delimited JSON (JSONL/NDJSON) to CSV.
 
@@ -48,6 +49,7 @@ use crate::{
     util,
 };
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[derive(Deserialize)]
 struct Args {
     arg_input:          Option<String>,
@@ -151,6 +153,35 @@ fn json_line_to_csv_record(value: &Value, headers: &[Vec<String>]) -> csv::Strin
     record
 }
 
+/// Converts JSONL to CSV sequentially, inferring the headers from the first line.
+/// This is used by `Config` to read JSONL files as CSV in other commands.
+pub fn jsonl_to_csv<R: BufRead, W: io::Write>(rdr: R, wtr: &mut csv::Writer<W>) -> io::Result<()> {
+    let mut headers: Vec<Vec<String>> = Vec::new();
+    let mut headers_emitted = false;
+
+    for (idx, line) in rdr.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Could not parse JSONL line {} as JSON: {e}", idx + 1),
+            )
+        })?;
+        if !headers_emitted {
+            headers = infer_headers(&value);
+            wtr.write_record(headers.iter().map(|v| v.join(".")))?;
+            headers_emitted = true;
+        }
+        wtr.write_record(&json_line_to_csv_record(&value, &headers))?;
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
     let mut wtr = Config::new(args.flag_output.as_ref())
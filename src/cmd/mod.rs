@@ -8,6 +8,7 @@ pub mod behead;
 pub mod cat;
 #[cfg(feature = "clipboard")]
 pub mod clipboard;
+pub mod completions;
 pub mod count;
 pub mod datefmt;
 pub mod dedup;
@@ -31,6 +32,8 @@ pub mod fetchpost;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod fill;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod fixedwidth;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod fixlengths;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod flatten;
@@ -61,6 +64,8 @@ pub mod lens;
 pub mod luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 pub mod partition;
+#[cfg(feature = "feature_capable")]
+pub mod pipeline;
 #[cfg(all(
     feature = "polars",
     any(feature = "feature_capable", feature = "datapusher_plus")
@@ -70,10 +75,14 @@ pub mod pivotp;
 pub mod pro;
 #[cfg(feature = "prompt")]
 pub mod prompt;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod prune;
 pub mod pseudo;
 #[cfg(all(feature = "python", feature = "feature_capable"))]
 pub mod python;
 pub mod rename;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub mod repair;
 pub mod replace;
 pub mod reverse;
 pub mod safenames;
@@ -83,6 +92,8 @@ pub mod schema;
 pub mod search;
 pub mod searchset;
 pub mod select;
+#[cfg(all(feature = "serve", feature = "feature_capable"))]
+pub mod serve;
 pub mod slice;
 pub mod snappy;
 pub mod sniff;
@@ -0,0 +1,717 @@
+static USAGE: &str = r#"
+Run a pipeline of qsv commands from a declarative YAML, TOML or JSON spec.
+
+Each step of the pipeline runs a qsv command with its arguments. Steps are dispatched
+directly to the qsv commands - no shell is involved. A step's output can be given a
+name, which later steps reference as ${name} in their arguments.
+
+Named outputs are written to a work directory. By default, a temporary directory is
+used (on a RAM-backed tmpfs like /dev/shm if available, so intermediate outputs are
+handed off in memory) and deleted when the pipeline finishes. Use --workdir to keep
+the intermediate outputs, which also allows the pipeline to be resumed with --resume.
+
+A spec has a list of steps, where each step has:
+  name     A unique name for the step. Required.
+  command  The qsv command to run (e.g. "select").
+  args     The list of arguments of the command. Numbers & booleans are converted to
+           strings. ${name} is replaced with the path of the named output of an
+           earlier step.
+  output   The name of the step's output. The output is written to
+           <workdir>/<output>.csv (or <workdir>/<output> if the name has an extension)
+           by appending "--output <path>" to the arguments, so the command must
+           support the --output option. Steps without an output write to stdout.
+  parallel Instead of a command, a list of steps that are run in parallel.
+           Parallel steps can only reference the outputs of the steps before them,
+           and cannot be nested.
+
+For example, the following YAML spec:
+
+  steps:
+    - name: pick
+      command: select
+      args: ["name,state,population", "cities.csv"]
+      output: picked
+    - name: stats_and_dedup
+      parallel:
+        - name: stats
+          command: stats
+          args: ["${picked}", "--everything"]
+          output: picked_stats
+        - name: dedup
+          command: dedup
+          args: ["${picked}"]
+          output: deduped
+    - name: sort
+      command: sort
+      args: ["--select", "population", "--numeric", "--reverse", "${deduped}"]
+
+is equivalent to:
+
+  $ qsv select name,state,population cities.csv --output picked.csv
+  $ qsv stats picked.csv --everything --output picked_stats.csv &
+  $ qsv dedup picked.csv --output deduped.csv
+  $ qsv sort --select population --numeric --reverse deduped.csv
+
+The first time a command is used in a pipeline, it is run in-process. Parallel steps &
+subsequent uses of the same command are run in a separate qsv process.
+
+When the pipeline finishes (or fails), a table with the mode, status & elapsed time of
+each step is written to stderr, unless --quiet is set.
+
+With --workdir, the completed steps are recorded in <workdir>/pipeline.state.json.
+With --resume, completed steps whose spec is unchanged & whose outputs still exist are
+skipped, up to the first step that needs to be run. All the steps after it are run.
+
+Examples:
+
+Run the pipeline in pipeline.yaml:
+  $ qsv pipeline pipeline.yaml
+
+Run the pipeline, keeping the intermediate outputs in the work directory:
+  $ qsv pipeline pipeline.toml --workdir work
+
+Resume the pipeline after fixing a failed step:
+  $ qsv pipeline pipeline.toml --workdir work --resume
+
+Show the commands that would be run:
+  $ qsv pipeline pipeline.yaml --dry-run
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_pipeline.rs.
+
+Usage:
+    qsv pipeline [options] <spec>
+    qsv pipeline --help
+
+pipeline arguments:
+    <spec>                 The pipeline spec. Its format is determined by its extension:
+                           .yaml/.yml for YAML, .toml for TOML & .json for JSON.
+
+pipeline options:
+    --workdir <dir>        The directory where the named outputs are written.
+                           It is created if it does not exist & it is not deleted
+                           when the pipeline finishes.
+                           If not set, a temporary directory is used.
+    --resume               Skip the completed steps of a previous run with the same
+                           --workdir. Requires --workdir.
+    --dry-run              Do not run the pipeline. Write the steps & their commands
+                           to stdout.
+    --report <file>        Write a JSON report with the status & timings of each step
+                           to <file>.
+
+Common options:
+    -h, --help             Display this message
+    -q, --quiet            Do not write the table of step timings to stderr.
+"#;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tabwriter::TabWriter;
+
+use crate::{CliResult, Command, util};
+
+const STATE_FILENAME: &str = "pipeline.state.json";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_spec:     String,
+    flag_workdir: Option<String>,
+    flag_resume:  bool,
+    flag_dry_run: bool,
+    flag_report:  Option<String>,
+    flag_quiet:   bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PipelineSpec {
+    steps: Vec<StepSpec>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct StepSpec {
+    name:     String,
+    command:  Option<String>,
+    #[serde(default)]
+    args:     Vec<StepArg>,
+    output:   Option<String>,
+    parallel: Option<Vec<StepSpec>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+enum StepArg {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl StepArg {
+    fn to_arg_string(&self) -> String {
+        match self {
+            StepArg::Str(s) => s.clone(),
+            StepArg::Int(n) => n.to_string(),
+            StepArg::Float(f) => f.to_string(),
+            StepArg::Bool(b) => b.to_string(),
+        }
+    }
+}
+
+/// a command to run, with its resolved arguments
+struct Task {
+    name:    String,
+    command: String,
+    argv:    Vec<String>,
+    output:  Option<PathBuf>,
+}
+
+/// a top-level step of the pipeline, with its tasks
+/// (one task for a command step, one task per branch for a parallel step)
+struct PlannedStep {
+    spec:  StepSpec,
+    tasks: Vec<Task>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct CompletedStep {
+    name: String,
+    spec: serde_json::Value,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct PipelineState {
+    completed: Vec<CompletedStep>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    InProcess,
+    Subprocess,
+    Parallel,
+    Skipped,
+}
+
+impl Mode {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Mode::InProcess => "in-process",
+            Mode::Subprocess => "subprocess",
+            Mode::Parallel => "parallel",
+            Mode::Skipped => "skipped",
+        }
+    }
+}
+
+struct StepTiming {
+    name:    String,
+    command: String,
+    mode:    Mode,
+    status:  &'static str,
+    elapsed: Duration,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_resume && args.flag_workdir.is_none() {
+        return fail_incorrectusage_clierror!("--resume requires --workdir.");
+    }
+
+    let spec = load_spec(Path::new(&args.arg_spec))?;
+
+    // the temporary work directory is deleted when it goes out of scope
+    let (workdir, _temp_workdir) = if let Some(ref dir) = args.flag_workdir {
+        fs::create_dir_all(dir)?;
+        (PathBuf::from(dir), None)
+    } else {
+        let temp_workdir = temp_workdir()?;
+        (temp_workdir.path().to_path_buf(), Some(temp_workdir))
+    };
+    log::info!("pipeline workdir: {}", workdir.display());
+
+    let program = argv.first().copied().unwrap_or("qsv");
+    let planned = plan_pipeline(spec, &workdir, program)?;
+
+    let state_path = workdir.join(STATE_FILENAME);
+    let previous_state = if args.flag_resume && state_path.exists() {
+        read_state(&state_path)?
+    } else {
+        PipelineState::default()
+    };
+
+    let qsv_bin = std::env::current_exe()?;
+    let mut in_process_cmds: HashSet<String> = HashSet::new();
+    let mut resuming = args.flag_resume;
+    let mut state = PipelineState::default();
+    let mut timings: Vec<StepTiming> = Vec::new();
+    let mut failure: Option<String> = None;
+    let pipeline_start = Instant::now();
+
+    for step in planned {
+        let spec_value = serde_json::to_value(&step.spec)?;
+        let is_parallel = step.spec.parallel.is_some();
+
+        if resuming {
+            let completed = previous_state
+                .completed
+                .iter()
+                .any(|c| c.name == step.spec.name && c.spec == spec_value)
+                && step
+                    .tasks
+                    .iter()
+                    .all(|task| task.output.as_ref().is_none_or(|path| path.exists()));
+            if completed {
+                for task in &step.tasks {
+                    if args.flag_dry_run {
+                        wout!("# {} (skipped): {}", task.name, task.argv[1..].join(" "));
+                    }
+                    timings.push(StepTiming {
+                        name:    task.name.clone(),
+                        command: task.command.clone(),
+                        mode:    Mode::Skipped,
+                        status:  "skipped",
+                        elapsed: Duration::ZERO,
+                    });
+                }
+                state.completed.push(CompletedStep {
+                    name: step.spec.name,
+                    spec: spec_value,
+                });
+                continue;
+            }
+            // once a step is run, all the following steps are run
+            resuming = false;
+        }
+
+        if is_parallel {
+            if args.flag_dry_run {
+                for task in &step.tasks {
+                    wout!("# {} (parallel): {}", task.name, task.argv[1..].join(" "));
+                }
+                continue;
+            }
+            let mut branch_results = Vec::with_capacity(step.tasks.len());
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = step
+                    .tasks
+                    .iter()
+                    .map(|task| {
+                        let qsv_bin = &qsv_bin;
+                        scope.spawn(move || {
+                            let start = Instant::now();
+                            let result = run_subprocess(qsv_bin, &task.argv);
+                            (result, start.elapsed())
+                        })
+                    })
+                    .collect();
+                for handle in handles {
+                    // safety: run_subprocess does not panic
+                    branch_results.push(handle.join().unwrap());
+                }
+            });
+            for (task, (result, elapsed)) in step.tasks.iter().zip(branch_results) {
+                timings.push(StepTiming {
+                    name: task.name.clone(),
+                    command: task.command.clone(),
+                    mode: Mode::Parallel,
+                    status: if result.is_ok() { "ok" } else { "failed" },
+                    elapsed,
+                });
+                if let Err(e) = result {
+                    if failure.is_none() {
+                        failure = Some(format!(
+                            "Step \"{}\" ({}) failed: {e}",
+                            task.name, task.command
+                        ));
+                    }
+                }
+            }
+        } else {
+            // safety: a command step always has exactly one task
+            let task = &step.tasks[0];
+            let mode = if in_process_cmds.contains(&task.command) {
+                Mode::Subprocess
+            } else {
+                Mode::InProcess
+            };
+            if args.flag_dry_run {
+                wout!(
+                    "# {} ({}): {}",
+                    task.name,
+                    mode.as_str(),
+                    task.argv[1..].join(" ")
+                );
+                if mode == Mode::InProcess {
+                    in_process_cmds.insert(task.command.clone());
+                }
+                continue;
+            }
+            let start = Instant::now();
+            let result = if mode == Mode::InProcess {
+                in_process_cmds.insert(task.command.clone());
+                run_in_process(&task.command, &task.argv)
+            } else {
+                run_subprocess(&qsv_bin, &task.argv)
+            };
+            timings.push(StepTiming {
+                name: task.name.clone(),
+                command: task.command.clone(),
+                mode,
+                status: if result.is_ok() { "ok" } else { "failed" },
+                elapsed: start.elapsed(),
+            });
+            if let Err(e) = result {
+                failure = Some(format!(
+                    "Step \"{}\" ({}) failed: {e}",
+                    task.name, task.command
+                ));
+            }
+        }
+
+        if failure.is_some() {
+            break;
+        }
+        state.completed.push(CompletedStep {
+            name: step.spec.name,
+            spec: spec_value,
+        });
+        if args.flag_workdir.is_some() {
+            write_state(&state_path, &state)?;
+        }
+    }
+
+    if args.flag_dry_run {
+        return Ok(());
+    }
+
+    let pipeline_elapsed = pipeline_start.elapsed();
+    if !args.flag_quiet {
+        write_timings_table(&timings, pipeline_elapsed)?;
+    }
+    if let Some(ref report_path) = args.flag_report {
+        write_report(
+            Path::new(report_path),
+            &args.arg_spec,
+            &workdir,
+            failure.as_deref(),
+            &timings,
+            pipeline_elapsed,
+        )?;
+    }
+
+    match failure {
+        Some(msg) => fail_clierror!("{msg}"),
+        None => Ok(()),
+    }
+}
+
+/// load the pipeline spec, using its extension to determine its format
+fn load_spec(path: &Path) -> CliResult<PipelineSpec> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => return fail_clierror!("Cannot read pipeline spec {}: {e}", path.display()),
+    };
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let spec: Result<PipelineSpec, String> = match extension.as_str() {
+        "yaml" | "yml" => serde_norway::from_str(&contents).map_err(|e| e.to_string()),
+        "toml" => toml::from_str(&contents).map_err(|e| e.to_string()),
+        "json" => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+        _ => {
+            return fail_incorrectusage_clierror!(
+                "Unsupported pipeline spec format: {}. Use a .yaml, .yml, .toml or .json file.",
+                path.display()
+            );
+        },
+    };
+    match spec {
+        Ok(spec) => Ok(spec),
+        Err(e) => fail_clierror!("Invalid pipeline spec {}: {e}", path.display()),
+    }
+}
+
+/// create a temporary work directory, on a RAM-backed tmpfs if available
+fn temp_workdir() -> io::Result<tempfile::TempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("qsv-pipeline-");
+    let shm = Path::new("/dev/shm");
+    if shm.is_dir() {
+        if let Ok(dir) = builder.tempdir_in(shm) {
+            return Ok(dir);
+        }
+    }
+    builder.tempdir()
+}
+
+/// the path of a named output in the work directory
+fn output_path(workdir: &Path, name: &str) -> PathBuf {
+    if Path::new(name).extension().is_some() {
+        workdir.join(name)
+    } else {
+        workdir.join(format!("{name}.csv"))
+    }
+}
+
+/// validate the spec & resolve the arguments of each step, before anything is run
+fn plan_pipeline(spec: PipelineSpec, workdir: &Path, program: &str) -> CliResult<Vec<PlannedStep>> {
+    if spec.steps.is_empty() {
+        return fail_incorrectusage_clierror!("The pipeline spec has no steps.");
+    }
+
+    let mut names: HashSet<String> = HashSet::new();
+    let mut outputs: HashMap<String, PathBuf> = HashMap::new();
+    let mut planned = Vec::with_capacity(spec.steps.len());
+
+    for step in spec.steps {
+        if !names.insert(step.name.clone()) {
+            return fail_incorrectusage_clierror!("Duplicate step name \"{}\".", step.name);
+        }
+
+        let tasks = if let Some(ref branches) = step.parallel {
+            if step.command.is_some() || !step.args.is_empty() || step.output.is_some() {
+                return fail_incorrectusage_clierror!(
+                    "Step \"{}\": a parallel step cannot have a command, args or output.",
+                    step.name
+                );
+            }
+            if branches.is_empty() {
+                return fail_incorrectusage_clierror!(
+                    "Step \"{}\" has no parallel steps.",
+                    step.name
+                );
+            }
+
+            // parallel steps can only reference the outputs of the steps before them
+            let mut tasks = Vec::with_capacity(branches.len());
+            for branch in branches {
+                if branch.parallel.is_some() {
+                    return fail_incorrectusage_clierror!(
+                        "Step \"{}\": parallel steps cannot be nested.",
+                        branch.name
+                    );
+                }
+                if !names.insert(branch.name.clone()) {
+                    return fail_incorrectusage_clierror!(
+                        "Duplicate step name \"{}\".",
+                        branch.name
+                    );
+                }
+                tasks.push(plan_task(branch, &outputs, workdir, program)?);
+            }
+            for (branch, task) in branches.iter().zip(&tasks) {
+                add_output(&mut outputs, branch, task)?;
+            }
+            tasks
+        } else {
+            let task = plan_task(&step, &outputs, workdir, program)?;
+            add_output(&mut outputs, &step, &task)?;
+            vec![task]
+        };
+
+        planned.push(PlannedStep { spec: step, tasks });
+    }
+    Ok(planned)
+}
+
+fn add_output(
+    outputs: &mut HashMap<String, PathBuf>,
+    step: &StepSpec,
+    task: &Task,
+) -> CliResult<()> {
+    if let (Some(name), Some(path)) = (&step.output, &task.output) {
+        if outputs.insert(name.clone(), path.clone()).is_some() {
+            return fail_incorrectusage_clierror!(
+                "Step \"{}\": output \"{name}\" is already the output of another step.",
+                step.name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn plan_task(
+    step: &StepSpec,
+    outputs: &HashMap<String, PathBuf>,
+    workdir: &Path,
+    program: &str,
+) -> CliResult<Task> {
+    let Some(ref command) = step.command else {
+        return fail_incorrectusage_clierror!("Step \"{}\" has no command.", step.name);
+    };
+    if command == "pipeline" || command == "help" {
+        return fail_incorrectusage_clierror!(
+            "Step \"{}\": the \"{command}\" command cannot be used in a pipeline.",
+            step.name
+        );
+    }
+    if Command::from_name(command).is_none() {
+        return fail_incorrectusage_clierror!(
+            "Step \"{}\": unknown command \"{command}\".",
+            step.name
+        );
+    }
+
+    let mut argv = vec![program.to_string(), command.clone()];
+    for arg in &step.args {
+        let arg = match resolve_placeholders(&arg.to_arg_string(), outputs) {
+            Ok(arg) => arg,
+            Err(name) => {
+                return fail_incorrectusage_clierror!(
+                    "Step \"{}\": ${{{name}}} is not the output of an earlier step.",
+                    step.name
+                );
+            },
+        };
+        argv.push(arg);
+    }
+
+    let output = if let Some(ref name) = step.output {
+        if name.is_empty() || name.contains(['/', '\\']) || name == STATE_FILENAME {
+            return fail_incorrectusage_clierror!(
+                "Step \"{}\": invalid output name \"{name}\".",
+                step.name
+            );
+        }
+        let path = output_path(workdir, name);
+        // add --output before "--", if present
+        let insert_at = argv
+            .iter()
+            .position(|arg| arg == "--")
+            .unwrap_or(argv.len());
+        argv.insert(insert_at, "--output".to_string());
+        argv.insert(insert_at + 1, path.to_string_lossy().to_string());
+        Some(path)
+    } else {
+        None
+    };
+
+    Ok(Task {
+        name: step.name.clone(),
+        command: command.clone(),
+        argv,
+        output,
+    })
+}
+
+/// replace the ${name} placeholders with the paths of the named outputs.
+/// Returns the name of the first unknown output as the error.
+fn resolve_placeholders(arg: &str, outputs: &HashMap<String, PathBuf>) -> Result<String, String> {
+    static PLACEHOLDER_RE: OnceLock<Regex> = OnceLock::new();
+    // safety: the regex is valid
+    let placeholder_re = PLACEHOLDER_RE.get_or_init(|| Regex::new(r"\$\{([^}]+)\}").unwrap());
+
+    let mut unknown = None;
+    let resolved = placeholder_re.replace_all(arg, |caps: &regex::Captures| {
+        let name = caps[1].trim();
+        if let Some(path) = outputs.get(name) {
+            path.to_string_lossy().to_string()
+        } else {
+            unknown.get_or_insert_with(|| name.to_string());
+            String::new()
+        }
+    });
+    match unknown {
+        Some(name) => Err(name),
+        None => Ok(resolved.into_owned()),
+    }
+}
+
+/// run a command in this process, through the qsv command table
+fn run_in_process(command: &str, argv: &[String]) -> CliResult<()> {
+    let Some(cmd) = Command::from_name(command) else {
+        return fail_clierror!("Unknown command \"{command}\".");
+    };
+    let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+    // don't let cached per-run state (e.g. row counts) leak from a previous step
+    util::reset_run_state();
+    cmd.run_with_argv(&argv)
+}
+
+/// run a command in a separate qsv process
+fn run_subprocess(qsv_bin: &Path, argv: &[String]) -> CliResult<()> {
+    let status = process::Command::new(qsv_bin).args(&argv[1..]).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        fail_clierror!("qsv {} exited with {status}", argv[1])
+    }
+}
+
+fn read_state(path: &Path) -> CliResult<PipelineState> {
+    let contents = fs::read_to_string(path)?;
+    match serde_json::from_str(&contents) {
+        Ok(state) => Ok(state),
+        Err(e) => fail_clierror!("Invalid pipeline state file {}: {e}", path.display()),
+    }
+}
+
+fn write_state(path: &Path, state: &PipelineState) -> CliResult<()> {
+    fs::write(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+fn write_timings_table(timings: &[StepTiming], total: Duration) -> CliResult<()> {
+    let mut tabwtr = TabWriter::new(io::stderr());
+    writeln!(tabwtr, "step\tcommand\tmode\tstatus\telapsed")?;
+    for timing in timings {
+        writeln!(
+            tabwtr,
+            "{}\t{}\t{}\t{}\t{:.3}s",
+            timing.name,
+            timing.command,
+            timing.mode.as_str(),
+            timing.status,
+            timing.elapsed.as_secs_f64()
+        )?;
+    }
+    writeln!(tabwtr, "total\t\t\t\t{:.3}s", total.as_secs_f64())?;
+    tabwtr.flush()?;
+    Ok(())
+}
+
+fn write_report(
+    path: &Path,
+    spec: &str,
+    workdir: &Path,
+    failure: Option<&str>,
+    timings: &[StepTiming],
+    total: Duration,
+) -> CliResult<()> {
+    let steps: Vec<_> = timings
+        .iter()
+        .map(|timing| {
+            json!({
+                "name": timing.name,
+                "command": timing.command,
+                "mode": timing.mode.as_str(),
+                "status": timing.status,
+                "elapsed_ms": timing.elapsed.as_millis(),
+            })
+        })
+        .collect();
+    let report = json!({
+        "spec": spec,
+        "workdir": workdir,
+        "status": if failure.is_some() { "failed" } else { "ok" },
+        "error": failure,
+        "elapsed_ms": total.as_millis(),
+        "steps": steps,
+    });
+    fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    Ok(())
+}
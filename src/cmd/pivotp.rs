@@ -122,6 +122,8 @@ fn calculate_pivot_metadata(
         flag_prefer_dmy:      false,
         flag_force:           false,
         flag_stdout:          false,
+        flag_format:          None,
+        flag_detect_formats:  false,
         flag_jobs:            None,
         flag_no_headers:      false,
         flag_delimiter:       args.flag_delimiter,
@@ -226,6 +228,8 @@ fn suggest_agg_function(
         flag_prefer_dmy:      false,
         flag_force:           false,
         flag_stdout:          false,
+        flag_format:          None,
+        flag_detect_formats:  false,
         flag_jobs:            None,
         flag_no_headers:      false,
         flag_delimiter:       args.flag_delimiter,
@@ -0,0 +1,233 @@
+static USAGE: &str = r#"
+Drop empty, sparse and constant columns from CSV data.
+
+Wide extracts often carry many columns that are entirely empty or that have the same
+value in every row. This command uses the stats cache (see `qsv stats`) to find and drop them.
+If the stats cache is current, the input is only read once more to write the pruned CSV.
+Otherwise, `qsv stats` is run first to create the stats cache.
+
+A column is dropped if:
+  * its sparsity (the fraction of empty values) is >= the --sparsity threshold; or
+  * its cardinality is 1 (i.e. it's a constant column), unless --keep-constant is set.
+
+A summary of the dropped columns is written to stderr, unless --quiet is set.
+For a detailed report, use the --report option.
+
+Examples:
+
+Drop all empty and constant columns:
+  $ qsv prune data.csv --output pruned.csv
+
+Also drop columns that are at least 90% empty & write a report of the dropped columns:
+  $ qsv prune --sparsity 0.9 --report dropped.csv data.csv --output pruned.csv
+
+Only show what would be dropped:
+  $ qsv prune --dry-run --report - data.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_prune.rs.
+
+Usage:
+    qsv prune [options] [<input>]
+    qsv prune --help
+
+prune options:
+    --sparsity <threshold>  Drop columns whose sparsity is >= this threshold.
+                            Sparsity is the fraction of empty values in a column,
+                            from 0.0 to 1.0. The default only drops empty columns.
+                            [default: 1.0]
+    --keep-constant         Do not drop constant columns (cardinality of 1).
+    --report <file>         Write a CSV report of the dropped columns to <file>,
+                            with the field name, type, nullcount, sparsity,
+                            cardinality & the reason it was dropped.
+                            Use "-" to write the report to stdout.
+    --dry-run               Do not write the pruned CSV. Use with --report
+                            to only see which columns would be dropped.
+    --force                 Force recomputing the stats cache.
+    -j, --jobs <arg>        The number of jobs to run in parallel when the stats
+                            cache needs to be computed.
+                            When not set, the number of jobs is set to the
+                            number of CPUs detected.
+
+Common options:
+    -h, --help              Display this message
+    -o, --output <file>     Write output to <file> instead of stdout.
+    -n, --no-headers        When set, the first row will not be interpreted
+                            as headers.
+    -d, --delimiter <arg>   The field delimiter for reading CSV data.
+                            Must be a single character. (default: ,)
+    -q, --quiet             Do not write the summary of dropped columns to stderr.
+    --memcheck              Check if there is enough memory to load the entire
+                            CSV into memory using CONSERVATIVE heuristics
+                            when computing the stats cache.
+"#;
+
+use serde::Deserialize;
+
+use crate::{
+    CliResult,
+    cmd::stats::StatsData,
+    config::{Config, Delimiter},
+    select::SelectColumns,
+    util,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:          Option<String>,
+    flag_sparsity:      f64,
+    flag_keep_constant: bool,
+    flag_report:        Option<String>,
+    flag_dry_run:       bool,
+    flag_force:         bool,
+    flag_jobs:          Option<usize>,
+    flag_output:        Option<String>,
+    flag_no_headers:    bool,
+    flag_delimiter:     Option<Delimiter>,
+    flag_quiet:         bool,
+    flag_memcheck:      bool,
+}
+
+/// the reason a column was dropped
+fn drop_reason(
+    stats: &StatsData,
+    sparsity_threshold: f64,
+    keep_constant: bool,
+) -> Option<&'static str> {
+    let sparsity = stats.sparsity.unwrap_or_default();
+    if stats.r#type == "NULL" || sparsity >= 1.0 {
+        Some("empty")
+    } else if sparsity >= sparsity_threshold {
+        Some("sparse")
+    } else if !keep_constant && stats.cardinality == 1 {
+        Some("constant")
+    } else {
+        None
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if !(0.0..=1.0).contains(&args.flag_sparsity) {
+        return fail_incorrectusage_clierror!(
+            "--sparsity must be between 0.0 and 1.0, got {}.",
+            args.flag_sparsity
+        );
+    }
+
+    if args.arg_input.is_none() || args.arg_input.as_deref() == Some("-") {
+        return fail_incorrectusage_clierror!(
+            "prune requires an input file, as it uses the stats cache. It cannot read from stdin."
+        );
+    }
+
+    let schema_args = util::SchemaArgs {
+        flag_enum_threshold:  0,
+        flag_ignore_case:     false,
+        flag_strict_dates:    false,
+        flag_pattern_columns: SelectColumns::parse("").unwrap(),
+        flag_dates_whitelist: String::new(),
+        flag_prefer_dmy:      false,
+        flag_force:           args.flag_force,
+        flag_stdout:          false,
+        flag_format:          None,
+        flag_detect_formats:  false,
+        flag_jobs:            Some(util::njobs(args.flag_jobs)),
+        flag_no_headers:      args.flag_no_headers,
+        flag_delimiter:       args.flag_delimiter,
+        arg_input:            args.arg_input.clone(),
+        flag_memcheck:        args.flag_memcheck,
+    };
+    let requested_mode = if args.flag_force {
+        util::StatsMode::FrequencyForceStats
+    } else {
+        util::StatsMode::Frequency
+    };
+    let (_, csv_stats, _) = util::get_stats_records(&schema_args, requested_mode)?;
+    if csv_stats.is_empty() {
+        return fail_clierror!(
+            "Cannot load the stats cache for {}. prune requires the stats cache, so \
+             QSV_STATSCACHE_MODE cannot be 'none'.",
+            args.arg_input.unwrap_or_default()
+        );
+    }
+
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+
+    if csv_stats.len() != headers.len() {
+        return fail_clierror!(
+            "The stats cache has {} columns, but the CSV has {}. Use --force to recompute the \
+             stats cache.",
+            csv_stats.len(),
+            headers.len()
+        );
+    }
+
+    let mut keep: Vec<usize> = Vec::with_capacity(headers.len());
+    let mut dropped: Vec<(&StatsData, &'static str)> = Vec::new();
+    for (i, stats) in csv_stats.iter().enumerate() {
+        match drop_reason(stats, args.flag_sparsity, args.flag_keep_constant) {
+            Some(reason) => dropped.push((stats, reason)),
+            None => keep.push(i),
+        }
+    }
+
+    if let Some(report_path) = args.flag_report {
+        let report_path = if report_path == "-" {
+            None
+        } else {
+            Some(report_path)
+        };
+        let mut report_wtr = Config::new(report_path.as_ref()).writer()?;
+        report_wtr.write_record([
+            "field",
+            "type",
+            "nullcount",
+            "sparsity",
+            "cardinality",
+            "reason",
+        ])?;
+        for &(stats, reason) in &dropped {
+            report_wtr.write_record([
+                stats.field.as_str(),
+                stats.r#type.as_str(),
+                itoa::Buffer::new().format(stats.nullcount),
+                stats.sparsity.unwrap_or_default().to_string().as_str(),
+                itoa::Buffer::new().format(stats.cardinality),
+                reason,
+            ])?;
+        }
+        report_wtr.flush()?;
+    }
+
+    if !args.flag_quiet {
+        let dropped_names: Vec<&str> = dropped
+            .iter()
+            .map(|(stats, _)| stats.field.as_str())
+            .collect();
+        winfo!(
+            "Dropped {} of {} columns: {dropped_names:?}",
+            dropped.len(),
+            headers.len()
+        );
+    }
+
+    if args.flag_dry_run {
+        return Ok(());
+    }
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    if !rconfig.no_headers {
+        wtr.write_record(keep.iter().map(|&i| &headers[i]))?;
+    }
+    let mut record = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+        wtr.write_record(keep.iter().map(|&i| &record[i]))?;
+    }
+
+    Ok(wtr.flush()?)
+}
@@ -0,0 +1,394 @@
+static USAGE: &str = r#"
+Heuristically repair broken CSV data, and log every repair with its line number.
+
+Unlike `fixlengths`, which only pads or truncates records, and `validate`, which only
+reports RFC 4180 violations, `repair` fixes common breakage:
+
+  * stray carriage returns - CRs that are not part of a CRLF line ending are removed.
+  * unescaped quotes - quotes inside a field that are not escaped by doubling them
+    (e.g. `"say "hi" now"` or `5" screen`) are kept as literal quotes & properly escaped.
+  * unbalanced quotes - a quoted field that is never closed swallows the rows after it.
+    If the quote cannot be closed by joining the next --max-lines lines into a record with
+    the same number of fields as the header, or if one of the lines to join looks like a
+    complete row on its own, the opening quote is treated as a literal quote.
+  * split rows - rows with fewer fields than the header are re-joined with the following
+    lines, if their field counts add up to the header's. The line breaks are kept
+    in the re-joined field.
+
+Quoted fields with embedded newlines that are properly closed are left as is.
+The number of fields of the first row (the header) is used as the expected number of fields.
+Rows that still don't have the expected number of fields are written as is, and can be
+fixed with `fixlengths`.
+
+The repaired CSV is written with the standard RFC 4180 quoting rules.
+A summary of the repairs is written to stderr, unless --quiet is set.
+For a detailed log, use the --log option, which writes a CSV with the line number
+(of the original input), the repair & its details.
+
+Examples:
+
+Repair a CSV & write the repair log to repairs.csv:
+  $ qsv repair --log repairs.csv broken.csv --output fixed.csv
+
+Repair a semicolon-delimited file that uses single quotes:
+  $ qsv repair --delimiter ";" --quote "'" broken.csv
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_repair.rs.
+
+Usage:
+    qsv repair [options] [<input>]
+    qsv repair --help
+
+repair options:
+    --quote <arg>          The quote character to use. [default: "]
+    --max-lines <arg>      The maximum number of following lines to join when
+                           closing a quoted field or re-joining a split row.
+                           [default: 10]
+    --log <file>           Write a CSV log of every repair to <file>, with the
+                           line, repair & details columns.
+                           Use "-" to write the log to stderr.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character. (default: ,)
+    -q, --quiet            Do not write the summary of repairs to stderr.
+"#;
+
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead},
+    mem,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    CliResult,
+    config::{Config, DEFAULT_RDR_BUFFER_CAPACITY, Delimiter},
+    util,
+};
+
+#[derive(Deserialize)]
+struct Args {
+    arg_input:      Option<String>,
+    flag_quote:     Delimiter,
+    flag_max_lines: usize,
+    flag_log:       Option<String>,
+    flag_output:    Option<String>,
+    flag_delimiter: Option<Delimiter>,
+    flag_quiet:     bool,
+}
+
+const STRAY_CR: &str = "stray_cr";
+const UNESCAPED_QUOTE: &str = "unescaped_quote";
+const UNBALANCED_QUOTE: &str = "unbalanced_quote";
+const REJOINED_ROW: &str = "rejoined_row";
+
+/// a repair of the record starting at line
+struct Repair {
+    line:    u64,
+    kind:    &'static str,
+    // the number of characters or rows repaired, for the summary
+    count:   u64,
+    details: String,
+}
+
+/// reads the physical lines of the input, with lookahead
+struct LineReader {
+    rdr:       io::BufReader<Box<dyn io::Read + Send + 'static>>,
+    pending:   VecDeque<(u64, String)>,
+    line_no:   u64,
+    buf:       Vec<u8>,
+    stray_crs: Vec<Repair>,
+}
+
+impl LineReader {
+    /// returns the next line & its line number, without its line ending.
+    /// Stray CRs are removed & recorded.
+    fn next_line(&mut self) -> io::Result<Option<(u64, String)>> {
+        if let Some(line) = self.pending.pop_front() {
+            return Ok(Some(line));
+        }
+
+        self.buf.clear();
+        if self.rdr.read_until(b'\n', &mut self.buf)? == 0 {
+            return Ok(None);
+        }
+        self.line_no += 1;
+        if self.buf.last() == Some(&b'\n') {
+            self.buf.pop();
+            if self.buf.last() == Some(&b'\r') {
+                self.buf.pop();
+            }
+        }
+        let mut line = String::from_utf8_lossy(&self.buf).into_owned();
+        let stray_crs = line.matches('\r').count();
+        if stray_crs > 0 {
+            line.retain(|c| c != '\r');
+            self.stray_crs.push(Repair {
+                line:    self.line_no,
+                kind:    STRAY_CR,
+                count:   stray_crs as u64,
+                details: format!("removed {stray_crs} stray carriage return(s)"),
+            });
+        }
+        Ok(Some((self.line_no, line)))
+    }
+
+    /// pushes back lines that were read ahead, so they're read again
+    fn unread(&mut self, lines: Vec<(u64, String)>) {
+        for line in lines.into_iter().rev() {
+            self.pending.push_front(line);
+        }
+    }
+}
+
+struct Parsed {
+    fields:           Vec<String>,
+    /// the byte position of the opening quote of a quoted field that was not closed
+    open_quote:       Option<usize>,
+    unescaped_quotes: usize,
+}
+
+/// leniently parses a record. Quotes inside a field that are not escaped are kept as
+/// literal quotes, as are the opening quotes at the literal_quotes byte positions.
+fn parse_record(text: &str, delimiter: char, quote: char, literal_quotes: &[usize]) -> Parsed {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut unescaped_quotes = 0;
+    let mut open_quote: Option<usize> = None;
+    let mut at_field_start = true;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if open_quote.is_some() {
+            if c == quote {
+                match chars.peek() {
+                    // an escaped quote
+                    Some(&(_, next)) if next == quote => {
+                        field.push(quote);
+                        chars.next();
+                    },
+                    // the closing quote
+                    Some(&(_, next)) if next == delimiter => open_quote = None,
+                    None => open_quote = None,
+                    Some(_) => {
+                        field.push(quote);
+                        unescaped_quotes += 1;
+                    },
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == delimiter {
+            fields.push(mem::take(&mut field));
+            at_field_start = true;
+        } else {
+            if c == quote {
+                if at_field_start && !literal_quotes.contains(&i) {
+                    open_quote = Some(i);
+                    at_field_start = false;
+                    continue;
+                }
+                unescaped_quotes += 1;
+            }
+            field.push(c);
+            at_field_start = false;
+        }
+    }
+    fields.push(field);
+
+    Parsed {
+        fields,
+        open_quote,
+        unescaped_quotes,
+    }
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let rconfig = Config::new(args.arg_input.as_ref()).delimiter(args.flag_delimiter);
+    let delimiter = rconfig.get_delimiter() as char;
+    let quote = args.flag_quote.as_byte() as char;
+    if delimiter == quote {
+        return fail_incorrectusage_clierror!("The delimiter and quote cannot be the same.");
+    }
+
+    let mut lines = LineReader {
+        rdr:       io::BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, rconfig.io_reader()?),
+        pending:   VecDeque::new(),
+        line_no:   0,
+        buf:       Vec::with_capacity(1024),
+        stray_crs: Vec::new(),
+    };
+
+    let mut wtr = Config::new(args.flag_output.as_ref()).writer()?;
+    let mut log_wtr = match args.flag_log.as_deref() {
+        Some("-") => Some(csv::Writer::from_writer(
+            Box::new(io::stderr()) as Box<dyn io::Write>
+        )),
+        Some(_) => Some(Config::new(args.flag_log.as_ref()).writer()?),
+        None => None,
+    };
+    if let Some(ref mut log_wtr) = log_wtr {
+        log_wtr.write_record(["line", "repair", "details"])?;
+    }
+
+    let mut expected_width: Option<usize> = None;
+    let mut repairs: Vec<Repair> = Vec::new();
+    let mut repair_counts = [0_u64; 4];
+    let mut total_records = 0_u64;
+
+    while let Some((line_no, line)) = lines.next_line()? {
+        let mut literal_quotes: Vec<usize> = Vec::new();
+
+        // close quoted fields with embedded newlines by joining the following lines.
+        // If that doesn't result in a record with the expected width,
+        // the opening quote is unbalanced, and is treated as a literal quote.
+        let parsed = loop {
+            let parsed = parse_record(&line, delimiter, quote, &literal_quotes);
+            let Some(open_quote) = parsed.open_quote else {
+                break parsed;
+            };
+
+            let mut text = line.clone();
+            let mut read_ahead = Vec::new();
+            let mut closed = None;
+            while read_ahead.len() < args.flag_max_lines {
+                let Some((next_line_no, next_line)) = lines.next_line()? else {
+                    break;
+                };
+                // a line that looks like a complete row was swallowed by the open quote
+                if expected_width.is_some_and(|width| {
+                    let next = parse_record(&next_line, delimiter, quote, &[]);
+                    next.open_quote.is_none() && next.fields.len() == width
+                }) {
+                    read_ahead.push((next_line_no, next_line));
+                    break;
+                }
+                text.push('\n');
+                text.push_str(&next_line);
+                read_ahead.push((next_line_no, next_line));
+                let joined = parse_record(&text, delimiter, quote, &literal_quotes);
+                if joined.open_quote.is_none() {
+                    if expected_width.is_none_or(|width| joined.fields.len() == width) {
+                        closed = Some(joined);
+                    }
+                    break;
+                }
+            }
+            if let Some(joined) = closed {
+                break joined;
+            }
+
+            lines.unread(read_ahead);
+            literal_quotes.push(open_quote);
+            repairs.push(Repair {
+                line:    line_no,
+                kind:    UNBALANCED_QUOTE,
+                count:   1,
+                details: format!(
+                    "the quote at character {} was never closed & was treated as a literal quote",
+                    line[..open_quote].chars().count() + 1
+                ),
+            });
+        };
+
+        let mut fields = parsed.fields;
+        let mut unescaped_quotes = parsed.unescaped_quotes;
+
+        // re-join rows that were split across lines inside a field
+        if let Some(width) = expected_width {
+            if fields.len() < width {
+                let mut joined_fields = fields.clone();
+                let mut joined_unescaped_quotes = unescaped_quotes;
+                let mut read_ahead = Vec::new();
+                while joined_fields.len() < width && read_ahead.len() < args.flag_max_lines {
+                    let Some((next_line_no, next_line)) = lines.next_line()? else {
+                        break;
+                    };
+                    let next = parse_record(&next_line, delimiter, quote, &[]);
+                    if next.open_quote.is_some() {
+                        lines.unread(vec![(next_line_no, next_line)]);
+                        break;
+                    }
+                    let mut next_fields = next.fields.into_iter();
+                    // safety: parse_record always returns at least one field
+                    let last_field = joined_fields.last_mut().unwrap();
+                    last_field.push('\n');
+                    last_field.push_str(&next_fields.next().unwrap_or_default());
+                    joined_fields.extend(next_fields);
+                    joined_unescaped_quotes += next.unescaped_quotes;
+                    read_ahead.push((next_line_no, next_line));
+                }
+
+                if joined_fields.len() == width && !read_ahead.is_empty() {
+                    repairs.push(Repair {
+                        line:    line_no,
+                        kind:    REJOINED_ROW,
+                        count:   1,
+                        details: format!(
+                            "re-joined lines {line_no}-{} into one row",
+                            read_ahead.last().map_or(line_no, |(n, _)| *n)
+                        ),
+                    });
+                    fields = joined_fields;
+                    unescaped_quotes = joined_unescaped_quotes;
+                } else {
+                    lines.unread(read_ahead);
+                }
+            }
+        } else {
+            expected_width = Some(fields.len());
+        }
+
+        if unescaped_quotes > 0 {
+            repairs.push(Repair {
+                line:    line_no,
+                kind:    UNESCAPED_QUOTE,
+                count:   unescaped_quotes as u64,
+                details: format!("escaped {unescaped_quotes} unescaped quote(s)"),
+            });
+        }
+
+        wtr.write_record(&fields)?;
+        total_records += 1;
+
+        repairs.append(&mut lines.stray_crs);
+        repairs.sort_by_key(|repair| repair.line);
+        for repair in repairs.drain(..) {
+            let kind_idx = match repair.kind {
+                STRAY_CR => 0,
+                UNESCAPED_QUOTE => 1,
+                UNBALANCED_QUOTE => 2,
+                _ => 3,
+            };
+            repair_counts[kind_idx] += repair.count;
+            if let Some(ref mut log_wtr) = log_wtr {
+                log_wtr.write_record([
+                    itoa::Buffer::new().format(repair.line),
+                    repair.kind,
+                    &repair.details,
+                ])?;
+            }
+        }
+    }
+
+    if let Some(ref mut log_wtr) = log_wtr {
+        log_wtr.flush()?;
+    }
+
+    if !args.flag_quiet {
+        let [stray_crs, unescaped, unbalanced, rejoined] = repair_counts;
+        winfo!(
+            "Wrote {total_records} records. Repaired {stray_crs} stray carriage return(s), \
+             {unescaped} unescaped quote(s), {unbalanced} unbalanced quote(s) & {rejoined} split \
+             row(s)."
+        );
+    }
+
+    Ok(wtr.flush()?)
+}
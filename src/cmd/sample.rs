@@ -351,6 +351,8 @@ fn check_stats_cache(
         flag_strict_dates:    false,
         flag_pattern_columns: SelectColumns::parse("")?,
         flag_stdout:          false,
+        flag_format:          None,
+        flag_detect_formats:  false,
     };
 
     // Get stats records
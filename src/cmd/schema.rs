@@ -21,6 +21,46 @@ a `stdin.csv` file will be created with stdin's contents as well.
 
 Note that `stdin.csv` will be overwritten if it already exists.
 
+With `--format tableschema`, a Frictionless Table Schema is generated instead
+(https://specs.frictionlessdata.io/table-schema/), using the same stats-derived
+types and constraints. Fields without nulls are marked `required`, and fields whose
+cardinality equals the row count are marked `unique`. The generated Table Schema file
+has a `.tableschema.json` suffix (e.g. `mydata.csv.tableschema.json`), and can be used
+directly with the `validate` command.
+
+The stats cache can also be turned into a schema for loading the data into a typed table:
+  * postgresql, sqlite, duckdb & bigquery - a `CREATE TABLE` statement named after the
+    input file (e.g. `mydata.csv.postgresql.sql` creates table "mydata"). Columns without
+    nulls are NOT NULL, strings get VARCHAR/STRING lengths from their max length, and
+    floats get NUMERIC/DECIMAL precision & scale from their range & max_precision.
+    SQLite has no date/time types, so dates are TEXT.
+  * avro - an Avro record schema (`mydata.csv.avsc`). Characters other than letters,
+    digits & underscores in field names are replaced with underscores.
+  * arrow - an Arrow schema in the Arrow integration JSON format
+    (`mydata.csv.arrow-schema.json`).
+  * polars - a Polars schema (`mydata.pschema.json`), the same schema file used by
+    `sqlp --cache-schema`. Requires the polars feature.
+Integers use 32-bit types when their range allows it, and 64-bit types otherwise.
+Date & datetime types are only inferred for the columns selected by --dates-whitelist.
+These formats are not supported for multiple inputs.
+
+With --detect-formats, string fields whose non-empty values all match one of the unambiguous
+custom formats supported by the `validate` command - uuid-v1/v3/v4/v5/v6/v7, iban, e164,
+credit-card, isbn, issn & uk-postcode - get a corresponding "format" constraint.
+
+The input can also be a directory, a glob pattern (e.g. 'daily/*.csv') or an
+".infile-list" file to infer one schema that admits all the files. The schema of each file
+is inferred, and then merged:
+  * types are widened from integer to number to string as needed,
+  * enums are unioned, as long as they're within --enum-threshold,
+  * minimum/maximum & minLength/maxLength ranges are merged,
+  * fields missing from some of the files are not required, and
+  * patterns are combined, with formats only kept if all the files agree.
+The files that forced each widening are reported. The merged schema file is named after
+the directory, the glob's directory or the ".infile-list" file (e.g. `daily.schema.json`).
+With `--format tableschema`, fields are not marked `unique` as uniqueness isn't checked
+across files.
+
 Schema generation can be a compute-intensive process, especially for large CSV files.
 To speed up generation, the `schema` command will reuse a `stats.csv.data.jsonl` file if it
 exists and is current (i.e. stats generated with --cardinality and --infer-dates options).
@@ -50,6 +90,10 @@ Schema options:
                                that matches all values for each specified column.
                                Columns are selected using `select` syntax 
                                (see `qsv select --help` for details).
+    --detect-formats           Add "format" constraints for the String columns whose
+                               values all match a custom format of the `validate` command.
+                               This takes an extra pass over each input file, stopping
+                               early for the columns that can't match any format.
     --dates-whitelist <list>   The case-insensitive patterns to look for when 
                                shortlisting fields for date inference.
                                i.e. if the field's name has any of these patterns,
@@ -61,6 +105,10 @@ Schema options:
                                Otherwise, use mdy format.
     --force                    Force recomputing cardinality and unique values
                                even if stats cache file exists and is current.
+    --format <format>          The schema format to generate. Valid values are
+                               "jsonschema", "tableschema", "postgresql", "sqlite",
+                               "duckdb", "bigquery", "avro", "arrow" and "polars".
+                               [default: jsonschema]
     --stdout                   Send generated JSON schema file to stdout instead.
     -j, --jobs <arg>           The number of jobs to run in parallel.
                                When not set, the number of jobs is set to the
@@ -78,7 +126,11 @@ Common options:
                                CSV into memory using CONSERVATIVE heuristics.
 "#;
 
-use std::{fs::File, io::Write, path::Path};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use csv::ByteRecord;
 use foldhash::{HashMap, HashMapExt, HashSet};
@@ -89,13 +141,69 @@ use rayon::slice::ParallelSliceMut;
 use serde_json::{Map, Value, json, value::Number};
 use stats::Frequencies;
 
-use crate::{CliResult, cmd::stats::StatsData, config::Config, util, util::StatsMode};
+use crate::{
+    CliResult, cmd::stats::StatsData, config::Config, formats::FormatDetector, util,
+    util::StatsMode,
+};
 
 const STDIN_CSV: &str = "stdin.csv";
 
+// the schema file basename of a multi-file input without a directory name (e.g. `*.csv`)
+const MERGED_BASENAME: &str = "merged";
+
+// the files qsv writes next to its inputs, which are skipped in multi-file inputs
+const SIDECAR_SUFFIXES: &[&str] = &[
+    ".schema.json",
+    ".tableschema.json",
+    ".stats.csv",
+    ".stats.csv.data.jsonl",
+    ".stats.csv.json",
+    ".pschema.json",
+    ".sql",
+    ".avsc",
+    ".arrow-schema.json",
+    ".idx",
+];
+
+const SCHEMA_FORMATS: &[&str] = &[
+    "jsonschema",
+    "tableschema",
+    "postgresql",
+    "sqlite",
+    "duckdb",
+    "bigquery",
+    "avro",
+    "arrow",
+    "polars",
+];
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut args: util::SchemaArgs = util::get_args(USAGE, argv)?;
 
+    let schema_format = args
+        .flag_format
+        .as_deref()
+        .unwrap_or("jsonschema")
+        .to_ascii_lowercase();
+    if !SCHEMA_FORMATS.contains(&schema_format.as_str()) {
+        return fail_incorrectusage_clierror!(
+            "Invalid --format value: {schema_format}. Must be one of: {}",
+            SCHEMA_FORMATS.join(", ")
+        );
+    }
+
+    // we can do this directly here, since args is mutable and
+    // Config has not been created yet at this point
+    args.flag_prefer_dmy = args.flag_prefer_dmy || util::get_envvar_flag("QSV_PREFER_DMY");
+    if args.flag_prefer_dmy {
+        winfo!("Prefer DMY set.");
+    }
+
+    let multi_file_input = args
+        .arg_input
+        .as_deref()
+        .is_some_and(util::is_multi_file_input);
+
     // if using stdin, we create a stdin.csv file as stdin is not seekable and we need to
     // open the file multiple times to compile stats/unique values, etc.
     // We use a fixed "stdin.csv" filename instead of a temporary file with random characters
@@ -109,6 +217,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         drop(stdin_handle);
         args.arg_input = Some(STDIN_CSV.to_string());
         (STDIN_CSV.to_string(), STDIN_CSV.to_string())
+    } else if multi_file_input {
+        multi_file_output_base(args.arg_input.as_ref().unwrap())
     } else {
         let filename = Path::new(args.arg_input.as_ref().unwrap())
             .file_name()
@@ -118,23 +228,105 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         (args.arg_input.clone().unwrap(), filename)
     };
 
+    // the SQL DDL, Avro, Arrow & Polars schemas are generated from the stats cache only
+    if !["jsonschema", "tableschema"].contains(&schema_format.as_str()) {
+        if multi_file_input {
+            return fail_incorrectusage_clierror!(
+                "--format {schema_format} does not support multiple inputs. Use jsonschema or \
+                 tableschema."
+            );
+        }
+        return generate_stats_schema(&args, &schema_format, &input_path, &input_filename);
+    }
+
+    let (properties_map, required_fields, unique_fields) = if multi_file_input {
+        let (properties_map, required_fields) = infer_merged_schema(&args, &input_filename)?;
+        // uniqueness across files can't be derived from the per-file stats
+        (properties_map, required_fields, HashSet::default())
+    } else {
+        let properties_map = infer_file_schema(&args, &input_path, &input_filename, false)?;
+        // generate list of required fields
+        let required_fields = get_required_fields(&properties_map);
+        let unique_fields = if schema_format == "tableschema" {
+            get_unique_fields(&args)?
+        } else {
+            HashSet::default()
+        };
+        (properties_map, required_fields, unique_fields)
+    };
+
+    let (schema, schema_suffix) = if schema_format == "tableschema" {
+        (
+            build_table_schema(&properties_map, &required_fields, &unique_fields),
+            ".tableschema.json",
+        )
+    } else {
+        // create final JSON object for output
+        (
+            json!({
+                "$schema": "https://json-schema.org/draft-07/schema",
+                "title": format!("JSON Schema for {input_filename}"),
+                "description": "Inferred JSON Schema from QSV schema command",
+                "type": "object",
+                "properties": Value::Object(properties_map),
+                "required": Value::Array(required_fields)
+            }),
+            ".schema.json",
+        )
+    };
+
+    let schema_pretty = match serde_json::to_string_pretty(&schema) {
+        Ok(s) => s,
+        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
+    };
+
+    write_schema(&args, &schema_pretty, &(input_path + schema_suffix))
+}
+
+/// write the generated schema to its output file, or to stdout with --stdout
+fn write_schema(
+    args: &util::SchemaArgs,
+    schema: &str,
+    schema_output_filename: &str,
+) -> CliResult<()> {
+    if args.flag_stdout {
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+
+        handle.write_all(schema.as_bytes())?;
+        handle.flush()?;
+
+        info!("Schema written to stdout");
+    } else {
+        let mut schema_output_file = File::create(schema_output_filename)?;
+
+        schema_output_file.write_all(schema.as_bytes())?;
+        schema_output_file.flush()?;
+
+        woutinfo!("Schema written to {schema_output_filename}");
+    }
+
+    Ok(())
+}
+
+/// infer the "properties" map of a single CSV file from its stats & frequency tables,
+/// with regex pattern & custom format constraints for its String columns
+fn infer_file_schema(
+    args: &util::SchemaArgs,
+    input_path: &str,
+    input_filename: &str,
+    quiet: bool,
+) -> CliResult<Map<String, Value>> {
     // we're loading the entire file into memory, we need to check avail mem
     util::mem_file_check(
-        &std::path::PathBuf::from(&input_path),
+        &std::path::PathBuf::from(input_path),
         false,
         args.flag_memcheck,
     )?;
 
-    // we can do this directly here, since args is mutable and
-    // Config has not been created yet at this point
-    args.flag_prefer_dmy = args.flag_prefer_dmy || util::get_envvar_flag("QSV_PREFER_DMY");
-    if args.flag_prefer_dmy {
-        winfo!("Prefer DMY set.");
-    }
-
     // build schema for each field by their inferred type, min/max value/length, and unique values
     let mut properties_map: Map<String, Value> =
-        match infer_schema_from_stats(&args, &input_filename, false) {
+        match infer_schema_from_stats(args, input_filename, quiet) {
             Ok(map) => map,
             Err(e) => {
                 return fail_clierror!(
@@ -144,7 +336,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         };
 
     // generate regex pattern for selected String columns
-    let pattern_map = generate_string_patterns(&args, &properties_map)?;
+    let pattern_map = generate_string_patterns(args, &properties_map)?;
 
     // enrich properties map with pattern constraint for String fields
     for (field_name, field_def) in &mut properties_map {
@@ -153,47 +345,342 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             let field_def_map = field_def.as_object_mut().unwrap();
             let pattern = Value::String(pattern_map[field_name].clone());
             field_def_map.insert("pattern".to_string(), pattern.clone());
-            winfo!("Added regex pattern constraint for field: {field_name} -> {pattern}");
+            if !quiet {
+                winfo!("Added regex pattern constraint for field: {field_name} -> {pattern}");
+            }
         }
     }
 
-    // generate list of required fields
-    let required_fields = get_required_fields(&properties_map);
+    // add format constraints for String fields where all values match a detectable format
+    let format_map = if args.flag_detect_formats {
+        detect_string_formats(args, &properties_map)?
+    } else {
+        HashMap::new()
+    };
+    for (field_name, format) in format_map {
+        if let Some(field_def_map) = properties_map
+            .get_mut(&field_name)
+            .and_then(Value::as_object_mut)
+        {
+            field_def_map.insert("format".to_string(), Value::String(format.to_string()));
+            if !quiet {
+                winfo!("Added format constraint for field: {field_name} -> {format}");
+            }
+        }
+    }
 
-    // create final JSON object for output
-    let schema = json!({
-        "$schema": "https://json-schema.org/draft-07/schema",
-        "title": format!("JSON Schema for {input_filename}"),
-        "description": "Inferred JSON Schema from QSV schema command",
-        "type": "object",
-        "properties": Value::Object(properties_map),
-        "required": Value::Array(required_fields)
-    });
+    Ok(properties_map)
+}
 
-    let schema_pretty = match serde_json::to_string_pretty(&schema) {
-        Ok(s) => s,
-        Err(e) => return fail_clierror!("Cannot prettify schema json: {e}"),
+/// the schema file path & name of a multi-file input. It's the input itself for directories
+/// & ".infile-list" files, and the directory of the glob pattern for globs
+/// (e.g. `daily/*.csv` -> `daily.schema.json`).
+fn multi_file_output_base(input: &str) -> (String, String) {
+    let mut base_path = Path::new(input);
+    while base_path
+        .to_str()
+        .is_some_and(|p| p.contains(['*', '?', '[']))
+    {
+        base_path = base_path.parent().unwrap_or_else(|| Path::new(""));
+    }
+    let base = base_path
+        .to_string_lossy()
+        .trim_end_matches(['/', '\\'])
+        .to_string();
+    let base = if base.is_empty() || base == "." {
+        MERGED_BASENAME.to_string()
+    } else {
+        base
     };
+    let filename = Path::new(&base)
+        .file_name()
+        .map_or_else(|| base.clone(), |f| f.to_string_lossy().to_string());
+    (base, filename)
+}
 
-    if args.flag_stdout {
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
+/// infer the schema of each file of a multi-file input and merge them into one schema
+/// that admits all of them. Returns the merged "properties" map and the required fields,
+/// i.e. the fields present in all files.
+fn infer_merged_schema(
+    args: &util::SchemaArgs,
+    input_filename: &str,
+) -> CliResult<(Map<String, Value>, Vec<Value>)> {
+    let tmpdir = tempfile::tempdir()?;
+    // safety: only called for a multi-file input
+    let input = args.arg_input.clone().unwrap();
+    let files = util::process_input(vec![PathBuf::from(&input)], &tmpdir, "")?
+        .into_iter()
+        .filter(|path| {
+            // skip the sidecar files qsv creates next to its inputs
+            let name = path.to_string_lossy();
+            !SIDECAR_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+        })
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        return fail_clierror!("No files to infer a schema from in '{input}'.");
+    }
 
-        handle.write_all(schema_pretty.as_bytes())?;
-        handle.flush()?;
+    let mut merged_fields: Vec<MergedField> = Vec::new();
+    let mut field_positions: HashMap<String, usize> = HashMap::new();
+    let mut file_args = args.clone();
+    for file in &files {
+        let file_path = file.to_string_lossy().to_string();
+        let file_name = file
+            .file_name()
+            .map_or_else(|| file_path.clone(), |f| f.to_string_lossy().to_string());
+        winfo!("Inferring schema for {file_path}...");
+        // the row count is cached per command run, so reset it for each file
+        util::reset_run_state();
+        file_args.arg_input = Some(file_path.clone());
+        let properties_map = infer_file_schema(&file_args, &file_path, &file_name, true)?;
+
+        for (field_name, field_def) in properties_map {
+            let Value::Object(field_def) = field_def else {
+                continue;
+            };
+            if let Some(&pos) = field_positions.get(&field_name) {
+                merged_fields[pos].merge(&field_def, &file_path, args.flag_enum_threshold);
+            } else {
+                field_positions.insert(field_name.clone(), merged_fields.len());
+                merged_fields.push(MergedField::new(field_name, &field_def, &file_path));
+            }
+        }
+    }
 
-        info!("Schema written to stdout");
-    } else {
-        let schema_output_filename = input_path + ".schema.json";
-        let mut schema_output_file = File::create(&schema_output_filename)?;
+    let mut properties_map: Map<String, Value> = Map::with_capacity(merged_fields.len());
+    let mut required_fields: Vec<Value> = Vec::with_capacity(merged_fields.len());
+    for field in merged_fields {
+        if field.files.len() == files.len() {
+            required_fields.push(Value::String(field.name.clone()));
+        } else {
+            let missing_from = files
+                .iter()
+                .map(|file| file.to_string_lossy())
+                .filter(|file| !field.files.iter().any(|f| f.as_str() == file.as_ref()))
+                .join(", ");
+            winfo!(
+                "Field '{}' is optional. Missing from: {missing_from}",
+                field.name
+            );
+        }
+        let (field_name, field_def) = field.into_field_def(input_filename);
+        properties_map.insert(field_name, Value::Object(field_def));
+    }
+    winfo!("Merged schema of {} files.", files.len());
 
-        schema_output_file.write_all(schema_pretty.as_bytes())?;
-        schema_output_file.flush()?;
+    Ok((properties_map, required_fields))
+}
 
-        woutinfo!("Schema written to {schema_output_filename}");
+/// a field's inferred JSON Schema definition, merged across the files of a multi-file input
+struct MergedField {
+    name:      String,
+    /// the field definition, without null in "type" & "enum", and with "const"
+    /// folded into "enum"
+    def:       Map<String, Value>,
+    /// the JSON type - "null", "integer", "number" or "string"
+    json_type: String,
+    nullable:  bool,
+    /// the files that have the field
+    files:     Vec<String>,
+}
+
+impl MergedField {
+    fn new(name: String, field_def: &Map<String, Value>, file: &str) -> Self {
+        let (def, json_type, nullable) = normalize_field_def(field_def);
+        Self {
+            name,
+            def,
+            json_type,
+            nullable,
+            files: vec![file.to_string()],
+        }
     }
 
-    Ok(())
+    /// merge the field definition of another file, widening its type & constraints
+    /// so the merged definition admits the values of both
+    fn merge(&mut self, field_def: &Map<String, Value>, file: &str, enum_threshold: u64) {
+        let (def, json_type, nullable) = normalize_field_def(field_def);
+        self.nullable = self.nullable || nullable;
+        self.files.push(file.to_string());
+
+        // an all-null column in a file only tells us the field is nullable
+        if json_type == "null" {
+            return;
+        }
+        if self.json_type == "null" {
+            self.def = def;
+            self.json_type = json_type;
+            return;
+        }
+
+        let widened_type = if type_rank(&json_type) > type_rank(&self.json_type) {
+            json_type.clone()
+        } else {
+            self.json_type.clone()
+        };
+        if widened_type != self.json_type {
+            winfo!(
+                "Widened field '{}' from {} to {widened_type}, forced by {file}",
+                self.name,
+                self.json_type
+            );
+        }
+        let same_type = json_type == self.json_type;
+        let merged = &mut self.def;
+
+        if widened_type == "string" && !same_type {
+            // numeric constraints don't apply to strings, and the string constraints
+            // of only one of the files don't cover the numbers of the other
+            for key in [
+                "minimum",
+                "maximum",
+                "minLength",
+                "maxLength",
+                "format",
+                "pattern",
+            ] {
+                merged.remove(key);
+            }
+        } else {
+            merge_bound(merged, &def, "minimum", true);
+            merge_bound(merged, &def, "maximum", false);
+            merge_bound(merged, &def, "minLength", true);
+            merge_bound(merged, &def, "maxLength", false);
+            if merged.get("format") != def.get("format") {
+                if let Some(format) = merged.remove("format") {
+                    winfo!(
+                        "Dropped \"{}\" format of field '{}', forced by {file}",
+                        format.as_str().unwrap_or_default(),
+                        self.name
+                    );
+                }
+            }
+            // a value matching one of the patterns is valid
+            match (merged.get("pattern"), def.get("pattern")) {
+                (Some(Value::String(p1)), Some(Value::String(p2))) if p1 != p2 => {
+                    let pattern = format!("{p1}|{p2}");
+                    merged.insert("pattern".to_string(), Value::String(pattern));
+                },
+                (Some(_), None) => {
+                    merged.remove("pattern");
+                },
+                _ => {},
+            }
+        }
+
+        // union the enum sets, as long as all the files have one and it's within the threshold
+        match (merged.remove("enum"), def.get("enum")) {
+            (Some(Value::Array(enum_list)), Some(Value::Array(other_list))) => {
+                // numbers are stringified when integer & string columns are merged
+                let stringify = widened_type == "string";
+                let mut union: Vec<Value> = Vec::with_capacity(enum_list.len() + other_list.len());
+                for value in enum_list.into_iter().chain(other_list.iter().cloned()) {
+                    let value = match value {
+                        Value::Number(n) if stringify => Value::String(n.to_string()),
+                        value => value,
+                    };
+                    if !union.contains(&value) {
+                        union.push(value);
+                    }
+                }
+                if union.len() as u64 <= enum_threshold {
+                    merged.insert("enum".to_string(), Value::Array(union));
+                }
+            },
+            // a file without an enum leaves the field's values unconstrained
+            _ => {},
+        }
+
+        self.json_type = widened_type;
+    }
+
+    /// the merged field definition, with the description pointing to the multi-file input
+    fn into_field_def(self, input_filename: &str) -> (String, Map<String, Value>) {
+        let mut def = self.def;
+        def.insert(
+            "description".to_string(),
+            Value::String(format!("{} column from {input_filename}", self.name)),
+        );
+
+        let mut type_list = vec![Value::String(self.json_type.clone())];
+        if self.nullable && self.json_type != "null" {
+            type_list.push(Value::String("null".to_string()));
+        }
+        def.insert("type".to_string(), Value::Array(type_list));
+
+        if let Some(Value::Array(mut enum_list)) = def.remove("enum") {
+            if enum_list.len() == 1 && !self.nullable {
+                def.insert("const".to_string(), enum_list.swap_remove(0));
+            } else {
+                if self.nullable {
+                    enum_list.push(Value::Null);
+                }
+                sort_enum_list(&mut enum_list);
+                def.insert("enum".to_string(), Value::Array(enum_list));
+                // same as for a single file, patterns are only emitted without an enum
+                def.remove("pattern");
+            }
+        }
+
+        (self.name, def)
+    }
+}
+
+/// normalize a field definition for merging. Returns the definition without "type" &
+/// "description", with "const" folded into "enum" and nulls removed from "enum",
+/// along with its JSON type and whether it's nullable.
+fn normalize_field_def(field_def: &Map<String, Value>) -> (Map<String, Value>, String, bool) {
+    let mut def = field_def.clone();
+    def.remove("description");
+
+    let type_list = match def.remove("type") {
+        Some(Value::Array(type_list)) => type_list,
+        Some(json_type) => vec![json_type],
+        None => Vec::new(),
+    };
+    let nullable = type_list.iter().any(|t| t == "null");
+    let json_type = type_list
+        .iter()
+        .filter_map(Value::as_str)
+        .find(|t| *t != "null")
+        .unwrap_or("null")
+        .to_string();
+
+    if let Some(const_value) = def.remove("const") {
+        def.insert("enum".to_string(), Value::Array(vec![const_value]));
+    }
+    if let Some(Value::Array(enum_list)) = def.get_mut("enum") {
+        enum_list.retain(|value| !value.is_null());
+    }
+
+    (def, json_type, nullable)
+}
+
+/// the order in which types are widened: integer -> number -> string
+fn type_rank(json_type: &str) -> u8 {
+    match json_type {
+        "null" => 0,
+        "integer" => 1,
+        "number" => 2,
+        _ => 3,
+    }
+}
+
+/// merge a minimum/maximum or minLength/maxLength bound, keeping the lower (for minimums)
+/// or higher (for maximums) of the two. The bound is dropped if either side doesn't have it.
+fn merge_bound(merged: &mut Map<String, Value>, def: &Map<String, Value>, key: &str, min: bool) {
+    let other = def.get(key).and_then(|v| v.as_f64().map(|f| (v, f)));
+    let current = merged.get(key).and_then(Value::as_f64);
+    match (current, other) {
+        (Some(current), Some((other_value, other))) => {
+            if (min && other < current) || (!min && other > current) {
+                merged.insert(key.to_string(), other_value.clone());
+            }
+        },
+        _ => {
+            merged.remove(key);
+        },
+    }
 }
 
 /// Builds JSON MAP object that corresponds to the "properties" object of JSON Schema (Draft 7
@@ -406,32 +893,7 @@ pub fn infer_schema_from_stats(
                 }
             }
         } else {
-            // sort enum list
-            enum_list.sort_unstable_by(|a, b| {
-                match (a, b) {
-                    (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
-                    (Value::Null, _) => std::cmp::Ordering::Less,
-                    (_, Value::Null) => std::cmp::Ordering::Greater,
-                    (Value::String(a_str), Value::String(b_str)) => a_str.cmp(b_str),
-                    (Value::Number(a_num), Value::Number(b_num)) => a_num
-                        .as_f64()
-                        .unwrap_or_default()
-                        .partial_cmp(&b_num.as_f64().unwrap_or_default())
-                        .unwrap_or(std::cmp::Ordering::Equal),
-                    // Compare types by their "priority"
-                    _ => {
-                        let type_priority = |v: &Value| match v {
-                            Value::Null => 0,
-                            Value::Bool(_) => 1,
-                            Value::Number(_) => 2,
-                            Value::String(_) => 3,
-                            Value::Array(_) => 4,
-                            Value::Object(_) => 5,
-                        };
-                        type_priority(a).cmp(&type_priority(b))
-                    },
-                }
-            });
+            sort_enum_list(&mut enum_list);
 
             field_map.insert("enum".to_string(), Value::Array(enum_list.clone()));
             if !quiet {
@@ -449,6 +911,450 @@ pub fn infer_schema_from_stats(
     Ok(properties_map)
 }
 
+/// sort an enum list, with null first, so the schema can be diff'ed between runs
+fn sort_enum_list(enum_list: &mut [Value]) {
+    enum_list.sort_unstable_by(|a, b| {
+        match (a, b) {
+            (Value::Null, Value::Null) => std::cmp::Ordering::Equal,
+            (Value::Null, _) => std::cmp::Ordering::Less,
+            (_, Value::Null) => std::cmp::Ordering::Greater,
+            (Value::String(a_str), Value::String(b_str)) => a_str.cmp(b_str),
+            (Value::Number(a_num), Value::Number(b_num)) => a_num
+                .as_f64()
+                .unwrap_or_default()
+                .partial_cmp(&b_num.as_f64().unwrap_or_default())
+                .unwrap_or(std::cmp::Ordering::Equal),
+            // Compare types by their "priority"
+            _ => {
+                let type_priority = |v: &Value| match v {
+                    Value::Null => 0,
+                    Value::Bool(_) => 1,
+                    Value::Number(_) => 2,
+                    Value::String(_) => 3,
+                    Value::Array(_) => 4,
+                    Value::Object(_) => 5,
+                };
+                type_priority(a).cmp(&type_priority(b))
+            },
+        }
+    });
+}
+
+/// Builds a Frictionless Table Schema (https://specs.frictionlessdata.io/table-schema/)
+/// from the types & constraints already inferred in the JSON Schema "properties" map.
+/// Supported Table Schema constraints:
+///  * required - when the field is a required field without nulls
+///  * unique - when the field is in unique_fields (see `get_unique_fields`)
+///  * enum (from JSON Schema enum or const)
+///  * minLength/maxLength
+///  * minimum/maximum
+///  * pattern
+fn build_table_schema(
+    properties_map: &Map<String, Value>,
+    required_fields: &[Value],
+    unique_fields: &HashSet<String>,
+) -> Value {
+    let mut fields: Vec<Value> = Vec::with_capacity(properties_map.len());
+
+    for (field_name, field_def) in properties_map {
+        let type_list = field_def["type"].as_array().cloned().unwrap_or_default();
+        let nullable = type_list.iter().any(|t| t == "null");
+        let json_type = type_list
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .unwrap_or("null");
+
+        // date & date-time formats are only set with --strict-dates
+        let field_type = match (json_type, field_def.get("format").and_then(Value::as_str)) {
+            ("integer", _) => "integer",
+            ("number", _) => "number",
+            ("string", Some("date")) => "date",
+            ("string", Some("date-time")) => "datetime",
+            ("null", _) => "any",
+            _ => "string",
+        };
+
+        let mut constraints = Map::new();
+        if !nullable && required_fields.contains(&Value::String(field_name.clone())) {
+            constraints.insert("required".to_string(), Value::Bool(true));
+            if unique_fields.contains(field_name) {
+                constraints.insert("unique".to_string(), Value::Bool(true));
+            }
+        }
+        for key in ["minLength", "maxLength", "minimum", "maximum", "pattern"] {
+            if let Some(value) = field_def.get(key) {
+                constraints.insert(key.to_string(), value.clone());
+            }
+        }
+        // Table Schema has no const constraint, so we use a single-value enum instead.
+        // Missing values are handled by "required" & "missingValues", so nulls are skipped.
+        if let Some(Value::Array(enum_list)) = field_def.get("enum") {
+            let enum_list: Vec<Value> =
+                enum_list.iter().filter(|v| !v.is_null()).cloned().collect();
+            constraints.insert("enum".to_string(), Value::Array(enum_list));
+        } else if let Some(const_value) = field_def.get("const") {
+            constraints.insert("enum".to_string(), json!([const_value]));
+        }
+
+        let mut field = Map::with_capacity(5);
+        field.insert("name".to_string(), Value::String(field_name.clone()));
+        field.insert("type".to_string(), Value::String(field_type.to_string()));
+        if matches!(field_type, "date" | "datetime") {
+            field.insert("format".to_string(), Value::String("default".to_string()));
+        }
+        if let Some(description) = field_def.get("description") {
+            field.insert("description".to_string(), description.clone());
+        }
+        if !constraints.is_empty() {
+            field.insert("constraints".to_string(), Value::Object(constraints));
+        }
+        fields.push(Value::Object(field));
+    }
+
+    json!({
+        "fields": fields,
+        "missingValues": [""]
+    })
+}
+
+/// get the fields whose cardinality equals the row count, using the stats cache
+fn get_unique_fields(args: &util::SchemaArgs) -> CliResult<HashSet<String>> {
+    // the stats cache was just refreshed by infer_schema_from_stats if it had to be,
+    // so don't force recomputing it again
+    let mut stats_args = args.clone();
+    stats_args.flag_force = false;
+    let (csv_fields, csv_stats, dataset_stats) =
+        util::get_stats_records(&stats_args, StatsMode::Schema)?;
+
+    let row_count = match dataset_stats
+        .get("qsv__rowcount")
+        .and_then(|count| count.parse::<u64>().ok())
+    {
+        Some(count) => count,
+        None => {
+            let rconfig = Config::new(args.arg_input.as_ref())
+                .delimiter(args.flag_delimiter)
+                .no_headers(args.flag_no_headers);
+            util::count_rows(&rconfig)?
+        },
+    };
+
+    let mut unique_fields = HashSet::default();
+    for (i, csv_field) in csv_fields.iter().enumerate() {
+        if row_count > 0 && csv_stats[i].nullcount == 0 && csv_stats[i].cardinality == row_count {
+            unique_fields.insert(convert_to_string(csv_field)?);
+        }
+    }
+    Ok(unique_fields)
+}
+
+/// generate a SQL DDL, Avro, Arrow or Polars schema from the stats cache
+fn generate_stats_schema(
+    args: &util::SchemaArgs,
+    schema_format: &str,
+    input_path: &str,
+    input_filename: &str,
+) -> CliResult<()> {
+    let (csv_fields, csv_stats, _) = util::get_stats_records(args, StatsMode::Schema)?;
+    let headers = csv_fields
+        .iter()
+        .map(convert_to_string)
+        .collect::<CliResult<Vec<String>>>()?;
+    // the table/record name is the input's file name without its extension
+    let table_name = Path::new(input_filename).file_stem().map_or_else(
+        || input_filename.to_string(),
+        |stem| stem.to_string_lossy().to_string(),
+    );
+
+    let (schema, schema_output_filename) = match schema_format {
+        "postgresql" | "sqlite" | "duckdb" | "bigquery" => {
+            let dialect = match schema_format {
+                "postgresql" => SqlDialect::Postgres,
+                "sqlite" => SqlDialect::Sqlite,
+                "duckdb" => SqlDialect::DuckDb,
+                _ => SqlDialect::BigQuery,
+            };
+            (
+                build_sql_ddl(dialect, &table_name, &headers, &csv_stats),
+                format!("{input_path}.{schema_format}.sql"),
+            )
+        },
+        "avro" => (
+            serde_json::to_string_pretty(&build_avro_schema(
+                &table_name,
+                input_filename,
+                &headers,
+                &csv_stats,
+            ))?,
+            format!("{input_path}.avsc"),
+        ),
+        "arrow" => (
+            serde_json::to_string_pretty(&build_arrow_schema(&headers, &csv_stats))?,
+            format!("{input_path}.arrow-schema.json"),
+        ),
+        _ => (
+            build_polars_schema(&csv_fields, &csv_stats)?,
+            // same name as the schema cached by `sqlp --cache-schema`, so sqlp can use it
+            Path::new(input_path)
+                .with_extension("pschema.json")
+                .to_string_lossy()
+                .to_string(),
+        ),
+    };
+
+    write_schema(args, &schema, &schema_output_filename)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SqlDialect {
+    Postgres,
+    Sqlite,
+    DuckDb,
+    BigQuery,
+}
+
+/// build a `CREATE TABLE` statement with a column for each field. Columns without
+/// nulls are NOT NULL, and string lengths & numeric precision are taken from the stats.
+fn build_sql_ddl(
+    dialect: SqlDialect,
+    table_name: &str,
+    headers: &[String],
+    csv_stats: &[StatsData],
+) -> String {
+    let columns = headers
+        .iter()
+        .zip(csv_stats)
+        .map(|(header, stat)| {
+            let not_null = if stat.nullcount == 0 && stat.r#type != "NULL" {
+                " NOT NULL"
+            } else {
+                ""
+            };
+            format!(
+                "  {} {}{not_null}",
+                quote_sql_identifier(dialect, header),
+                sql_column_type(dialect, stat)
+            )
+        })
+        .join(",\n");
+
+    format!(
+        "CREATE TABLE {} (\n{columns}\n);\n",
+        quote_sql_identifier(dialect, table_name)
+    )
+}
+
+fn quote_sql_identifier(dialect: SqlDialect, identifier: &str) -> String {
+    if dialect == SqlDialect::BigQuery {
+        format!("`{}`", identifier.replace('`', "\\`"))
+    } else {
+        format!("\"{}\"", identifier.replace('"', "\"\""))
+    }
+}
+
+fn sql_column_type(dialect: SqlDialect, stat: &StatsData) -> String {
+    match stat.r#type.as_str() {
+        "Integer" => match dialect {
+            SqlDialect::Sqlite => "INTEGER",
+            SqlDialect::BigQuery => "INT64",
+            _ if fits_i32(stat) => "INTEGER",
+            _ => "BIGINT",
+        }
+        .to_string(),
+        "Float" => match (dialect, numeric_precision(stat)) {
+            (SqlDialect::Postgres, Some((precision, scale))) if precision <= 1000 => {
+                format!("NUMERIC({precision},{scale})")
+            },
+            (SqlDialect::DuckDb, Some((precision, scale))) if precision <= 38 => {
+                format!("DECIMAL({precision},{scale})")
+            },
+            (SqlDialect::BigQuery, Some((precision, scale)))
+                if scale <= 9 && precision - scale <= 29 =>
+            {
+                format!("NUMERIC({precision},{scale})")
+            },
+            (SqlDialect::BigQuery, Some((precision, scale)))
+                if scale <= 38 && precision - scale <= 38 =>
+            {
+                format!("BIGNUMERIC({precision},{scale})")
+            },
+            (SqlDialect::Postgres, _) => "DOUBLE PRECISION".to_string(),
+            (SqlDialect::Sqlite, _) => "REAL".to_string(),
+            (SqlDialect::DuckDb, _) => "DOUBLE".to_string(),
+            (SqlDialect::BigQuery, _) => "FLOAT64".to_string(),
+        },
+        "String" => match (dialect, stat.max_length.filter(|len| *len > 0)) {
+            (SqlDialect::Postgres, Some(max_length)) => format!("VARCHAR({max_length})"),
+            (SqlDialect::BigQuery, Some(max_length)) => format!("STRING({max_length})"),
+            (dialect, _) => sql_text_type(dialect).to_string(),
+        },
+        // SQLite has no date/time types, they're stored as ISO 8601 strings
+        "Date" => match dialect {
+            SqlDialect::Sqlite => "TEXT",
+            _ => "DATE",
+        }
+        .to_string(),
+        "DateTime" => match dialect {
+            SqlDialect::Sqlite => "TEXT",
+            SqlDialect::BigQuery => "DATETIME",
+            _ => "TIMESTAMP",
+        }
+        .to_string(),
+        "Boolean" => match dialect {
+            SqlDialect::Sqlite => "INTEGER",
+            SqlDialect::BigQuery => "BOOL",
+            _ => "BOOLEAN",
+        }
+        .to_string(),
+        _ => sql_text_type(dialect).to_string(),
+    }
+}
+
+const fn sql_text_type(dialect: SqlDialect) -> &'static str {
+    match dialect {
+        SqlDialect::Postgres | SqlDialect::Sqlite => "TEXT",
+        SqlDialect::DuckDb => "VARCHAR",
+        SqlDialect::BigQuery => "STRING",
+    }
+}
+
+/// does the integer column's range fit in 32 bits?
+fn fits_i32(stat: &StatsData) -> bool {
+    [stat.min.as_deref(), stat.max.as_deref()]
+        .into_iter()
+        .all(|value| value.is_some_and(|v| v.parse::<i32>().is_ok()))
+}
+
+/// the (precision, scale) of a float column, with the scale from max_precision
+/// and the integer digits from its min & max values
+fn numeric_precision(stat: &StatsData) -> Option<(u32, u32)> {
+    let scale = stat.max_precision?;
+    let mut integer_digits = 1;
+    for value in [stat.min.as_deref(), stat.max.as_deref()] {
+        let value = value?.trim_start_matches('-');
+        // scientific notation, inf & NaN can't be represented as decimals
+        if !value.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+            return None;
+        }
+        let integer_part = value
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches('0');
+        integer_digits = integer_digits.max(integer_part.len() as u32);
+    }
+    Some((integer_digits + scale, scale))
+}
+
+/// build an Avro record schema (https://avro.apache.org/docs/current/specification/).
+/// Avro names only allow letters, digits & underscores, so other characters are replaced
+/// with underscores, with the original column name kept in the field's "doc".
+fn build_avro_schema(
+    table_name: &str,
+    input_filename: &str,
+    headers: &[String],
+    csv_stats: &[StatsData],
+) -> Value {
+    let mut field_names: HashSet<String> = HashSet::default();
+    let fields: Vec<Value> = headers
+        .iter()
+        .zip(csv_stats)
+        .map(|(header, stat)| {
+            let base_name = avro_name(header);
+            let mut name = base_name.clone();
+            let mut suffix = 2;
+            while !field_names.insert(name.clone()) {
+                name = format!("{base_name}_{suffix}");
+                suffix += 1;
+            }
+
+            let avro_type = match stat.r#type.as_str() {
+                "Integer" if fits_i32(stat) => json!("int"),
+                "Integer" => json!("long"),
+                "Float" => json!("double"),
+                "Boolean" => json!("boolean"),
+                "Date" => json!({"type": "int", "logicalType": "date"}),
+                "DateTime" => json!({"type": "long", "logicalType": "timestamp-millis"}),
+                _ => json!("string"),
+            };
+
+            let mut field = Map::with_capacity(4);
+            field.insert("name".to_string(), Value::String(name));
+            if stat.nullcount > 0 || stat.r#type == "NULL" {
+                field.insert("type".to_string(), json!(["null", avro_type]));
+                field.insert("default".to_string(), Value::Null);
+            } else {
+                field.insert("type".to_string(), avro_type);
+            }
+            field.insert(
+                "doc".to_string(),
+                Value::String(format!("{header} column from {input_filename}")),
+            );
+            Value::Object(field)
+        })
+        .collect();
+
+    json!({
+        "type": "record",
+        "name": avro_name(table_name),
+        "fields": fields
+    })
+}
+
+fn avro_name(name: &str) -> String {
+    let mut avro_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if avro_name.is_empty() || avro_name.starts_with(|c: char| c.is_ascii_digit()) {
+        avro_name.insert(0, '_');
+    }
+    avro_name
+}
+
+/// build an Arrow schema, in the JSON format of the Arrow integration tests
+/// (https://arrow.apache.org/docs/format/Integration.html#json-test-data-format)
+fn build_arrow_schema(headers: &[String], csv_stats: &[StatsData]) -> Value {
+    let fields: Vec<Value> = headers
+        .iter()
+        .zip(csv_stats)
+        .map(|(header, stat)| {
+            let arrow_type = match stat.r#type.as_str() {
+                "Integer" => json!({
+                    "name": "int",
+                    "isSigned": true,
+                    "bitWidth": if fits_i32(stat) { 32 } else { 64 }
+                }),
+                "Float" => json!({"name": "floatingpoint", "precision": "DOUBLE"}),
+                "Boolean" => json!({"name": "bool"}),
+                "Date" => json!({"name": "date", "unit": "DAY"}),
+                "DateTime" => json!({"name": "timestamp", "unit": "MILLISECOND"}),
+                _ => json!({"name": "utf8"}),
+            };
+            json!({
+                "name": header,
+                "nullable": stat.nullcount > 0 || stat.r#type == "NULL",
+                "type": arrow_type,
+                "children": []
+            })
+        })
+        .collect();
+
+    json!({ "fields": fields })
+}
+
+#[cfg(feature = "polars")]
+fn build_polars_schema(csv_fields: &ByteRecord, csv_stats: &[StatsData]) -> CliResult<String> {
+    let schema = util::polars_schema_from_stats(csv_fields, csv_stats);
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+#[cfg(not(feature = "polars"))]
+fn build_polars_schema(_csv_fields: &ByteRecord, _csv_stats: &[StatsData]) -> CliResult<String> {
+    fail_incorrectusage_clierror!("--format polars requires the polars feature.")
+}
+
 /// get column selector argument string for low cardinality columns
 fn build_low_cardinality_column_selector_arg(
     low_cardinality_column_indices: &mut Vec<u64>,
@@ -666,6 +1572,52 @@ fn generate_string_patterns(
     Ok(pattern_map)
 }
 
+/// detect custom string formats (see `validate --help`) of String columns.
+/// A format is only detected if all the non-empty values of the column match it.
+fn detect_string_formats(
+    args: &util::SchemaArgs,
+    properties_map: &Map<String, Value>,
+) -> CliResult<HashMap<String, &'static str>> {
+    let rconfig = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers);
+
+    let mut rdr = rconfig.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+
+    // only String columns that don't have a format yet (e.g. date/date-time) are candidates
+    let mut detectors: Vec<(usize, String, FormatDetector)> = Vec::new();
+    for (i, header) in headers.iter().enumerate() {
+        let header_string = convert_to_string(header)?;
+        let Some(field_def) = properties_map.get(&header_string) else {
+            continue;
+        };
+        let is_string = field_def["type"]
+            .as_array()
+            .is_some_and(|types| types.contains(&Value::String("string".to_string())));
+        if is_string && field_def.get("format").is_none() {
+            detectors.push((i, header_string, FormatDetector::default()));
+        }
+    }
+
+    let mut record = csv::ByteRecord::new();
+    while !detectors.is_empty() && rdr.read_byte_record(&mut record)? {
+        for (i, _, detector) in &mut detectors {
+            let value = record.get(*i).unwrap_or_default();
+            if !value.is_empty() {
+                detector.update(&String::from_utf8_lossy(value));
+            }
+        }
+        // stop checking columns that can't match any format
+        detectors.retain(|(_, _, detector)| !detector.is_done());
+    }
+
+    Ok(detectors
+        .into_iter()
+        .filter_map(|(_, header, detector)| detector.format().map(|format| (header, format)))
+        .collect())
+}
+
 // only emit "pattern" constraint for String fields without enum constraint
 fn should_emit_pattern_constraint(field_def: &Value) -> bool {
     let type_list = field_def[&"type"].as_array().unwrap();
@@ -49,6 +49,26 @@ selected using regular expressions.
   # remove SSN, account_no and password columns
   $ qsv select '!/SSN|account_no|password/'
 
+  Select columns by the data types & summary statistics in the stats cache.
+  These stats-based selectors work with the --select option of other commands too.
+  If the stats cache is missing or stale, `qsv stats` is run to create it first.
+  # select all Integer columns
+  $ qsv select type:Integer
+  # select all numeric columns
+  $ qsv select 'type:Float|Integer'
+  # select all non-String columns
+  $ qsv select '!type:String'
+  # drop columns that are more than half empty (nulls is an alias for sparsity)
+  $ qsv select '!nulls>0.5'
+  # drop constant columns
+  $ qsv select '!cardinality=1'
+  Supported stats are nullcount, nulls/sparsity, cardinality & uniqueness_ratio with
+  the =, !=, >, >=, < & <= operators. Valid types are NULL, Integer, Float, String,
+  Date, DateTime & Boolean. A stats cache created without --infer-dates is
+  recreated, so Date & DateTime columns can be selected.
+  A column whose name is exactly the selector (e.g. a "type:id" header) is selected
+  by name instead. Stats-based selectors cannot be used with stdin.
+
   Re-order and duplicate columns arbitrarily using different types of selectors:
   $ qsv select 3-1,Header3-Header1,Header1,Foo[2],Header1
 
@@ -63,8 +83,8 @@ Usage:
 
 select arguments:
     <selection>            The columns to select. 
-                           You can select columns by index, by name, by range, by regex and
-                           any combination of these. If the first character is '!', the
+                           You can select columns by index, by name, by range, by regex,
+                           by inferred type or stats and any combination of these. If the first character is '!', the
                            selection will be inverted. If the selection contains embedded
                            spaces or characters that conflict with selector syntax, it must
                            be quoted. See examples above.
@@ -0,0 +1,608 @@
+static USAGE: &str = r#"
+Run a local HTTP server that exposes qsv commands as an HTTP API.
+
+Each request runs a qsv command in a separate qsv process, in its own temporary
+workspace directory, which is deleted when the command finishes.
+
+Endpoints:
+  GET  /health          Returns the status of the server as JSON, e.g.
+                        {"status":"ok","version":"5.0.0","running":1,"max_concurrent":8}
+
+  POST /run/<command>   Runs <command>. Its arguments are passed in order with
+                        "arg" query parameters (e.g. /run/select?arg=1,3&arg=input.csv).
+                        The request body (e.g. a CSV file) is streamed to the file
+                        input.csv in the workspace (use the "filename" query parameter
+                        to change its name), which is the command's current directory.
+                        The request body is also the command's stdin, so commands that
+                        read from stdin do not need the file name as an argument.
+
+The output of the command (its stdout) is streamed back as the response body.
+If the command fails before writing any output, the response has a 422 status and its
+body is the JSON error of the command
+(see https://github.com/dathere/qsv/blob/master/docs/ERROR_CODES.md).
+If the command fails after its output started streaming, the response is truncated.
+
+When --max-concurrent commands are running, new requests get a 429 (Too Many Requests)
+response. Request bodies larger than --max-body-size get a 413 (Payload Too Large) response.
+
+The server is meant for trusted clients. Commands run with the permissions of the server,
+so arguments that are URLs, absolute paths or that reference a parent directory (..),
+including option values (e.g. --output=/tmp/x or -o/tmp/x), are rejected with a
+400 (Bad Request) response - commands can only use the files in their workspace.
+Commands ignore the qsv.toml project configuration (see QSV_SKIP_QSV_TOML).
+The commands that can run arbitrary code, need a terminal, make network requests, or read &
+write files & databases outside of the workspace are denied by default (see --deny).
+
+The server listens on the loopback interface by default. To listen on another address,
+an --auth-token is required, and clients must send it in an
+"Authorization: Bearer <token>" header, otherwise they get a 401 (Unauthorized) response.
+
+Examples:
+
+Start the server on port 8080:
+  $ qsv serve
+
+Count the rows of a CSV file:
+  $ curl --data-binary @data.csv http://localhost:8080/run/count
+
+Select the first & third columns, and sort them:
+  $ curl --data-binary @data.csv "http://localhost:8080/run/select?arg=1,3" \
+      | curl --data-binary @- "http://localhost:8080/run/sort?arg=--select&arg=1"
+
+Compute stats, using the uploaded file (stats needs a file to use its cache):
+  $ curl --data-binary @data.csv \
+      "http://localhost:8080/run/stats?arg=--everything&arg=input.csv"
+
+Start the server on a random port, with up to 4 running commands:
+  $ qsv serve --port 0 --max-concurrent 4
+
+For more examples, see https://github.com/dathere/qsv/blob/master/tests/test_serve.rs.
+
+Usage:
+    qsv serve [options]
+    qsv serve --help
+
+serve options:
+    --bind <address>        The address to listen on. A non-loopback address
+                            requires --auth-token. [default: 127.0.0.1]
+    --port <port>           The port to listen on. Use 0 to use a random free port.
+                            The address & port are written to stderr when the
+                            server starts. [default: 8080]
+    --max-concurrent <n>    The maximum number of commands that can run at the same time.
+                            Defaults to the number of logical CPUs, or QSV_MAX_JOBS if set.
+    --max-body-size <mb>    The maximum size of a request body, in megabytes.
+                            [default: 100]
+    --workers <n>           The number of HTTP worker threads.
+                            Defaults to the number of logical CPUs.
+    --workdir <dir>         The directory where the request workspaces are created.
+                            Defaults to the system temporary directory.
+    --auth-token <token>    The token clients must send in an
+                            "Authorization: Bearer <token>" header.
+                            If not set, the QSV_SERVE_AUTH_TOKEN environment variable
+                            is used. Required when --bind is not a loopback address.
+    --deny <commands>       Comma-separated list of the commands that cannot be run.
+                            Set to "" to allow all commands.
+                            [default: clipboard,describegpt,fetch,fetchpost,foreach,geocode,lens,luau,pipeline,prompt,py,serve,sniff,sqlp,to,validate]
+
+Common options:
+    -h, --help              Display this message
+"#;
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Read, Write},
+    net::IpAddr,
+    path::{Component, Path, PathBuf},
+    process::{self, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use actix_web::{
+    App, HttpRequest, HttpResponse, HttpServer,
+    http::{StatusCode, header},
+    rt,
+    web::{self, Bytes},
+};
+use futures::{SinkExt, StreamExt, channel::mpsc, executor::block_on, future::ready, stream};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{CliResult, Command, projectconfig::QSV_TOML_FILENAME, util};
+
+const DEFAULT_INPUT_FILENAME: &str = "input.csv";
+const OUTPUT_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+struct Args {
+    flag_bind:           String,
+    flag_port:           u16,
+    flag_max_concurrent: Option<usize>,
+    flag_max_body_size:  usize,
+    flag_workers:        Option<usize>,
+    flag_workdir:        Option<String>,
+    flag_auth_token:     Option<String>,
+    flag_deny:           String,
+}
+
+struct ServerState {
+    qsv_bin:        PathBuf,
+    workdir:        Option<PathBuf>,
+    auth_token:     Option<String>,
+    denied:         HashSet<String>,
+    max_concurrent: usize,
+    max_body_size:  usize,
+    running:        AtomicUsize,
+}
+
+/// a running command slot, released when dropped
+struct RunningGuard {
+    state: web::Data<ServerState>,
+}
+
+impl RunningGuard {
+    fn try_acquire(state: &web::Data<ServerState>) -> Option<Self> {
+        state
+            .running
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (running < state.max_concurrent).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| Self {
+                state: state.clone(),
+            })
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.state.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// the output of a running command
+enum CommandOutput {
+    Data(Bytes),
+    Exit { success: bool, stderr: String },
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let max_concurrent = args.flag_max_concurrent.unwrap_or_else(util::max_jobs);
+    if max_concurrent == 0 {
+        return fail_incorrectusage_clierror!("--max-concurrent must be greater than zero.");
+    }
+    let workers = args.flag_workers.unwrap_or_else(util::num_cpus).max(1);
+
+    let workdir = args.flag_workdir.map(PathBuf::from);
+    if let Some(ref workdir) = workdir {
+        fs::create_dir_all(workdir)?;
+    }
+
+    let auth_token = args
+        .flag_auth_token
+        .or_else(|| std::env::var("QSV_SERVE_AUTH_TOKEN").ok())
+        .filter(|token| !token.is_empty());
+    if auth_token.is_none() && !is_loopback(&args.flag_bind) {
+        return fail_incorrectusage_clierror!(
+            "--auth-token is required to listen on a non-loopback address ({}).",
+            args.flag_bind
+        );
+    }
+
+    let denied: HashSet<String> = args
+        .flag_deny
+        .split(',')
+        .map(|cmd| cmd.trim().to_lowercase())
+        .filter(|cmd| !cmd.is_empty())
+        .collect();
+
+    let state = web::Data::new(ServerState {
+        qsv_bin: std::env::current_exe()?,
+        workdir,
+        auth_token,
+        denied,
+        max_concurrent,
+        max_body_size: args.flag_max_body_size.saturating_mul(1024 * 1024),
+        running: AtomicUsize::new(0),
+    });
+
+    rt::System::new().block_on(async move {
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(state.clone())
+                .route("/health", web::get().to(health))
+                .route("/run/{command}", web::post().to(run_command))
+                .default_service(web::to(not_found))
+        })
+        .workers(workers)
+        .bind((args.flag_bind.as_str(), args.flag_port))?;
+
+        for addr in server.addrs() {
+            winfo!("qsv serve listening on http://{addr}");
+        }
+        server.run().await
+    })?;
+    Ok(())
+}
+
+fn error_response(status: StatusCode, message: &str) -> HttpResponse {
+    HttpResponse::build(status).json(json!({
+        "error": {
+            "status": status.as_u16(),
+            "message": message,
+        }
+    }))
+}
+
+fn body_too_large(max_body_size: usize) -> HttpResponse {
+    error_response(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        &format!("The request body is larger than the maximum of {max_body_size} bytes."),
+    )
+}
+
+async fn not_found() -> HttpResponse {
+    error_response(
+        StatusCode::NOT_FOUND,
+        "Not found. Use GET /health or POST /run/<command>.",
+    )
+}
+
+/// whether the server can only be reached from the local machine
+fn is_loopback(bind: &str) -> bool {
+    bind.eq_ignore_ascii_case("localhost")
+        || bind
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|addr| addr.is_loopback())
+}
+
+/// check the request's bearer token, comparing in constant time
+fn is_authorized(req: &HttpRequest, state: &ServerState) -> bool {
+    let Some(ref auth_token) = state.auth_token else {
+        return true;
+    };
+    let Some(token) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    token.len() == auth_token.len()
+        && token
+            .bytes()
+            .zip(auth_token.bytes())
+            .fold(0_u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn unauthorized() -> HttpResponse {
+    HttpResponse::build(StatusCode::UNAUTHORIZED)
+        .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+        .json(json!({
+            "error": {
+                "status": StatusCode::UNAUTHORIZED.as_u16(),
+                "message": "Missing or invalid auth token.",
+            }
+        }))
+}
+
+/// an argument that is a URL, an absolute path or references a parent directory,
+/// including the value of a `--flag=value` argument and the value attached to a short
+/// option, which can follow other short flags (e.g. `-o/tmp/x` or `-no../x`).
+/// The value of a `--flag value` pair is checked as an argument of its own.
+fn escapes_workspace(arg: &str) -> bool {
+    fn is_outside(value: &str) -> bool {
+        let path = Path::new(value);
+        value.contains("://")
+            || path.is_absolute()
+            || path.has_root()
+            || path
+                .components()
+                .any(|component| matches!(component, Component::ParentDir | Component::Prefix(_)))
+    }
+
+    if is_outside(arg) {
+        return true;
+    }
+    if let Some(long) = arg.strip_prefix("--") {
+        return long
+            .split_once('=')
+            .is_some_and(|(_, value)| is_outside(value));
+    }
+    arg.strip_prefix('-')
+        .is_some_and(|short| short.char_indices().any(|(i, _)| is_outside(&short[i..])))
+}
+
+async fn health(req: HttpRequest, state: web::Data<ServerState>) -> HttpResponse {
+    if !is_authorized(&req, &state) {
+        return unauthorized();
+    }
+    HttpResponse::Ok().json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+        "running": state.running.load(Ordering::SeqCst),
+        "max_concurrent": state.max_concurrent,
+    }))
+}
+
+async fn run_command(
+    req: HttpRequest,
+    state: web::Data<ServerState>,
+    command: web::Path<String>,
+    query: web::Query<Vec<(String, String)>>,
+    mut payload: web::Payload,
+) -> HttpResponse {
+    if !is_authorized(&req, &state) {
+        return unauthorized();
+    }
+    let command = command.into_inner();
+    if command == "help" || Command::from_name(&command).is_none() {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            &format!("Unknown command \"{command}\"."),
+        );
+    }
+    if state.denied.contains(&command) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            &format!("The \"{command}\" command is not allowed."),
+        );
+    }
+
+    let mut cmd_args = vec![command];
+    let mut input_filename = DEFAULT_INPUT_FILENAME.to_string();
+    for (key, value) in query.into_inner() {
+        match key.as_str() {
+            "arg" => {
+                if escapes_workspace(&value) {
+                    return error_response(
+                        StatusCode::BAD_REQUEST,
+                        &format!(
+                            "Invalid argument \"{value}\". Arguments cannot be URLs, absolute \
+                             paths or reference a parent directory."
+                        ),
+                    );
+                }
+                cmd_args.push(value);
+            },
+            "filename" => {
+                // the workspace must not have a qsv.toml, in case commands look for it
+                if value.is_empty()
+                    || value.contains(['/', '\\'])
+                    || value == "."
+                    || value == ".."
+                    || value.eq_ignore_ascii_case(QSV_TOML_FILENAME)
+                {
+                    return error_response(
+                        StatusCode::BAD_REQUEST,
+                        &format!("Invalid filename \"{value}\"."),
+                    );
+                }
+                input_filename = value;
+            },
+            _ => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Unknown query parameter \"{key}\". Use \"arg\" or \"filename\"."),
+                );
+            },
+        }
+    }
+
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<usize>().ok());
+    if content_length.is_some_and(|len| len > state.max_body_size) {
+        return body_too_large(state.max_body_size);
+    }
+
+    let Some(guard) = RunningGuard::try_acquire(&state) else {
+        return error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            &format!(
+                "Too many running commands (max: {}). Try again later.",
+                state.max_concurrent
+            ),
+        );
+    };
+
+    // the workspace is deleted when it goes out of scope, after the command finishes
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("qsv-serve-");
+    let workspace = match state.workdir {
+        Some(ref workdir) => builder.tempdir_in(workdir),
+        None => builder.tempdir(),
+    };
+    let workspace = match workspace {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Cannot create workspace: {e}"),
+            );
+        },
+    };
+
+    // stream the request body to the input file
+    let input_path = workspace.path().join(&input_filename);
+    let mut input_file = match fs::File::create(&input_path) {
+        Ok(file) => io::BufWriter::new(file),
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Cannot create input file: {e}"),
+            );
+        },
+    };
+    let mut body_size = 0_usize;
+    while let Some(chunk) = payload.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Cannot read request body: {e}"),
+                );
+            },
+        };
+        body_size += chunk.len();
+        if body_size > state.max_body_size {
+            return body_too_large(state.max_body_size);
+        }
+        if let Err(e) = input_file.write_all(&chunk) {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Cannot write input file: {e}"),
+            );
+        }
+    }
+    if let Err(e) = input_file.flush() {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Cannot write input file: {e}"),
+        );
+    }
+    drop(input_file);
+    let stdin = match fs::File::open(&input_path) {
+        Ok(stdin) => stdin,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Cannot read input file: {e}"),
+            );
+        },
+    };
+
+    log::info!("serve: running qsv {}", cmd_args.join(" "));
+    let child = process::Command::new(&state.qsv_bin)
+        .args(&cmd_args)
+        .current_dir(workspace.path())
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // so failed commands report structured errors, without progress events,
+        // and don't use the server's project configuration
+        .env("QSV_ERROR_FORMAT", "json")
+        .env("QSV_SKIP_QSV_TOML", "1")
+        .env_remove("QSV_PROGRESS_FORMAT")
+        .env_remove("QSV_TOML_PATH")
+        .env_remove("QSV_PROFILE")
+        .spawn();
+    let child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Cannot run qsv: {e}"),
+            );
+        },
+    };
+
+    let (tx, mut rx) = mpsc::channel::<CommandOutput>(8);
+    thread::spawn(move || {
+        stream_command_output(child, tx);
+        // release the running slot & delete the workspace only after the command exits
+        drop(guard);
+        drop(workspace);
+    });
+
+    // wait for the first output, so commands that fail right away get an error response
+    match rx.next().await {
+        None => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The command exited unexpectedly.",
+        ),
+        Some(CommandOutput::Exit { success: true, .. }) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .finish(),
+        Some(CommandOutput::Exit {
+            success: false,
+            stderr,
+        }) => command_error_response(&stderr),
+        Some(CommandOutput::Data(first)) => {
+            let rest = rx.filter_map(|output| {
+                ready(match output {
+                    CommandOutput::Data(data) => Some(Ok(data)),
+                    CommandOutput::Exit { success: true, .. } => None,
+                    CommandOutput::Exit {
+                        success: false,
+                        stderr,
+                    } => Some(Err(io::Error::other(stderr.trim().to_string()))),
+                })
+            });
+            HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .streaming(stream::once(ready(Ok::<_, io::Error>(first))).chain(rest))
+        },
+    }
+}
+
+/// read the command's stdout in chunks & send them to the response,
+/// followed by the command's exit status & stderr
+fn stream_command_output(mut child: process::Child, mut tx: mpsc::Sender<CommandOutput>) {
+    // read stderr in a separate thread, so a command with a lot of stderr output doesn't block
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut buf = String::new();
+            let _ = stderr.read_to_string(&mut buf);
+            buf
+        })
+    });
+
+    let mut client_gone = false;
+    if let Some(mut stdout) = child.stdout.take() {
+        let mut buf = vec![0_u8; OUTPUT_CHUNK_SIZE];
+        loop {
+            match stdout.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let data = Bytes::copy_from_slice(&buf[..n]);
+                    if block_on(tx.send(CommandOutput::Data(data))).is_err() {
+                        client_gone = true;
+                        break;
+                    }
+                },
+            }
+        }
+    }
+    if client_gone {
+        log::info!("serve: client disconnected, stopping the command");
+        let _ = child.kill();
+    }
+
+    let success = child.wait().is_ok_and(|status| status.success());
+    let stderr = stderr_reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    let _ = block_on(tx.send(CommandOutput::Exit { success, stderr }));
+}
+
+/// the response of a command that failed before writing any output.
+/// Commands run with QSV_ERROR_FORMAT=json, so their stderr has a JSON error.
+fn command_error_response(stderr: &str) -> HttpResponse {
+    let json_error = stderr
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .find(|value| value.get("error").is_some());
+    match json_error {
+        Some(json_error) => HttpResponse::build(StatusCode::UNPROCESSABLE_ENTITY).json(json_error),
+        None => {
+            let message = if stderr.trim().is_empty() {
+                "The command failed."
+            } else {
+                stderr.trim()
+            };
+            error_response(StatusCode::UNPROCESSABLE_ENTITY, message)
+        },
+    }
+}
@@ -1,6 +1,7 @@
 static USAGE: &str = r#"
 Quickly sniff the first n rows and infer CSV metadata (delimiter, header row, number of
-preamble rows, quote character, flexible, is_utf8, average record length, number of records,
+multi-character delimiter, preamble rows, quote character, flexible, is_utf8, encoding,
+average record length, number of records,
 content length and estimated number of records if sniffing a URL, file size, number of fields,
 field names & data types) using a Viterbi algorithm. (https://en.wikipedia.org/wiki/Viterbi_algorithm)
 
@@ -103,7 +104,7 @@ Common options:
 use std::{
     cmp::min,
     fmt, fs,
-    io::{Seek, SeekFrom, Write, copy},
+    io::{Read, Seek, SeekFrom, Write, copy},
     path::PathBuf,
     time::Duration,
 };
@@ -125,7 +126,7 @@ use url::Url;
 
 use crate::{
     CliResult,
-    config::{Config, Delimiter},
+    config::{Config, DEFAULT_RDR_BUFFER_CAPACITY, Delimiter},
     util,
     util::format_systemtime,
 };
@@ -152,27 +153,29 @@ struct Args {
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 struct SniffStruct {
-    path:            String,
-    sniff_timestamp: String,
-    last_modified:   String,
-    delimiter_char:  char,
-    header_row:      bool,
-    preamble_rows:   usize,
-    quote_char:      String,
-    flexible:        bool,
-    is_utf8:         bool,
-    detected_mime:   String,
-    detected_kind:   String,
-    retrieved_size:  usize,
-    file_size:       usize,
-    sampled_records: usize,
-    estimated:       bool,
-    num_records:     usize,
-    avg_record_len:  usize,
-    num_fields:      usize,
-    stats_types:     bool,
-    fields:          Vec<String>,
-    types:           Vec<String>,
+    path:                String,
+    sniff_timestamp:     String,
+    last_modified:       String,
+    delimiter_char:      char,
+    multichar_delimiter: Option<String>,
+    header_row:          bool,
+    preamble_rows:       usize,
+    quote_char:          String,
+    flexible:            bool,
+    is_utf8:             bool,
+    encoding:            String,
+    detected_mime:       String,
+    detected_kind:       String,
+    retrieved_size:      usize,
+    file_size:           usize,
+    sampled_records:     usize,
+    estimated:           bool,
+    num_records:         usize,
+    avg_record_len:      usize,
+    num_fields:          usize,
+    stats_types:         bool,
+    fields:              Vec<String>,
+    types:               Vec<String>,
 }
 impl fmt::Display for SniffStruct {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -204,6 +207,9 @@ impl fmt::Display for SniffStruct {
                 self.delimiter_char.to_string()
             }
         )?;
+        if let Some(ref multichar_delimiter) = self.multichar_delimiter {
+            writeln!(f, "Multi-char Delimiter: {multichar_delimiter}")?;
+        }
         writeln!(f, "Header Row: {}", self.header_row)?;
         writeln!(
             f,
@@ -213,6 +219,7 @@ impl fmt::Display for SniffStruct {
         writeln!(f, "Quote Char: {}", self.quote_char)?;
         writeln!(f, "Flexible: {}", self.flexible)?;
         writeln!(f, "Is UTF8: {}", self.is_utf8)?;
+        writeln!(f, "Encoding: {}", self.encoding)?;
         writeln!(f, "Detected Mime Type: {}", self.detected_mime)?;
         writeln!(f, "Detected Kind: {}", self.detected_kind)?;
         writeln!(
@@ -283,6 +290,44 @@ struct SniffFileStruct {
     downloaded_records: usize,
 }
 
+/// common multi-character delimiters, longest first
+const MULTICHAR_DELIMITERS: [&str; 8] = ["~|~", "|~|", "^|^", "||", "\t\t", "::", ";;", "~~"];
+
+/// detect a common multi-character delimiter that occurs the same number of times in every
+/// line of the sample, with its characters not occurring outside of it (so that a
+/// single-character delimited file with empty fields is not mistaken for one).
+/// It is returned with tabs escaped, as expected by `qsv input --multi-delimiter`.
+fn sniff_multichar_delimiter(sample: &[u8]) -> Option<String> {
+    // only check complete lines, unless the sample is a single line
+    let sample = sample
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(sample, |i| &sample[..i]);
+    let sample = String::from_utf8_lossy(sample);
+    let lines: Vec<&str> = sample
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(1000)
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    MULTICHAR_DELIMITERS
+        .iter()
+        .find(|delimiter| {
+            let count = lines[0].matches(**delimiter).count();
+            count > 0
+                && lines.iter().all(|line| {
+                    line.matches(**delimiter).count() == count
+                        && delimiter.chars().all(|c| {
+                            line.matches(c).count() == count * delimiter.matches(c).count()
+                        })
+                })
+        })
+        .map(|delimiter| delimiter.replace('\t', "\\t"))
+}
+
 const fn rowcount(
     metadata: &qsv_sniffer::metadata::Metadata,
     sniff_file_info: &SniffFileStruct,
@@ -893,6 +938,15 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
         _ => qsv_sniffer::metadata::Quote::None,
     };
 
+    // detect the character encoding from the start of the file,
+    // so it can be transcoded with `qsv input --encoding`
+    let mut encoding_sample = Vec::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY);
+    fs::File::open(&sfile_info.file_to_sniff)?
+        .take(DEFAULT_RDR_BUFFER_CAPACITY as u64)
+        .read_to_end(&mut encoding_sample)?;
+    let encoding = util::detect_encoding(&encoding_sample).name().to_string();
+    let multichar_delimiter = sniff_multichar_delimiter(&encoding_sample);
+
     // now that we have all the sniffing parameters, we can sniff the file
     let sniff_results = if sample_all {
         log::info!("Sniffing ALL rows...");
@@ -955,6 +1009,7 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
                 sniff_timestamp: sniffed_ts,
                 last_modified: sfile_info.last_modified.clone(),
                 delimiter_char,
+                multichar_delimiter,
                 header_row: metadata.dialect.header.has_header_row,
                 preamble_rows: metadata.dialect.header.num_preamble_rows,
                 quote_char: match metadata.dialect.quote {
@@ -963,6 +1018,7 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
                 },
                 flexible: metadata.dialect.flexible,
                 is_utf8: metadata.dialect.is_utf8,
+                encoding,
                 detected_mime: if delimiter_char == ',' {
                     "application/csv".to_string()
                 } else {
@@ -986,7 +1042,14 @@ async fn sniff_main(mut args: Args) -> CliResult<()> {
             };
         },
         Err(e) => {
-            sniff_error = Some(format!("{e}"));
+            sniff_error = Some(if let Some(multichar_delimiter) = multichar_delimiter {
+                format!(
+                    "{e}. The file seems to use a multi-character delimiter: \
+                     {multichar_delimiter}. Normalize it first with `qsv input --multi-delimiter`"
+                )
+            } else {
+                format!("{e}")
+            });
         },
     }
 
@@ -156,6 +156,9 @@ Usage:
 sqlp arguments:
     input                  The CSV file/s to query. Use '-' for standard input.
                            If input is a directory, all files in the directory will be read as input.
+                           Set QSV_INPUT_RECURSIVE to also read the files in its subdirectories.
+                           If input is a glob pattern (e.g. 'data/**/*.csv' - quote it so it is
+                           not expanded by the shell), all matching files will be read as input.
                            If the input is a file with a '.infile-list' extension, the
                            file will be read as a list of files to use as input.
                            If the input are snappy compressed file(s), it will be
@@ -798,6 +801,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                         flag_prefer_dmy:      false,
                         flag_force:           false,
                         flag_stdout:          false,
+                        flag_format:          None,
+                        flag_detect_formats:  false,
                         flag_jobs:            Some(util::njobs(None)),
                         flag_no_headers:      false,
                         flag_delimiter:       args.flag_delimiter,
@@ -807,44 +812,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     let (csv_fields, csv_stats, _) =
                         get_stats_records(&schema_args, util::StatsMode::PolarsSchema)?;
 
-                    let mut schema = Schema::with_capacity(csv_stats.len());
-                    for (idx, stat) in csv_stats.iter().enumerate() {
-                        schema.insert(
-                            PlSmallStr::from_str(
-                                simdutf8::basic::from_utf8(csv_fields.get(idx).unwrap()).unwrap(),
-                            ),
-                            {
-                                let datatype = &stat.r#type;
-                                #[allow(clippy::match_same_arms)]
-                                match datatype.as_str() {
-                                    "String" => polars::datatypes::DataType::String,
-                                    "Integer" => {
-                                        let min = stat.min.as_ref().unwrap();
-                                        let max = stat.max.as_ref().unwrap();
-                                        if min.parse::<i32>().is_ok() && max.parse::<i32>().is_ok()
-                                        {
-                                            polars::datatypes::DataType::Int32
-                                        } else {
-                                            polars::datatypes::DataType::Int64
-                                        }
-                                    },
-                                    "Float" => {
-                                        let min = stat.min.as_ref().unwrap();
-                                        let max = stat.max.as_ref().unwrap();
-                                        if min.parse::<f32>().is_ok() && max.parse::<f32>().is_ok()
-                                        {
-                                            polars::datatypes::DataType::Float32
-                                        } else {
-                                            polars::datatypes::DataType::Float64
-                                        }
-                                    },
-                                    "Boolean" => polars::datatypes::DataType::Boolean,
-                                    "Date" => polars::datatypes::DataType::Date,
-                                    _ => polars::datatypes::DataType::String,
-                                }
-                            },
-                        );
-                    }
+                    let schema = util::polars_schema_from_stats(&csv_fields, &csv_stats);
                     let stats_schema = Arc::new(schema);
                     let stats_schema_json = serde_json::to_string_pretty(&stats_schema)?;
 
@@ -295,7 +295,10 @@ use std::{
     iter::repeat_n,
     path::{Path, PathBuf},
     str,
-    sync::OnceLock,
+    sync::{
+        OnceLock, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
 use crossbeam_channel;
@@ -441,6 +444,8 @@ impl StatsArgs {
     }
 }
 
+// some fields are only read by commands that are not part of the library API
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[derive(Clone, Serialize, Deserialize, PartialEq, Default, Debug)]
 pub struct StatsData {
     pub field:                String,
@@ -548,8 +553,10 @@ pub static STATSDATA_TYPES_MAP: phf::Map<&'static str, JsonTypes> = phf_map! {
     "qsv__value" => JsonTypes::Int,
 };
 
-static INFER_DATE_FLAGS: OnceLock<SmallVec<[bool; 50]>> = OnceLock::new();
-static RECORD_COUNT: OnceLock<u64> = OnceLock::new();
+// INFER_DATE_FLAGS, RECORD_COUNT & BOOLEAN_PATTERNS are (re)initialized on every run
+// so stats can be run more than once in the same process (e.g. pipeline & library API)
+static INFER_DATE_FLAGS: RwLock<Option<SmallVec<[bool; 50]>>> = RwLock::new(None);
+static RECORD_COUNT: AtomicU64 = AtomicU64::new(1);
 static ANTIMODES_LEN: OnceLock<usize> = OnceLock::new();
 
 // standard overflow and underflow strings
@@ -579,7 +586,7 @@ const DEFAULT_ANTIMODES_LEN: usize = 100;
 // in one column, i.e. antimodes/modes & percentiles
 pub const DEFAULT_STATS_SEPARATOR: &str = "|";
 
-static BOOLEAN_PATTERNS: OnceLock<Vec<BooleanPattern>> = OnceLock::new();
+static BOOLEAN_PATTERNS: RwLock<Option<Vec<BooleanPattern>>> = RwLock::new(None);
 #[derive(Clone, Debug)]
 struct BooleanPattern {
     true_pattern:  String,
@@ -651,7 +658,10 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         if !args.flag_cardinality {
             args.flag_cardinality = true;
         }
-        let _ = BOOLEAN_PATTERNS.set(parse_boolean_patterns(&args.flag_boolean_patterns));
+        *BOOLEAN_PATTERNS.write().unwrap() =
+            Some(parse_boolean_patterns(&args.flag_boolean_patterns));
+    } else {
+        *BOOLEAN_PATTERNS.write().unwrap() = None;
     }
 
     // check prefer_dmy env var
@@ -927,7 +937,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 },
             }?;
             // we cache the record count so we don't have to count the records again
-            let _ = RECORD_COUNT.set(record_count);
+            RECORD_COUNT.store(record_count, Ordering::Relaxed);
             // log::info!("scanned {record_count} records...");
 
             let stats_sr_vec = args.stats_to_records(stats, args.flag_vis_whitespace);
@@ -1142,8 +1152,9 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     if stdout_output_flag {
         // if we're outputting to stdout, copy the stats file to stdout
         let currstats = fs::read_to_string(currstats_filename)?;
-        io::stdout().write_all(currstats.as_bytes())?;
-        io::stdout().flush()?;
+        let mut stdout = Config::new(None).io_writer()?;
+        stdout.write_all(currstats.as_bytes())?;
+        stdout.flush()?;
     } else if let Some(output) = args.flag_output {
         // if we're outputting to a file, copy the stats file to the output file
         if currstats_filename != output {
@@ -1252,7 +1263,7 @@ impl Args {
         let mut stats = self.new_stats(sel_len);
 
         // safety: we know INFER_DATE_FLAGS is Some because we called init_date_inference
-        let infer_date_flags = INFER_DATE_FLAGS.get().unwrap();
+        let infer_date_flags = INFER_DATE_FLAGS.read().unwrap().clone().unwrap();
 
         // so we don't need to get infer_boolean/prefer_dmy from big args struct for each iteration
         // and hopefully the compiler will optimize this and use registers in the hot loop
@@ -1434,9 +1445,10 @@ fn init_date_inference(
 ) -> Result<(), String> {
     if !infer_dates {
         // we're not inferring dates, set INFER_DATE_FLAGS to all false
-        INFER_DATE_FLAGS
-            .set(SmallVec::from_elem(false, headers.len()))
-            .map_err(|_| "Cannot init empty date inference flags".to_string())?;
+        *INFER_DATE_FLAGS
+            .write()
+            .map_err(|_| "Cannot init empty date inference flags".to_string())? =
+            Some(SmallVec::from_elem(false, headers.len()));
         return Ok(());
     }
 
@@ -1463,9 +1475,9 @@ fn init_date_inference(
             .collect()
     };
 
-    INFER_DATE_FLAGS
-        .set(infer_date_flags)
-        .map_err(|e| format!("Cannot init date inference flags: {e:?}"))?;
+    *INFER_DATE_FLAGS
+        .write()
+        .map_err(|e| format!("Cannot init date inference flags: {e:?}"))? = Some(infer_date_flags);
     Ok(())
 }
 
@@ -1722,7 +1734,7 @@ impl Stats {
             ]);
         }
 
-        let record_count = *RECORD_COUNT.get().unwrap_or(&1);
+        let record_count = RECORD_COUNT.load(Ordering::Relaxed);
 
         // get the stats separator
         let stats_separator = if self.which.mode || self.which.percentiles {
@@ -1859,8 +1871,8 @@ impl Stats {
         // type
         if cardinality == 2 && infer_boolean {
             // if cardinality is 2, it's a boolean if its in the true/false patterns
-            let patterns = BOOLEAN_PATTERNS.get();
-            if let Some(patterns) = patterns {
+            let patterns = BOOLEAN_PATTERNS.read().unwrap();
+            if let Some(patterns) = patterns.as_ref() {
                 let mut is_boolean = false;
                 for pattern in patterns {
                     if pattern.matches(&minval).is_some() && pattern.matches(&maxval).is_some() {
@@ -2049,7 +2061,7 @@ impl Stats {
 
         // sparsity
         #[allow(clippy::cast_precision_loss)]
-        let sparsity: f64 = self.nullcount as f64 / *RECORD_COUNT.get().unwrap_or(&1) as f64;
+        let sparsity: f64 = self.nullcount as f64 / RECORD_COUNT.load(Ordering::Relaxed) as f64;
         pieces.push(util::round_num(sparsity, round_places));
 
         // quartiles
@@ -146,6 +146,8 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         flag_prefer_dmy:      false,
         flag_force:           false,
         flag_stdout:          false,
+        flag_format:          None,
+        flag_detect_formats:  false,
         flag_jobs:            Some(util::njobs(args.flag_jobs)),
         flag_no_headers:      false,
         flag_delimiter:       args.flag_delimiter,
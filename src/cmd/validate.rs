@@ -32,6 +32,27 @@ qsv supports a custom format - `currency`. This format will only accept a valid
       Negative amounts: ($100.00) or -$100.00
       Different styles: 1.000,00 (used in some countries for euros)
 
+It also supports these custom string formats:
+  iban               International Bank Account Number, with country-specific length
+                     & check digit validation. Spaces between groups are allowed.
+  credit-card        12 to 19 digit payment card number that passes the Luhn check.
+                     Digits may be grouped with single spaces or hyphens.
+  e164               E.164 international phone number (e.g. +14155552671)
+  iso3166-1-alpha2   ISO 3166-1 two-letter country code (e.g. PH)
+  iso3166-1-alpha3   ISO 3166-1 three-letter country code (e.g. PHL)
+  iso3166-2          ISO 3166-2 subdivision code (e.g. US-NY). Only the country prefix
+                     is checked against the ISO 3166-1 list.
+  iso4217            ISO 4217 three-letter currency code (e.g. EUR)
+  us-zip             US ZIP code, in 5-digit or ZIP+4 form
+  uk-postcode        UK postcode in upper case (e.g. SW1A 1AA)
+  uuid-v1 .. uuid-v8 hyphenated UUID of the given version
+  latitude           decimal degrees between -90 & 90
+  longitude          decimal degrees between -180 & 180
+  isbn               ISBN-10 or ISBN-13 with a valid check digit
+  issn               ISSN (e.g. 0317-8471) with a valid check digit
+Like all formats, these only apply to string values, so latitude & longitude
+columns should have a "string" type.
+
 qsv also supports a custom keyword - `dynamicEnum`. It allows for dynamic validation against a CSV.
 This is useful for validating against a set of values unknown at the time of schema creation or
 when the set of valid values is dynamic or too large to hardcode into the JSON Schema.
@@ -82,6 +103,77 @@ when the set of valid values is dynamic or too large to hardcode into the JSON S
 
 If colname is not specified, the first column of the CSV file is read and used for validation.
 
+qsv also supports the dataset-level `uniqueKey` and `primaryKey` keywords, set at the top level
+of the JSON Schema (i.e. alongside "properties"), as JSON Schema validation is per-row and
+cannot check for duplicates across rows:
+
+  // the id column must be unique. Rows with an empty id are not checked.
+  "uniqueKey": "id"
+
+  // the date & store columns are a composite unique key
+  "uniqueKey": ["date", "store"]
+
+  // email & username are both unique
+  "uniqueKey": [["email"], ["username"]]
+
+  // the date & store columns are a composite primary key - they must be unique,
+  // and none of them can be empty
+  "primaryKey": ["date", "store"]
+
+Keys are checked in a streaming pass, with an on-disk hash table used for very large files.
+Rows with a duplicate key are put in the invalid file, with the row number of the first
+occurrence of the key in the validation error report. Only the first 10,000 duplicates are
+reported individually - the rest are reported as a count for each key.
+
+Row-level rules over multiple columns are set with the top-level `rowRules` keyword.
+Each rule has a "name", an "assert" expression that must be true, an optional "if" expression
+to only check the rule when it's true, and an optional "message":
+
+  "rowRules": [
+    { "name": "closed_date_set",
+      "if": "status == 'closed'",
+      "assert": "!empty(closed_date) && closed_date >= opened_date" },
+    { "name": "total_matches", "assert": "qty * price == total" }
+  ]
+
+Expressions can use column names (quote them with backticks if they have spaces or special
+characters, e.g. `opened on`), 'string' & numeric literals, true, false & null,
+the ==, !=, <, <=, >, >=, +, -, *, /, %, && (and), || (or) & ! (not) operators, and the
+empty(x), len(x), lower(x), upper(x), trim(x), number(x), date(x), days_between(from, to),
+in(x, a, b, ...) & matches(x, 'regex') functions. Empty fields are null.
+Values are compared as numbers if both are numbers, as dates if both are dates (set
+QSV_PREFER_DMY to parse ambiguous dates as day/month/year), and as strings otherwise.
+Rows failing a rule are invalid, with the rule name in the field column of the error report.
+
+Cross-file foreign keys are set with the top-level `foreignKeys` keyword, using the same
+form as Frictionless Table Schema foreign keys. The "resource" is a CSV file on the local
+filesystem, or "" to reference the input CSV itself:
+
+  // the (store, region) pair of each row must exist in the (id, region) columns of stores.csv
+  "foreignKeys": [
+    { "fields": ["store", "region"],
+      "reference": { "resource": "stores.csv", "fields": ["id", "region"] } }
+  ]
+
+By default, the referenced keys are loaded into a hash table that spills to disk.
+For very large reference files, sort them on the referenced columns
+(e.g. `qsv sort --select id,region stores.csv`) and set `"sorted": true` in "reference".
+The reference file is then binary searched using its index, which is written next to it
+(e.g. stores.csv.idx) if it doesn't exist. It's checked to be sorted first, so an unsorted
+reference file is an error. The reference rows are trimmed with --trim, and their Table Schema
+"missingValues" are blanked, like the input rows. Rows with an empty foreign key column are not
+checked. Orphan rows are put in the invalid file, with their foreign key values in the
+validation error report.
+
+JSONL (.jsonl, .ndjson), Parquet (.parquet, .pqt) & Arrow IPC (.arrow, .ipc) inputs are
+validated as JSON instances instead of being converted to CSV, so nested objects & arrays and
+JSON types are validated as-is. Each line of a JSONL file is one instance, with blank lines
+skipped. Parquet & Arrow inputs are converted to JSONL first (requires the polars feature).
+The "row_number" in the error report is the JSONL line number, and nested fields are reported
+with their JSON pointer (e.g. address/city). Valid & invalid lines are written to JSONL
+"valid" & "invalid" files. The rowRules, uniqueKey, primaryKey & foreignKeys keywords and
+--coerce only apply to CSV inputs.
+
 You can create a JSON Schema file from a reference CSV file using the `qsv schema` command.
 Once the schema is created, you can fine-tune it to your needs and use it to validate other CSV
 files that have the same structure.
@@ -116,6 +208,22 @@ If piped from stdin, the filenames will use `stdin.csv` as the base filename. Fo
 `validate` also has a `schema` subcommand to validate JSON Schema files. For example:
   `qsv validate schema myjsonschema.json`
 
+Frictionless Table Schema files (https://specs.frictionlessdata.io/table-schema/) can also
+be used in place of a JSON Schema. A Table Schema is detected by its top-level "fields" array,
+and is mapped to an equivalent JSON Schema before validation, with errors reported the same way:
+
+  * types: string, integer, number, boolean, date, datetime, time, year, yearmonth & duration.
+    Other types (e.g. geopoint, object, array, any) are validated as strings.
+  * formats: email, uri & uuid for strings, and the "default" ISO 8601 format for
+    date, datetime & time. Custom date/time patterns are not validated.
+  * boolean "trueValues" & "falseValues".
+  * constraints: required, minLength, maxLength, minimum, maximum, pattern & enum.
+  * "missingValues": matching values are treated as missing (null).
+
+  * the unique constraint, "primaryKey" & "foreignKeys", using the `uniqueKey`, `primaryKey`
+    & `foreignKeys` keywords. Foreign key "resource"s are CSV file paths.
+You can create a Table Schema from a reference CSV with `qsv schema --format tableschema`.
+
 RFC 4180 VALIDATION MODE:
 ========================
 
@@ -137,15 +245,30 @@ Usage:
 
 Validate arguments:
     <input>                    Input CSV file to validate. If not provided, will read from stdin.
+                               If input is a directory, a quoted glob pattern (e.g. 'data/**/*.csv')
+                               or a file with a '.infile-list' extension, each file is validated
+                               in turn (see --recursive, --include & --exclude to control which
+                               files are used). Hidden files & directories, and the output files
+                               of previous validate runs are skipped. The exit code is 0 only
+                               if all the files are valid.
     <json-schema>              JSON Schema file to validate against. If not provided, `validate`
                                will run in RFC 4180 validation mode. The file can be a local file
                                or a URL (http and https schemes supported).
+                               A Frictionless Table Schema file can also be used.
 
 Validate options:
     --trim                     Trim leading and trailing whitespace from fields before validating.
     --fail-fast                Stops on first error.
     --valid <suffix>           Valid record output file suffix. [default: valid]
     --invalid <suffix>         Invalid record output file suffix. [default: invalid]
+    --recursive                When <input> is a directory, also validate the files in its
+                               subdirectories. Defaults to QSV_INPUT_RECURSIVE.
+    --include <patterns>       When <input> is a directory or a glob pattern, only validate the
+                               files whose names match one of these comma-separated glob
+                               patterns (e.g. '*.csv,*.tsv'). Defaults to QSV_INPUT_INCLUDE.
+    --exclude <patterns>       When <input> is a directory or a glob pattern, skip the files
+                               whose names match one of these comma-separated glob patterns.
+                               Takes precedence over --include. Defaults to QSV_INPUT_EXCLUDE.
     --json                     When validating without a schema, return the RFC 4180 check
                                as a JSON file instead of a message.
     --pretty-json              Same as --json, but pretty printed.
@@ -163,6 +286,26 @@ Validate options:
                                Set to 0 to load all rows in one batch.
                                Set to 1 to force batch optimization even for files with
                                less than 50000 rows. [default: 50000]
+    --report-format <format>   Also write an aggregated validation report with the error count
+                               per field & keyword, the first examples of each, and a
+                               pass/fail verdict, for CI systems & code review tools.
+                               Valid formats are "json", "junit" (JUnit XML) & "sarif"
+                               (SARIF 2.1.0). The report is written to
+                               <input>.validation-report.json, .validation-report.xml or
+                               .validation-report.sarif respectively, even if all records
+                               are valid. Only used when validating against a schema.
+    --report-examples <n>      The maximum number of example errors per field & keyword
+                               to include in the report. [default: 5]
+    --coerce                   Attempt deterministic, schema-driven fixes of invalid rows:
+                               trimming leading & trailing whitespace, removing thousands
+                               separators from numbers, reformatting dates of "date" &
+                               "date-time" format fields to ISO 8601, and fixing the
+                               casing of enum values. Only the fields with errors are fixed,
+                               and a row is only coerced if that fixes all its errors.
+                               Coerced rows are written with their fixed values to the
+                               valid output (which is then always written), and each
+                               coercion is logged to <input>.coercions.tsv with the
+                               row_number, field, old_value & new_value.
     --timeout <seconds>        Timeout for downloading json-schemas on URLs and for
                                'dynamicEnum' lookups on URLs. [default: 30]
     --cache-dir <dir>          The directory to use for caching downloaded dynamicEnum resources.
@@ -196,21 +339,26 @@ Common options:
 "#;
 
 use std::{
+    borrow::Cow,
     env,
     fs::File,
-    io::{BufReader, BufWriter, Read, Write},
+    io::{BufRead, BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
     str,
     sync::{
-        OnceLock,
+        OnceLock, RwLock,
         atomic::{AtomicU16, Ordering},
     },
 };
 
+use chrono::{NaiveTime, SecondsFormat};
 use csv::ByteRecord;
-use foldhash::{HashSet, HashSetExt};
+use csv_index::RandomAccessSimple;
+use foldhash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use indicatif::HumanCount;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 use indicatif::{ProgressBar, ProgressDrawTarget};
+use itertools::Itertools;
 use jsonschema::{
     Keyword, ValidationError, Validator,
     output::BasicOutput,
@@ -218,9 +366,10 @@ use jsonschema::{
 };
 use log::{debug, info, log_enabled};
 use qsv_currency::Currency;
+use qsv_dateparser::parse_with_preference;
 use rayon::{
     iter::{IndexedParallelIterator, ParallelIterator},
-    prelude::IntoParallelRefIterator,
+    prelude::{IntoParallelRefIterator, IntoParallelRefMutIterator},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value, json, value::Number};
@@ -233,24 +382,40 @@ use crate::lookup;
 use crate::lookup::{LookupTableOptions, load_lookup_table};
 use crate::{
     CliError, CliResult,
-    config::{Config, DEFAULT_WTR_BUFFER_CAPACITY, Delimiter},
+    clitypes::{self, ErrorCode},
+    config::{
+        Config, DEFAULT_RDR_BUFFER_CAPACITY, DEFAULT_WTR_BUFFER_CAPACITY, Delimiter, InputFormat,
+    },
+    formats,
+    index::Indexed,
+    odhtcache, regex_oncelock,
+    rowrules::RowRule,
     util,
 };
 
 // to save on repeated init/allocs
 static NULL_TYPE: OnceLock<Value> = OnceLock::new();
 
+const UNIQUE_KEY: &str = "uniqueKey";
+const PRIMARY_KEY: &str = "primaryKey";
+// memory used by the key constraint cache before spilling to disk
+const KEY_CACHE_MEMORY_LIMIT: u64 = 100 * 1_000_000; // 100 MB
+// duplicate keys reported individually, with their first occurrence. The rest are counted,
+// so memory use doesn't grow with the number of duplicates
+const MAX_REPORTED_KEY_DUPES: usize = 10_000;
+
 static TIMEOUT_SECS: AtomicU16 = AtomicU16::new(30);
 
+// these are set on every run, so validate can be run more than once in the same process
 #[cfg(not(feature = "lite"))]
-static QSV_CACHE_DIR: OnceLock<String> = OnceLock::new();
+static QSV_CACHE_DIR: RwLock<String> = RwLock::new(String::new());
 
 #[cfg(not(feature = "lite"))]
-static CKAN_API: OnceLock<String> = OnceLock::new();
+static CKAN_API: RwLock<Option<String>> = RwLock::new(None);
 
 #[cfg(not(feature = "lite"))]
-static CKAN_TOKEN: OnceLock<Option<String>> = OnceLock::new();
-static DELIMITER: OnceLock<Option<Delimiter>> = OnceLock::new();
+static CKAN_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+static DELIMITER: RwLock<Option<Delimiter>> = RwLock::new(None);
 
 /// write to stderr and log::error, using ValidationError
 macro_rules! fail_validation_error {
@@ -267,29 +432,35 @@ macro_rules! fail_validation_error {
     }};
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 #[allow(dead_code)]
 struct Args {
-    cmd_schema:        bool,
-    flag_trim:         bool,
-    flag_fail_fast:    bool,
-    flag_valid:        Option<String>,
-    flag_invalid:      Option<String>,
-    flag_json:         bool,
-    flag_pretty_json:  bool,
-    flag_valid_output: Option<String>,
-    flag_jobs:         Option<usize>,
-    flag_batch:        usize,
-    flag_no_headers:   bool,
-    flag_delimiter:    Option<Delimiter>,
-    flag_progressbar:  bool,
-    flag_quiet:        bool,
-    arg_input:         Option<String>,
-    arg_json_schema:   Option<String>,
-    flag_timeout:      u16,
-    flag_cache_dir:    String,
-    flag_ckan_api:     String,
-    flag_ckan_token:   Option<String>,
+    cmd_schema:           bool,
+    flag_trim:            bool,
+    flag_fail_fast:       bool,
+    flag_valid:           Option<String>,
+    flag_invalid:         Option<String>,
+    flag_recursive:       bool,
+    flag_include:         Option<String>,
+    flag_exclude:         Option<String>,
+    flag_json:            bool,
+    flag_pretty_json:     bool,
+    flag_valid_output:    Option<String>,
+    flag_report_format:   Option<String>,
+    flag_report_examples: usize,
+    flag_coerce:          bool,
+    flag_jobs:            Option<usize>,
+    flag_batch:           usize,
+    flag_no_headers:      bool,
+    flag_delimiter:       Option<Delimiter>,
+    flag_progressbar:     bool,
+    flag_quiet:           bool,
+    arg_input:            Option<String>,
+    arg_json_schema:      Option<String>,
+    flag_timeout:         u16,
+    flag_cache_dir:       String,
+    flag_ckan_api:        String,
+    flag_ckan_token:      Option<String>,
 }
 
 enum JSONtypes {
@@ -651,10 +822,10 @@ fn dyn_enum_validator_factory<'a>(
         name: lookup_name,
         uri: final_uri,
         cache_age_secs,
-        cache_dir: QSV_CACHE_DIR.get().unwrap().to_string(),
-        delimiter: DELIMITER.get().copied().flatten(),
-        ckan_api_url: CKAN_API.get().cloned(),
-        ckan_token: CKAN_TOKEN.get().and_then(std::clone::Clone::clone),
+        cache_dir: QSV_CACHE_DIR.read().unwrap().clone(),
+        delimiter: *DELIMITER.read().unwrap(),
+        ckan_api_url: CKAN_API.read().unwrap().clone(),
+        ckan_token: CKAN_TOKEN.read().unwrap().clone(),
         timeout_secs: TIMEOUT_SECS.load(Ordering::Relaxed),
     };
 
@@ -829,6 +1000,140 @@ fn dyn_enum_validator_factory<'a>(
     }
 }
 
+/// set up dynamicEnum lookups, then load & compile the JSON Schema (or Table Schema).
+/// Returns the schema, the compiled schema & the values to treat as missing.
+fn compile_schema(args: &Args) -> CliResult<(Value, Validator, Vec<Vec<u8>>)> {
+    // safety: only called when validating against a schema
+    let schema_uri = args.arg_json_schema.as_deref().unwrap();
+
+    #[cfg(not(feature = "lite"))]
+    let qsv_cache_dir = lookup::set_qsv_cache_dir(&args.flag_cache_dir)?;
+    #[cfg(not(feature = "lite"))]
+    *QSV_CACHE_DIR.write().unwrap() = qsv_cache_dir;
+
+    // check the QSV_CKAN_API environment variable
+    #[cfg(not(feature = "lite"))]
+    *CKAN_API.write().unwrap() = Some(if let Ok(api) = std::env::var("QSV_CKAN_API") {
+        api
+    } else {
+        args.flag_ckan_api.clone()
+    });
+
+    // check the QSV_CKAN_TOKEN environment variable
+    #[cfg(not(feature = "lite"))]
+    *CKAN_TOKEN.write().unwrap() = if let Ok(token) = std::env::var("QSV_CKAN_TOKEN") {
+        Some(token)
+    } else {
+        args.flag_ckan_token.clone()
+    };
+
+    // values to treat as missing, in addition to empty fields. Only set by Table Schemas
+    let mut missing_values: Vec<Vec<u8>> = Vec::new();
+
+    // parse and compile supplied JSON Schema
+    let (schema_json, schema_compiled): (Value, Validator) = match load_json(schema_uri) {
+        Ok(s) => {
+            // parse JSON string
+            let mut s_slice = s.as_bytes().to_vec();
+            match simd_json::serde::from_slice(&mut s_slice) {
+                Ok(json) => {
+                    // map Frictionless Table Schemas to the equivalent JSON Schema
+                    let json = if is_table_schema(&json) {
+                        let (json_schema, table_missing_values) =
+                            table_schema_to_json_schema(&json)?;
+                        missing_values = table_missing_values;
+                        json_schema
+                    } else {
+                        json
+                    };
+
+                    // compile JSON Schema
+                    let options = formats::CUSTOM_FORMATS.iter().fold(
+                        Validator::options().with_format("currency", currency_format_checker),
+                        |options, (name, checker)| options.with_format(*name, *checker),
+                    );
+                    match options
+                        .with_keyword("dynamicEnum", dyn_enum_validator_factory)
+                        .should_validate_formats(true)
+                        .build(&json)
+                    {
+                        Ok(schema) => (json, schema),
+                        Err(e) => {
+                            return fail_clierror!(
+                                r#"Cannot compile JSONschema. error: {e}
+Try running `qsv validate schema {}` to check the JSON Schema file."#,
+                                schema_uri
+                            );
+                        },
+                    }
+                },
+                Err(e) => {
+                    return fail_clierror!(
+                        r#"Unable to parse JSONschema. error: {e}
+Try running `qsv validate schema {}` to check the JSON Schema file."#,
+                        schema_uri
+                    );
+                },
+            }
+        },
+        Err(e) => {
+            return fail_clierror!("Unable to retrieve JSONschema. error: {e}");
+        },
+    };
+
+    Ok((schema_json, schema_compiled, missing_values))
+}
+
+/// validate each file of a multi-file input in turn. The per-run state, e.g. the cached
+/// row count, is reset before each file.
+fn validate_multiple_files(input: &str, args: &Args, report_format: Option<&str>) -> CliResult<()> {
+    let tmpdir = tempfile::tempdir()?;
+    let valid_suffix = format!(".{}", args.flag_valid.as_deref().unwrap_or("valid"));
+    let invalid_suffix = format!(".{}", args.flag_invalid.as_deref().unwrap_or("invalid"));
+    let filter = util::InputFileFilter::new(
+        args.flag_include.as_deref(),
+        args.flag_exclude.as_deref(),
+        args.flag_recursive.then_some(true),
+    )?;
+    let files = util::process_input_filtered(vec![PathBuf::from(input)], &tmpdir, "", &filter)?
+        .into_iter()
+        .filter(|path| {
+            // skip the output files of previous validate runs
+            let name = path.to_string_lossy();
+            !(name.ends_with(&valid_suffix)
+                || name.ends_with(&invalid_suffix)
+                || name.ends_with(".validation-errors.tsv")
+                || name.ends_with(".coercions.tsv")
+                || name.contains(".validation-report."))
+        })
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        return fail_clierror!("No files to validate in '{input}'.");
+    }
+
+    let mut failed_files = 0_usize;
+    for file in &files {
+        if !args.flag_quiet {
+            winfo!("Validating {}...", file.display());
+        }
+        util::reset_run_state();
+        let mut file_args = args.clone();
+        file_args.arg_input = Some(file.to_string_lossy().into_owned());
+        if let Err(e) = validate_input(&file_args, report_format) {
+            werr!("{e}");
+            failed_files += 1;
+        }
+    }
+
+    if failed_files > 0 {
+        return fail_clierror!("{failed_files} of {} files failed validation.", files.len());
+    }
+    if !args.flag_quiet {
+        winfo!("All {} files are valid.", files.len());
+    }
+    Ok(())
+}
+
 pub fn run(argv: &[&str]) -> CliResult<()> {
     let args: Args = util::get_args(USAGE, argv)?;
 
@@ -836,19 +1141,27 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     if args.cmd_schema {
         if let Some(ref schema) = args.arg_json_schema {
             let schema_json = load_json(schema)?;
-            match jsonschema::meta::try_is_valid(&serde_json::from_str(&schema_json)?) {
+            let mut schema_value: Value = serde_json::from_str(&schema_json)?;
+            let schema_kind = if is_table_schema(&schema_value) {
+                // a Table Schema is valid if it maps to a valid JSON Schema
+                schema_value = table_schema_to_json_schema(&schema_value)?.0;
+                "Table Schema"
+            } else {
+                "JSON Schema"
+            };
+            match jsonschema::meta::try_is_valid(&schema_value) {
                 Ok(is_valid) => {
                     if is_valid {
                         if !args.flag_quiet {
-                            winfo!("Valid JSON Schema.");
+                            winfo!("Valid {schema_kind}.");
                             return Ok(());
                         }
                     } else {
-                        return fail_clierror!("Invalid JSON Schema.");
+                        return fail_clierror!("Invalid {schema_kind}.");
                     }
                 },
                 Err(e) => {
-                    return fail_clierror!("Invalid JSON Schema: {e}");
+                    return fail_clierror!("Invalid {schema_kind}: {e}");
                 },
             }
         } else {
@@ -856,6 +1169,30 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    let report_format = args
+        .flag_report_format
+        .as_deref()
+        .map(str::to_ascii_lowercase);
+    if let Some(ref report_format) = report_format {
+        if !["json", "junit", "sarif"].contains(&report_format.as_str()) {
+            return fail_incorrectusage_clierror!(
+                "Invalid --report-format value: {report_format}. Must be one of: json, junit, \
+                 sarif"
+            );
+        }
+    }
+
+    if let Some(ref input) = args.arg_input {
+        if util::is_multi_file_input(input) {
+            return validate_multiple_files(input, &args, report_format.as_deref());
+        }
+    }
+
+    validate_input(&args, report_format.as_deref())
+}
+
+/// validate a single CSV, JSONL, Parquet or Arrow IPC input
+fn validate_input(args: &Args, report_format: Option<&str>) -> CliResult<()> {
     TIMEOUT_SECS.store(
         util::timeout_secs(args.flag_timeout)? as u16,
         Ordering::Relaxed,
@@ -866,12 +1203,22 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     if args.flag_delimiter.is_some() {
         rconfig = rconfig.delimiter(args.flag_delimiter);
     }
-    DELIMITER.set(args.flag_delimiter).unwrap();
+    *DELIMITER.write().unwrap() = args.flag_delimiter;
+
+    // JSONL, Parquet & Arrow IPC inputs are validated as JSON instances instead of
+    // being converted to CSV, so their nested objects, arrays & types are preserved
+    if args.arg_json_schema.is_some() && rconfig.input_format() != InputFormat::Csv {
+        return validate_json_input(args, rconfig.input_format(), report_format);
+    }
 
     let mut rdr = rconfig.reader()?;
 
     // if no JSON Schema supplied, only let csv reader RFC4180-validate csv file
     if args.arg_json_schema.is_none() {
+        if report_format.is_some() {
+            return fail_incorrectusage_clierror!("--report-format requires a JSON Schema.");
+        }
+
         // just read csv file and let csv reader report problems
         // since we're using csv::StringRecord, this will also detect non-utf8 sequences
 
@@ -966,6 +1313,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         'rfc4180_check: loop {
             result = rdr.read_byte_record(&mut record);
             if let Err(e) = result {
+                clitypes::set_error_context(
+                    ErrorCode::Rfc4180ValidationFailed,
+                    Some(record_idx + 1),
+                    e.position().map(csv::Position::line),
+                    None,
+                );
                 // read_byte_record() does not validate utf8, so we know this is not a utf8 error
                 if flag_json {
                     // we're returning a JSON error, so we have more machine-friendly details
@@ -1007,6 +1360,12 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
             // use SIMD accelerated UTF-8 validation, validate the entire record in one go
             if simdutf8::basic::from_utf8(record.as_slice()).is_err() {
+                clitypes::set_error_context(
+                    ErrorCode::Utf8ValidationFailed,
+                    Some(record_idx + 1),
+                    record.position().map(csv::Position::line),
+                    None,
+                );
                 // there's a UTF-8 error, so we report utf8 error metadata
                 if flag_json {
                     let validation_error = json!({
@@ -1089,7 +1448,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     #[cfg(any(feature = "feature_capable", feature = "lite"))]
     let show_progress =
-        (args.flag_progressbar || util::get_envvar_flag("QSV_PROGRESSBAR")) && !rconfig.is_stdin();
+        (args.flag_progressbar || util::progress_envvar_enabled()) && !rconfig.is_stdin();
 
     #[cfg(any(feature = "feature_capable", feature = "lite"))]
     if show_progress {
@@ -1105,62 +1464,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let headers = rdr.byte_headers()?.clone();
     let header_len = headers.len();
 
-    #[cfg(not(feature = "lite"))]
-    let qsv_cache_dir = lookup::set_qsv_cache_dir(&args.flag_cache_dir)?;
-    #[cfg(not(feature = "lite"))]
-    QSV_CACHE_DIR.set(qsv_cache_dir)?;
-
-    // check the QSV_CKAN_API environment variable
-    #[cfg(not(feature = "lite"))]
-    CKAN_API.set(if let Ok(api) = std::env::var("QSV_CKAN_API") {
-        api
-    } else {
-        args.flag_ckan_api.clone()
-    })?;
-
-    // check the QSV_CKAN_TOKEN environment variable
-    #[cfg(not(feature = "lite"))]
-    CKAN_TOKEN
-        .set(if let Ok(token) = std::env::var("QSV_CKAN_TOKEN") {
-            Some(token)
-        } else {
-            args.flag_ckan_token.clone()
-        })
-        .unwrap();
-
-    // parse and compile supplied JSON Schema
-    let (schema_json, schema_compiled): (Value, Validator) =
-        // safety: we know the schema is_some() because we checked above
-        match load_json(&args.arg_json_schema.clone().unwrap()) {
-            Ok(s) => {
-                // parse JSON string
-                let mut s_slice = s.as_bytes().to_vec();
-                match simd_json::serde::from_slice(&mut s_slice) {
-                    Ok(json) => {
-                        // compile JSON Schema
-                        match Validator::options()
-                            .with_format("currency", currency_format_checker)
-                            .with_keyword("dynamicEnum", dyn_enum_validator_factory)
-                            .should_validate_formats(true)
-                            .build(&json)
-                        {
-                            Ok(schema) => (json, schema),
-                            Err(e) => {
-                                return fail_clierror!(r#"Cannot compile JSONschema. error: {e}
-Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_json_schema.unwrap());
-                            },
-                        }
-                    },
-                    Err(e) => {
-                        return fail_clierror!(r#"Unable to parse JSONschema. error: {e}
-Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_json_schema.unwrap());
-                    },
-                }
-            },
-            Err(e) => {
-                return fail_clierror!("Unable to retrieve JSONschema. error: {e}");
-            },
-        };
+    let (schema_json, schema_compiled, missing_values) = compile_schema(args)?;
 
     if log::log_enabled!(log::Level::Debug) {
         // only log if debug is enabled
@@ -1169,11 +1473,48 @@ Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_
     }
 
     // set this once, as this is used repeatedly in a hot loop
-    NULL_TYPE.set(Value::String("null".to_string())).unwrap();
+    let _ = NULL_TYPE.get_or_init(|| Value::String("null".to_string()));
 
     // get JSON types for each column in CSV file
     let header_types = get_json_types(&headers, &schema_json)?;
 
+    // row-level rules over multiple columns
+    let row_rules = get_row_rules(&schema_json, &headers)?;
+
+    // dataset-level uniqueKey/primaryKey constraints, checked sequentially after each batch
+    let mut key_constraints = get_key_constraints(&schema_json, &headers)?;
+    // dataset-level foreignKeys constraints, also checked sequentially after each batch
+    let mut fk_constraints = get_foreign_key_constraints(
+        &schema_json,
+        &headers,
+        args.arg_input.as_ref(),
+        args.flag_trim,
+        &missing_values,
+    )?;
+    // schema-driven fixes of invalid rows, with --coerce
+    let coercer = args.flag_coerce.then(|| {
+        Coercer::new(
+            &schema_json,
+            &header_types,
+            util::get_envvar_flag("QSV_PREFER_DMY"),
+        )
+    });
+    let mut coercions: Vec<Option<Vec<Coercion>>> = Vec::new();
+    // the coercions of valid rows are logged as they're found, and re-applied from the log
+    // when writing the valid output, so the fixed rows aren't kept in memory
+    let mut coercion_wtr: Option<csv::Writer<Box<dyn Write>>> = None;
+    let mut coerced_count: u64 = 0;
+
+    let input_path = args
+        .arg_input
+        .clone()
+        .unwrap_or_else(|| "stdin.csv".to_string());
+
+    // rows with duplicate keys - (row number, key constraint index, key),
+    // up to MAX_REPORTED_KEY_DUPES
+    let mut key_dupes: Vec<(u64, usize, String)> = Vec::new();
+    let mut key = String::new();
+
     // how many rows read and processed as batches
     let mut row_number: u64 = 0;
     // how many invalid rows found
@@ -1204,6 +1545,9 @@ Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_
                     if flag_trim {
                         record.trim();
                     }
+                    if !missing_values.is_empty() {
+                        blank_missing_values(&mut record, header_len, &missing_values);
+                    }
                     // we use mem::take() to avoid cloning & clearing the record
                     batch.push(std::mem::take(&mut record));
                 },
@@ -1224,20 +1568,124 @@ Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_
         batch
             .par_iter()
             .with_min_len(1024)
-            .map(|record| do_json_validation(&header_types, header_len, record, &schema_compiled))
+            .map(|record| {
+                do_json_validation(
+                    &header_types,
+                    header_len,
+                    record,
+                    &schema_compiled,
+                    &row_rules,
+                )
+            })
             .collect_into_vec(&mut validation_results);
 
+        // try to fix invalid rows, replacing the record & clearing its errors if successful
+        if let Some(ref coercer) = coercer {
+            batch
+                .par_iter_mut()
+                .zip(validation_results.par_iter_mut())
+                .with_min_len(1024)
+                .map(|(record, result)| {
+                    coercer.coerce(
+                        record,
+                        result,
+                        &header_types,
+                        header_len,
+                        &schema_compiled,
+                        &row_rules,
+                    )
+                })
+                .collect_into_vec(&mut coercions);
+        }
+
+        // check key constraints in row order, so the first occurrence of a key is always valid.
+        // Rows with duplicate keys are flagged as invalid now, but their error messages are only
+        // added after the batch loop, once the row numbers of the first occurrences are known.
+        if !key_constraints.is_empty() || !fk_constraints.is_empty() {
+            for (record, result) in batch.iter().zip(validation_results.iter_mut()) {
+                let row_number_string =
+                    simdutf8::basic::from_utf8(record.get(header_len).unwrap()).unwrap();
+                for (constraint_idx, constraint) in key_constraints.iter_mut().enumerate() {
+                    match constraint.check(record, &mut key) {
+                        KeyCheck::Valid => {},
+                        KeyCheck::Missing(column) => {
+                            let msg = format!(
+                                "{row_number_string}\t{column}\t{PRIMARY_KEY} value is \
+                                 missing\t{PRIMARY_KEY}"
+                            );
+                            match result {
+                                Some(error_msg) if !error_msg.is_empty() => {
+                                    error_msg.push('\n');
+                                    error_msg.push_str(&msg);
+                                },
+                                _ => *result = Some(msg),
+                            }
+                        },
+                        KeyCheck::Duplicate => {
+                            if key_dupes.len() < MAX_REPORTED_KEY_DUPES {
+                                constraint.dupe_keys.entry(key.clone()).or_insert(0);
+                                key_dupes.push((
+                                    atoi_simd::parse::<u64>(row_number_string.as_bytes()).unwrap(),
+                                    constraint_idx,
+                                    key.clone(),
+                                ));
+                            } else {
+                                constraint.unreported_dupes += 1;
+                            }
+                            result.get_or_insert_with(String::new);
+                        },
+                    }
+                }
+                for constraint in &mut fk_constraints {
+                    if constraint.check(record, &mut key)? {
+                        continue;
+                    }
+                    let msg = format!(
+                        "{row_number_string}\t{columns}\tforeign key value {values:?} not found \
+                         in {resource} ({ref_columns})\tforeignKeys",
+                        columns = constraint.columns.join(","),
+                        values = key_values(&key),
+                        resource = constraint.resource,
+                        ref_columns = constraint.ref_columns.join(","),
+                    );
+                    match result {
+                        Some(error_msg) if !error_msg.is_empty() => {
+                            error_msg.push('\n');
+                            error_msg.push_str(&msg);
+                        },
+                        _ => *result = Some(msg),
+                    }
+                }
+            }
+        }
+
         // write to validation error report, but keep Vec<bool> to gen valid/invalid files later
         // because Rayon collect() guarantees original order, we can sequentially append results
         // to vector with each batch
-        for result in &validation_results {
+        for (i, result) in validation_results.iter().enumerate() {
             if let Some(validation_error_msg) = result {
                 invalid_count += 1;
                 valid_flags.push(false);
 
-                validation_error_messages.push(validation_error_msg.to_string());
+                // rows that only have duplicate keys get their messages after the batch loop
+                if !validation_error_msg.is_empty() {
+                    validation_error_messages.push(validation_error_msg.to_string());
+                }
             } else {
                 valid_flags.push(true);
+
+                // log the coercions of coerced rows that are still valid after key checks
+                if let Some(row_coercions) = coercions.get_mut(i).and_then(Option::take) {
+                    let coerced_row =
+                        atoi_simd::parse::<u64>(batch[i].get(header_len).unwrap()).unwrap();
+                    if coercion_wtr.is_none() {
+                        coercion_wtr = Some(create_coercion_log(&input_path)?);
+                    }
+                    if let Some(ref mut wtr) = coercion_wtr {
+                        write_coercions(wtr, coerced_row, &row_coercions)?;
+                    }
+                    coerced_count += 1;
+                }
             }
         }
 
@@ -1262,10 +1710,97 @@ Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_
         util::finish_progress(&progress);
     }
 
+    if !key_dupes.is_empty() {
+        find_first_key_occurrences(
+            &rconfig,
+            flag_trim,
+            &missing_values,
+            header_len,
+            &mut key_constraints,
+        )?;
+
+        for (dupe_row, constraint_idx, dupe_key) in key_dupes {
+            let constraint = &key_constraints[constraint_idx];
+            let first_row = constraint
+                .dupe_keys
+                .get(&dupe_key)
+                .copied()
+                .unwrap_or_default();
+            validation_error_messages.push(format!(
+                "{dupe_row}\t{columns}\tduplicate {keyword} value {values:?}, first seen in row \
+                 {first_row}\t{keyword}",
+                columns = constraint.columns.join(","),
+                keyword = constraint.keyword,
+                values = key_values(&dupe_key),
+            ));
+        }
+
+        // keep the error report in row order. The sort is stable, so the per-row JSON Schema
+        // errors stay ahead of the key constraint errors
+        validation_error_messages.sort_by_cached_key(|msg| {
+            msg.split('\t')
+                .next()
+                .and_then(|row| row.parse::<u64>().ok())
+                .unwrap_or_default()
+        });
+    }
+
+    // the duplicates past MAX_REPORTED_KEY_DUPES, as (columns, keyword, count)
+    let unreported_dupes: Vec<(String, String, u64)> = key_constraints
+        .iter()
+        .filter(|kc| kc.unreported_dupes > 0)
+        .map(|kc| {
+            (
+                kc.columns.join(","),
+                kc.keyword.to_string(),
+                kc.unreported_dupes,
+            )
+        })
+        .collect();
+
+    if let Some(report_format) = report_format {
+        let report = ValidationReport::new(
+            &input_path,
+            args.arg_json_schema.as_deref().unwrap_or_default(),
+            &headers,
+            row_number,
+            invalid_count,
+            &validation_error_messages,
+            &unreported_dupes,
+            args.flag_report_examples,
+        );
+        report.write(report_format)?;
+    }
+
+    // the error report lists the unreported duplicates last, without a row number
+    for (columns, keyword, count) in unreported_dupes {
+        validation_error_messages.push(format!(
+            "\t{columns}\t{count} more rows with a duplicate {keyword} value, not reported \
+             individually\t{keyword}"
+        ));
+    }
+
+    let valid_suffix = args.flag_valid.as_deref().unwrap_or("valid");
+    let invalid_suffix = args.flag_invalid.as_deref().unwrap_or("invalid");
+
+    let coercion_log_path = input_path.clone() + ".coercions.tsv";
+    let coercion_log = if let Some(mut wtr) = coercion_wtr {
+        wtr.flush()?;
+        if !args.flag_quiet {
+            winfo!(
+                "{} records coerced. Coercions logged to {coercion_log_path}",
+                HumanCount(coerced_count)
+            );
+        }
+        Some(coercion_log_path.as_str())
+    } else {
+        None
+    };
+
     if invalid_count == 0 {
         // no invalid records found
         // see if we need to pass all valid records to output
-        if let Some(valid_output) = args.flag_valid_output {
+        if let Some(ref valid_output) = args.flag_valid_output {
             // pass all valid records to output and return exit code 1
             let valid_path = if valid_output == "-" {
                 // write to stdout
@@ -1274,40 +1809,56 @@ Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_
                 Some(valid_output)
             };
 
-            let mut valid_wtr = Config::new(valid_path.as_ref()).writer()?;
+            let mut valid_wtr = Config::new(valid_path).writer()?;
             valid_wtr.write_byte_record(&headers)?;
 
+            let mut coercion_log = coercion_log
+                .map(|path| CoercionLogReader::open(path, &headers))
+                .transpose()?;
             let mut rdr = rconfig.reader()?;
             let mut record = csv::ByteRecord::new();
+            let mut valid_row_number = 0_u64;
             while rdr.read_byte_record(&mut record)? {
+                valid_row_number += 1;
+                // write the fixed record of coerced rows
+                if let Some(ref mut coercion_log) = coercion_log {
+                    coercion_log.apply(valid_row_number, &mut record)?;
+                }
                 valid_wtr.write_byte_record(&record)?;
             }
             valid_wtr.flush()?;
             // return 1 as an exitcode and the number of valid rows to stderr
             return fail_clierror!("{row_number}");
         }
+
+        // coerced rows are only written to the valid output
+        if coercion_log.is_some() {
+            split_invalid_records(
+                &rconfig,
+                &valid_flags[..],
+                coercion_log,
+                &headers,
+                &input_path,
+                valid_suffix,
+                None,
+            )?;
+        }
     } else {
         // there are invalid records. write out invalid/valid/errors output files.
         // if 100% invalid, valid file isn't needed, but this is rare so OK creating empty file.
         woutinfo!("Writing invalid/valid/error files...");
 
-        let input_path = args
-            .arg_input
-            .clone()
-            .unwrap_or_else(|| "stdin.csv".to_string());
-
+        set_first_error_context(&validation_error_messages);
         write_error_report(&input_path, validation_error_messages)?;
 
-        let valid_suffix = args.flag_valid.unwrap_or_else(|| "valid".to_string());
-        let invalid_suffix = args.flag_invalid.unwrap_or_else(|| "invalid".to_string());
-
         split_invalid_records(
             &rconfig,
             &valid_flags[..],
+            coercion_log,
             &headers,
             &input_path,
-            &valid_suffix,
-            &invalid_suffix,
+            valid_suffix,
+            Some(invalid_suffix),
         )?;
 
         // done with validation; print output
@@ -1333,55 +1884,298 @@ Try running `qsv validate schema {}` to check the JSON Schema file."#, args.arg_
     Ok(())
 }
 
-fn split_invalid_records(
-    rconfig: &Config,
-    valid_flags: &[bool],
-    headers: &ByteRecord,
-    input_path: &str,
-    valid_suffix: &str,
-    invalid_suffix: &str,
+/// validate each line of a JSONL input as a JSON instance against the schema.
+/// Parquet & Arrow IPC inputs are converted to JSONL first. Valid & invalid lines are
+/// written to JSONL output files, with the same error report as for CSV inputs.
+fn validate_json_input(
+    args: &Args,
+    input_format: InputFormat,
+    report_format: Option<&str>,
 ) -> CliResult<()> {
-    // track how many rows read for splitting into valid/invalid
-    // should not exceed row_number when aborted early due to fail-fast
-    let mut split_row_num: usize = 0;
+    if args.flag_coerce {
+        return fail_incorrectusage_clierror!(
+            "--coerce is not supported for JSONL, Parquet & Arrow inputs."
+        );
+    }
 
-    // prepare output writers
-    let mut valid_wtr =
-        Config::new(Some(input_path.to_owned() + "." + valid_suffix).as_ref()).writer()?;
-    valid_wtr.write_byte_record(headers)?;
+    let (schema_json, schema_compiled, _) = compile_schema(args)?;
+    // these keywords work on CSV columns
+    for keyword in ["rowRules", UNIQUE_KEY, PRIMARY_KEY, "foreignKeys"] {
+        if schema_json.get(keyword).is_some() && !args.flag_quiet {
+            wwarn!("The \"{keyword}\" keyword is ignored for {input_format:?} inputs.");
+        }
+    }
 
-    let mut invalid_wtr =
-        Config::new(Some(input_path.to_owned() + "." + invalid_suffix).as_ref()).writer()?;
-    invalid_wtr.write_byte_record(headers)?;
+    // safety: only files have a non-CSV input format
+    let input_path = args.arg_input.clone().unwrap();
+    let mut jsonl_file = if input_format == InputFormat::Jsonl {
+        File::open(&input_path)?
+    } else {
+        convert_to_jsonl(&input_path, input_format)?
+    };
 
-    let mut rdr = rconfig.reader()?;
+    util::njobs(args.flag_jobs);
+    let batch_size = if args.flag_batch == 0 {
+        usize::MAX
+    } else {
+        args.flag_batch
+    };
 
-    let mut record = csv::ByteRecord::new();
-    while rdr.read_byte_record(&mut record)? {
-        split_row_num += 1;
+    // how many lines read, including blank lines which are skipped
+    let mut line_number: u64 = 0;
+    let mut record_count: u64 = 0;
+    let mut invalid_count: u64 = 0;
+    let mut batch: Vec<(u64, String)> = Vec::new();
+    let mut validation_results: Vec<Option<String>> = Vec::new();
+    let mut valid_flags: Vec<bool> = Vec::new();
+    let mut validation_error_messages: Vec<String> = Vec::new();
 
-        // length of valid_flags is max number of rows we can split
-        if split_row_num > valid_flags.len() {
-            break;
+    let mut lines =
+        BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, jsonl_file.try_clone()?).lines();
+    'batch_loop: loop {
+        while batch.len() < batch_size {
+            match lines.next() {
+                Some(Ok(line)) => {
+                    line_number += 1;
+                    if !line.trim().is_empty() {
+                        batch.push((line_number, line));
+                    }
+                },
+                Some(Err(e)) => {
+                    return fail_clierror!("Error reading line {}: {e}", line_number + 1);
+                },
+                None => break,
+            }
         }
 
-        // vector is 0-based, row_num is 1-based
-        let is_valid = valid_flags[split_row_num - 1];
+        if batch.is_empty() {
+            break 'batch_loop;
+        }
 
-        if is_valid {
-            valid_wtr.write_byte_record(&record)?;
-        } else {
-            invalid_wtr.write_byte_record(&record)?;
+        batch
+            .par_iter()
+            .with_min_len(1024)
+            .map(|(line_number, line)| do_jsonl_validation(*line_number, line, &schema_compiled))
+            .collect_into_vec(&mut validation_results);
+
+        for result in &validation_results {
+            if let Some(validation_error_msg) = result {
+                invalid_count += 1;
+                valid_flags.push(false);
+                validation_error_messages.push(validation_error_msg.to_string());
+            } else {
+                valid_flags.push(true);
+            }
+        }
+        record_count += batch.len() as u64;
+        batch.clear();
+
+        if args.flag_fail_fast && invalid_count > 0 {
+            break 'batch_loop;
+        }
+    }
+    drop(lines);
+
+    if let Some(report_format) = report_format {
+        // the top-level properties stand in for the CSV headers
+        let properties = schema_json["properties"]
+            .as_object()
+            .map(|properties| properties.keys().map(String::as_str).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let report = ValidationReport::new(
+            &input_path,
+            args.arg_json_schema.as_deref().unwrap_or_default(),
+            &ByteRecord::from(properties),
+            record_count,
+            invalid_count,
+            &validation_error_messages,
+            &[],
+            args.flag_report_examples,
+        );
+        report.write(report_format)?;
+    }
+
+    jsonl_file.rewind()?;
+    let mut lines = BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, jsonl_file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .zip(valid_flags);
+
+    if invalid_count == 0 {
+        // see if we need to pass all valid records to output
+        if let Some(ref valid_output) = args.flag_valid_output {
+            let mut valid_wtr: Box<dyn Write> = if valid_output == "-" {
+                Box::new(BufWriter::new(std::io::stdout().lock()))
+            } else {
+                Box::new(BufWriter::new(File::create(valid_output)?))
+            };
+            for (line, _) in lines {
+                valid_wtr.write_all(line.as_bytes())?;
+                valid_wtr.write_all(b"\n")?;
+            }
+            valid_wtr.flush()?;
+            // return 1 as an exitcode and the number of valid rows to stderr
+            return fail_clierror!("{record_count}");
         }
+        if !args.flag_quiet {
+            winfo!("All {} records valid.", HumanCount(record_count));
+        }
+        return Ok(());
     }
 
+    woutinfo!("Writing invalid/valid/error files...");
+    set_first_error_context(&validation_error_messages);
+    write_error_report(&input_path, validation_error_messages)?;
+
+    let valid_suffix = args.flag_valid.as_deref().unwrap_or("valid");
+    let invalid_suffix = args.flag_invalid.as_deref().unwrap_or("invalid");
+    let mut valid_wtr = BufWriter::new(File::create(format!("{input_path}.{valid_suffix}"))?);
+    let mut invalid_wtr = BufWriter::new(File::create(format!("{input_path}.{invalid_suffix}"))?);
+    for (line, is_valid) in lines.by_ref() {
+        let wtr = if is_valid {
+            &mut valid_wtr
+        } else {
+            &mut invalid_wtr
+        };
+        wtr.write_all(line.as_bytes())?;
+        wtr.write_all(b"\n")?;
+    }
     valid_wtr.flush()?;
     invalid_wtr.flush()?;
 
-    Ok(())
+    let fail_fast_msg = if args.flag_fail_fast {
+        format!(
+            "fail-fast enabled. stopped after line {}.\n",
+            HumanCount(line_number)
+        )
+    } else {
+        String::new()
+    };
+    fail_clierror!(
+        "{fail_fast_msg}{} out of {} records invalid.",
+        HumanCount(invalid_count),
+        HumanCount(record_count)
+    )
 }
 
-fn write_error_report(input_path: &str, validation_error_messages: Vec<String>) -> CliResult<()> {
+/// convert a Parquet or Arrow IPC file to JSONL, keeping its nested types.
+/// The JSONL is written to an anonymous temporary file, returned rewound to the start.
+#[cfg(feature = "polars")]
+fn convert_to_jsonl(input_path: &str, input_format: InputFormat) -> CliResult<File> {
+    use polars::prelude::{IpcReader, JsonFormat, JsonWriter, ParquetReader, SerReader, SerWriter};
+
+    let file = File::open(input_path)?;
+    let df_result = if input_format == InputFormat::Parquet {
+        ParquetReader::new(file).finish()
+    } else {
+        IpcReader::new(file).finish()
+    };
+    let mut df = match df_result {
+        Ok(df) => df,
+        Err(e) => return fail_clierror!("Cannot read {input_path}: {e}"),
+    };
+
+    let mut jsonl_file = tempfile::tempfile()?;
+    if let Err(e) = JsonWriter::new(&mut jsonl_file)
+        .with_json_format(JsonFormat::JsonLines)
+        .finish(&mut df)
+    {
+        return fail_clierror!("Cannot convert {input_path} to JSONL: {e}");
+    }
+    jsonl_file.rewind()?;
+    Ok(jsonl_file)
+}
+
+#[cfg(not(feature = "polars"))]
+fn convert_to_jsonl(input_path: &str, input_format: InputFormat) -> CliResult<File> {
+    fail_clierror!("{input_format:?} input is not supported in this qsv build: {input_path}")
+}
+
+/// split the input into valid & invalid files, writing the fixed record of coerced rows,
+/// re-applying the coercions of the coercion log. The invalid file is only written if
+/// invalid_suffix is set.
+fn split_invalid_records(
+    rconfig: &Config,
+    valid_flags: &[bool],
+    coercion_log: Option<&str>,
+    headers: &ByteRecord,
+    input_path: &str,
+    valid_suffix: &str,
+    invalid_suffix: Option<&str>,
+) -> CliResult<()> {
+    // track how many rows read for splitting into valid/invalid
+    // should not exceed row_number when aborted early due to fail-fast
+    let mut split_row_num: usize = 0;
+
+    // prepare output writers
+    let mut valid_wtr =
+        Config::new(Some(input_path.to_owned() + "." + valid_suffix).as_ref()).writer()?;
+    valid_wtr.write_byte_record(headers)?;
+
+    let mut invalid_wtr = match invalid_suffix {
+        Some(invalid_suffix) => {
+            let mut wtr = Config::new(Some(input_path.to_owned() + "." + invalid_suffix).as_ref())
+                .writer()?;
+            wtr.write_byte_record(headers)?;
+            Some(wtr)
+        },
+        None => None,
+    };
+
+    let mut coercion_log = coercion_log
+        .map(|path| CoercionLogReader::open(path, headers))
+        .transpose()?;
+
+    let mut rdr = rconfig.reader()?;
+
+    let mut record = csv::ByteRecord::new();
+    while rdr.read_byte_record(&mut record)? {
+        split_row_num += 1;
+
+        // length of valid_flags is max number of rows we can split
+        if split_row_num > valid_flags.len() {
+            break;
+        }
+
+        // vector is 0-based, row_num is 1-based
+        let is_valid = valid_flags[split_row_num - 1];
+
+        if is_valid {
+            if let Some(ref mut coercion_log) = coercion_log {
+                coercion_log.apply(split_row_num as u64, &mut record)?;
+            }
+            valid_wtr.write_byte_record(&record)?;
+        } else if let Some(ref mut invalid_wtr) = invalid_wtr {
+            invalid_wtr.write_byte_record(&record)?;
+        }
+    }
+
+    valid_wtr.flush()?;
+    if let Some(ref mut invalid_wtr) = invalid_wtr {
+        invalid_wtr.flush()?;
+    }
+
+    Ok(())
+}
+
+/// report the position of the first validation error
+fn set_first_error_context(validation_error_messages: &[String]) {
+    // error messages are in the "row_number<TAB>field<TAB>error<TAB>keyword" format
+    let mut first_error = validation_error_messages
+        .first()
+        .map(String::as_str)
+        .unwrap_or_default()
+        .splitn(3, '\t');
+    clitypes::set_error_context(
+        ErrorCode::SchemaValidationFailed,
+        first_error.next().and_then(|row| row.parse().ok()),
+        None,
+        first_error.next().map(str::to_string),
+    );
+}
+
+fn write_error_report(input_path: &str, validation_error_messages: Vec<String>) -> CliResult<()> {
     let wtr_capacitys = env::var("QSV_WTR_BUFFER_CAPACITY")
         .unwrap_or_else(|_| DEFAULT_WTR_BUFFER_CAPACITY.to_string());
     let wtr_buffer_size: usize = wtr_capacitys.parse().unwrap_or(DEFAULT_WTR_BUFFER_CAPACITY);
@@ -1392,12 +2186,17 @@ fn write_error_report(input_path: &str, validation_error_messages: Vec<String>)
 
     output_writer.write_all(b"row_number\tfield\terror\n")?;
 
-    // write out error report
+    // write out error report, without the trailing keyword field of each error line
     for error_msg in validation_error_messages {
-        output_writer.write_all(error_msg.as_bytes())?;
-        // since writer is buffered, it's more efficient to do additional write than append Newline
-        // to message
-        output_writer.write_all(b"\n")?;
+        for error_line in error_msg.lines() {
+            let error_line = error_line
+                .rsplit_once('\t')
+                .map_or(error_line, |(line, _keyword)| line);
+            output_writer.write_all(error_line.as_bytes())?;
+            // since writer is buffered, it's more efficient to do additional write than append
+            // Newline to message
+            output_writer.write_all(b"\n")?;
+        }
     }
 
     // flush error report; file gets closed automagically when out-of-scope
@@ -1406,40 +2205,1415 @@ fn write_error_report(input_path: &str, validation_error_messages: Vec<String>)
     Ok(())
 }
 
-/// if given record is valid, return None, otherwise, error file entry string
+fn create_coercion_log(input_path: &str) -> CliResult<csv::Writer<Box<dyn Write>>> {
+    let mut wtr = Config::new(Some(input_path.to_owned() + ".coercions.tsv").as_ref())
+        .delimiter(Some(Delimiter(b'\t')))
+        .writer()?;
+    wtr.write_record(["row_number", "field", "old_value", "new_value"])?;
+    Ok(wtr)
+}
+
+fn write_coercions(
+    wtr: &mut csv::Writer<Box<dyn Write>>,
+    row: u64,
+    coercions: &[Coercion],
+) -> CliResult<()> {
+    let mut itoa_buffer = itoa::Buffer::new();
+    let row = itoa_buffer.format(row);
+    for coercion in coercions {
+        wtr.write_record([
+            row,
+            coercion.field.as_str(),
+            coercion.old_value.as_str(),
+            coercion.new_value.as_str(),
+        ])?;
+    }
+    Ok(())
+}
+
+/// reads the coercion log in row order, to re-apply the coercions of each row
+struct CoercionLogReader {
+    rdr:          csv::Reader<File>,
+    header_names: Vec<String>,
+    // the next coercion - (row number, column index, new value)
+    next:         Option<(u64, usize, String)>,
+}
+
+impl CoercionLogReader {
+    fn open(path: &str, headers: &ByteRecord) -> CliResult<Self> {
+        let rdr = csv::ReaderBuilder::new().delimiter(b'\t').from_path(path)?;
+        let header_names = headers
+            .iter()
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect();
+        let mut log = Self {
+            rdr,
+            header_names,
+            next: None,
+        };
+        log.next = log.read_next()?;
+        Ok(log)
+    }
+
+    fn read_next(&mut self) -> CliResult<Option<(u64, usize, String)>> {
+        let mut log_record = csv::StringRecord::new();
+        if !self.rdr.read_record(&mut log_record)? {
+            return Ok(None);
+        }
+        let Some(row) = log_record.get(0).and_then(|row| row.parse::<u64>().ok()) else {
+            return fail_clierror!("Invalid row number in the coercion log: {log_record:?}");
+        };
+        let field = log_record.get(1).unwrap_or_default();
+        let Some(column) = self.header_names.iter().position(|name| name == field) else {
+            return fail_clierror!("Unknown field in the coercion log: {field}");
+        };
+        Ok(Some((
+            row,
+            column,
+            log_record.get(3).unwrap_or_default().to_string(),
+        )))
+    }
+
+    /// replace the coerced fields of the record of the given row
+    fn apply(&mut self, row: u64, record: &mut ByteRecord) -> CliResult<()> {
+        if self
+            .next
+            .as_ref()
+            .is_none_or(|(next_row, ..)| *next_row != row)
+        {
+            return Ok(());
+        }
+        let mut new_values: Vec<(usize, String)> = Vec::new();
+        while let Some((next_row, column, new_value)) = self.next.take() {
+            if next_row != row {
+                self.next = Some((next_row, column, new_value));
+                break;
+            }
+            new_values.push((column, new_value));
+            self.next = self.read_next()?;
+        }
+
+        let mut fixed_record = ByteRecord::with_capacity(record.as_slice().len(), record.len());
+        for (i, field) in record.iter().enumerate() {
+            match new_values.iter().find(|(column, _)| *column == i) {
+                Some((_, new_value)) => fixed_record.push_field(new_value.as_bytes()),
+                None => fixed_record.push_field(field),
+            }
+        }
+        *record = fixed_record;
+        Ok(())
+    }
+}
+
+/// a fix applied to a field by --coerce
+struct Coercion {
+    field:     String,
+    old_value: String,
+    new_value: String,
+}
+
+#[derive(Clone, Copy)]
+enum CoerceDateFormat {
+    Date,
+    DateTime,
+}
+
+/// the fixes --coerce can apply to a column, derived from its schema definition
+struct ColumnCoercer {
+    numeric:     bool,
+    date_format: Option<CoerceDateFormat>,
+    enum_values: Vec<String>,
+}
+
+impl ColumnCoercer {
+    /// returns the fixed value, or None if there's nothing to fix
+    fn coerce(&self, value: &str, prefer_dmy: bool) -> Option<String> {
+        let mut coerced = value.trim().to_string();
+
+        if self.numeric
+            && regex_oncelock!(r"^[+-]?\d{1,3}(?:,\d{3})+(?:\.\d+)?$").is_match(&coerced)
+        {
+            coerced.retain(|c| c != ',');
+        }
+
+        if let Some(date_format) = self.date_format {
+            if let Ok(dt) = parse_with_preference(&coerced, prefer_dmy) {
+                match date_format {
+                    // don't drop the time of day when coercing to a date
+                    CoerceDateFormat::Date if dt.time() == NaiveTime::MIN => {
+                        coerced = dt.format("%Y-%m-%d").to_string();
+                    },
+                    CoerceDateFormat::DateTime => {
+                        coerced = dt.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+                    },
+                    CoerceDateFormat::Date => {},
+                }
+            }
+        }
+
+        // only fix the casing if it matches exactly one enum value
+        if !self.enum_values.is_empty() && !self.enum_values.contains(&coerced) {
+            let lowercase = coerced.to_lowercase();
+            let mut matches = self
+                .enum_values
+                .iter()
+                .filter(|v| v.to_lowercase() == lowercase);
+            if let (Some(enum_value), None) = (matches.next(), matches.next()) {
+                coerced.clone_from(enum_value);
+            }
+        }
+
+        (coerced != value).then_some(coerced)
+    }
+}
+
+struct Coercer {
+    columns:    Vec<ColumnCoercer>,
+    prefer_dmy: bool,
+}
+
+impl Coercer {
+    fn new(schema: &Value, header_types: &[(String, JSONtypes)], prefer_dmy: bool) -> Self {
+        let columns = header_types
+            .iter()
+            .map(|(name, json_type)| {
+                let field_def = &schema["properties"][name];
+                ColumnCoercer {
+                    numeric:     matches!(json_type, JSONtypes::Number | JSONtypes::Integer),
+                    date_format: match field_def["format"].as_str() {
+                        Some("date") => Some(CoerceDateFormat::Date),
+                        Some("date-time") => Some(CoerceDateFormat::DateTime),
+                        _ => None,
+                    },
+                    enum_values: field_def["enum"]
+                        .as_array()
+                        .map(|values| {
+                            values
+                                .iter()
+                                .filter_map(|v| v.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        Self {
+            columns,
+            prefer_dmy,
+        }
+    }
+
+    /// try to fix the fields of an invalid row that have errors or can't be cast to their
+    /// type. As a record that can't be cast isn't validated any further, this is repeated
+    /// with the errors of the fixed row until it's valid or no more fields can be fixed,
+    /// fixing each field at most once. If the fixed row is valid, the record is replaced,
+    /// its errors are cleared and the coercions applied are returned.
+    fn coerce(
+        &self,
+        record: &mut ByteRecord,
+        result: &mut Option<String>,
+        header_types: &[(String, JSONtypes)],
+        header_len: usize,
+        schema_compiled: &Validator,
+        row_rules: &[RowRule],
+    ) -> Option<Vec<Coercion>> {
+        let mut errors = result.clone()?;
+        let mut fixed_record = record.clone();
+        let mut coerced_columns = vec![false; header_len];
+        let mut coercions: Vec<Coercion> = Vec::new();
+
+        loop {
+            // error lines are in the "row_number<TAB>field<TAB>error<TAB>keyword" format
+            let error_fields: HashSet<&str> = errors
+                .lines()
+                .filter_map(|line| line.split('\t').nth(1))
+                .collect();
+
+            let mut fixed_values: Vec<Option<String>> = vec![None; header_len];
+            let mut fixed_any = false;
+            for (i, ((name, json_type), column)) in
+                header_types.iter().zip(&self.columns).enumerate()
+            {
+                if coerced_columns[i] {
+                    continue;
+                }
+                let Some(value) = fixed_record
+                    .get(i)
+                    .and_then(|v| simdutf8::basic::from_utf8(v).ok())
+                else {
+                    continue;
+                };
+                if !error_fields.contains(name.as_str()) && casts_to_json_type(json_type, value) {
+                    continue;
+                }
+                if let Some(new_value) = column.coerce(value, self.prefer_dmy) {
+                    coercions.push(Coercion {
+                        field:     name.clone(),
+                        old_value: value.to_string(),
+                        new_value: new_value.clone(),
+                    });
+                    fixed_values[i] = Some(new_value);
+                    coerced_columns[i] = true;
+                    fixed_any = true;
+                }
+            }
+            if !fixed_any {
+                return None;
+            }
+
+            let mut next_record =
+                ByteRecord::with_capacity(fixed_record.as_slice().len(), fixed_record.len());
+            for (i, field) in fixed_record.iter().enumerate() {
+                match fixed_values.get(i) {
+                    Some(Some(new_value)) => next_record.push_field(new_value.as_bytes()),
+                    _ => next_record.push_field(field),
+                }
+            }
+            fixed_record = next_record;
+
+            match do_json_validation(
+                header_types,
+                header_len,
+                &fixed_record,
+                schema_compiled,
+                row_rules,
+            ) {
+                Some(next_errors) => errors = next_errors,
+                None => break,
+            }
+        }
+
+        *record = fixed_record;
+        *result = None;
+        Some(coercions)
+    }
+}
+
+/// can the value be cast to the JSON type by to_json_instance?
+fn casts_to_json_type(json_type: &JSONtypes, value: &str) -> bool {
+    value.is_empty()
+        || match json_type {
+            JSONtypes::Number => fast_float2::parse::<f64, _>(value).is_ok(),
+            JSONtypes::Integer => atoi_simd::parse::<i64>(value.as_bytes()).is_ok(),
+            JSONtypes::Boolean => matches!(value, "true" | "1" | "false" | "0"),
+            JSONtypes::String | JSONtypes::Unsupported => true,
+        }
+}
+
+/// the errors of a (field, keyword) pair, aggregated for the validation report
+struct ErrorGroup {
+    field:      String,
+    keyword:    String,
+    count:      u64,
+    // the errors counted, but not reported row by row (e.g. duplicate keys past
+    // MAX_REPORTED_KEY_DUPES)
+    unreported: u64,
+    // (row number, error message)
+    examples:   Vec<(u64, String)>,
+}
+
+impl ErrorGroup {
+    /// the group of a (field, keyword) pair, added if it's new
+    fn get<'a>(
+        error_groups: &'a mut Vec<Self>,
+        group_idx: &mut HashMap<(String, String), usize>,
+        field: &str,
+        keyword: &str,
+    ) -> &'a mut Self {
+        let idx = *group_idx
+            .entry((field.to_string(), keyword.to_string()))
+            .or_insert_with(|| {
+                error_groups.push(Self {
+                    field:      field.to_string(),
+                    keyword:    keyword.to_string(),
+                    count:      0,
+                    unreported: 0,
+                    examples:   Vec::new(),
+                });
+                error_groups.len() - 1
+            });
+        &mut error_groups[idx]
+    }
+}
+
+/// an aggregated summary of a validation run, written with --report-format
+struct ValidationReport {
+    input:         String,
+    schema:        String,
+    fields:        Vec<String>,
+    record_count:  u64,
+    invalid_count: u64,
+    error_count:   u64,
+    // in the order the (field, keyword) pairs were first seen
+    error_groups:  Vec<ErrorGroup>,
+}
+
+impl ValidationReport {
+    fn new(
+        input: &str,
+        schema: &str,
+        headers: &ByteRecord,
+        record_count: u64,
+        invalid_count: u64,
+        validation_error_messages: &[String],
+        unreported_errors: &[(String, String, u64)],
+        max_examples: usize,
+    ) -> Self {
+        let mut error_groups: Vec<ErrorGroup> = Vec::new();
+        let mut group_idx: HashMap<(String, String), usize> = HashMap::new();
+        let mut error_count = 0_u64;
+
+        // error lines are in the "row_number<TAB>field<TAB>error<TAB>keyword" format
+        for error_line in validation_error_messages.iter().flat_map(|msg| msg.lines()) {
+            let Some((line, keyword)) = error_line.rsplit_once('\t') else {
+                continue;
+            };
+            let mut parts = line.splitn(3, '\t');
+            let row = parts
+                .next()
+                .and_then(|row| row.parse::<u64>().ok())
+                .unwrap_or_default();
+            let field = parts.next().unwrap_or_default();
+            let error = parts.next().unwrap_or_default();
+            error_count += 1;
+
+            let group = ErrorGroup::get(&mut error_groups, &mut group_idx, field, keyword);
+            group.count += 1;
+            if group.examples.len() < max_examples {
+                group.examples.push((row, error.to_string()));
+            }
+        }
+        for (field, keyword, count) in unreported_errors {
+            error_count += count;
+            let group = ErrorGroup::get(&mut error_groups, &mut group_idx, field, keyword);
+            group.count += count;
+            group.unreported += count;
+        }
+
+        Self {
+            input: input.to_string(),
+            schema: schema.to_string(),
+            fields: headers
+                .iter()
+                .map(|h| String::from_utf8_lossy(h).to_string())
+                .collect(),
+            record_count,
+            invalid_count,
+            error_count,
+            error_groups,
+        }
+    }
+
+    const fn verdict(&self) -> &'static str {
+        if self.invalid_count == 0 {
+            "pass"
+        } else {
+            "fail"
+        }
+    }
+
+    fn write(&self, report_format: &str) -> CliResult<()> {
+        let (report, extension) = match report_format {
+            "junit" => (self.to_junit(), "xml"),
+            "sarif" => (serde_json::to_string_pretty(&self.to_sarif())?, "sarif"),
+            _ => (serde_json::to_string_pretty(&self.to_json())?, "json"),
+        };
+        let report_path = format!("{}.validation-report.{extension}", self.input);
+        std::fs::write(&report_path, report)?;
+        Ok(())
+    }
+
+    fn to_json(&self) -> Value {
+        let error_groups: Vec<Value> = self
+            .error_groups
+            .iter()
+            .map(|group| {
+                json!({
+                    "field": group.field,
+                    "keyword": group.keyword,
+                    "count": group.count,
+                    "unreported": group.unreported,
+                    "examples": group
+                        .examples
+                        .iter()
+                        .map(|(row, error)| json!({"row": row, "error": error}))
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        json!({
+            "input": self.input,
+            "schema": self.schema,
+            "verdict": self.verdict(),
+            "records": self.record_count,
+            "invalid_records": self.invalid_count,
+            "errors": self.error_count,
+            "error_groups": error_groups,
+        })
+    }
+
+    /// one testcase per field, plus one for each rule or multi-column constraint with errors
+    fn to_junit(&self) -> String {
+        let mut testcases: Vec<(&str, Vec<&ErrorGroup>)> = self
+            .fields
+            .iter()
+            .map(|f| (f.as_str(), Vec::new()))
+            .collect();
+        for group in &self.error_groups {
+            match testcases.iter_mut().find(|(name, _)| *name == group.field) {
+                Some((_, groups)) => groups.push(group),
+                None => testcases.push((group.field.as_str(), vec![group])),
+            }
+        }
+        let failures = testcases.iter().filter(|(_, g)| !g.is_empty()).count();
+
+        let input = xml_escape(&self.input);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"qsv validate\" tests=\"{tests}\" failures=\"{failures}\">\n",
+            tests = testcases.len()
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{input}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" \
+             skipped=\"0\">\n",
+            tests = testcases.len()
+        ));
+        xml.push_str("    <properties>\n");
+        for (name, value) in [
+            ("schema", xml_escape(&self.schema)),
+            ("verdict", self.verdict().to_string()),
+            ("records", self.record_count.to_string()),
+            ("invalid_records", self.invalid_count.to_string()),
+            ("errors", self.error_count.to_string()),
+        ] {
+            xml.push_str(&format!(
+                "      <property name=\"{name}\" value=\"{value}\"/>\n"
+            ));
+        }
+        xml.push_str("    </properties>\n");
+        for (name, groups) in testcases {
+            let name = xml_escape(name);
+            if groups.is_empty() {
+                xml.push_str(&format!(
+                    "    <testcase classname=\"{input}\" name=\"{name}\"/>\n"
+                ));
+                continue;
+            }
+            let count: u64 = groups.iter().map(|g| g.count).sum();
+            let summary = groups
+                .iter()
+                .map(|g| format!("{} ({})", g.keyword, g.count))
+                .join(", ");
+            let details = groups
+                .iter()
+                .flat_map(|g| {
+                    g.examples
+                        .iter()
+                        .map(|(row, error)| format!("row {row}: {error}"))
+                        .chain((g.unreported > 0).then(|| {
+                            format!(
+                                "{} more {} errors, not reported individually",
+                                g.unreported, g.keyword
+                            )
+                        }))
+                })
+                .join("\n");
+            xml.push_str(&format!(
+                "    <testcase classname=\"{input}\" name=\"{name}\">\n      <failure \
+                 type=\"{types}\" message=\"{message}\">{details}</failure>\n    </testcase>\n",
+                types = xml_escape(&groups.iter().map(|g| g.keyword.as_str()).join(",")),
+                message = xml_escape(&format!(
+                    "{count} error{s}: {summary}",
+                    s = if count == 1 { "" } else { "s" }
+                )),
+                details = xml_escape(&details),
+            ));
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    fn to_sarif(&self) -> Value {
+        let mut rule_ids: Vec<&str> = Vec::new();
+        for group in &self.error_groups {
+            if !rule_ids.contains(&group.keyword.as_str()) {
+                rule_ids.push(&group.keyword);
+            }
+        }
+        let rules: Vec<Value> = rule_ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "id": id,
+                    "shortDescription": {"text": format!("{id} validation error")},
+                })
+            })
+            .collect();
+
+        let mut results: Vec<Value> = Vec::new();
+        for group in &self.error_groups {
+            // safety: all keywords were added to rule_ids above
+            let rule_index = rule_ids.iter().position(|id| *id == group.keyword).unwrap();
+            for (row, error) in &group.examples {
+                results.push(json!({
+                    "ruleId": group.keyword,
+                    "ruleIndex": rule_index,
+                    "level": "error",
+                    "message": {"text": format!("{}: {error}", group.field)},
+                    // no region, as a row's line number isn't row + 1 if any record spans
+                    // several lines. The row is in the properties instead
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": {"uri": self.input},
+                        }
+                    }],
+                    "properties": {"row": row, "field": group.field},
+                }));
+            }
+        }
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "qsv validate",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": "https://github.com/dathere/qsv",
+                        "rules": rules,
+                    }
+                },
+                "invocations": [{"executionSuccessful": true}],
+                "artifacts": [{"location": {"uri": self.input}}],
+                "results": results,
+                "properties": {
+                    "schema": self.schema,
+                    "verdict": self.verdict(),
+                    "records": self.record_count,
+                    "invalid_records": self.invalid_count,
+                    "errors": self.error_count,
+                    "error_counts": self
+                        .error_groups
+                        .iter()
+                        .map(|g| {
+                            json!({
+                                "field": g.field,
+                                "keyword": g.keyword,
+                                "count": g.count,
+                                "unreported": g.unreported,
+                            })
+                        })
+                        .collect::<Vec<_>>(),
+                },
+            }]
+        })
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// if given record is valid, return None, otherwise, error file entry string.
+/// Each error line is in the "row_number<TAB>field<TAB>error<TAB>keyword" format.
 #[inline]
 fn do_json_validation(
     header_types: &[(String, JSONtypes)],
     header_len: usize,
     record: &ByteRecord,
     schema_compiled: &Validator,
+    row_rules: &[RowRule],
 ) -> Option<String> {
     // safety: row number was added as last column. We can unwrap safely since we know its there
     let row_number_string = simdutf8::basic::from_utf8(record.get(header_len).unwrap()).unwrap();
 
-    validate_json_instance(
-        &(match to_json_instance(header_types, header_len, record) {
-            Ok(obj) => obj,
-            Err(e) => {
-                return Some(format!("{row_number_string}\t<RECORD>\t{e}"));
+    let mut error_msg = match to_json_instance(header_types, header_len, record) {
+        Ok(obj) => validate_json_instance(&obj, schema_compiled)
+            .map(|validation_errors| validation_error_lines(row_number_string, &validation_errors)),
+        Err(e) => Some(format!("{row_number_string}\t<RECORD>\t{e}\ttype")),
+    };
+
+    // row rules work on the CSV record, so they're checked even if it can't be cast to JSON.
+    // The rule name is reported in the field column.
+    for rule in row_rules {
+        if rule.check(record) {
+            continue;
+        }
+        let rule_msg = format!(
+            "{row_number_string}\t{}\t{}\trowRules",
+            rule.name, rule.message
+        );
+        match error_msg {
+            Some(ref mut msg) => {
+                msg.push('\n');
+                msg.push_str(&rule_msg);
             },
-        }),
-        schema_compiled,
-    )
-    .map(|validation_errors| {
-        // squash multiple errors into one long String with linebreaks
-        validation_errors
-            .iter()
-            .map(|(field, error)| {
-                // validation error file format: row_number, field, error
-                format!(
-                    "{row_number_string}\t{field}\t{error}",
-                    field = field.trim_start_matches('/')
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
-    })
+            None => error_msg = Some(rule_msg),
+        }
+    }
+
+    error_msg
+}
+
+/// squash multiple validation errors of a row into one long String with linebreaks
+fn validation_error_lines(
+    row_number: &str,
+    validation_errors: &[(String, String, String)],
+) -> String {
+    validation_errors
+        .iter()
+        .map(|(field, keyword, error)| {
+            // validation error file format: row_number, field, error
+            // the keyword is only used for the --report-format report
+            format!(
+                "{row_number}\t{field}\t{error}\t{keyword}",
+                field = field.trim_start_matches('/')
+            )
+        })
+        .join("\n")
+}
+
+/// if the given JSONL line is valid, return None, otherwise, error file entry string.
+/// Nested objects & arrays are reported with their JSON pointer, e.g. "address/city".
+fn do_jsonl_validation(row_number: u64, line: &str, schema_compiled: &Validator) -> Option<String> {
+    let row_number = itoa::Buffer::new().format(row_number).to_owned();
+    match serde_json::from_str::<Value>(line) {
+        Ok(instance) => validate_json_instance(&instance, schema_compiled)
+            .map(|validation_errors| validation_error_lines(&row_number, &validation_errors)),
+        Err(e) => Some(format!("{row_number}\t<RECORD>\tinvalid JSON: {e}\tjson")),
+    }
+}
+
+/// parse the `rowRules` keyword from the top level of the schema. It's an array of
+/// named rules, each with an "assert" expression, and optional "if" & "message":
+///   "rowRules": [{"name": "closed_date_set", "if": "status == 'closed'",
+///                 "assert": "!empty(closed_date) && closed_date >= opened_date"}]
+fn get_row_rules(schema: &Value, headers: &ByteRecord) -> CliResult<Vec<RowRule>> {
+    let Some(rules) = schema.get("rowRules") else {
+        return Ok(Vec::new());
+    };
+    let Some(rules) = rules.as_array() else {
+        return fail_clierror!("'rowRules' must be an array: {rules}");
+    };
+
+    let prefer_dmy = util::get_envvar_flag("QSV_PREFER_DMY");
+    let mut row_rules = Vec::with_capacity(rules.len());
+    for (i, rule) in rules.iter().enumerate() {
+        let name = rule
+            .get("name")
+            .and_then(Value::as_str)
+            .map_or_else(|| format!("rule_{}", i + 1), str::to_string);
+        let Some(assertion) = rule.get("assert").and_then(Value::as_str) else {
+            return fail_clierror!(
+                "'rowRules' rule '{name}' is missing its \"assert\" expression."
+            );
+        };
+        match RowRule::new(
+            &name,
+            rule.get("if").and_then(Value::as_str),
+            assertion,
+            rule.get("message").and_then(Value::as_str),
+            headers,
+            prefer_dmy,
+        ) {
+            Ok(row_rule) => row_rules.push(row_rule),
+            Err(e) => return fail_clierror!("'rowRules' rule '{name}': {e}"),
+        }
+    }
+
+    Ok(row_rules)
+}
+
+/// a dataset-level key constraint, set with the `uniqueKey` or `primaryKey` keywords
+struct KeyConstraint {
+    keyword:          &'static str,
+    columns:          Vec<String>,
+    indices:          Vec<usize>,
+    seen:             odhtcache::ExtDedupCache,
+    // reported duplicate keys, with the row number of their first occurrence (0 until found)
+    dupe_keys:        HashMap<String, u64>,
+    // duplicates past MAX_REPORTED_KEY_DUPES
+    unreported_dupes: u64,
+}
+
+enum KeyCheck {
+    Valid,
+    // a primary key column is empty
+    Missing(String),
+    Duplicate,
+}
+
+/// build the key of a record from the given columns,
+/// returning the position of the first empty key column if any.
+/// Each value is prefixed with its length ("<len>:<value>"), so the values of a composite key
+/// can't run into each other, whatever characters they have.
+#[inline]
+fn build_key(record: &ByteRecord, indices: &[usize], key: &mut String) -> Result<(), usize> {
+    key.clear();
+    for (pos, &idx) in indices.iter().enumerate() {
+        let field = record.get(idx).unwrap_or_default();
+        if field.is_empty() {
+            return Err(pos);
+        }
+        let value = match simdutf8::basic::from_utf8(field) {
+            Ok(s) => Cow::Borrowed(s),
+            Err(_) => String::from_utf8_lossy(field),
+        };
+        key.push_str(itoa::Buffer::new().format(value.len()));
+        key.push(':');
+        key.push_str(&value);
+    }
+    Ok(())
+}
+
+/// the values of a key built by build_key
+fn key_values(key: &str) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut rest = key;
+    while let Some((len, tail)) = rest.split_once(':') {
+        let len = len.parse::<usize>().unwrap_or(tail.len()).min(tail.len());
+        let (value, tail) = tail.split_at(len);
+        values.push(value);
+        rest = tail;
+    }
+    values
+}
+
+impl KeyConstraint {
+    /// check a record's key, leaving the key in `key` for duplicate reporting
+    #[inline]
+    fn check(&mut self, record: &ByteRecord, key: &mut String) -> KeyCheck {
+        match build_key(record, &self.indices, key) {
+            Ok(()) => {
+                if self.seen.insert(key) {
+                    KeyCheck::Valid
+                } else {
+                    KeyCheck::Duplicate
+                }
+            },
+            // unique keys, like SQL UNIQUE constraints, are not checked when empty
+            Err(_) if self.keyword == UNIQUE_KEY => KeyCheck::Valid,
+            Err(pos) => KeyCheck::Missing(self.columns[pos].clone()),
+        }
+    }
+}
+
+/// parse the `uniqueKey` & `primaryKey` keywords from the top level of the schema.
+/// Both can be a column name or an array of column names for a composite key.
+/// `uniqueKey` can also be an array of keys, each an array of column names.
+fn get_key_constraints(schema: &Value, headers: &ByteRecord) -> CliResult<Vec<KeyConstraint>> {
+    let mut key_constraints = Vec::new();
+
+    for keyword in [PRIMARY_KEY, UNIQUE_KEY] {
+        let Some(value) = schema.get(keyword) else {
+            continue;
+        };
+
+        let as_columns = |v: &Value| -> Option<Vec<String>> {
+            match v {
+                Value::String(column) => Some(vec![column.clone()]),
+                Value::Array(columns) if !columns.is_empty() => columns
+                    .iter()
+                    .map(|c| c.as_str().map(str::to_string))
+                    .collect(),
+                _ => None,
+            }
+        };
+        let keys = match value {
+            Value::Array(keys) if keyword == UNIQUE_KEY && keys.iter().any(Value::is_array) => {
+                keys.iter().map(as_columns).collect::<Option<Vec<_>>>()
+            },
+            _ => as_columns(value).map(|columns| vec![columns]),
+        };
+        let Some(keys) = keys else {
+            return fail_clierror!(
+                "'{keyword}' must be a column name or an array of column names: {value}"
+            );
+        };
+
+        for columns in keys {
+            let mut indices = Vec::with_capacity(columns.len());
+            for column in &columns {
+                let Some(idx) = headers.iter().position(|h| h == column.as_bytes()) else {
+                    return fail_clierror!("'{keyword}' column '{column}' not found in the CSV.");
+                };
+                indices.push(idx);
+            }
+            key_constraints.push(KeyConstraint {
+                keyword,
+                columns,
+                indices,
+                seen: odhtcache::ExtDedupCache::new(KEY_CACHE_MEMORY_LIMIT, None),
+                dupe_keys: HashMap::new(),
+                unreported_dupes: 0,
+            });
+        }
+    }
+
+    Ok(key_constraints)
+}
+
+/// second pass to find the row numbers of the first occurrences of duplicate keys.
+/// Stops as soon as all of them are found.
+fn find_first_key_occurrences(
+    rconfig: &Config,
+    flag_trim: bool,
+    missing_values: &[Vec<u8>],
+    header_len: usize,
+    key_constraints: &mut [KeyConstraint],
+) -> CliResult<()> {
+    let mut remaining: usize = key_constraints.iter().map(|kc| kc.dupe_keys.len()).sum();
+
+    let mut rdr = rconfig.reader()?;
+    let mut record = ByteRecord::new();
+    let mut key = String::new();
+    let mut row_number: u64 = 0;
+
+    while remaining > 0 && rdr.read_byte_record(&mut record)? {
+        row_number += 1;
+        if flag_trim {
+            record.trim();
+        }
+        if !missing_values.is_empty() {
+            blank_missing_values(&mut record, header_len, missing_values);
+        }
+        for constraint in key_constraints.iter_mut() {
+            if build_key(&record, &constraint.indices, &mut key).is_err() {
+                continue;
+            }
+            if let Some(first_row) = constraint
+                .dupe_keys
+                .get_mut(&key)
+                .filter(|first_row| **first_row == 0)
+            {
+                *first_row = row_number;
+                remaining -= 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// a dataset-level foreign key constraint, set with the `foreignKeys` keyword
+struct ForeignKeyConstraint {
+    columns:     Vec<String>,
+    indices:     Vec<usize>,
+    resource:    String,
+    ref_columns: Vec<String>,
+    lookup:      ForeignKeyLookup,
+}
+
+enum ForeignKeyLookup {
+    // the referenced keys, loaded into a cache that spills to disk
+    Cached(odhtcache::ExtDedupCache),
+    // binary search on a reference file sorted on the referenced columns, using its index
+    Sorted {
+        idx:            Indexed<File, File>,
+        indices:        Vec<usize>,
+        record:         ByteRecord,
+        // the reference rows are normalized like the input rows
+        trim:           bool,
+        missing_values: Vec<Vec<u8>>,
+        // foreign keys often repeat in consecutive rows, so remember the last lookup
+        last_key:       String,
+        last_found:     bool,
+    },
+}
+
+impl ForeignKeyConstraint {
+    /// check if a record's foreign key exists in the reference file,
+    /// leaving the key in `key` for orphan reporting.
+    /// Rows with an empty foreign key column are not checked.
+    #[inline]
+    fn check(&mut self, record: &ByteRecord, key: &mut String) -> CliResult<bool> {
+        if build_key(record, &self.indices, key).is_err() {
+            return Ok(true);
+        }
+        match &mut self.lookup {
+            ForeignKeyLookup::Cached(cache) => Ok(cache.contains(key)),
+            ForeignKeyLookup::Sorted {
+                idx,
+                indices,
+                record: ref_record,
+                trim,
+                missing_values,
+                last_key,
+                last_found,
+            } => {
+                if *last_key == *key {
+                    return Ok(*last_found);
+                }
+                let mut found = false;
+                let values = key_values(key);
+                let (mut lo, mut hi) = (0_u64, idx.count());
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    idx.seek(mid)?;
+                    if !idx.read_byte_record(ref_record)? {
+                        break;
+                    }
+                    normalize_reference_record(ref_record, *trim, missing_values);
+                    let ordering = indices
+                        .iter()
+                        .zip(&values)
+                        .map(|(&i, key_value)| {
+                            ref_record
+                                .get(i)
+                                .unwrap_or_default()
+                                .cmp(key_value.as_bytes())
+                        })
+                        .find(|ordering| ordering.is_ne())
+                        .unwrap_or(std::cmp::Ordering::Equal);
+                    match ordering {
+                        std::cmp::Ordering::Less => lo = mid + 1,
+                        std::cmp::Ordering::Greater => hi = mid,
+                        std::cmp::Ordering::Equal => {
+                            found = true;
+                            break;
+                        },
+                    }
+                }
+                last_key.clone_from(key);
+                *last_found = found;
+                Ok(found)
+            },
+        }
+    }
+}
+
+/// parse the `foreignKeys` keyword from the top level of the schema. It uses the same form as
+/// Table Schema foreign keys, with the reference "resource" being a local CSV file, or "" to
+/// reference the input file itself:
+///   "foreignKeys": [{"fields": ["a", "b"],
+///                    "reference": {"resource": "x.csv", "fields": ["c", "d"], "sorted": true}}]
+fn get_foreign_key_constraints(
+    schema: &Value,
+    headers: &ByteRecord,
+    input: Option<&String>,
+    flag_trim: bool,
+    missing_values: &[Vec<u8>],
+) -> CliResult<Vec<ForeignKeyConstraint>> {
+    let Some(foreign_keys) = schema.get("foreignKeys") else {
+        return Ok(Vec::new());
+    };
+    let Some(foreign_keys) = foreign_keys.as_array() else {
+        return fail_clierror!("'foreignKeys' must be an array: {foreign_keys}");
+    };
+
+    let as_columns = |v: Option<&Value>| -> Option<Vec<String>> {
+        match v? {
+            Value::String(column) => Some(vec![column.clone()]),
+            Value::Array(columns) if !columns.is_empty() => columns
+                .iter()
+                .map(|c| c.as_str().map(str::to_string))
+                .collect(),
+            _ => None,
+        }
+    };
+
+    let mut fk_constraints = Vec::with_capacity(foreign_keys.len());
+    for foreign_key in foreign_keys {
+        let reference = foreign_key.get("reference");
+        let (Some(columns), Some(ref_columns), Some(resource)) = (
+            as_columns(foreign_key.get("fields")),
+            as_columns(reference.and_then(|r| r.get("fields"))),
+            reference
+                .and_then(|r| r.get("resource"))
+                .and_then(Value::as_str),
+        ) else {
+            return fail_clierror!(
+                "'foreignKeys' entries must have \"fields\", and a \"reference\" with \
+                 \"resource\" & \"fields\": {foreign_key}"
+            );
+        };
+        if columns.len() != ref_columns.len() {
+            return fail_clierror!(
+                "'foreignKeys' fields & reference fields must have the same number of columns: \
+                 {foreign_key}"
+            );
+        }
+        let sorted = reference
+            .and_then(|r| r.get("sorted"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let mut indices = Vec::with_capacity(columns.len());
+        for column in &columns {
+            let Some(idx) = headers.iter().position(|h| h == column.as_bytes()) else {
+                return fail_clierror!("'foreignKeys' column '{column}' not found in the CSV.");
+            };
+            indices.push(idx);
+        }
+
+        // an empty resource references the input file itself
+        let ref_path = if resource.is_empty() {
+            let Some(input) = input else {
+                return fail_clierror!("Self-referencing 'foreignKeys' require an input file.");
+            };
+            input.clone()
+        } else {
+            resource.to_string()
+        };
+        let ref_config = Config::new(Some(&ref_path));
+        let mut ref_rdr = ref_config.reader()?;
+        let ref_headers = ref_rdr.byte_headers()?.clone();
+        let mut ref_indices = Vec::with_capacity(ref_columns.len());
+        for column in &ref_columns {
+            let Some(idx) = ref_headers.iter().position(|h| h == column.as_bytes()) else {
+                return fail_clierror!(
+                    "'foreignKeys' reference column '{column}' not found in {ref_path}."
+                );
+            };
+            ref_indices.push(idx);
+        }
+
+        let lookup = if sorted {
+            // the binary search needs the reference file to be sorted on the referenced
+            // columns, so check it is
+            let mut ref_record = ByteRecord::new();
+            let mut prev_record = ByteRecord::new();
+            let mut ref_row: u64 = 0;
+            while ref_rdr.read_byte_record(&mut ref_record)? {
+                ref_row += 1;
+                normalize_reference_record(&mut ref_record, flag_trim, missing_values);
+                let ordering = ref_indices
+                    .iter()
+                    .map(|&i| {
+                        ref_record
+                            .get(i)
+                            .unwrap_or_default()
+                            .cmp(prev_record.get(i).unwrap_or_default())
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(std::cmp::Ordering::Equal);
+                if ref_row > 1 && ordering.is_lt() {
+                    return fail_clierror!(
+                        "'foreignKeys' reference file {ref_path} is not sorted on {columns} (row \
+                         {ref_row} is out of order). Sort it with `qsv sort --select {columns}` \
+                         or remove \"sorted\": true.",
+                        columns = ref_columns.join(","),
+                    );
+                }
+                std::mem::swap(&mut prev_record, &mut ref_record);
+            }
+
+            if ref_config.indexed()?.is_none() {
+                // create the index so we can binary search the sorted reference file
+                let mut idx_wtr = BufWriter::with_capacity(
+                    DEFAULT_WTR_BUFFER_CAPACITY,
+                    File::create(util::idx_path(Path::new(&ref_path)))?,
+                );
+                RandomAccessSimple::create(&mut ref_config.reader_file()?, &mut idx_wtr)?;
+                idx_wtr.flush()?;
+            }
+            let Some(idx) = ref_config.indexed()? else {
+                return fail_clierror!("Cannot index 'foreignKeys' reference file {ref_path}.");
+            };
+            ForeignKeyLookup::Sorted {
+                idx,
+                indices: ref_indices,
+                record: ByteRecord::new(),
+                trim: flag_trim,
+                missing_values: missing_values.to_vec(),
+                last_key: String::new(),
+                last_found: false,
+            }
+        } else {
+            let mut cache = odhtcache::ExtDedupCache::new(KEY_CACHE_MEMORY_LIMIT, None);
+            let mut ref_record = ByteRecord::new();
+            let mut key = String::new();
+            while ref_rdr.read_byte_record(&mut ref_record)? {
+                normalize_reference_record(&mut ref_record, flag_trim, missing_values);
+                if build_key(&ref_record, &ref_indices, &mut key).is_ok() {
+                    cache.insert(&key);
+                }
+            }
+            ForeignKeyLookup::Cached(cache)
+        };
+
+        fk_constraints.push(ForeignKeyConstraint {
+            columns,
+            indices,
+            resource: ref_path,
+            ref_columns,
+            lookup,
+        });
+    }
+
+    Ok(fk_constraints)
+}
+
+/// trim a reference record & blank its missing values, as is done for the input rows
+#[inline]
+fn normalize_reference_record(record: &mut ByteRecord, trim: bool, missing_values: &[Vec<u8>]) {
+    if trim {
+        record.trim();
+    }
+    if !missing_values.is_empty() {
+        let len = record.len();
+        blank_missing_values(record, len, missing_values);
+    }
+}
+
+/// Frictionless Table Schemas have a top-level "fields" array,
+/// instead of JSON Schema's "properties" object
+fn is_table_schema(schema: &Value) -> bool {
+    schema.get("fields").is_some_and(Value::is_array) && schema.get("properties").is_none()
+}
+
+/// Maps a Frictionless Table Schema (https://specs.frictionlessdata.io/table-schema/)
+/// to the equivalent JSON Schema, so it can be compiled and reported on like any other schema.
+/// Also returns the non-empty "missingValues", as empty fields are already treated as nulls.
+fn table_schema_to_json_schema(table_schema: &Value) -> CliResult<(Value, Vec<Vec<u8>>)> {
+    let Some(fields) = table_schema.get("fields").and_then(Value::as_array) else {
+        return fail_clierror!("Table Schema missing 'fields' array");
+    };
+
+    let mut missing_values: Vec<Vec<u8>> = Vec::new();
+    if let Some(values) = table_schema.get("missingValues") {
+        let Some(values) = values.as_array() else {
+            return fail_clierror!("Table Schema 'missingValues' must be an array of strings");
+        };
+        for value in values {
+            let Some(value) = value.as_str() else {
+                return fail_clierror!("Table Schema 'missingValues' must be an array of strings");
+            };
+            if !value.is_empty() {
+                missing_values.push(value.as_bytes().to_vec());
+            }
+        }
+    }
+
+    let mut properties = Map::with_capacity(fields.len());
+    let mut required = Vec::with_capacity(fields.len());
+    let mut unique_keys: Vec<Value> = Vec::new();
+
+    for field in fields {
+        let Some(name) = field.get("name").and_then(Value::as_str) else {
+            return fail_clierror!("Table Schema field missing 'name': {field}");
+        };
+        let field_type = field
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("string");
+        let field_format = field
+            .get("format")
+            .and_then(Value::as_str)
+            .unwrap_or("default");
+        let constraints = field.get("constraints").and_then(Value::as_object);
+        let is_required = constraints
+            .and_then(|c| c.get("required"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let json_type = match field_type {
+            "integer" | "year" => "integer",
+            "number" => "number",
+            _ => "string",
+        };
+
+        let mut property = Map::with_capacity(8);
+        if let Some(description) = field.get("description") {
+            property.insert("description".to_string(), description.clone());
+        }
+        property.insert(
+            "type".to_string(),
+            if is_required {
+                json!([json_type])
+            } else {
+                json!([json_type, "null"])
+            },
+        );
+
+        match (field_type, field_format) {
+            ("string", "email" | "uri" | "uuid") => {
+                property.insert("format".to_string(), json!(field_format));
+            },
+            ("date", "default") => {
+                property.insert("format".to_string(), json!("date"));
+            },
+            ("datetime", "default") => {
+                property.insert("format".to_string(), json!("date-time"));
+            },
+            ("time", "default") => {
+                property.insert("format".to_string(), json!("time"));
+            },
+            ("duration", _) => {
+                property.insert("format".to_string(), json!("duration"));
+            },
+            ("yearmonth", _) => {
+                property.insert("pattern".to_string(), json!(r"^\d{4}-(0[1-9]|1[0-2])$"));
+            },
+            ("boolean", _) => {
+                let true_values = field
+                    .get("trueValues")
+                    .cloned()
+                    .unwrap_or_else(|| json!(["true", "True", "TRUE", "1"]));
+                let false_values = field
+                    .get("falseValues")
+                    .cloned()
+                    .unwrap_or_else(|| json!(["false", "False", "FALSE", "0"]));
+                let mut enum_list: Vec<Value> = true_values
+                    .as_array()
+                    .into_iter()
+                    .chain(false_values.as_array())
+                    .flatten()
+                    .cloned()
+                    .collect();
+                if !is_required {
+                    enum_list.push(Value::Null);
+                }
+                property.insert("enum".to_string(), Value::Array(enum_list));
+            },
+            ("date" | "datetime" | "time", "any") => {},
+            ("date" | "datetime" | "time", _) => {
+                wwarn!(
+                    "Table Schema field '{name}': custom {field_type} format '{field_format}' is \
+                     not validated."
+                );
+            },
+            _ => {},
+        }
+
+        for (key, value) in constraints.into_iter().flatten() {
+            match key.as_str() {
+                "required" => {},
+                "unique" => {
+                    if value.as_bool() == Some(true) {
+                        unique_keys.push(json!([name]));
+                    }
+                },
+                "minLength" | "maxLength" => {
+                    property.insert(key.clone(), value.clone());
+                },
+                "minimum" | "maximum" if json_type != "string" => {
+                    let Some(number) = table_schema_number(value, json_type) else {
+                        return fail_clierror!(
+                            "Table Schema field '{name}': invalid {key} constraint: {value}"
+                        );
+                    };
+                    property.insert(key.clone(), number);
+                },
+                "pattern" => {
+                    // Table Schema patterns must match the whole value
+                    let Some(pattern) = value.as_str() else {
+                        return fail_clierror!(
+                            "Table Schema field '{name}': invalid pattern constraint: {value}"
+                        );
+                    };
+                    property.insert(key.clone(), json!(format!("^(?:{pattern})$")));
+                },
+                "enum" => {
+                    let Some(values) = value.as_array() else {
+                        return fail_clierror!(
+                            "Table Schema field '{name}': invalid enum constraint: {value}"
+                        );
+                    };
+                    let mut enum_list = Vec::with_capacity(values.len() + 1);
+                    for enum_value in values {
+                        if json_type == "string" {
+                            enum_list.push(match enum_value {
+                                Value::String(_) => enum_value.clone(),
+                                _ => Value::String(enum_value.to_string()),
+                            });
+                        } else if let Some(number) = table_schema_number(enum_value, json_type) {
+                            enum_list.push(number);
+                        } else {
+                            return fail_clierror!(
+                                "Table Schema field '{name}': invalid enum value: {enum_value}"
+                            );
+                        }
+                    }
+                    if !is_required {
+                        enum_list.push(Value::Null);
+                    }
+                    property.insert(key.clone(), Value::Array(enum_list));
+                },
+                _ => {
+                    wwarn!("Table Schema field '{name}': {key} constraint is not checked.");
+                },
+            }
+        }
+
+        properties.insert(name.to_string(), Value::Object(property));
+        required.push(Value::String(name.to_string()));
+    }
+
+    let mut json_schema = json!({
+        "$schema": "https://json-schema.org/draft-07/schema",
+        "type": "object",
+        "properties": properties,
+        "required": required
+    });
+    if !unique_keys.is_empty() {
+        json_schema[UNIQUE_KEY] = Value::Array(unique_keys);
+    }
+    if let Some(primary_key) = table_schema.get(PRIMARY_KEY) {
+        json_schema[PRIMARY_KEY] = primary_key.clone();
+    }
+    if let Some(foreign_keys) = table_schema.get("foreignKeys") {
+        json_schema["foreignKeys"] = foreign_keys.clone();
+    }
+    // qsv's rowRules keyword can also be used in Table Schemas
+    if let Some(row_rules) = table_schema.get("rowRules") {
+        json_schema["rowRules"] = row_rules.clone();
+    }
+
+    Ok((json_schema, missing_values))
+}
+
+/// Table Schema numeric constraints can be JSON numbers, or strings in the field's
+/// physical representation
+fn table_schema_number(value: &Value, json_type: &str) -> Option<Value> {
+    match value {
+        Value::Number(_) => Some(value.clone()),
+        Value::String(s) if json_type == "integer" => s
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .map(|int| Value::Number(Number::from(int))),
+        Value::String(s) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        _ => None,
+    }
+}
+
+/// replace fields matching a Table Schema's "missingValues" with empty fields,
+/// so they're treated as nulls. The row number in the last column is left as is.
+fn blank_missing_values(record: &mut ByteRecord, header_len: usize, missing_values: &[Vec<u8>]) {
+    let is_missing = |(i, field): &(usize, &[u8])| {
+        *i < header_len && missing_values.iter().any(|mv| mv.as_slice() == *field)
+    };
+    if !record.iter().enumerate().any(|f| is_missing(&f)) {
+        return;
+    }
+
+    let mut blanked = ByteRecord::with_capacity(record.as_slice().len(), record.len());
+    for (i, field) in record.iter().enumerate() {
+        if is_missing(&(i, field)) {
+            blanked.push_field(b"");
+        } else {
+            blanked.push_field(field);
+        }
+    }
+    *record = blanked;
 }
 
 /// convert CSV Record into JSON instance by referencing JSON types
@@ -1675,20 +3849,27 @@ mod tests_for_csv_to_json_conversion {
 }
 
 /// Validate JSON instance against compiled JSON Schema
-/// If invalid, returns Some(Vec<(String,String)>) holding the error messages
+/// If invalid, returns Some(Vec<(String,String,String)>) holding the instance location,
+/// the failed keyword & the error message of each error
 #[inline]
 fn validate_json_instance(
     instance: &Value,
     schema_compiled: &Validator,
-) -> Option<Vec<(String, String)>> {
+) -> Option<Vec<(String, String, String)>> {
     match schema_compiled.apply(instance).basic() {
         BasicOutput::Valid(_) => None,
         BasicOutput::Invalid(errors) => Some(
             errors
                 .iter()
                 .map(|e| {
+                    let keyword_location = e.keyword_location().to_string();
                     (
                         e.instance_location().to_string(),
+                        keyword_location
+                            .rsplit('/')
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
                         e.error_description().to_string(),
                     )
                 })
@@ -1825,6 +4006,7 @@ mod tests_for_schema_validation {
         similar_asserts::assert_eq!(
             vec![(
                 "/name".to_string(),
+                "minLength".to_string(),
                 "\"X\" is shorter than 2 characters".to_string()
             )],
             result.unwrap()
@@ -1837,7 +4019,7 @@ fn test_validate_currency_email_dynamicenum_validator() {
     #[cfg(not(feature = "lite"))]
     let qsv_cache_dir = lookup::set_qsv_cache_dir("~/.qsv-cache").unwrap();
     #[cfg(not(feature = "lite"))]
-    QSV_CACHE_DIR.get_or_init(|| qsv_cache_dir);
+    *QSV_CACHE_DIR.write().unwrap() = qsv_cache_dir;
 
     fn schema_currency_json() -> Value {
         serde_json::json!({
@@ -1901,6 +4083,7 @@ fn test_validate_currency_email_dynamicenum_validator() {
         result,
         Some(vec![(
             "/fee".to_owned(),
+            "format".to_owned(),
             "\"Ð 100.00\" is not a \"currency\"".to_owned()
         )])
     );
@@ -1930,10 +4113,12 @@ fn test_validate_currency_email_dynamicenum_validator() {
         Some(vec![
             (
                 "/fee".to_owned(),
+                "format".to_owned(),
                 "\"Ð 100.00\" is not a \"currency\"".to_owned()
             ),
             (
                 "/email".to_owned(),
+                "format".to_owned(),
                 "\"thisisnotanemail\" is not a \"email\"".to_owned()
             )
         ])
@@ -1976,10 +4161,12 @@ fn test_validate_currency_email_dynamicenum_validator() {
                 Some(vec![
                     (
                         "/name".to_owned(),
+                        "minLength".to_owned(),
                         "\"T\" is shorter than 2 characters".to_owned()
                     ),
                     (
                         "/agency".to_owned(),
+                        "dynamicEnum".to_owned(),
                         "\"MODA\" is not a valid dynamicEnum value".to_owned()
                     )
                 ])
@@ -1988,6 +4175,7 @@ fn test_validate_currency_email_dynamicenum_validator() {
                 result,
                 Some(vec![(
                     "/name".to_owned(),
+                    "minLength".to_owned(),
                     "\"X\" is shorter than 2 characters".to_owned()
                 )])
             ),
@@ -1996,6 +4184,7 @@ fn test_validate_currency_email_dynamicenum_validator() {
                 result,
                 Some(vec![(
                     "/agency".to_owned(),
+                    "dynamicEnum".to_owned(),
                     "\"NYFD\" is not a valid dynamicEnum value".to_owned()
                 )])
             ),
@@ -2003,6 +4192,7 @@ fn test_validate_currency_email_dynamicenum_validator() {
                 result,
                 Some(vec![(
                     "/fee".to_owned(),
+                    "format".to_owned(),
                     "\"WAX 100.000,00\" is not a \"currency\"".to_owned()
                 )])
             ),
@@ -2011,14 +4201,17 @@ fn test_validate_currency_email_dynamicenum_validator() {
                 Some(vec![
                     (
                         "/fee".to_owned(),
+                        "format".to_owned(),
                         "\"B 1,000,000\" is not a \"currency\"".to_owned()
                     ),
                     (
                         "/email".to_owned(),
+                        "format".to_owned(),
                         "\"71076.964-compuserve\" is not a \"email\"".to_owned()
                     ),
                     (
                         "/agency".to_owned(),
+                        "dynamicEnum".to_owned(),
                         "\"ABCD\" is not a valid dynamicEnum value".to_owned()
                     )
                 ])
@@ -2033,7 +4226,7 @@ fn test_load_json_via_url() {
     #[cfg(not(feature = "lite"))]
     let qsv_cache_dir = lookup::set_qsv_cache_dir("~/.qsv-cache").unwrap();
     #[cfg(not(feature = "lite"))]
-    QSV_CACHE_DIR.get_or_init(|| qsv_cache_dir);
+    *QSV_CACHE_DIR.write().unwrap() = qsv_cache_dir;
 
     let json_string_result = load_json("https://geojson.org/schema/FeatureCollection.json");
     assert!(&json_string_result.is_ok());
@@ -2048,7 +4241,7 @@ fn test_dyn_enum_validator() {
     #[cfg(not(feature = "lite"))]
     let qsv_cache_dir = lookup::set_qsv_cache_dir("~/.qsv-cache").unwrap();
     #[cfg(not(feature = "lite"))]
-    QSV_CACHE_DIR.get_or_init(|| qsv_cache_dir);
+    *QSV_CACHE_DIR.write().unwrap() = qsv_cache_dir;
 
     let schema = json!({"dynamicEnum": "https://raw.githubusercontent.com/dathere/qsv/refs/heads/master/resources/test/fruits.csv", "type": "string"});
     let validator = jsonschema::options()
@@ -1,8 +1,13 @@
 use std::{
+    cell::RefCell,
     env, fs,
-    io::{self, Read},
+    io::{self, Read, Seek},
     path::{Path, PathBuf},
-    sync::atomic::{AtomicBool, Ordering},
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
 };
 
 use log::{debug, info, warn};
@@ -10,7 +15,9 @@ use qsv_sniffer::{SampleSize, Sniffer};
 use serde::de::{Deserialize, Deserializer, Error};
 
 use crate::{
-    CliResult,
+    CliResult, clitypes,
+    cmd::stats::StatsData,
+    events,
     index::Indexed,
     select::{SelectColumns, Selection},
     util,
@@ -30,6 +37,104 @@ const NO_INDEX_WARNING_FILESIZE: u64 = 100_000_000; // 100MB
 // so we don't have to keep checking if the index has been created
 static AUTO_INDEXED: AtomicBool = AtomicBool::new(false);
 
+thread_local! {
+    // stdin & stdout of the commands run by the library API, instead of the process' stdio
+    static STDIN_PIPE: RefCell<Option<PipeReader>> = const { RefCell::new(None) };
+    static STDOUT_PIPE: RefCell<Option<PipeWriter>> = const { RefCell::new(None) };
+}
+
+/// The writing end of an in-process pipe, cloned for each of the stdout writers of a command.
+/// Each write is sent as a chunk to the `PipeReader`, blocking while the pipe is full.
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct PipeWriter(mpsc::SyncSender<Vec<u8>>);
+
+impl io::Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The reading end of an in-process pipe, at EOF once all its `PipeWriter`s are dropped.
+#[allow(dead_code)]
+pub struct PipeReader {
+    rx:    mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos:   usize,
+}
+
+impl io::Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                },
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// A bounded in-process pipe, holding at most `capacity` chunks.
+#[allow(dead_code)]
+pub fn pipe(capacity: usize) -> (PipeWriter, PipeReader) {
+    let (tx, rx) = mpsc::sync_channel(capacity);
+    (
+        PipeWriter(tx),
+        PipeReader {
+            rx,
+            chunk: Vec::new(),
+            pos: 0,
+        },
+    )
+}
+
+/// Use in-process pipes instead of stdin & stdout in the current thread,
+/// until `clear_stdio_pipes` is called. Only used by the library API.
+#[allow(dead_code)]
+pub fn set_stdio_pipes(stdin: PipeReader, stdout: PipeWriter) {
+    STDIN_PIPE.with(|pipe| *pipe.borrow_mut() = Some(stdin));
+    STDOUT_PIPE.with(|pipe| *pipe.borrow_mut() = Some(stdout));
+}
+
+/// drop the pipes of the current thread, so their other ends see EOF or a broken pipe
+#[allow(dead_code)]
+pub fn clear_stdio_pipes() {
+    STDIN_PIPE.with(|pipe| *pipe.borrow_mut() = None);
+    STDOUT_PIPE.with(|pipe| *pipe.borrow_mut() = None);
+}
+
+/// whether a command is run by the library API, with its stdio redirected to pipes
+pub fn stdio_piped() -> bool {
+    STDOUT_PIPE.with(|pipe| pipe.borrow().is_some())
+}
+
+/// the stdin pipe can only be read once, like stdin
+fn take_stdin_pipe() -> Option<PipeReader> {
+    STDIN_PIPE.with(|pipe| pipe.borrow_mut().take())
+}
+
+fn stdout_pipe() -> Option<PipeWriter> {
+    STDOUT_PIPE.with(|pipe| pipe.borrow().clone())
+}
+
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub static SPONSOR_MESSAGE: &str = r#"sponsored by datHere - Data Infrastructure Engineering (https://qsv.datHere.com)
 Need a UI & more advanced data-wrangling? Upgrade to qsv pro (https://qsvpro.datHere.com)
 "#;
@@ -65,6 +170,38 @@ impl Delimiter {
     }
 }
 
+/// The format of an input file, as detected by its file extension.
+///
+/// Non-CSV formats are transparently converted to CSV when they're read through `Config`,
+/// so every command that reads CSV can also read them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+    Arrow,
+}
+
+impl InputFormat {
+    fn from_extension(file_extension: &str) -> InputFormat {
+        match file_extension {
+            "jsonl" | "ndjson" => InputFormat::Jsonl,
+            "parquet" | "pqt" => InputFormat::Parquet,
+            "arrow" | "ipc" | "feather" => InputFormat::Arrow,
+            _ => InputFormat::Csv,
+        }
+    }
+
+    /// Returns true if this qsv build can convert the input format to CSV.
+    const fn is_supported(self) -> bool {
+        match self {
+            InputFormat::Csv => true,
+            InputFormat::Jsonl => cfg!(any(feature = "feature_capable", feature = "lite")),
+            InputFormat::Parquet | InputFormat::Arrow => cfg!(feature = "polars"),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Delimiter {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Delimiter, D::Error> {
         let s = String::deserialize(d)?;
@@ -99,6 +236,9 @@ pub struct Config {
     pub write_buffer:      u32,
     pub skip_format_check: bool,
     pub format_error:      Option<String>,
+    input_format:          InputFormat,
+    // stats of the input, loaded once to resolve stats-based selectors
+    selection_stats:       OnceLock<Vec<StatsData>>,
 }
 
 // Empty trait as an alias for Seek and Read that avoids auto trait errors
@@ -141,6 +281,12 @@ impl Config {
     /// - `QSV_RDR_BUFFER_CAPACITY`: Sets read buffer capacity.
     /// - `QSV_WTR_BUFFER_CAPACITY`: Sets write buffer capacity.
     /// - `QSV_SKIP_FORMAT_CHECK`: Set to skip file extension checking.
+    ///
+    /// # Input Formats
+    ///
+    /// JSONL (.jsonl/.ndjson), Parquet (.parquet/.pqt) and Arrow IPC (.arrow/.ipc/.feather)
+    /// files are detected by extension and converted to CSV when they're read.
+    /// Parquet and Arrow IPC require the `polars` feature.
     pub fn new(path: Option<&String>) -> Config {
         let default_delim = match env::var("QSV_DEFAULT_DELIMITER") {
             Ok(delim) => Delimiter::decode_delimiter(&delim).unwrap().as_byte(),
@@ -150,6 +296,7 @@ impl Config {
             || util::get_envvar_flag("QSV_SNIFF_PREAMBLE");
         let mut skip_format_check = true;
         let mut format_error = None;
+        let mut input_format = InputFormat::Csv;
         let (path, mut delim, snappy) = match path {
             None => (None, default_delim, false),
             // WIP: support remote files; currently only http(s) is supported
@@ -176,11 +323,17 @@ impl Config {
                     || util::get_envvar_flag("QSV_SKIP_FORMAT_CHECK")
                     || path.starts_with(std::env::temp_dir());
                 let (file_extension, delim, snappy) = get_delim_by_extension(&path, default_delim);
+                if !snappy {
+                    input_format = InputFormat::from_extension(&file_extension);
+                }
                 format_error = if skip_format_check {
                     None
                 } else {
                     match file_extension.as_str() {
                         "csv" | "tsv" | "tab" | "ssv" => None,
+                        _ if input_format != InputFormat::Csv && input_format.is_supported() => {
+                            None
+                        },
                         ext => Some(format!(
                             "{} is using an unsupported file format: {ext}. Set \
                              QSV_SKIP_FORMAT_CHECK to skip input format checking.",
@@ -254,6 +407,8 @@ impl Config {
                 .unwrap_or(DEFAULT_WTR_BUFFER_CAPACITY as u32),
             format_error,
             skip_format_check,
+            input_format,
+            selection_stats: OnceLock::new(),
         }
     }
 
@@ -273,6 +428,7 @@ impl Config {
         self
     }
 
+    #[cfg_attr(feature = "lib", allow(dead_code))]
     pub const fn get_dmy_preference(&self) -> bool {
         self.prefer_dmy
     }
@@ -367,6 +523,11 @@ impl Config {
         self.snappy
     }
 
+    #[cfg(feature = "polars")]
+    pub const fn input_format(&self) -> InputFormat {
+        self.input_format
+    }
+
     #[inline]
     /// Returns a `Selection` based on the config's `select_columns` & the first record of the CSV.
     ///
@@ -382,13 +543,67 @@ impl Config {
     ///
     /// This function will return an error if:
     /// * The `Config` has no `SelectColumns` (i.e., `Config::select` was not called).
+    /// * The selection has stats-based selectors (e.g. `type:Integer`) and the stats cache of the
+    ///   input file cannot be loaded (e.g. the input is stdin).
+    ///
+    /// The stats are only loaded on the first call that needs them.
     pub fn selection(&self, first_record: &csv::ByteRecord) -> Result<Selection, String> {
         match self.select_columns {
             None => fail!("Config has no 'SelectColumns'. Did you call Config::select?"),
+            Some(ref sel) if sel.needs_stats(first_record, !self.no_headers) => {
+                let csv_stats = if let Some(csv_stats) = self.selection_stats.get() {
+                    csv_stats
+                } else {
+                    let csv_stats = self.load_selection_stats()?;
+                    self.selection_stats.get_or_init(|| csv_stats)
+                };
+                sel.selection_with_stats(first_record, !self.no_headers, csv_stats)
+            },
             Some(ref sel) => sel.selection(first_record, !self.no_headers),
         }
     }
 
+    /// Loads the stats cache of the input file to resolve stats-based selectors.
+    /// If the cache is missing or stale, `qsv stats` is run to create it, inferring
+    /// dates on all columns so the Date & DateTime types can be selected.
+    /// A cache created without date inference is also treated as stale.
+    fn load_selection_stats(&self) -> Result<Vec<StatsData>, String> {
+        // the stats cache is created by running the qsv binary, which the library API never does
+        if stdio_piped() {
+            return fail!("Stats-based selectors are not supported by the library API.");
+        }
+        let Some(ref path) = self.path else {
+            return fail!("Stats-based selectors cannot be used with <stdin>.");
+        };
+        let schema_args = util::SchemaArgs {
+            flag_enum_threshold:  0,
+            flag_ignore_case:     false,
+            flag_strict_dates:    false,
+            flag_pattern_columns: SelectColumns::parse("").unwrap(),
+            flag_dates_whitelist: "all".to_string(),
+            flag_prefer_dmy:      self.prefer_dmy,
+            flag_force:           false,
+            flag_stdout:          false,
+            flag_format:          None,
+            flag_detect_formats:  false,
+            flag_jobs:            None,
+            flag_no_headers:      self.no_headers,
+            flag_delimiter:       Some(Delimiter(self.delimiter)),
+            arg_input:            Some(path.to_string_lossy().to_string()),
+            flag_memcheck:        false,
+        };
+        let (_, csv_stats, _) = util::get_stats_records(&schema_args, util::StatsMode::Schema)
+            .map_err(|e| format!("Cannot load stats for stats-based selectors: {e}"))?;
+        if csv_stats.is_empty() {
+            return fail_format!(
+                "No stats available for {}. Stats-based selectors require the stats cache, so \
+                 QSV_STATSCACHE_MODE cannot be 'none'.",
+                path.display()
+            );
+        }
+        Ok(csv_stats)
+    }
+
     /// Writes the headers from a CSV reader to a CSV writer.
     ///
     /// This function reads the headers from the given CSV reader and writes them to the CSV writer,
@@ -439,13 +654,14 @@ impl Config {
                 "Cannot use <stdin> here",
             )),
             Some(ref p) => {
+                clitypes::set_error_input(&p.display().to_string());
                 if !self.skip_format_check && self.format_error.is_some() {
                     Err(io::Error::new(
                         io::ErrorKind::InvalidInput,
                         self.format_error.clone().unwrap(),
                     ))
                 } else {
-                    fs::File::open(p).map(|f| self.from_reader(f))
+                    self.open_input_file(p).map(|f| self.from_reader(f))
                 }
             },
         }
@@ -457,8 +673,12 @@ impl Config {
             None => {
                 // Create a buffer in memory for stdin
                 let mut buffer: Vec<u8> = Vec::new();
-                let stdin = io::stdin();
-                stdin.lock().read_to_end(&mut buffer)?;
+                if let Some(mut stdin_pipe) = take_stdin_pipe() {
+                    stdin_pipe.read_to_end(&mut buffer)?;
+                } else {
+                    let stdin = io::stdin();
+                    stdin.lock().read_to_end(&mut buffer)?;
+                }
                 self.from_reader(Box::new(io::Cursor::new(buffer)))
             },
             Some(ref p) => {
@@ -468,11 +688,81 @@ impl Config {
                         self.format_error.clone().unwrap(),
                     ));
                 }
-                self.from_reader(Box::new(fs::File::open(p)?))
+                self.from_reader(Box::new(self.open_input_file(p)?))
             },
         })
     }
 
+    /// Opens the input file. Non-CSV input formats are converted to CSV first.
+    fn open_input_file(&self, path: &Path) -> io::Result<fs::File> {
+        if self.input_format == InputFormat::Csv {
+            fs::File::open(path)
+        } else {
+            self.convert_input_to_csv(path)
+        }
+    }
+
+    /// Converts a JSONL, Parquet or Arrow IPC input file to CSV, using the configured delimiter.
+    ///
+    /// The CSV is written to an anonymous temporary file that is deleted automatically
+    /// when it's closed, and it's returned rewound to the start.
+    fn convert_input_to_csv(&self, path: &Path) -> io::Result<fs::File> {
+        info!(
+            "converting {:?} input to CSV: {}",
+            self.input_format,
+            path.display()
+        );
+        let mut csv_file = tempfile::tempfile()?;
+        match self.input_format {
+            #[cfg(any(feature = "feature_capable", feature = "lite"))]
+            InputFormat::Jsonl => {
+                let rdr = io::BufReader::with_capacity(
+                    DEFAULT_RDR_BUFFER_CAPACITY,
+                    fs::File::open(path)?,
+                );
+                let mut wtr = csv::WriterBuilder::new()
+                    .delimiter(self.delimiter)
+                    .from_writer(&mut csv_file);
+                crate::cmd::jsonl::jsonl_to_csv(rdr, &mut wtr)?;
+                wtr.flush()?;
+            },
+            #[cfg(feature = "polars")]
+            InputFormat::Parquet | InputFormat::Arrow => {
+                use polars::prelude::{CsvWriter, IpcReader, ParquetReader, SerReader, SerWriter};
+
+                let file = fs::File::open(path)?;
+                let df_result = if self.input_format == InputFormat::Parquet {
+                    ParquetReader::new(file).finish()
+                } else {
+                    IpcReader::new(file).finish()
+                };
+                let mut df = df_result.map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("cannot read {}: {e}", path.display()),
+                    )
+                })?;
+                CsvWriter::new(&mut csv_file)
+                    .with_separator(self.delimiter)
+                    .finish(&mut df)
+                    .map_err(|e| io::Error::other(format!("cannot convert to CSV: {e}")))?;
+            },
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "{:?} input is not supported in this qsv build: {}",
+                        self.input_format,
+                        path.display()
+                    ),
+                ));
+            },
+        }
+        csv_file.rewind()?;
+        Ok(csv_file)
+    }
+
     /// Automatically creates an index file for the CSV file.
     ///
     /// This function attempts to create an index file for the CSV file specified in `self.path`.
@@ -492,7 +782,7 @@ impl Config {
     /// While this function doesn't return any errors, it logs debug messages for both successful
     /// and failed index creation attempts.
     fn autoindex_file(&self) {
-        if self.snappy {
+        if self.snappy || self.input_format != InputFormat::Csv {
             return;
         }
 
@@ -533,6 +823,11 @@ impl Config {
         // use it & return immediately.
         let auto_indexed = AUTO_INDEXED.load(Ordering::Relaxed);
 
+        // non-CSV input formats are converted to CSV on the fly, so they can't be indexed
+        if self.input_format != InputFormat::Csv {
+            return Ok(None);
+        }
+
         let (csv_file, mut idx_file) = if auto_indexed {
             (
                 fs::File::open(self.path.clone().unwrap())?,
@@ -616,9 +911,18 @@ impl Config {
     }
 
     pub fn io_reader(&self) -> io::Result<Box<dyn io::Read + Send + 'static>> {
-        Ok(match self.path {
-            None => Box::new(io::stdin()),
-            Some(ref p) => match fs::File::open(p) {
+        clitypes::set_error_input(
+            &self
+                .path
+                .as_ref()
+                .map_or_else(|| "stdin".to_string(), |p| p.display().to_string()),
+        );
+        let rdr: Box<dyn io::Read + Send + 'static> = match self.path {
+            None => match take_stdin_pipe() {
+                Some(stdin_pipe) => Box::new(stdin_pipe),
+                None => Box::new(io::stdin()),
+            },
+            Some(ref p) => match self.open_input_file(p) {
                 Ok(x) => {
                     if self.snappy {
                         info!("decoding snappy-compressed file: {}", p.display());
@@ -632,6 +936,12 @@ impl Config {
                     return Err(io::Error::new(io::ErrorKind::NotFound, msg));
                 },
             },
+        };
+        // count the bytes read for the NDJSON progress & metrics events
+        Ok(if events::enabled() {
+            Box::new(events::CountingReader::new(rdr))
+        } else {
+            rdr
         })
     }
 
@@ -652,7 +962,10 @@ impl Config {
 
     pub fn io_writer(&self) -> io::Result<Box<dyn io::Write + 'static>> {
         Ok(match self.path {
-            None => Box::new(io::stdout()),
+            None => match stdout_pipe() {
+                Some(stdout_pipe) => Box::new(stdout_pipe),
+                None => Box::new(io::stdout()),
+            },
             Some(ref p) => {
                 let p_str = p.as_os_str();
                 if p_str == "sink" {
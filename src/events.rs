@@ -0,0 +1,225 @@
+// Structured progress & metrics events for orchestration.
+//
+// When QSV_PROGRESS_FORMAT is set to `ndjson`, qsv writes newline-delimited JSON events
+// to stderr, or to the file set in QSV_PROGRESS_OUTPUT (e.g. /dev/fd/3 for file descriptor 3),
+// instead of drawing progress bars.
+//
+// For details, see https://github.com/dathere/qsv/blob/master/docs/PROGRESS_EVENTS.md
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Read, Write},
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+use indicatif::ProgressBar;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::{CliResult, clitypes::CURRENT_COMMAND};
+
+#[cfg_attr(feature = "lib", allow(dead_code))]
+const DEFAULT_PROGRESS_INTERVAL_MS: u64 = 1000;
+
+static BYTES_READ: AtomicU64 = AtomicU64::new(0);
+#[cfg_attr(feature = "lib", allow(dead_code))]
+static ROWS_PROCESSED: AtomicU64 = AtomicU64::new(0);
+#[cfg_attr(feature = "lib", allow(dead_code))]
+static CACHE_STATS: Mutex<Option<CacheStats>> = Mutex::new(None);
+
+// only the commands with caches (fetch, fetchpost & geocode) set cache stats
+#[allow(dead_code)]
+#[derive(Serialize, Clone, Debug)]
+pub struct CacheStats {
+    pub hits:      u64,
+    pub misses:    Option<u64>,
+    pub hit_ratio: f64,
+    pub entries:   Option<u64>,
+    pub capacity:  Option<u64>,
+}
+
+/// are NDJSON progress & metrics events enabled? (QSV_PROGRESS_FORMAT=ndjson)
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("QSV_PROGRESS_FORMAT").is_ok_and(|fmt| fmt.eq_ignore_ascii_case("ndjson"))
+    })
+}
+
+#[cfg_attr(feature = "lib", allow(dead_code))]
+fn event_writer() -> &'static Mutex<Box<dyn Write + Send>> {
+    static EVENT_WRITER: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+    EVENT_WRITER.get_or_init(|| {
+        let writer: Box<dyn Write + Send> = match std::env::var("QSV_PROGRESS_OUTPUT") {
+            Ok(path) if !path.is_empty() && path != "stderr" => {
+                match OpenOptions::new().create(true).append(true).open(&path) {
+                    Ok(file) => Box::new(file),
+                    Err(e) => {
+                        log::warn!("Cannot open QSV_PROGRESS_OUTPUT {path}: {e}. Using stderr.");
+                        Box::new(io::stderr())
+                    },
+                }
+            },
+            _ => Box::new(io::stderr()),
+        };
+        Mutex::new(writer)
+    })
+}
+
+/// write an event, adding the event name, the command & a timestamp to the event's fields
+#[cfg_attr(feature = "lib", allow(dead_code))]
+pub fn emit(event: &str, fields: Value) {
+    if !enabled() {
+        return;
+    }
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let command = CURRENT_COMMAND
+        .get()
+        .cloned()
+        .or_else(|| std::env::args().nth(1));
+
+    let mut obj = Map::new();
+    obj.insert("event".to_string(), json!(event));
+    obj.insert("command".to_string(), json!(command));
+    obj.insert("timestamp_ms".to_string(), json!(timestamp_ms));
+    if let Value::Object(fields) = fields {
+        obj.extend(fields);
+    }
+
+    if let Ok(mut wtr) = event_writer().lock() {
+        // events are best-effort, and should never fail the command
+        let _ = writeln!(wtr, "{}", Value::Object(obj));
+        let _ = wtr.flush();
+    }
+}
+
+/// record cache statistics, reported in progress & end events
+#[allow(dead_code)]
+pub fn set_cache_stats(stats: CacheStats) {
+    if let Ok(mut cache_stats) = CACHE_STATS.lock() {
+        *cache_stats = Some(stats);
+    }
+}
+
+#[cfg_attr(feature = "lib", allow(dead_code))]
+fn cache_stats() -> Option<CacheStats> {
+    CACHE_STATS.lock().ok().and_then(|stats| stats.clone())
+}
+
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+#[allow(clippy::cast_precision_loss)]
+fn emit_progress(event: &str, progress: &ProgressBar) {
+    let rows = progress.position();
+    ROWS_PROCESSED.store(rows, Ordering::Relaxed);
+    let total_rows = progress.length();
+    let elapsed = progress.elapsed();
+    let elapsed_secs = elapsed.as_secs_f64();
+    let rows_per_sec = if elapsed_secs > 0.0 {
+        rows as f64 / elapsed_secs
+    } else {
+        0.0
+    };
+    let percent = total_rows
+        .filter(|total| *total > 0)
+        .map(|total| (rows as f64 / total as f64) * 100.0);
+
+    emit(
+        event,
+        json!({
+            "rows": rows,
+            "total_rows": total_rows,
+            "percent": percent,
+            "bytes_read": BYTES_READ.load(Ordering::Relaxed),
+            "elapsed_ms": elapsed.as_millis(),
+            "rows_per_sec": rows_per_sec,
+            "cache": cache_stats(),
+        }),
+    );
+}
+
+/// start emitting progress events for a progress bar, every QSV_PROGRESS_INTERVAL_MS
+/// milliseconds (default: 1000), until the progress bar is finished
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub fn start_progress_events(progress: &ProgressBar) {
+    emit_progress("progress_start", progress);
+
+    let interval = std::env::var("QSV_PROGRESS_INTERVAL_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .filter(|ms| *ms > 0)
+        .unwrap_or(DEFAULT_PROGRESS_INTERVAL_MS);
+    let progress = progress.clone();
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(Duration::from_millis(interval));
+            if progress.is_finished() {
+                break;
+            }
+            emit_progress("progress", &progress);
+        }
+    });
+}
+
+/// emit the final progress event of a progress bar
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+pub fn finish_progress_events(progress: &ProgressBar) {
+    emit_progress("progress_end", progress);
+}
+
+/// emit the start-of-run event
+#[cfg_attr(feature = "lib", allow(dead_code))]
+pub fn emit_run_start() {
+    emit("start", json!({ "version": env!("CARGO_PKG_VERSION") }));
+}
+
+/// emit the end-of-run metrics event
+#[cfg_attr(feature = "lib", allow(dead_code))]
+pub fn emit_run_end(result: &CliResult<()>, start: Instant) {
+    if !enabled() {
+        return;
+    }
+    let exit_code = match result {
+        Ok(()) => 0_u8,
+        Err(err) => err.exit_code() as u8,
+    };
+    let status = if exit_code == 0 { "ok" } else { "error" };
+    let rows = ROWS_PROCESSED.load(Ordering::Relaxed);
+    emit(
+        "end",
+        json!({
+            "status": status,
+            "exit_code": exit_code,
+            "elapsed_ms": start.elapsed().as_millis(),
+            "bytes_read": BYTES_READ.load(Ordering::Relaxed),
+            "rows": if rows > 0 { Some(rows) } else { None },
+            "cache": cache_stats(),
+        }),
+    );
+}
+
+/// A reader that counts the bytes read, reported in progress & end events
+pub struct CountingReader<R> {
+    inner: R,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        BYTES_READ.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
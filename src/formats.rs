@@ -0,0 +1,592 @@
+// Built-in custom string formats for the `validate` command, some of which
+// are also auto-detected by the `schema` command.
+//
+// ISO 3166 & ISO 4217 codes are checked against embedded code lists.
+// ISO 3166-2 subdivision codes are checked for a valid country prefix and
+// a well-formed subdivision part, but not against the full subdivision list.
+use crate::regex_oncelock;
+
+/// A format checker, as registered with the JSON Schema validator.
+pub type FormatChecker = fn(&str) -> bool;
+
+/// All the custom formats supported by `validate`, in addition to the
+/// "currency" format and the formats defined by the JSON Schema spec.
+pub const CUSTOM_FORMATS: &[(&str, FormatChecker)] = &[
+    ("iban", is_iban),
+    ("credit-card", is_credit_card),
+    ("e164", is_e164),
+    ("iso3166-1-alpha2", is_iso3166_alpha2),
+    ("iso3166-1-alpha3", is_iso3166_alpha3),
+    ("iso3166-2", is_iso3166_2),
+    ("iso4217", is_iso4217),
+    ("us-zip", is_us_zip),
+    ("uk-postcode", is_uk_postcode),
+    ("uuid-v1", is_uuid_v1),
+    ("uuid-v2", is_uuid_v2),
+    ("uuid-v3", is_uuid_v3),
+    ("uuid-v4", is_uuid_v4),
+    ("uuid-v5", is_uuid_v5),
+    ("uuid-v6", is_uuid_v6),
+    ("uuid-v7", is_uuid_v7),
+    ("uuid-v8", is_uuid_v8),
+    ("latitude", is_latitude),
+    ("longitude", is_longitude),
+    ("isbn", is_isbn),
+    ("issn", is_issn),
+];
+
+/// The custom formats `schema` tries to detect, in order of precedence.
+/// Only formats that are unlikely to match by accident are included - e.g. any
+/// two-letter code could be a country code & any number in range a latitude.
+// only used by `schema`, which qsvdp & the library don't have
+#[cfg_attr(any(feature = "datapusher_plus", feature = "lib"), allow(dead_code))]
+pub const DETECTABLE_FORMATS: &[(&str, FormatChecker)] = &[
+    ("uuid-v1", is_uuid_v1),
+    ("uuid-v3", is_uuid_v3),
+    ("uuid-v4", is_uuid_v4),
+    ("uuid-v5", is_uuid_v5),
+    ("uuid-v6", is_uuid_v6),
+    ("uuid-v7", is_uuid_v7),
+    ("iban", is_iban),
+    ("e164", is_e164),
+    ("credit-card", is_credit_card),
+    ("isbn", is_isbn),
+    ("issn", is_issn),
+    ("uk-postcode", is_uk_postcode),
+];
+
+// ISO 3166-1 alpha-2 country codes, sorted
+const ISO3166_ALPHA2: &[&str] = &[
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+// ISO 3166-1 alpha-3 country codes, sorted
+const ISO3166_ALPHA3: &[&str] = &[
+    "ABW", "AFG", "AGO", "AIA", "ALA", "ALB", "AND", "ARE", "ARG", "ARM", "ASM", "ATA", "ATF",
+    "ATG", "AUS", "AUT", "AZE", "BDI", "BEL", "BEN", "BES", "BFA", "BGD", "BGR", "BHR", "BHS",
+    "BIH", "BLM", "BLR", "BLZ", "BMU", "BOL", "BRA", "BRB", "BRN", "BTN", "BVT", "BWA", "CAF",
+    "CAN", "CCK", "CHE", "CHL", "CHN", "CIV", "CMR", "COD", "COG", "COK", "COL", "COM", "CPV",
+    "CRI", "CUB", "CUW", "CXR", "CYM", "CYP", "CZE", "DEU", "DJI", "DMA", "DNK", "DOM", "DZA",
+    "ECU", "EGY", "ERI", "ESH", "ESP", "EST", "ETH", "FIN", "FJI", "FLK", "FRA", "FRO", "FSM",
+    "GAB", "GBR", "GEO", "GGY", "GHA", "GIB", "GIN", "GLP", "GMB", "GNB", "GNQ", "GRC", "GRD",
+    "GRL", "GTM", "GUF", "GUM", "GUY", "HKG", "HMD", "HND", "HRV", "HTI", "HUN", "IDN", "IMN",
+    "IND", "IOT", "IRL", "IRN", "IRQ", "ISL", "ISR", "ITA", "JAM", "JEY", "JOR", "JPN", "KAZ",
+    "KEN", "KGZ", "KHM", "KIR", "KNA", "KOR", "KWT", "LAO", "LBN", "LBR", "LBY", "LCA", "LIE",
+    "LKA", "LSO", "LTU", "LUX", "LVA", "MAC", "MAF", "MAR", "MCO", "MDA", "MDG", "MDV", "MEX",
+    "MHL", "MKD", "MLI", "MLT", "MMR", "MNE", "MNG", "MNP", "MOZ", "MRT", "MSR", "MTQ", "MUS",
+    "MWI", "MYS", "MYT", "NAM", "NCL", "NER", "NFK", "NGA", "NIC", "NIU", "NLD", "NOR", "NPL",
+    "NRU", "NZL", "OMN", "PAK", "PAN", "PCN", "PER", "PHL", "PLW", "PNG", "POL", "PRI", "PRK",
+    "PRT", "PRY", "PSE", "PYF", "QAT", "REU", "ROU", "RUS", "RWA", "SAU", "SDN", "SEN", "SGP",
+    "SGS", "SHN", "SJM", "SLB", "SLE", "SLV", "SMR", "SOM", "SPM", "SRB", "SSD", "STP", "SUR",
+    "SVK", "SVN", "SWE", "SWZ", "SXM", "SYC", "SYR", "TCA", "TCD", "TGO", "THA", "TJK", "TKL",
+    "TKM", "TLS", "TON", "TTO", "TUN", "TUR", "TUV", "TWN", "TZA", "UGA", "UKR", "UMI", "URY",
+    "USA", "UZB", "VAT", "VCT", "VEN", "VGB", "VIR", "VNM", "VUT", "WLF", "WSM", "YEM", "ZAF",
+    "ZMB", "ZWE",
+];
+
+// active ISO 4217 currency codes, including funds & precious metals, sorted
+const ISO4217: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BOV", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD",
+    "CAD", "CDF", "CHE", "CHF", "CHW", "CLF", "CLP", "CNY", "COP", "COU", "CRC", "CUC", "CUP",
+    "CVE", "CZK", "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP",
+    "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS",
+    "INR", "IQD", "IRR", "ISK", "JMD", "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW",
+    "KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD",
+    "MMK", "MNT", "MOP", "MRU", "MUR", "MVR", "MWK", "MXN", "MXV", "MYR", "MZN", "NAD", "NGN",
+    "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR",
+    "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SOS",
+    "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD",
+    "TWD", "TZS", "UAH", "UGX", "USD", "USN", "UYI", "UYU", "UYW", "UZS", "VED", "VES", "VND",
+    "VUV", "WST", "XAF", "XAG", "XAU", "XBA", "XBB", "XBC", "XBD", "XCD", "XCG", "XDR", "XOF",
+    "XPD", "XPF", "XPT", "XSU", "XTS", "XUA", "XXX", "YER", "ZAR", "ZMW", "ZWG",
+];
+
+// IBAN lengths of the countries in the SWIFT IBAN registry.
+// IBANs of countries not listed here are only checked for their check digits.
+const IBAN_LENGTHS: &[(&str, usize)] = &[
+    ("AD", 24),
+    ("AE", 23),
+    ("AL", 28),
+    ("AT", 20),
+    ("AZ", 28),
+    ("BA", 20),
+    ("BE", 16),
+    ("BG", 22),
+    ("BH", 22),
+    ("BR", 29),
+    ("BY", 28),
+    ("CH", 21),
+    ("CR", 22),
+    ("CY", 28),
+    ("CZ", 24),
+    ("DE", 22),
+    ("DK", 18),
+    ("DO", 28),
+    ("EE", 20),
+    ("EG", 29),
+    ("ES", 24),
+    ("FI", 18),
+    ("FO", 18),
+    ("FR", 27),
+    ("GB", 22),
+    ("GE", 22),
+    ("GI", 23),
+    ("GL", 18),
+    ("GR", 27),
+    ("GT", 28),
+    ("HR", 21),
+    ("HU", 28),
+    ("IE", 22),
+    ("IL", 23),
+    ("IQ", 23),
+    ("IS", 26),
+    ("IT", 27),
+    ("JO", 30),
+    ("KW", 30),
+    ("KZ", 20),
+    ("LB", 28),
+    ("LC", 32),
+    ("LI", 21),
+    ("LT", 20),
+    ("LU", 20),
+    ("LV", 21),
+    ("MC", 27),
+    ("MD", 24),
+    ("ME", 22),
+    ("MK", 19),
+    ("MR", 27),
+    ("MT", 31),
+    ("MU", 30),
+    ("NL", 18),
+    ("NO", 15),
+    ("PK", 24),
+    ("PL", 28),
+    ("PS", 29),
+    ("PT", 25),
+    ("QA", 29),
+    ("RO", 24),
+    ("RS", 22),
+    ("SA", 24),
+    ("SC", 31),
+    ("SE", 24),
+    ("SI", 19),
+    ("SK", 24),
+    ("SM", 27),
+    ("ST", 25),
+    ("SV", 28),
+    ("TL", 23),
+    ("TN", 24),
+    ("TR", 26),
+    ("UA", 29),
+    ("VA", 22),
+    ("VG", 24),
+    ("XK", 20),
+];
+
+/// Detects which of the `DETECTABLE_FORMATS` all the values of a column satisfy,
+/// dropping candidate formats as soon as a value fails their check.
+#[cfg_attr(any(feature = "datapusher_plus", feature = "lib"), allow(dead_code))]
+pub struct FormatDetector {
+    candidates: Vec<(&'static str, FormatChecker)>,
+    has_values: bool,
+}
+
+impl Default for FormatDetector {
+    fn default() -> Self {
+        Self {
+            candidates: DETECTABLE_FORMATS.to_vec(),
+            has_values: false,
+        }
+    }
+}
+
+#[cfg_attr(any(feature = "datapusher_plus", feature = "lib"), allow(dead_code))]
+impl FormatDetector {
+    /// check a non-empty value against the remaining candidate formats
+    pub fn update(&mut self, value: &str) {
+        self.has_values = true;
+        self.candidates.retain(|(_, checker)| checker(value));
+    }
+
+    /// true if no candidate formats remain
+    pub fn is_done(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// the first candidate format that all the values satisfied, if any
+    pub fn format(&self) -> Option<&'static str> {
+        if self.has_values {
+            self.candidates.first().map(|(name, _)| *name)
+        } else {
+            None
+        }
+    }
+}
+
+/// International Bank Account Number, with or without spaces between groups.
+/// Checks the country code, the country-specific length & the mod 97 check digits.
+pub fn is_iban(s: &str) -> bool {
+    let iban: String = s.chars().filter(|c| *c != ' ').collect();
+    let bytes = iban.as_bytes();
+    if !(15..=34).contains(&bytes.len())
+        || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+        || !bytes[2..4].iter().all(u8::is_ascii_digit)
+        || !bytes[4..]
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    {
+        return false;
+    }
+    let country = &iban[..2];
+    if country != "XK" && ISO3166_ALPHA2.binary_search(&country).is_err() {
+        return false;
+    }
+    if let Some((_, len)) = IBAN_LENGTHS.iter().find(|(c, _)| *c == country) {
+        if *len != bytes.len() {
+            return false;
+        }
+    }
+
+    // move the first four characters to the end, convert letters to numbers
+    // (A = 10 ... Z = 35) & compute the remainder piecewise
+    let mut remainder: u32 = 0;
+    for b in bytes[4..].iter().chain(&bytes[..4]) {
+        if b.is_ascii_digit() {
+            remainder = (remainder * 10 + u32::from(b - b'0')) % 97;
+        } else {
+            remainder = (remainder * 100 + u32::from(b - b'A' + 10)) % 97;
+        }
+    }
+    remainder == 1
+}
+
+/// Payment card number of 12 to 19 digits that passes the Luhn check.
+/// Digits may be grouped with single spaces or hyphens.
+pub fn is_credit_card(s: &str) -> bool {
+    if s.starts_with([' ', '-']) || s.ends_with([' ', '-']) || s.contains("  ") || s.contains("--")
+    {
+        return false;
+    }
+    let mut digits = Vec::with_capacity(19);
+    for c in s.chars() {
+        match c {
+            '0'..='9' => digits.push(c as u32 - '0' as u32),
+            ' ' | '-' => {},
+            _ => return false,
+        }
+    }
+    (12..=19).contains(&digits.len()) && luhn_valid(&digits)
+}
+
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                *d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+/// E.164 international phone number, e.g. +14155552671
+pub fn is_e164(s: &str) -> bool {
+    regex_oncelock!(r"^\+[1-9][0-9]{1,14}$").is_match(s)
+}
+
+pub fn is_iso3166_alpha2(s: &str) -> bool {
+    ISO3166_ALPHA2.binary_search(&s).is_ok()
+}
+
+pub fn is_iso3166_alpha3(s: &str) -> bool {
+    ISO3166_ALPHA3.binary_search(&s).is_ok()
+}
+
+/// ISO 3166-2 subdivision code, e.g. US-NY or GB-ENG.
+/// Only the country prefix is checked against the ISO 3166-1 list.
+pub fn is_iso3166_2(s: &str) -> bool {
+    match s.split_once('-') {
+        Some((country, subdivision)) => {
+            is_iso3166_alpha2(country)
+                && (1..=3).contains(&subdivision.len())
+                && subdivision
+                    .bytes()
+                    .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        },
+        None => false,
+    }
+}
+
+pub fn is_iso4217(s: &str) -> bool {
+    ISO4217.binary_search(&s).is_ok()
+}
+
+/// US ZIP code, in 5-digit or ZIP+4 form
+pub fn is_us_zip(s: &str) -> bool {
+    regex_oncelock!(r"^[0-9]{5}(?:-[0-9]{4})?$").is_match(s)
+}
+
+/// UK postcode in upper case, with or without the space before the inward code
+pub fn is_uk_postcode(s: &str) -> bool {
+    regex_oncelock!(r"^(?:[A-Z][A-HJ-Y]?[0-9][A-Z0-9]? ?[0-9][ABD-HJLNP-UW-Z]{2}|GIR ?0AA)$")
+        .is_match(s)
+}
+
+// UUIDs must be in their hyphenated form with the RFC 9562 variant
+fn uuid_version(s: &str) -> Option<usize> {
+    if s.len() != 36 {
+        return None;
+    }
+    let uuid = uuid::Uuid::try_parse(s).ok()?;
+    if uuid.get_variant() == uuid::Variant::RFC4122 {
+        Some(uuid.get_version_num())
+    } else {
+        None
+    }
+}
+
+pub fn is_uuid_v1(s: &str) -> bool {
+    uuid_version(s) == Some(1)
+}
+
+pub fn is_uuid_v2(s: &str) -> bool {
+    uuid_version(s) == Some(2)
+}
+
+pub fn is_uuid_v3(s: &str) -> bool {
+    uuid_version(s) == Some(3)
+}
+
+pub fn is_uuid_v4(s: &str) -> bool {
+    uuid_version(s) == Some(4)
+}
+
+pub fn is_uuid_v5(s: &str) -> bool {
+    uuid_version(s) == Some(5)
+}
+
+pub fn is_uuid_v6(s: &str) -> bool {
+    uuid_version(s) == Some(6)
+}
+
+pub fn is_uuid_v7(s: &str) -> bool {
+    uuid_version(s) == Some(7)
+}
+
+pub fn is_uuid_v8(s: &str) -> bool {
+    uuid_version(s) == Some(8)
+}
+
+fn parse_coordinate(s: &str, limit: f64) -> bool {
+    let s = s.trim();
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'+'))
+        && fast_float2::parse::<f64, _>(s).is_ok_and(|n| n.is_finite() && n.abs() <= limit)
+}
+
+/// Decimal degrees latitude, between -90 & 90
+pub fn is_latitude(s: &str) -> bool {
+    parse_coordinate(s, 90.0)
+}
+
+/// Decimal degrees longitude, between -180 & 180
+pub fn is_longitude(s: &str) -> bool {
+    parse_coordinate(s, 180.0)
+}
+
+/// ISBN-10 or ISBN-13, with or without hyphens or spaces, with a valid check digit
+pub fn is_isbn(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().filter(|c| *c != '-' && *c != ' ').collect();
+    match chars.len() {
+        10 => {
+            let mut sum = 0;
+            for (i, c) in chars.iter().enumerate() {
+                let value = match c {
+                    '0'..='9' => *c as u32 - '0' as u32,
+                    'X' if i == 9 => 10,
+                    _ => return false,
+                };
+                sum += value * (10 - i as u32);
+            }
+            sum % 11 == 0
+        },
+        13 => {
+            if !chars.iter().all(char::is_ascii_digit) {
+                return false;
+            }
+            if chars[..3] != ['9', '7', '8'] && chars[..3] != ['9', '7', '9'] {
+                return false;
+            }
+            let sum: u32 = chars
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let d = *c as u32 - '0' as u32;
+                    if i % 2 == 0 { d } else { d * 3 }
+                })
+                .sum();
+            sum % 10 == 0
+        },
+        _ => false,
+    }
+}
+
+/// ISSN, e.g. 0317-8471, with a valid check digit
+pub fn is_issn(s: &str) -> bool {
+    if !regex_oncelock!(r"^[0-9]{4}-?[0-9]{3}[0-9X]$").is_match(s) {
+        return false;
+    }
+    let chars: Vec<char> = s.chars().filter(|c| *c != '-').collect();
+    let sum: u32 = chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let value = if *c == 'X' {
+                10
+            } else {
+                *c as u32 - '0' as u32
+            };
+            value * (8 - i as u32)
+        })
+        .sum();
+    sum % 11 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_lists_sorted() {
+        assert!(ISO3166_ALPHA2.is_sorted());
+        assert!(ISO3166_ALPHA3.is_sorted());
+        assert!(ISO4217.is_sorted());
+    }
+
+    #[test]
+    fn test_iban() {
+        assert!(is_iban("GB82WEST12345698765432"));
+        assert!(is_iban("GB82 WEST 1234 5698 7654 32"));
+        assert!(is_iban("DE89370400440532013000"));
+        assert!(!is_iban("GB82WEST12345698765431"));
+        assert!(!is_iban("DE8937040044053201300"));
+        assert!(!is_iban("ZZ82WEST12345698765432"));
+    }
+
+    #[test]
+    fn test_ascii_digits_only() {
+        assert!(is_e164("+14155552671"));
+        assert!(!is_e164("+١٤١٥٥٥٥٢٦٧١"));
+        assert!(is_us_zip("12345-6789"));
+        assert!(!is_us_zip("١٢٣٤٥"));
+        assert!(is_issn("0317-8471"));
+        assert!(!is_issn("٠٣١٧-٨٤٧١"));
+        assert!(!is_uk_postcode("SW1A ١AA"));
+    }
+
+    #[test]
+    fn test_credit_card() {
+        assert!(is_credit_card("4111111111111111"));
+        assert!(is_credit_card("4111 1111 1111 1111"));
+        assert!(is_credit_card("5500-0000-0000-0004"));
+        assert!(!is_credit_card("4111111111111112"));
+        assert!(!is_credit_card("4111--1111111111111"));
+        assert!(!is_credit_card("1234"));
+    }
+
+    #[test]
+    fn test_iso_codes() {
+        assert!(is_e164("+14155552671"));
+        assert!(!is_e164("01903 221471"));
+        assert!(is_iso3166_alpha2("PH"));
+        assert!(!is_iso3166_alpha2("ph"));
+        assert!(is_iso3166_alpha3("PHL"));
+        assert!(!is_iso3166_alpha3("PHX"));
+        assert!(is_iso3166_2("US-NY"));
+        assert!(is_iso3166_2("GB-ENG"));
+        assert!(!is_iso3166_2("XX-NY"));
+        assert!(!is_iso3166_2("US-"));
+        assert!(is_iso4217("EUR"));
+        assert!(!is_iso4217("DOG"));
+    }
+
+    #[test]
+    fn test_postal_codes() {
+        assert!(is_us_zip("10001"));
+        assert!(is_us_zip("10001-1234"));
+        assert!(!is_us_zip("1000"));
+        assert!(is_uk_postcode("SW1A 1AA"));
+        assert!(is_uk_postcode("BN15 8AG"));
+        assert!(is_uk_postcode("EC1A1BB"));
+        assert!(!is_uk_postcode("sw1a 1aa"));
+        assert!(!is_uk_postcode("10001"));
+    }
+
+    #[test]
+    fn test_uuid_versions() {
+        assert!(is_uuid_v4("f47ac10b-58cc-4372-a567-0e02b2c3d479"));
+        assert!(!is_uuid_v7("f47ac10b-58cc-4372-a567-0e02b2c3d479"));
+        assert!(is_uuid_v7("01890a5d-ac96-774b-bcce-b302099a8057"));
+        assert!(is_uuid_v1("c232ab00-9414-11ec-b3c8-9f6bdeced846"));
+        assert!(!is_uuid_v4("f47ac10b58cc4372a5670e02b2c3d479"));
+    }
+
+    #[test]
+    fn test_coordinates() {
+        assert!(is_latitude("40.7128"));
+        assert!(is_latitude("-90"));
+        assert!(!is_latitude("90.0001"));
+        assert!(is_longitude("-74.0060"));
+        assert!(!is_longitude("180.5"));
+        assert!(!is_longitude("NaN"));
+        assert!(!is_longitude("1e2"));
+    }
+
+    #[test]
+    fn test_isbn_issn() {
+        assert!(is_isbn("0306406152"));
+        assert!(is_isbn("0-8044-2957-X"));
+        assert!(is_isbn("9780306406157"));
+        assert!(is_isbn("978-0-306-40615-7"));
+        assert!(!is_isbn("9780306406158"));
+        assert!(!is_isbn("0306406153"));
+        assert!(is_issn("0317-8471"));
+        assert!(is_issn("2049-3630"));
+        assert!(!is_issn("0317-8472"));
+    }
+
+    #[test]
+    fn test_format_detector() {
+        let mut detector = FormatDetector::default();
+        assert_eq!(detector.format(), None);
+        detector.update("+14155552671");
+        detector.update("+442079460958");
+        assert_eq!(detector.format(), Some("e164"));
+        detector.update("n/a");
+        assert!(detector.is_done());
+        assert_eq!(detector.format(), None);
+    }
+}
@@ -0,0 +1,76 @@
+#![cfg_attr(
+    clippy,
+    allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        // things are often more readable this way
+        clippy::needless_raw_string_hashes,
+        clippy::cast_lossless,
+        clippy::module_name_repetitions,
+        clippy::type_complexity,
+        clippy::zero_prefixed_literal,
+        // correctly used
+        clippy::enum_glob_use,
+        clippy::result_unit_err,
+        // not practical
+        clippy::similar_names,
+        clippy::too_many_lines,
+        clippy::struct_excessive_bools,
+        // preference
+        clippy::doc_markdown,
+        clippy::unnecessary_wraps,
+        // false positive
+        clippy::needless_doctest_main,
+        // noisy
+        clippy::missing_errors_doc,
+        clippy::use_self,
+        clippy::cognitive_complexity,
+        clippy::option_if_let_else,
+    ),
+    warn(
+        clippy::missing_asserts_for_indexing,
+    )
+)]
+#![cfg(feature = "lib")]
+
+//! qsv as a library: run a subset of qsv commands in-process, with typed options
+//! and generic readers/writers instead of command-line arguments and stdio.
+//!
+//! See the [`api`] module and docs/LIBRARY.md.
+
+extern crate qsv_docopt as docopt;
+
+use crate::clitypes::CURRENT_COMMAND;
+pub use crate::clitypes::{CliError, CliResult};
+
+// the support modules are shared with the qsv binaries. The items the library API
+// doesn't use are marked with `cfg_attr(feature = "lib", allow(dead_code))`
+mod clitypes;
+mod config;
+mod events;
+mod formats;
+mod index;
+mod lookup;
+mod odhtcache;
+mod projectconfig;
+mod rowrules;
+mod select;
+mod util;
+
+mod cmd {
+    // only used by util, to count rows with polars
+    #[cfg(feature = "polars")]
+    pub mod count;
+    pub mod dedup;
+    pub mod frequency;
+    // only used by config, to convert JSONL input
+    pub mod jsonl;
+    pub mod search;
+    pub mod select;
+    pub mod sort;
+    pub mod stats;
+    pub mod validate;
+}
+
+pub mod api;
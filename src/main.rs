@@ -41,7 +41,7 @@ use rand::Rng;
 use serde::Deserialize;
 
 use crate::{
-    clitypes::{CURRENT_COMMAND, CliError, CliResult, QsvExitCode},
+    clitypes::{CURRENT_COMMAND, CliError, CliResult, ErrorCode, QsvExitCode},
     config::SPONSOR_MESSAGE,
 };
 
@@ -56,9 +56,13 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 mod clitypes;
 mod cmd;
 mod config;
+mod events;
+mod formats;
 mod index;
 mod lookup;
 mod odhtcache;
+mod projectconfig;
+mod rowrules;
 mod select;
 mod util;
 
@@ -86,9 +90,8 @@ struct Args {
     flag_updatenow: bool,
 }
 
-fn main() -> QsvExitCode {
-    util::qsv_custom_panic();
-
+/// the list of commands enabled in this build, with their descriptions
+fn enabled_commands() -> String {
     let mut enabled_commands = String::new();
     #[cfg(all(feature = "apply", feature = "feature_capable"))]
     enabled_commands.push_str("    apply       Apply series of transformations to a column\n");
@@ -103,7 +106,8 @@ fn main() -> QsvExitCode {
         .push_str("    clipboard   Provide input from clipboard or output to clipboard\n");
 
     enabled_commands.push_str(
-        "    count       Count records
+        "    completions Generate shell completion scripts
+    count       Count records
     datefmt     Format date/datetime strings
     dedup       Remove redundant rows
     describegpt Infer extended metadata using a LLM
@@ -125,6 +129,7 @@ fn main() -> QsvExitCode {
 
     enabled_commands.push_str(
         "    fill        Fill empty values
+    fixedwidth  Convert fixed-width text files to CSV
     fixlengths  Makes all records have same length
     flatten     Show one field per line
     fmt         Format CSV output (change field delimiter)\n",
@@ -161,7 +166,10 @@ fn main() -> QsvExitCode {
     #[cfg(all(feature = "luau", feature = "feature_capable"))]
     enabled_commands.push_str("    luau        Execute Luau script on CSV data\n");
 
-    enabled_commands.push_str("    partition   Partition CSV data based on a column value\n");
+    enabled_commands.push_str(
+        "    partition   Partition CSV data based on a column value
+    pipeline    Run a pipeline of qsv commands from a YAML/TOML/JSON spec\n",
+    );
 
     #[cfg(all(feature = "polars", feature = "feature_capable"))]
     enabled_commands.push_str("    pivotp      Pivots CSV files using the Pola.rs engine\n");
@@ -171,13 +179,17 @@ fn main() -> QsvExitCode {
     #[cfg(all(feature = "prompt", feature = "feature_capable"))]
     enabled_commands.push_str("    prompt      Open a file dialog to pick a file\n");
 
-    enabled_commands.push_str("    pseudo      Pseudonymise the values of a column\n");
+    enabled_commands.push_str(
+        "    prune       Drop empty, sparse & constant columns
+    pseudo      Pseudonymise the values of a column\n",
+    );
 
     #[cfg(all(feature = "python", feature = "feature_capable"))]
     enabled_commands.push_str("    py          Evaluate a Python expression on CSV data\n");
 
     enabled_commands.push_str(
         "    rename      Rename the columns of CSV data efficiently
+    repair      Repair broken quoting, stray CRs & split rows
     replace     Replace patterns in CSV data
     reverse     Reverse rows of CSV data
     safenames   Modify a CSV's header names to db-safe names
@@ -185,8 +197,14 @@ fn main() -> QsvExitCode {
     schema      Generate JSON Schema from CSV data
     search      Search CSV data with a regex
     searchset   Search CSV data with a regex set
-    select      Select, re-order, duplicate or drop columns
-    slice       Slice records from CSV
+    select      Select, re-order, duplicate or drop columns\n",
+    );
+
+    #[cfg(all(feature = "serve", feature = "feature_capable"))]
+    enabled_commands.push_str("    serve       Run a local HTTP server exposing qsv commands\n");
+
+    enabled_commands.push_str(
+        "    slice       Slice records from CSV
     snappy      Compress/decompress data using the Snappy algorithm
     sniff       Quickly sniff CSV metadata
     sort        Sort CSV data in alphabetical, numerical, reverse or random order
@@ -214,6 +232,13 @@ fn main() -> QsvExitCode {
         "    transpose   Transpose rows/columns of CSV data
     validate    Validate CSV data for RFC4180-compliance or with JSON Schema",
     );
+    enabled_commands
+}
+
+fn main() -> QsvExitCode {
+    util::qsv_custom_panic();
+
+    let enabled_commands = enabled_commands();
     let num_commands = enabled_commands.split('\n').count();
 
     let now = Instant::now();
@@ -270,65 +295,79 @@ fn main() -> QsvExitCode {
             util::log_end(qsv_args, now);
             QsvExitCode::Good
         },
-        Some(cmd) => match cmd.run() {
-            Ok(()) => {
-                util::log_end(qsv_args, now);
-                QsvExitCode::Good
-            },
-            Err(CliError::Help(usage_text)) => {
-                wout!("{usage_text}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Good
-            },
-            Err(CliError::Flag(err)) => {
-                werr!("{err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::IncorrectUsage
-            },
-            Err(CliError::IncorrectUsage(err)) => {
-                werr!("usage error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::IncorrectUsage
-            },
-            Err(CliError::Csv(err)) => {
-                werr!("csv error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
-                wwarn!("broken pipe warning");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Warning
-            },
-            Err(CliError::Io(err)) => {
-                werr!("io error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::NoMatch()) => {
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Other(msg)) => {
-                werr!("{msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Network(msg)) => {
-                werr!("network error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::NetworkError
-            },
-            Err(CliError::OutOfMemory(msg)) => {
-                werr!("out of memory error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::OutOfMemory
-            },
-            Err(CliError::Encoding(msg)) => {
-                werr!("encoding error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::EncodingError
-            },
+        Some(cmd) => {
+            events::emit_run_start();
+            let result = cmd.run();
+            events::emit_run_end(&result, now);
+            match result {
+                Ok(()) => {
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Good
+                },
+                Err(CliError::Help(usage_text)) => {
+                    wout!("{usage_text}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Good
+                },
+                Err(err)
+                    if clitypes::json_error_format()
+                        && !matches!(err, CliError::NoMatch())
+                        && err.code() != ErrorCode::BrokenPipe =>
+                {
+                    werr!("{}", err.to_json());
+                    util::log_end(qsv_args, now);
+                    err.exit_code()
+                },
+                Err(CliError::Flag(err)) => {
+                    werr!("{err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::IncorrectUsage
+                },
+                Err(CliError::IncorrectUsage(err)) => {
+                    werr!("usage error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::IncorrectUsage
+                },
+                Err(CliError::Csv(err)) => {
+                    werr!("csv error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
+                    wwarn!("broken pipe warning");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Warning
+                },
+                Err(CliError::Io(err)) => {
+                    werr!("io error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::NoMatch()) => {
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Other(msg)) => {
+                    werr!("{msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Network(msg)) => {
+                    werr!("network error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::NetworkError
+                },
+                Err(CliError::OutOfMemory(msg)) => {
+                    werr!("out of memory error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::OutOfMemory
+                },
+                Err(CliError::Encoding(msg)) => {
+                    werr!("encoding error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::EncodingError
+                },
+            }
         },
     }
 }
@@ -342,6 +381,7 @@ enum Command {
     Cat,
     #[cfg(all(feature = "clipboard", feature = "feature_capable"))]
     Clipboard,
+    Completions,
     Count,
     Datefmt,
     Dedup,
@@ -359,6 +399,7 @@ enum Command {
     #[cfg(all(feature = "fetch", feature = "feature_capable"))]
     FetchPost,
     Fill,
+    FixedWidth,
     FixLengths,
     Flatten,
     Fmt,
@@ -381,15 +422,18 @@ enum Command {
     #[cfg(all(feature = "luau", feature = "feature_capable"))]
     Luau,
     Partition,
+    Pipeline,
     #[cfg(all(feature = "polars", feature = "feature_capable"))]
     PivotP,
     Pro,
     #[cfg(all(feature = "prompt", feature = "feature_capable"))]
     Prompt,
+    Prune,
     Pseudo,
     #[cfg(all(feature = "python", feature = "feature_capable"))]
     Py,
     Rename,
+    Repair,
     Replace,
     Reverse,
     Safenames,
@@ -398,6 +442,8 @@ enum Command {
     Search,
     SearchSet,
     Select,
+    #[cfg(all(feature = "serve", feature = "feature_capable"))]
+    Serve,
     Slice,
     Snappy,
     Sniff,
@@ -420,8 +466,20 @@ impl Command {
     fn run(self) -> CliResult<()> {
         let argv: Vec<_> = env::args().collect();
         let argv: Vec<_> = argv.iter().map(|s| &**s).collect();
-        let argv = &*argv;
+        self.run_with_argv(&argv)
+    }
+
+    /// look up a command by its name, as used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Command> {
+        Command::deserialize(
+            serde::de::value::StrDeserializer::<serde::de::value::Error>::new(name),
+        )
+        .ok()
+    }
 
+    /// run the command with the given arguments, where argv[0] is the program name
+    /// and argv[1] is the command name. Used by the `pipeline` command to dispatch steps.
+    pub(crate) fn run_with_argv(self, argv: &[&str]) -> CliResult<()> {
         assert!(argv.len() > 1);
         if !argv[1].chars().all(char::is_lowercase) {
             return fail_incorrectusage_clierror!(
@@ -438,6 +496,7 @@ impl Command {
             Command::Cat => cmd::cat::run(argv),
             #[cfg(all(feature = "clipboard", feature = "feature_capable"))]
             Command::Clipboard => cmd::clipboard::run(argv),
+            Command::Completions => cmd::completions::run(argv, &enabled_commands()),
             Command::Count => cmd::count::run(argv),
             Command::Datefmt => cmd::datefmt::run(argv),
             Command::Dedup => cmd::dedup::run(argv),
@@ -457,6 +516,7 @@ impl Command {
             #[cfg(all(feature = "foreach", not(feature = "lite")))]
             Command::ForEach => cmd::foreach::run(argv),
             Command::Fill => cmd::fill::run(argv),
+            Command::FixedWidth => cmd::fixedwidth::run(argv),
             Command::FixLengths => cmd::fixlengths::run(argv),
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
@@ -481,15 +541,18 @@ impl Command {
             #[cfg(all(feature = "luau", feature = "feature_capable"))]
             Command::Luau => cmd::luau::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Pipeline => cmd::pipeline::run(argv),
             #[cfg(all(feature = "polars", feature = "feature_capable"))]
             Command::PivotP => cmd::pivotp::run(argv),
             Command::Pro => cmd::pro::run(argv),
             #[cfg(all(feature = "prompt", feature = "feature_capable"))]
             Command::Prompt => cmd::prompt::run(argv),
+            Command::Prune => cmd::prune::run(argv),
             Command::Pseudo => cmd::pseudo::run(argv),
             #[cfg(all(feature = "python", feature = "feature_capable"))]
             Command::Py => cmd::python::run(argv),
             Command::Rename => cmd::rename::run(argv),
+            Command::Repair => cmd::repair::run(argv),
             Command::Replace => cmd::replace::run(argv),
             Command::Reverse => cmd::reverse::run(argv),
             Command::Safenames => cmd::safenames::run(argv),
@@ -498,6 +561,8 @@ impl Command {
             Command::Search => cmd::search::run(argv),
             Command::SearchSet => cmd::searchset::run(argv),
             Command::Select => cmd::select::run(argv),
+            #[cfg(all(feature = "serve", feature = "feature_capable"))]
+            Command::Serve => cmd::serve::run(argv),
             Command::Slice => cmd::slice::run(argv),
             Command::Snappy => cmd::snappy::run(argv),
             Command::Sniff => cmd::sniff::run(argv),
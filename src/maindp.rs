@@ -39,7 +39,7 @@ use docopt::Docopt;
 use serde::Deserialize;
 
 use crate::{
-    clitypes::{CURRENT_COMMAND, CliError, CliResult, QsvExitCode},
+    clitypes::{CURRENT_COMMAND, CliError, CliResult, ErrorCode, QsvExitCode},
     config::SPONSOR_MESSAGE,
 };
 
@@ -53,6 +53,7 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 static COMMAND_LIST: &str = r#"
     applydp     Apply series of transformations to a column
+    completions Generate shell completion scripts
     count       Count records
     datefmt     Format date/datetime strings
     describegpt Infer extended metadata using a LLM
@@ -93,9 +94,13 @@ static COMMAND_LIST: &str = r#"
 mod clitypes;
 mod cmd;
 mod config;
+mod events;
+mod formats;
 mod index;
 mod lookup;
 mod odhtcache;
+mod projectconfig;
+mod rowrules;
 mod select;
 mod util;
 
@@ -175,65 +180,79 @@ fn main() -> QsvExitCode {
             util::log_end(qsv_args, now);
             QsvExitCode::Good
         },
-        Some(cmd) => match cmd.run() {
-            Ok(()) => {
-                util::log_end(qsv_args, now);
-                QsvExitCode::Good
-            },
-            Err(CliError::Help(usage_text)) => {
-                wout!("{usage_text}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Good
-            },
-            Err(CliError::Flag(err)) => {
-                werr!("{err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::IncorrectUsage
-            },
-            Err(CliError::IncorrectUsage(err)) => {
-                werr!("usage error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::IncorrectUsage
-            },
-            Err(CliError::Csv(err)) => {
-                werr!("csv error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
-                wwarn!("broken pipe warning");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Warning
-            },
-            Err(CliError::Io(err)) => {
-                werr!("io error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::NoMatch()) => {
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Other(msg)) => {
-                werr!("{msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Network(msg)) => {
-                werr!("network error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::NetworkError
-            },
-            Err(CliError::OutOfMemory(msg)) => {
-                werr!("out of memory error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::OutOfMemory
-            },
-            Err(CliError::Encoding(msg)) => {
-                werr!("encoding error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::EncodingError
-            },
+        Some(cmd) => {
+            events::emit_run_start();
+            let result = cmd.run();
+            events::emit_run_end(&result, now);
+            match result {
+                Ok(()) => {
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Good
+                },
+                Err(CliError::Help(usage_text)) => {
+                    wout!("{usage_text}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Good
+                },
+                Err(err)
+                    if clitypes::json_error_format()
+                        && !matches!(err, CliError::NoMatch())
+                        && err.code() != ErrorCode::BrokenPipe =>
+                {
+                    werr!("{}", err.to_json());
+                    util::log_end(qsv_args, now);
+                    err.exit_code()
+                },
+                Err(CliError::Flag(err)) => {
+                    werr!("{err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::IncorrectUsage
+                },
+                Err(CliError::IncorrectUsage(err)) => {
+                    werr!("usage error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::IncorrectUsage
+                },
+                Err(CliError::Csv(err)) => {
+                    werr!("csv error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
+                    wwarn!("broken pipe warning");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Warning
+                },
+                Err(CliError::Io(err)) => {
+                    werr!("io error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::NoMatch()) => {
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Other(msg)) => {
+                    werr!("{msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Network(msg)) => {
+                    werr!("network error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::NetworkError
+                },
+                Err(CliError::OutOfMemory(msg)) => {
+                    werr!("out of memory error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::OutOfMemory
+                },
+                Err(CliError::Encoding(msg)) => {
+                    werr!("encoding error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::EncodingError
+                },
+            }
         },
     }
 }
@@ -242,6 +261,7 @@ fn main() -> QsvExitCode {
 #[serde(rename_all = "lowercase")]
 enum Command {
     ApplyDP,
+    Completions,
     Count,
     Datefmt,
     Dedup,
@@ -286,8 +306,19 @@ impl Command {
     fn run(self) -> CliResult<()> {
         let argv: Vec<_> = env::args().collect();
         let argv: Vec<_> = argv.iter().map(|s| &**s).collect();
-        let argv = &*argv;
+        self.run_with_argv(&argv)
+    }
+
+    /// look up a command by its name, as used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Command> {
+        Command::deserialize(
+            serde::de::value::StrDeserializer::<serde::de::value::Error>::new(name),
+        )
+        .ok()
+    }
 
+    /// run the command with the given arguments, where argv[0] is the program name
+    pub(crate) fn run_with_argv(self, argv: &[&str]) -> CliResult<()> {
         assert!(argv.len() > 1);
         if !argv[1].chars().all(char::is_lowercase) {
             return fail_incorrectusage_clierror!(
@@ -298,6 +329,7 @@ impl Command {
         CURRENT_COMMAND.get_or_init(|| argv[1].to_lowercase());
         match self {
             Command::ApplyDP => cmd::applydp::run(argv),
+            Command::Completions => cmd::completions::run(argv, COMMAND_LIST),
             Command::Count => cmd::count::run(argv),
             Command::Datefmt => cmd::datefmt::run(argv),
             Command::Dedup => cmd::dedup::run(argv),
@@ -6,7 +6,7 @@ use rand::Rng;
 use serde::Deserialize;
 
 use crate::{
-    clitypes::{CURRENT_COMMAND, CliError, CliResult, QsvExitCode},
+    clitypes::{CURRENT_COMMAND, CliError, CliResult, ErrorCode, QsvExitCode},
     config::SPONSOR_MESSAGE,
 };
 
@@ -21,6 +21,7 @@ static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 static COMMAND_LIST: &str = r#"
     behead      Drop header from CSV file
     cat         Concatenate by row or column
+    completions Generate shell completion scripts
     count       Count records
     datefmt     Format date/datetime columns
     dedup       Remove redundant rows
@@ -34,6 +35,7 @@ static COMMAND_LIST: &str = r#"
     extdedup    Remove duplicates rows from an arbitrarily large text file
     extsort     Sort arbitrarily large text file
     fill        Fill empty values
+    fixedwidth  Convert fixed-width text files to CSV
     fixlengths  Makes all records have same length
     flatten     Show one field per line
     fmt         Format CSV output (change field delimiter)
@@ -47,8 +49,10 @@ static COMMAND_LIST: &str = r#"
     jsonl       Convert newline-delimited JSON files to CSV
     partition   Partition CSV data based on a column value
     pro         Interact with the qsv pro API
+    prune       Drop empty, sparse & constant columns
     pseudo      Pseudonymise the values of a column
     rename      Rename the columns of CSV data efficiently
+    repair      Repair broken quoting, stray CRs & split rows
     replace     Replace patterns in CSV data
     reverse     Reverse rows of CSV data
     safenames   Modify a CSV's header names to db-safe names
@@ -72,8 +76,12 @@ static COMMAND_LIST: &str = r#"
 mod clitypes;
 mod cmd;
 mod config;
+mod events;
+mod formats;
 mod index;
 mod odhtcache;
+mod projectconfig;
+mod rowrules;
 mod select;
 mod util;
 
@@ -148,7 +156,7 @@ fn main() -> QsvExitCode {
         None => {
             werr!(
                 "qsvlite is a suite of CSV command line utilities.\n\nPlease choose one of the \
-                 following 49 commands:\n{COMMAND_LIST}\n\n{SPONSOR_MESSAGE}",
+                 following 50 commands:\n{COMMAND_LIST}\n\n{SPONSOR_MESSAGE}",
             );
 
             // if no command is specified, auto-check for updates 50% of the time
@@ -159,65 +167,79 @@ fn main() -> QsvExitCode {
             util::log_end(qsv_args, now);
             QsvExitCode::Good
         },
-        Some(cmd) => match cmd.run() {
-            Ok(()) => {
-                util::log_end(qsv_args, now);
-                QsvExitCode::Good
-            },
-            Err(CliError::Help(usage_text)) => {
-                wout!("{usage_text}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Good
-            },
-            Err(CliError::Flag(err)) => {
-                werr!("{err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::IncorrectUsage
-            },
-            Err(CliError::IncorrectUsage(err)) => {
-                werr!("usage error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::IncorrectUsage
-            },
-            Err(CliError::Csv(err)) => {
-                werr!("csv error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
-                wwarn!("broken pipe warning");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Warning
-            },
-            Err(CliError::Io(err)) => {
-                werr!("io error: {err}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::NoMatch()) => {
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Other(msg)) => {
-                werr!("{msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::Bad
-            },
-            Err(CliError::Network(msg)) => {
-                werr!("network error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::NetworkError
-            },
-            Err(CliError::OutOfMemory(msg)) => {
-                werr!("out of memory error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::OutOfMemory
-            },
-            Err(CliError::Encoding(msg)) => {
-                werr!("encoding error: {msg}");
-                util::log_end(qsv_args, now);
-                QsvExitCode::EncodingError
-            },
+        Some(cmd) => {
+            events::emit_run_start();
+            let result = cmd.run();
+            events::emit_run_end(&result, now);
+            match result {
+                Ok(()) => {
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Good
+                },
+                Err(CliError::Help(usage_text)) => {
+                    wout!("{usage_text}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Good
+                },
+                Err(err)
+                    if clitypes::json_error_format()
+                        && !matches!(err, CliError::NoMatch())
+                        && err.code() != ErrorCode::BrokenPipe =>
+                {
+                    werr!("{}", err.to_json());
+                    util::log_end(qsv_args, now);
+                    err.exit_code()
+                },
+                Err(CliError::Flag(err)) => {
+                    werr!("{err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::IncorrectUsage
+                },
+                Err(CliError::IncorrectUsage(err)) => {
+                    werr!("usage error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::IncorrectUsage
+                },
+                Err(CliError::Csv(err)) => {
+                    werr!("csv error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
+                    wwarn!("broken pipe warning");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Warning
+                },
+                Err(CliError::Io(err)) => {
+                    werr!("io error: {err}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::NoMatch()) => {
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Other(msg)) => {
+                    werr!("{msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::Bad
+                },
+                Err(CliError::Network(msg)) => {
+                    werr!("network error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::NetworkError
+                },
+                Err(CliError::OutOfMemory(msg)) => {
+                    werr!("out of memory error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::OutOfMemory
+                },
+                Err(CliError::Encoding(msg)) => {
+                    werr!("encoding error: {msg}");
+                    util::log_end(qsv_args, now);
+                    QsvExitCode::EncodingError
+                },
+            }
         },
     }
 }
@@ -227,6 +249,7 @@ fn main() -> QsvExitCode {
 enum Command {
     Behead,
     Cat,
+    Completions,
     Count,
     Datefmt,
     Dedup,
@@ -240,6 +263,7 @@ enum Command {
     ExtDedup,
     ExtSort,
     Fill,
+    FixedWidth,
     FixLengths,
     Flatten,
     Fmt,
@@ -253,8 +277,10 @@ enum Command {
     Jsonl,
     Partition,
     Pro,
+    Prune,
     Pseudo,
     Rename,
+    Repair,
     Replace,
     Reverse,
     Safenames,
@@ -280,8 +306,19 @@ impl Command {
     fn run(self) -> CliResult<()> {
         let argv: Vec<_> = env::args().collect();
         let argv: Vec<_> = argv.iter().map(|s| &**s).collect();
-        let argv = &*argv;
+        self.run_with_argv(&argv)
+    }
+
+    /// look up a command by its name, as used on the command line
+    pub(crate) fn from_name(name: &str) -> Option<Command> {
+        Command::deserialize(
+            serde::de::value::StrDeserializer::<serde::de::value::Error>::new(name),
+        )
+        .ok()
+    }
 
+    /// run the command with the given arguments, where argv[0] is the program name
+    pub(crate) fn run_with_argv(self, argv: &[&str]) -> CliResult<()> {
         assert!(argv.len() > 1);
         if !argv[1].chars().all(char::is_lowercase) {
             return fail_incorrectusage_clierror!(
@@ -293,6 +330,7 @@ impl Command {
         match self {
             Command::Behead => cmd::behead::run(argv),
             Command::Cat => cmd::cat::run(argv),
+            Command::Completions => cmd::completions::run(argv, COMMAND_LIST),
             Command::Count => cmd::count::run(argv),
             Command::Datefmt => cmd::datefmt::run(argv),
             Command::Dedup => cmd::dedup::run(argv),
@@ -306,6 +344,7 @@ impl Command {
             Command::ExtDedup => cmd::extdedup::run(argv),
             Command::ExtSort => cmd::extsort::run(argv),
             Command::Fill => cmd::fill::run(argv),
+            Command::FixedWidth => cmd::fixedwidth::run(argv),
             Command::FixLengths => cmd::fixlengths::run(argv),
             Command::Flatten => cmd::flatten::run(argv),
             Command::Fmt => cmd::fmt::run(argv),
@@ -323,8 +362,10 @@ impl Command {
             Command::Jsonl => cmd::jsonl::run(argv),
             Command::Partition => cmd::partition::run(argv),
             Command::Pro => cmd::pro::run(argv),
+            Command::Prune => cmd::prune::run(argv),
             Command::Pseudo => cmd::pseudo::run(argv),
             Command::Rename => cmd::rename::run(argv),
+            Command::Repair => cmd::repair::run(argv),
             Command::Replace => cmd::replace::run(argv),
             Command::Reverse => cmd::reverse::run(argv),
             Command::Safenames => cmd::safenames::run(argv),
@@ -0,0 +1,300 @@
+// Project-level configuration with the `qsv.toml` file.
+//
+// qsv looks for a `qsv.toml` file in the current working directory and its ancestors
+// (the first one found is used) and uses it to supply default options to commands.
+// Explicitly specified options always take precedence over the qsv.toml defaults.
+//
+// For details, see https://github.com/dathere/qsv/blob/master/docs/QSV_TOML.md
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{CliResult, config::get_delim_by_extension, util};
+
+pub const QSV_TOML_FILENAME: &str = "qsv.toml";
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Settings {
+    cache_dir: Option<String>,
+    jobs:      Option<u64>,
+    #[serde(default)]
+    commands:  BTreeMap<String, BTreeMap<String, toml::Value>>,
+    #[serde(default)]
+    profiles:  BTreeMap<String, Settings>,
+}
+
+impl Settings {
+    /// overlay a profile's settings on top of the base settings
+    fn merge(&mut self, profile: Settings) {
+        if profile.cache_dir.is_some() {
+            self.cache_dir = profile.cache_dir;
+        }
+        if profile.jobs.is_some() {
+            self.jobs = profile.jobs;
+        }
+        for (command, options) in profile.commands {
+            self.commands.entry(command).or_default().extend(options);
+        }
+    }
+}
+
+/// an option documented in a command's USAGE text
+struct UsageOption {
+    short:       Option<char>,
+    takes_value: bool,
+}
+
+/// find the qsv.toml file to use, if any. None if QSV_SKIP_QSV_TOML is set.
+/// QSV_TOML_PATH takes precedence. Otherwise, look in the current directory and its ancestors.
+fn find_qsv_toml() -> Option<PathBuf> {
+    if util::get_envvar_flag("QSV_SKIP_QSV_TOML") {
+        return None;
+    }
+    if let Ok(path) = std::env::var("QSV_TOML_PATH") {
+        return Some(PathBuf::from(path));
+    }
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(QSV_TOML_FILENAME))
+        .find(|path| path.is_file())
+}
+
+fn load_settings(path: &Path) -> CliResult<Settings> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => return fail_clierror!("Cannot read {}: {e}", path.display()),
+    };
+    let settings: Settings = match toml::from_str(&contents) {
+        Ok(settings) => settings,
+        Err(e) => return fail_clierror!("Invalid {}: {e}", path.display()),
+    };
+    for (name, profile) in &settings.profiles {
+        if !profile.profiles.is_empty() {
+            return fail_clierror!(
+                "Invalid {}: profile \"{name}\" cannot have nested profiles.",
+                path.display()
+            );
+        }
+    }
+    Ok(settings)
+}
+
+/// parse the options documented in a command's USAGE text, keyed by their long name
+fn usage_options(usage: &str) -> BTreeMap<String, UsageOption> {
+    static OPTION_RE: OnceLock<Regex> = OnceLock::new();
+    // safety: the regex is valid
+    let option_re = OPTION_RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:-([[:alnum:]]),\s*)?--([[:alnum:]][[:alnum:]_-]*)([ =]<)?").unwrap()
+    });
+
+    usage
+        .lines()
+        .filter_map(|line| option_re.captures(line))
+        .map(|caps| {
+            (
+                caps[2].to_string(),
+                UsageOption {
+                    short:       caps.get(1).and_then(|m| m.as_str().chars().next()),
+                    takes_value: caps.get(3).is_some(),
+                },
+            )
+        })
+        .collect()
+}
+
+/// resolve a long option name as docopt does, which also accepts unambiguous
+/// abbreviations (e.g. `--delim` for `--delimiter`)
+fn resolve_long<'a>(name: &'a str, options: &'a BTreeMap<String, UsageOption>) -> &'a str {
+    if options.contains_key(name) {
+        return name;
+    }
+    let mut candidates = options.keys().filter(|long| long.starts_with(name));
+    match (candidates.next(), candidates.next()) {
+        (Some(long), None) => long.as_str(),
+        _ => name,
+    }
+}
+
+/// the options explicitly set in the command line arguments, by their long name,
+/// and the positional arguments
+fn parse_args<'a>(
+    args: &'a [String],
+    options: &BTreeMap<String, UsageOption>,
+) -> (BTreeSet<String>, Vec<&'a str>) {
+    let mut set = BTreeSet::new();
+    let mut positionals = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positionals.extend(args.map(String::as_str));
+            break;
+        }
+        if let Some(name) = arg.strip_prefix("--") {
+            let (name, has_value) = name
+                .split_once('=')
+                .map_or((name, false), |(n, _)| (n, true));
+            let long = resolve_long(name, options);
+            if !has_value && options.get(long).is_some_and(|o| o.takes_value) {
+                // skip the option's value
+                args.next();
+            }
+            set.insert(long.to_string());
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            // short options may be bundled (e.g. -nq) or have an attached value (e.g. -d;)
+            for (i, c) in arg.char_indices().skip(1) {
+                let Some((long, option)) = options.iter().find(|(_, o)| o.short == Some(c)) else {
+                    continue;
+                };
+                set.insert(long.clone());
+                if option.takes_value {
+                    // the rest of the arg is the option's value, or the next arg if there's none
+                    if i + c.len_utf8() == arg.len() {
+                        args.next();
+                    }
+                    break;
+                }
+            }
+        } else {
+            positionals.push(arg.as_str());
+        }
+    }
+    (set, positionals)
+}
+
+/// Apply the qsv.toml defaults & the selected profile to a command's arguments.
+///
+/// The profile is selected with the `--profile <name>` option (which is removed from the
+/// returned arguments) or the QSV_PROFILE environment variable.
+/// The `cache-dir` & `jobs` settings are only applied to commands that support the
+/// corresponding option. Options that are explicitly set are never overridden, and a
+/// `delimiter` command option is not applied to inputs whose extension sets the delimiter.
+pub fn apply_qsv_toml(usage: &str, argv: &[&str]) -> CliResult<Vec<String>> {
+    let mut args: Vec<String> = argv.iter().map(|arg| (*arg).to_string()).collect();
+
+    // extract --profile, which is not part of any command's USAGE text
+    let mut profile = None;
+    let mut i = 2;
+    while i < args.len() && args[i] != "--" {
+        if args[i] == "--profile" {
+            if i + 1 >= args.len() {
+                return fail_incorrectusage_clierror!("--profile requires a profile name.");
+            }
+            profile = Some(args.remove(i + 1));
+            args.remove(i);
+        } else if let Some(name) = args[i].strip_prefix("--profile=") {
+            profile = Some(name.to_string());
+            args.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    if profile.is_none() {
+        profile = std::env::var("QSV_PROFILE").ok().filter(|p| !p.is_empty());
+    }
+
+    let Some(qsv_toml_path) = find_qsv_toml() else {
+        if let Some(profile) = profile {
+            return fail_incorrectusage_clierror!(
+                "Profile \"{profile}\" specified, but no {QSV_TOML_FILENAME} file was found."
+            );
+        }
+        return Ok(args);
+    };
+    log::info!("Using {}", qsv_toml_path.display());
+
+    let mut settings = load_settings(&qsv_toml_path)?;
+    if let Some(profile) = profile {
+        let Some(profile_settings) = settings.profiles.remove(&profile) else {
+            let available = settings
+                .profiles
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            return fail_incorrectusage_clierror!(
+                "Profile \"{profile}\" not found in {}. Available profiles: {available}",
+                qsv_toml_path.display()
+            );
+        };
+        log::info!("Using profile \"{profile}\"");
+        settings.merge(profile_settings);
+    }
+
+    // help is always shown as is, as there's nothing to apply the defaults to
+    if args.len() < 2 || args[2..].iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(args);
+    }
+    let command = args[1].to_lowercase();
+    let options = usage_options(usage);
+
+    // the global settings are only used by commands that support them
+    let mut defaults: Vec<(String, toml::Value)> = Vec::new();
+    if let Some(cache_dir) = settings.cache_dir {
+        defaults.push(("cache-dir".to_string(), toml::Value::String(cache_dir)));
+    }
+    if let Some(jobs) = settings.jobs {
+        defaults.push(("jobs".to_string(), toml::Value::String(jobs.to_string())));
+    }
+    defaults.retain(|(long, _)| options.contains_key(long));
+
+    // command options are always used, so unknown options are reported by the command
+    if let Some(command_options) = settings.commands.remove(&command) {
+        for (long, value) in command_options {
+            let long = long.trim_start_matches('-').to_string();
+            defaults.retain(|(l, _)| *l != long);
+            defaults.push((long, value));
+        }
+    }
+
+    let (set_options, positionals) = parse_args(&args[2..], &options);
+    // .csv, .tsv, .tab & .ssv inputs set their own delimiter
+    let delimiter_by_extension = positionals
+        .iter()
+        .any(|arg| get_delim_by_extension(Path::new(arg), 0).1 != 0);
+    let mut new_args = Vec::new();
+    for (long, value) in defaults {
+        if set_options.contains(&long) {
+            continue;
+        }
+        if long == "delimiter" && delimiter_by_extension {
+            log::debug!("{QSV_TOML_FILENAME} delimiter not used, as the input sets it");
+            continue;
+        }
+        let takes_value = options.get(&long).is_some_and(|o| o.takes_value);
+        let value = match value {
+            // options that take a value are set to "true" or "false"
+            toml::Value::Boolean(b) if takes_value => Some(b.to_string()),
+            toml::Value::Boolean(false) => continue,
+            toml::Value::Boolean(true) => None,
+            toml::Value::String(s) => Some(s),
+            toml::Value::Integer(n) => Some(n.to_string()),
+            toml::Value::Float(f) => Some(f.to_string()),
+            _ => {
+                return fail_clierror!(
+                    "Invalid {}: option \"{long}\" of command \"{command}\" must be a boolean, \
+                     string or number.",
+                    qsv_toml_path.display()
+                );
+            },
+        };
+        log::debug!("{QSV_TOML_FILENAME} default: --{long} {value:?}");
+        new_args.push(format!("--{long}"));
+        new_args.extend(value);
+    }
+
+    // insert the defaults before "--", if present
+    let insert_at = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    args.splice(insert_at..insert_at, new_args);
+
+    Ok(args)
+}
@@ -0,0 +1,628 @@
+// A small expression language for row-level rules over multiple columns,
+// used by the `rowRules` keyword of the `validate` command.
+//
+// Expressions support:
+//  * column references - bare names (e.g. `status`) or backtick-quoted (e.g. `closed date`)
+//  * literals - numbers, 'single' or "double" quoted strings, true, false & null
+//  * comparisons - ==, !=, <, <=, > & >=. Values are compared as numbers if both sides are numbers,
+//    as dates if both sides are dates, and as strings otherwise
+//  * arithmetic - +, -, *, / & %
+//  * logical operators - && (and), || (or) & ! (not)
+//  * functions - empty(x), len(x), lower(x), upper(x), trim(x), number(x), date(x),
+//    days_between(from, to), in(x, a, b, ...) & matches(x, 'regex')
+//
+// Empty fields are null. Comparisons with null are false, except for == & != against null.
+use std::{borrow::Cow, cmp::Ordering};
+
+use chrono::{DateTime, Utc};
+use csv::ByteRecord;
+use qsv_dateparser::parse_with_preference;
+use regex::Regex;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Func {
+    Empty,
+    Len,
+    Lower,
+    Upper,
+    Trim,
+    Number,
+    Date,
+    DaysBetween,
+    In,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value<'static>),
+    Column(usize),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CmpOp, Box<Expr>, Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+    Matches(Box<Expr>, Regex),
+}
+
+#[derive(Debug, Clone)]
+enum Value<'a> {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(Cow<'a, str>),
+    Date(DateTime<Utc>),
+}
+
+/// a named row-level rule. If the optional condition is true (or there is no condition),
+/// the assertion must be true for the row to be valid.
+#[derive(Debug)]
+pub struct RowRule {
+    pub name:    String,
+    pub message: String,
+    condition:   Option<Expr>,
+    assertion:   Expr,
+    prefer_dmy:  bool,
+}
+
+impl RowRule {
+    /// compile a rule, resolving column references against the CSV headers
+    pub fn new(
+        name: &str,
+        condition: Option<&str>,
+        assertion: &str,
+        message: Option<&str>,
+        headers: &ByteRecord,
+        prefer_dmy: bool,
+    ) -> Result<Self, String> {
+        let condition = match condition {
+            Some(expr) => Some(parse(expr, headers).map_err(|e| format!("'if' {e}"))?),
+            None => None,
+        };
+        let message =
+            message.map_or_else(|| format!("row rule failed: {assertion}"), str::to_string);
+        let assertion = parse(assertion, headers).map_err(|e| format!("'assert' {e}"))?;
+        Ok(Self {
+            name: name.to_string(),
+            message,
+            condition,
+            assertion,
+            prefer_dmy,
+        })
+    }
+
+    /// returns true if the record satisfies the rule
+    pub fn check(&self, record: &ByteRecord) -> bool {
+        let applies = self
+            .condition
+            .as_ref()
+            .is_none_or(|condition| eval(condition, record, self.prefer_dmy).is_truthy());
+        !applies || eval(&self.assertion, record, self.prefer_dmy).is_truthy()
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            },
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            },
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            },
+            '\'' | '"' | '`' => {
+                let quote = c;
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(format!("unterminated {quote} quote")),
+                        // a doubled quote is an escaped quote
+                        Some(&q) if q == quote && chars.get(i + 1) == Some(&quote) => {
+                            s.push(quote);
+                            i += 2;
+                        },
+                        Some(&q) if q == quote => {
+                            i += 1;
+                            break;
+                        },
+                        Some(&ch) => {
+                            s.push(ch);
+                            i += 1;
+                        },
+                    }
+                }
+                tokens.push(if quote == '`' {
+                    Token::Ident(s)
+                } else {
+                    Token::Str(s)
+                });
+            },
+            c if c.is_ascii_digit()
+                || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) =>
+            {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let num: String = chars[start..i].iter().collect();
+                tokens.push(Token::Num(
+                    num.parse::<f64>()
+                        .map_err(|_| format!("invalid number '{num}'"))?,
+                ));
+            },
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            },
+            _ => {
+                let two: String = chars[i..chars.len().min(i + 2)].iter().collect();
+                let op = match two.as_str() {
+                    "==" => "==",
+                    "!=" => "!=",
+                    "<=" => "<=",
+                    ">=" => ">=",
+                    "&&" => "&&",
+                    "||" => "||",
+                    _ => match c {
+                        '<' => "<",
+                        '>' => ">",
+                        '!' => "!",
+                        '+' => "+",
+                        '-' => "-",
+                        '*' => "*",
+                        '/' => "/",
+                        '%' => "%",
+                        '=' => "==",
+                        _ => return Err(format!("unexpected character '{c}'")),
+                    },
+                };
+                i += if op.len() == 2 && two == op { 2 } else { 1 };
+                tokens.push(Token::Op(op));
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens:  Vec<Token>,
+    pos:     usize,
+    headers: &'a ByteRecord,
+}
+
+fn parse(input: &str, headers: &ByteRecord) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        headers,
+    };
+    let expr = parser.or()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {token:?} in expression: {input}"));
+    }
+    Ok(expr)
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // consume the next token if it's one of the given operators or keywords
+    fn eat_op(&mut self, ops: &[&str]) -> Option<&'static str> {
+        let op = match self.peek()? {
+            Token::Op(op) if ops.contains(op) => *op,
+            Token::Ident(kw) if ops.contains(&kw.to_ascii_lowercase().as_str()) => {
+                match kw.to_ascii_lowercase().as_str() {
+                    "and" => "and",
+                    "or" => "or",
+                    _ => "not",
+                }
+            },
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(op)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.and()?;
+        while self.eat_op(&["||", "or"]).is_some() {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.not()?;
+        while self.eat_op(&["&&", "and"]).is_some() {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.not()?));
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat_op(&["!", "not"]).is_some() {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.sum()?;
+        let op = match self.eat_op(&["==", "!=", "<", "<=", ">", ">="]) {
+            Some("==") => CmpOp::Eq,
+            Some("!=") => CmpOp::Ne,
+            Some("<") => CmpOp::Lt,
+            Some("<=") => CmpOp::Le,
+            Some(">") => CmpOp::Gt,
+            Some(">=") => CmpOp::Ge,
+            _ => return Ok(lhs),
+        };
+        Ok(Expr::Compare(op, Box::new(lhs), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.product()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = if op == "+" {
+                ArithOp::Add
+            } else {
+                ArithOp::Sub
+            };
+            lhs = Expr::Arith(op, Box::new(lhs), Box::new(self.product()?));
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => ArithOp::Mul,
+                "/" => ArithOp::Div,
+                _ => ArithOp::Rem,
+            };
+            lhs = Expr::Arith(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op(&["-"]).is_some() {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Literal(Value::Num(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::Str(Cow::Owned(s)))),
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("missing ')'".to_string()),
+                }
+            },
+            Some(Token::Ident(name)) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    return self.call(&name);
+                }
+                match name.as_str() {
+                    "true" => return Ok(Expr::Literal(Value::Bool(true))),
+                    "false" => return Ok(Expr::Literal(Value::Bool(false))),
+                    "null" => return Ok(Expr::Literal(Value::Null)),
+                    _ => {},
+                }
+                match self.headers.iter().position(|h| h == name.as_bytes()) {
+                    Some(idx) => Ok(Expr::Column(idx)),
+                    None => Err(format!("unknown column '{name}'")),
+                }
+            },
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+        } else {
+            loop {
+                args.push(self.or()?);
+                match self.next() {
+                    Some(Token::Comma) => {},
+                    Some(Token::RParen) => break,
+                    _ => return Err(format!("missing ')' in {name}()")),
+                }
+            }
+        }
+
+        let (func, arity) = match name.to_ascii_lowercase().as_str() {
+            "empty" => (Func::Empty, 1),
+            "len" => (Func::Len, 1),
+            "lower" => (Func::Lower, 1),
+            "upper" => (Func::Upper, 1),
+            "trim" => (Func::Trim, 1),
+            "number" => (Func::Number, 1),
+            "date" => (Func::Date, 1),
+            "days_between" => (Func::DaysBetween, 2),
+            "in" if args.len() >= 2 => (Func::In, args.len()),
+            "in" => return Err("in() needs a value and at least one option".to_string()),
+            "matches" => {
+                let Some(Expr::Literal(Value::Str(pattern))) = args.get(1) else {
+                    return Err("matches() needs a value and a regex string".to_string());
+                };
+                let regex = Regex::new(pattern).map_err(|e| format!("invalid regex: {e}"))?;
+                let value = args.swap_remove(0);
+                return Ok(Expr::Matches(Box::new(value), regex));
+            },
+            _ => return Err(format!("unknown function '{name}'")),
+        };
+        if args.len() != arity {
+            return Err(format!("{name}() takes {arity} argument(s)"));
+        }
+        Ok(Expr::Call(func, args))
+    }
+}
+
+impl Value<'_> {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Num(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+            Value::Date(_) => true,
+        }
+    }
+
+    fn as_num(&self) -> Option<f64> {
+        match self {
+            Value::Num(n) => Some(*n),
+            Value::Str(s) => fast_float2::parse(s.trim()).ok(),
+            Value::Bool(b) => Some(f64::from(u8::from(*b))),
+            Value::Null | Value::Date(_) => None,
+        }
+    }
+
+    fn as_date(&self, prefer_dmy: bool) -> Option<DateTime<Utc>> {
+        match self {
+            Value::Date(d) => Some(*d),
+            Value::Str(s) => parse_with_preference(s.trim(), prefer_dmy).ok(),
+            _ => None,
+        }
+    }
+
+    fn to_text(&self) -> Cow<'_, str> {
+        match self {
+            Value::Null => Cow::Borrowed(""),
+            Value::Bool(b) => Cow::Owned(b.to_string()),
+            Value::Num(n) => Cow::Owned(n.to_string()),
+            Value::Str(s) => Cow::Borrowed(s),
+            Value::Date(d) => Cow::Owned(d.to_rfc3339()),
+        }
+    }
+}
+
+fn compare(lhs: &Value, rhs: &Value, prefer_dmy: bool) -> Option<Ordering> {
+    if let (Value::Bool(a), Value::Bool(b)) = (lhs, rhs) {
+        return Some(a.cmp(b));
+    }
+    if let (Some(a), Some(b)) = (lhs.as_num(), rhs.as_num()) {
+        return a.partial_cmp(&b);
+    }
+    if let (Some(a), Some(b)) = (lhs.as_date(prefer_dmy), rhs.as_date(prefer_dmy)) {
+        return Some(a.cmp(&b));
+    }
+    Some(lhs.to_text().cmp(&rhs.to_text()))
+}
+
+fn eval<'a>(expr: &'a Expr, record: &'a ByteRecord, prefer_dmy: bool) -> Value<'a> {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Column(idx) => match record.get(*idx) {
+            None | Some(b"") => Value::Null,
+            Some(field) => Value::Str(String::from_utf8_lossy(field)),
+        },
+        Expr::Not(e) => Value::Bool(!eval(e, record, prefer_dmy).is_truthy()),
+        Expr::Neg(e) => eval(e, record, prefer_dmy)
+            .as_num()
+            .map_or(Value::Null, |n| Value::Num(-n)),
+        Expr::And(a, b) => Value::Bool(
+            eval(a, record, prefer_dmy).is_truthy() && eval(b, record, prefer_dmy).is_truthy(),
+        ),
+        Expr::Or(a, b) => Value::Bool(
+            eval(a, record, prefer_dmy).is_truthy() || eval(b, record, prefer_dmy).is_truthy(),
+        ),
+        Expr::Compare(op, a, b) => {
+            let (lhs, rhs) = (eval(a, record, prefer_dmy), eval(b, record, prefer_dmy));
+            let result = match (&lhs, &rhs, op) {
+                (Value::Null, Value::Null, CmpOp::Eq) => true,
+                (Value::Null, Value::Null, CmpOp::Ne) => false,
+                (Value::Null, _, CmpOp::Ne) | (_, Value::Null, CmpOp::Ne) => true,
+                (Value::Null, _, _) | (_, Value::Null, _) => false,
+                _ => compare(&lhs, &rhs, prefer_dmy).is_some_and(|ordering| match op {
+                    CmpOp::Eq => ordering == Ordering::Equal,
+                    CmpOp::Ne => ordering != Ordering::Equal,
+                    CmpOp::Lt => ordering == Ordering::Less,
+                    CmpOp::Le => ordering != Ordering::Greater,
+                    CmpOp::Gt => ordering == Ordering::Greater,
+                    CmpOp::Ge => ordering != Ordering::Less,
+                }),
+            };
+            Value::Bool(result)
+        },
+        Expr::Arith(op, a, b) => {
+            let (lhs, rhs) = (eval(a, record, prefer_dmy), eval(b, record, prefer_dmy));
+            let (Some(x), Some(y)) = (lhs.as_num(), rhs.as_num()) else {
+                return Value::Null;
+            };
+            let result = match op {
+                ArithOp::Add => x + y,
+                ArithOp::Sub => x - y,
+                ArithOp::Mul => x * y,
+                ArithOp::Div if y == 0.0 => return Value::Null,
+                ArithOp::Div => x / y,
+                ArithOp::Rem if y == 0.0 => return Value::Null,
+                ArithOp::Rem => x % y,
+            };
+            Value::Num(result)
+        },
+        Expr::Matches(e, regex) => match eval(e, record, prefer_dmy) {
+            Value::Null => Value::Bool(false),
+            value => Value::Bool(regex.is_match(&value.to_text())),
+        },
+        Expr::Call(func, args) => {
+            let arg = |i: usize| eval(&args[i], record, prefer_dmy);
+            match func {
+                Func::Empty => Value::Bool(matches!(arg(0), Value::Null)),
+                Func::Len => match arg(0) {
+                    Value::Null => Value::Num(0.0),
+                    value => Value::Num(value.to_text().chars().count() as f64),
+                },
+                Func::Lower => match arg(0) {
+                    Value::Null => Value::Null,
+                    value => Value::Str(Cow::Owned(value.to_text().to_lowercase())),
+                },
+                Func::Upper => match arg(0) {
+                    Value::Null => Value::Null,
+                    value => Value::Str(Cow::Owned(value.to_text().to_uppercase())),
+                },
+                Func::Trim => match arg(0) {
+                    Value::Null => Value::Null,
+                    value => Value::Str(Cow::Owned(value.to_text().trim().to_string())),
+                },
+                Func::Number => arg(0).as_num().map_or(Value::Null, Value::Num),
+                Func::Date => arg(0).as_date(prefer_dmy).map_or(Value::Null, Value::Date),
+                Func::DaysBetween => {
+                    match (arg(0).as_date(prefer_dmy), arg(1).as_date(prefer_dmy)) {
+                        (Some(from), Some(to)) => Value::Num((to - from).num_days() as f64),
+                        _ => Value::Null,
+                    }
+                },
+                Func::In => {
+                    let value = arg(0);
+                    Value::Bool((1..args.len()).any(|i| {
+                        let option = arg(i);
+                        !matches!(value, Value::Null)
+                            && compare(&value, &option, prefer_dmy) == Some(Ordering::Equal)
+                    }))
+                },
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(condition: Option<&str>, assertion: &str, row: &[&str]) -> bool {
+        let headers = ByteRecord::from(vec!["status", "opened", "closed", "qty", "price"]);
+        let rule = RowRule::new("test", condition, assertion, None, &headers, false).unwrap();
+        rule.check(&ByteRecord::from(row.to_vec()))
+    }
+
+    #[test]
+    fn test_conditional_date_rule() {
+        let cond = Some("status == 'closed'");
+        let rule = "!empty(closed) && closed >= opened";
+        assert!(check(
+            cond,
+            rule,
+            &["closed", "2024-01-01", "2024-01-05", "", ""]
+        ));
+        assert!(!check(
+            cond,
+            rule,
+            &["closed", "2024-01-05", "2024-01-01", "", ""]
+        ));
+        assert!(!check(cond, rule, &["closed", "2024-01-05", "", "", ""]));
+        assert!(check(cond, rule, &["open", "2024-01-05", "", "", ""]));
+    }
+
+    #[test]
+    fn test_numbers_and_functions() {
+        let row = ["open", "2024-01-01", "2024-03-01", "10", "2.5"];
+        assert!(check(None, "qty * price == 25", &row));
+        assert!(check(None, "qty > 9 and price < 3", &row));
+        // numeric, not string, comparison
+        assert!(check(None, "qty > 9.5", &row));
+        assert!(check(None, "days_between(opened, closed) == 60", &row));
+        assert!(check(None, "in(status, 'open', 'closed')", &row));
+        assert!(check(None, "matches(opened, '^\\d{4}-')", &row));
+        assert!(check(
+            None,
+            "upper(status) == 'OPEN' || len(status) > 10",
+            &row
+        ));
+        assert!(!check(None, "not (qty >= 10)", &row));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let headers = ByteRecord::from(vec!["a"]);
+        assert!(RowRule::new("r", None, "b == 1", None, &headers, false).is_err());
+        assert!(RowRule::new("r", None, "a == (1", None, &headers, false).is_err());
+        assert!(RowRule::new("r", None, "nope(a)", None, &headers, false).is_err());
+        assert!(RowRule::new("r", None, "a == 'x", None, &headers, false).is_err());
+    }
+}
@@ -10,14 +10,23 @@ use foldhash::HashSet;
 use regex::bytes::Regex;
 use serde::de::{Deserialize, Deserializer, Error};
 
+use crate::cmd::stats::StatsData;
+
+// the data types inferred by `qsv stats`, lowercased
+const STATS_TYPES: [&str; 7] = [
+    "null", "integer", "float", "string", "date", "datetime", "boolean",
+];
+
 #[derive(Clone)]
 pub struct SelectColumns {
     selectors: Vec<Selector>,
     invert:    bool,
+    raw:       String,
 }
 
 impl SelectColumns {
     pub fn parse(mut s: &str) -> Result<SelectColumns, String> {
+        let raw = s.to_string();
         let is_empty = s.is_empty();
         let bytes = s.as_bytes();
         let invert = if !is_empty && bytes[0] == b'!' {
@@ -29,6 +38,7 @@ impl SelectColumns {
         Ok(SelectColumns {
             selectors: SelectorParser::new(s).parse()?,
             invert,
+            raw,
         })
     }
 
@@ -36,6 +46,37 @@ impl SelectColumns {
         &self,
         first_record: &csv::ByteRecord,
         use_names: bool,
+    ) -> Result<Selection, String> {
+        self.select_indices(first_record, use_names, None)
+    }
+
+    /// Same as `selection`, but also resolves stats-based selectors (e.g. `type:Integer`)
+    /// using `csv_stats`, the cached stats of each column in `first_record` order.
+    pub fn selection_with_stats(
+        &self,
+        first_record: &csv::ByteRecord,
+        use_names: bool,
+        csv_stats: &[StatsData],
+    ) -> Result<Selection, String> {
+        self.select_indices(first_record, use_names, Some(csv_stats))
+    }
+
+    /// Returns true if any of the selectors needs the stats cache to be resolved,
+    /// i.e. it is a stats-based selector that is not also a header name.
+    pub fn needs_stats(&self, first_record: &csv::ByteRecord, use_names: bool) -> bool {
+        self.selectors.iter().any(|sel| match *sel {
+            Selector::Stats(ref field, Ok(_)) => {
+                stats_field_as_name(first_record, use_names, field).is_none()
+            },
+            _ => false,
+        })
+    }
+
+    fn select_indices(
+        &self,
+        first_record: &csv::ByteRecord,
+        use_names: bool,
+        csv_stats: Option<&[StatsData]>,
     ) -> Result<Selection, String> {
         if self.selectors.is_empty() {
             return Ok(Selection(if self.invert {
@@ -48,7 +89,7 @@ impl SelectColumns {
 
         let mut map = vec![];
         for sel in &self.selectors {
-            let idxs = sel.indices(first_record, use_names);
+            let idxs = sel.indices(first_record, use_names, csv_stats);
             map.extend(idxs?);
         }
         if self.invert {
@@ -68,6 +109,12 @@ impl SelectColumns {
     pub fn is_empty(&self) -> bool {
         self.selectors.is_empty()
     }
+
+    /// the selection as originally specified
+    #[allow(dead_code)]
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
 }
 
 impl fmt::Debug for SelectColumns {
@@ -122,6 +169,15 @@ impl SelectorParser {
             if self.cur().is_none() {
                 break;
             }
+            // stats-based selectors (e.g. type:Integer, nullcount>0) span the whole field.
+            // Their errors are only reported if no header has the field as its name.
+            let field = self.peek_field();
+            if let Some(pred) = StatsPredicate::parse(&field) {
+                self.pos += field.chars().count();
+                self.bump();
+                sels.push(Selector::Stats(field, pred));
+                continue;
+            }
             let f1: OneSelector = if self.cur() == Some('-') {
                 OneSelector::Start
             } else {
@@ -236,6 +292,13 @@ impl SelectorParser {
             .map_err(|err| format!("Could not convert '{idx}' to an integer: {err}"))
     }
 
+    fn peek_field(&self) -> String {
+        self.chars[self.pos..]
+            .iter()
+            .take_while(|&&c| c != ',')
+            .collect()
+    }
+
     fn cur(&self) -> Option<char> {
         self.chars.get(self.pos).copied()
     }
@@ -260,6 +323,8 @@ enum Selector {
     One(OneSelector),
     Range(OneSelector, OneSelector),
     Regex(Regex),
+    // the selector as given, and the stats predicate parsed from it
+    Stats(String, Result<StatsPredicate, String>),
 }
 
 #[derive(Clone)]
@@ -270,11 +335,116 @@ enum OneSelector {
     IndexedName(String, usize),
 }
 
+#[derive(Clone, Debug)]
+enum StatsPredicate {
+    Type(Vec<String>),
+    Metric(StatsMetric, Comparison, f64),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum StatsMetric {
+    Nullcount,
+    Sparsity,
+    Cardinality,
+    UniquenessRatio,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Comparison {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl StatsPredicate {
+    /// Parses a stats-based selector, e.g. `type:Float|Integer`, `nulls>0.5` or
+    /// `cardinality=1`. Returns None if `s` is not a stats-based selector, so it can
+    /// be parsed as a regular selector instead.
+    fn parse(s: &str) -> Option<Result<StatsPredicate, String>> {
+        if let Some(types) = s.strip_prefix("type:") {
+            let types: Vec<String> = types
+                .split('|')
+                .map(|t| t.trim().to_ascii_lowercase())
+                .collect();
+            if let Some(invalid) = types.iter().find(|t| !STATS_TYPES.contains(&t.as_str())) {
+                return Some(fail_format!(
+                    "Invalid type '{invalid}' in selector '{s}'. Valid types are: NULL, Integer, \
+                     Float, String, Date, DateTime and Boolean."
+                ));
+            }
+            return Some(Ok(StatsPredicate::Type(types)));
+        }
+
+        let op_start = s.find(['=', '!', '<', '>'])?;
+        let metric = match &s[..op_start] {
+            "nullcount" => StatsMetric::Nullcount,
+            "nulls" | "sparsity" => StatsMetric::Sparsity,
+            "cardinality" => StatsMetric::Cardinality,
+            "uniqueness_ratio" => StatsMetric::UniquenessRatio,
+            _ => return None,
+        };
+        let op_expr = &s[op_start..];
+        let (cmp, value) = if let Some(v) = op_expr.strip_prefix(">=") {
+            (Comparison::Ge, v)
+        } else if let Some(v) = op_expr.strip_prefix("<=") {
+            (Comparison::Le, v)
+        } else if let Some(v) = op_expr.strip_prefix("!=") {
+            (Comparison::Ne, v)
+        } else if let Some(v) = op_expr.strip_prefix("==") {
+            (Comparison::Eq, v)
+        } else if let Some(v) = op_expr.strip_prefix('=') {
+            (Comparison::Eq, v)
+        } else if let Some(v) = op_expr.strip_prefix('>') {
+            (Comparison::Gt, v)
+        } else if let Some(v) = op_expr.strip_prefix('<') {
+            (Comparison::Lt, v)
+        } else {
+            return Some(fail_format!(
+                "Invalid comparison operator in selector '{s}'."
+            ));
+        };
+        let Ok(value) = value.trim().parse::<f64>() else {
+            return Some(fail_format!(
+                "Could not convert '{value}' in selector '{s}' to a number."
+            ));
+        };
+        Some(Ok(StatsPredicate::Metric(metric, cmp, value)))
+    }
+
+    fn is_match(&self, stats: &StatsData) -> bool {
+        match *self {
+            StatsPredicate::Type(ref types) => {
+                types.iter().any(|t| t.eq_ignore_ascii_case(&stats.r#type))
+            },
+            StatsPredicate::Metric(metric, cmp, value) => {
+                let actual = match metric {
+                    StatsMetric::Nullcount => stats.nullcount as f64,
+                    StatsMetric::Sparsity => stats.sparsity.unwrap_or_default(),
+                    StatsMetric::Cardinality => stats.cardinality as f64,
+                    StatsMetric::UniquenessRatio => stats.uniqueness_ratio.unwrap_or_default(),
+                };
+                match cmp {
+                    Comparison::Eq => actual == value,
+                    Comparison::Ne => actual != value,
+                    Comparison::Gt => actual > value,
+                    Comparison::Ge => actual >= value,
+                    Comparison::Lt => actual < value,
+                    Comparison::Le => actual <= value,
+                }
+            },
+        }
+    }
+}
+
 impl Selector {
     fn indices(
         &self,
         first_record: &csv::ByteRecord,
         use_names: bool,
+        csv_stats: Option<&[StatsData]>,
     ) -> Result<Vec<usize>, String> {
         match *self {
             Selector::One(ref sel) => sel.index(first_record, use_names).map(|i| vec![i]),
@@ -309,10 +479,49 @@ impl Selector {
                 }
                 Ok(inds)
             },
+            Selector::Stats(ref field, ref pred) => {
+                if let Some(i) = stats_field_as_name(first_record, use_names, field) {
+                    return Ok(vec![i]);
+                }
+                let pred = pred.as_ref().map_err(Clone::clone)?;
+                let Some(csv_stats) = csv_stats else {
+                    return fail_format!(
+                        "Stats selector '{pred}' requires the stats cache of an input file."
+                    );
+                };
+                let inds: Vec<usize> = csv_stats
+                    .iter()
+                    .take(first_record.len())
+                    .enumerate()
+                    .filter(|(_, stats)| pred.is_match(stats))
+                    .map(|(i, _)| i)
+                    .collect();
+                if inds.is_empty() {
+                    return fail_format!(
+                        "Stats selector '{pred}' does not match any columns in the CSV."
+                    );
+                }
+                Ok(inds)
+            },
         }
     }
 }
 
+/// Returns the index of the first header named exactly like the stats-based selector `field`,
+/// so columns like `type:id` can still be selected by name.
+fn stats_field_as_name(
+    first_record: &csv::ByteRecord,
+    use_names: bool,
+    field: &str,
+) -> Option<usize> {
+    if !use_names {
+        return None;
+    }
+    first_record
+        .iter()
+        .position(|name| name == field.as_bytes())
+}
+
 impl OneSelector {
     fn index(&self, first_record: &csv::ByteRecord, use_names: bool) -> Result<usize, String> {
         match *self {
@@ -374,6 +583,46 @@ impl fmt::Debug for Selector {
             Selector::One(ref sel) => sel.fmt(f),
             Selector::Range(ref s, ref e) => write!(f, "Range({s:?}, {e:?})"),
             Selector::Regex(ref re) => re.fmt(f),
+            Selector::Stats(ref field, _) => write!(f, "Stats({field})"),
+        }
+    }
+}
+
+impl fmt::Display for StatsPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StatsPredicate::Type(ref types) => {
+                let types: Vec<&str> = types
+                    .iter()
+                    .map(|t| match t.as_str() {
+                        "null" => "NULL",
+                        "integer" => "Integer",
+                        "float" => "Float",
+                        "string" => "String",
+                        "date" => "Date",
+                        "datetime" => "DateTime",
+                        _ => "Boolean",
+                    })
+                    .collect();
+                write!(f, "type:{}", types.join("|"))
+            },
+            StatsPredicate::Metric(metric, cmp, value) => {
+                let metric = match metric {
+                    StatsMetric::Nullcount => "nullcount",
+                    StatsMetric::Sparsity => "sparsity",
+                    StatsMetric::Cardinality => "cardinality",
+                    StatsMetric::UniquenessRatio => "uniqueness_ratio",
+                };
+                let cmp = match cmp {
+                    Comparison::Eq => "=",
+                    Comparison::Ne => "!=",
+                    Comparison::Gt => ">",
+                    Comparison::Ge => ">=",
+                    Comparison::Lt => "<",
+                    Comparison::Le => "<=",
+                };
+                write!(f, "{metric}{cmp}{value}")
+            },
         }
     }
 }
@@ -12,7 +12,7 @@ use std::{
     io::{BufRead, BufReader, BufWriter, Read, Write},
     path::{Path, PathBuf},
     str,
-    sync::OnceLock,
+    sync::{OnceLock, RwLock},
     time::SystemTime,
 };
 
@@ -55,7 +55,8 @@ const DEFAULT_BATCH_SIZE: usize = 50_000;
 
 const DEFAULT_STATSCACHE_MODE: &str = "auto";
 
-static ROW_COUNT: OnceLock<Option<u64>> = OnceLock::new();
+// the row count of the input, cached for the current command run
+static ROW_COUNT: RwLock<Option<u64>> = RwLock::new(None);
 
 static JOBS_TO_USE: OnceLock<usize> = OnceLock::new();
 
@@ -83,6 +84,8 @@ pub struct SchemaArgs {
     pub flag_prefer_dmy:      bool,
     pub flag_force:           bool,
     pub flag_stdout:          bool,
+    pub flag_format:          Option<String>,
+    pub flag_detect_formats:  bool,
     pub flag_jobs:            Option<usize>,
     pub flag_no_headers:      bool,
     pub flag_delimiter:       Option<Delimiter>,
@@ -192,6 +195,7 @@ pub fn visualize_whitespace(s: &str) -> String {
     result
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn qsv_custom_panic() {
     setup_panic!(
         human_panic::Metadata::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
@@ -420,8 +424,10 @@ pub fn version() -> String {
     }
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 const OTHER_ENV_VARS: &[&str] = &["all_proxy", "no_proxy", "http_proxy", "https_proxy"];
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn show_env_vars() {
     let mut env_var_set = false;
     for (n, v) in env::vars_os() {
@@ -461,11 +467,31 @@ pub fn show_env_vars() {
     }
 }
 
+/// reset the state cached during a command run, so another command can be run
+/// in the same process (used by the `pipeline` command & the library API)
+#[allow(dead_code)]
+pub fn reset_run_state() {
+    if let Ok(mut row_count) = ROW_COUNT.write() {
+        *row_count = None;
+    }
+    crate::clitypes::reset_error_context();
+}
+
+fn cached_row_count() -> Option<u64> {
+    ROW_COUNT.read().ok().and_then(|row_count| *row_count)
+}
+
+fn cache_row_count(count: u64) {
+    if let Ok(mut row_count) = ROW_COUNT.write() {
+        *row_count = Some(count);
+    }
+}
+
 #[inline]
 pub fn count_rows(conf: &Config) -> Result<u64, CliError> {
     // Check if ROW_COUNT is already initialized to avoid redundant counting
-    if let Some(count) = ROW_COUNT.get() {
-        return Ok(count.unwrap_or(0));
+    if let Some(count) = cached_row_count() {
+        return Ok(count);
     }
 
     // If not, try using index if available
@@ -479,17 +505,17 @@ pub fn count_rows(conf: &Config) -> Result<u64, CliError> {
     // Do this only once per invocation and cache the result in ROW_COUNT,
     // so we don't have to re-count rows every time we need to know the
     // rowcount for CSVs that don't have an index.
-    ROW_COUNT
-        .get_or_init(|| {
-            // Try different counting methods in order of preference
-            count_rows_with_best_method(conf)
-        })
-        .ok_or_else(|| CliError::Other("Unable to get row count".to_string()))
+    // Try different counting methods in order of preference
+    let count = count_rows_with_best_method(conf)
+        .ok_or_else(|| CliError::Other("Unable to get row count".to_string()))?;
+    cache_row_count(count);
+    Ok(count)
 }
 
 #[cfg(feature = "polars")]
 fn count_rows_with_best_method(conf: &Config) -> Option<u64> {
-    if !conf.no_headers {
+    // polars can only count CSV files directly
+    if !conf.no_headers && conf.input_format() == config::InputFormat::Csv {
         // Try polars first for files with headers
         if let Ok(polars_count) = polars_count_input(conf, false) {
             // If count is greater than 0, return the polars accelerated count
@@ -529,28 +555,21 @@ fn count_with_csv_reader(conf: &Config) -> Option<u64> {
 /// Count rows using "regular" CSV reader
 /// we don't use polars mem-mapped reader here
 /// even if it's available
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[inline]
 pub fn count_rows_regular(conf: &Config) -> Result<u64, CliError> {
     if let Some(idx) = conf.indexed().unwrap_or(None) {
         Ok(idx.count())
     } else {
         // index does not exist or is stale,
-        let count_opt =
-            ROW_COUNT.get_or_init(|| match conf.clone().skip_format_check(true).reader() {
-                Ok(mut rdr) => {
-                    let mut count = 0_u64;
-                    let mut _record = csv::ByteRecord::new();
-                    #[allow(clippy::used_underscore_binding)]
-                    while rdr.read_byte_record(&mut _record).unwrap_or_default() {
-                        count += 1;
-                    }
-                    Some(count)
-                },
-                _ => None,
-            });
-
-        match *count_opt {
-            Some(count) => Ok(count),
+        if let Some(count) = cached_row_count() {
+            return Ok(count);
+        }
+        match count_with_csv_reader(conf) {
+            Some(count) => {
+                cache_row_count(count);
+                Ok(count)
+            },
             None => Err(CliError::Other("Unable to get row count".to_string())),
         }
     }
@@ -577,6 +596,12 @@ pub fn prep_progress(progress: &ProgressBar, record_count: u64) {
     // draw progress bar for the first time using specified style
     progress.set_length(record_count);
 
+    // with NDJSON progress events, events are emitted instead of drawing the progress bar
+    if crate::events::enabled() {
+        progress.set_draw_target(ProgressDrawTarget::hidden());
+        crate::events::start_progress_events(progress);
+    }
+
     log::info!("Progress started... {record_count} records");
 }
 
@@ -588,6 +613,8 @@ pub fn finish_progress(progress: &ProgressBar) {
             .unwrap(),
     );
 
+    crate::events::finish_progress_events(progress);
+
     if progress.length().unwrap_or_default() == progress.position() {
         progress.finish();
         log::info!("Progress done... {}", progress.message());
@@ -612,6 +639,13 @@ macro_rules! update_cache_info {
                     #[allow(clippy::cast_precision_loss)]
                     let hit_ratio = (hits as f64 / (hits + misses) as f64) * 100.0;
                     let capacity = cache.cache_capacity();
+                    crate::events::set_cache_stats(crate::events::CacheStats {
+                        hits,
+                        misses: Some(misses),
+                        hit_ratio,
+                        entries: Some(size as u64),
+                        capacity: capacity.map(|c| c as u64),
+                    });
                     $progress.set_message(format!(
                         " of {} records. Cache {:.2}% entries: {} capacity: {}.",
                         HumanCount($progress.length().unwrap()),
@@ -629,6 +663,13 @@ macro_rules! update_cache_info {
 
         #[allow(clippy::cast_precision_loss)]
         let hit_ratio = ($cache_hits as f64 / $num_rows as f64) * 100.0;
+        crate::events::set_cache_stats(crate::events::CacheStats {
+            hits: $cache_hits as u64,
+            misses: None,
+            hit_ratio,
+            entries: None,
+            capacity: None,
+        });
         $progress.set_message(format!(
             " of {} records. Cache hit ratio: {hit_ratio:.2}%",
             HumanCount($progress.length().unwrap()),
@@ -643,15 +684,23 @@ pub fn get_args<T>(usage: &str, argv: &[&str]) -> CliResult<T>
 where
     T: DeserializeOwned,
 {
+    // apply the qsv.toml defaults, if any. Explicitly set options take precedence.
+    // The library API runs commands with the options it's given, so they're not applied.
+    let argv = if config::stdio_piped() {
+        argv.iter().map(|arg| (*arg).to_string()).collect()
+    } else {
+        crate::projectconfig::apply_qsv_toml(usage, argv)?
+    };
     Docopt::new(usage)
         .and_then(|d| {
-            d.argv(argv.iter().copied())
+            d.argv(argv.iter().map(String::as_str))
                 .version(Some(version()))
                 .deserialize()
         })
         .map_err(From::from)
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[inline]
 pub fn many_configs(
     inps: &[PathBuf],
@@ -679,6 +728,7 @@ pub fn many_configs(
     Ok(confs)
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn errif_greater_one_stdin(inps: &[Config]) -> Result<(), String> {
     let nstd = inps.iter().filter(|inp| inp.is_stdin()).count();
     if nstd > 1 {
@@ -914,6 +964,7 @@ impl<'de> Deserialize<'de> for FilenameTemplate {
     }
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn init_logger() -> CliResult<(String, flexi_logger::LoggerHandle)> {
     use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
 
@@ -957,6 +1008,7 @@ pub fn init_logger() -> CliResult<(String, flexi_logger::LoggerHandle)> {
     }
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[cfg(feature = "self_update")]
 pub fn qsv_check_for_update(check_only: bool, no_confirm: bool) -> Result<bool, String> {
     use self_update::cargo_crate_version;
@@ -1065,6 +1117,7 @@ Self-update only works with prebuilt binaries released on GitHub https://github.
     Ok(updated)
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[cfg(not(feature = "self_update"))]
 pub fn qsv_check_for_update(_check_only: bool, _no_confirm: bool) -> Result<bool, String> {
     Err("Self-update is disabled in this build.".to_string())
@@ -1172,6 +1225,7 @@ fn send_hwsurvey(
     }
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn safe_header_names(
     headers: &csv::StringRecord,
     check_first_char: bool,
@@ -1269,6 +1323,7 @@ pub fn safe_header_names(
     (name_vec, changed_count)
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[inline]
 pub fn is_safe_name(header_name: &str) -> bool {
     if header_name.trim().is_empty()
@@ -1285,6 +1340,7 @@ pub fn is_safe_name(header_name: &str) -> bool {
     safename_re.is_match(header_name)
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn log_end(mut qsv_args: String, now: std::time::Instant) {
     if log::log_enabled!(log::Level::Info) {
         let ellipsis = if qsv_args.len() > 24 {
@@ -1470,6 +1526,7 @@ pub fn round_num(dec_f64: f64, places: u32) -> String {
 /// # Returns
 ///
 /// * A `ByteString` (Vec<u8>) containing the transformed bytes
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn transform(bs: &[u8], casei: bool) -> ByteString {
     if let Ok(s) = simdutf8::basic::from_utf8(bs) {
         if casei {
@@ -1484,6 +1541,7 @@ pub fn transform(bs: &[u8], casei: bool) -> ByteString {
     }
 }
 
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn load_dotenv() -> CliResult<()> {
     // First, check if there is a QSV_DOTENV_PATH environment variable set
     // if there is, use that as the .env file.
@@ -1553,6 +1611,14 @@ pub fn load_dotenv() -> CliResult<()> {
     Ok(())
 }
 
+/// are progress bars or NDJSON progress events enabled with environment variables?
+/// (QSV_PROGRESSBAR or QSV_PROGRESS_FORMAT=ndjson)
+#[cfg_attr(feature = "lib", allow(dead_code))]
+#[inline]
+pub fn progress_envvar_enabled() -> bool {
+    get_envvar_flag("QSV_PROGRESSBAR") || crate::events::enabled()
+}
+
 #[inline]
 pub fn get_envvar_flag(key: &str) -> bool {
     if let Ok(tf_val) = std::env::var(key) {
@@ -1588,6 +1654,7 @@ pub fn decompress_snappy_file(
 /// if custom_user_agent is Some, it will be used as the user agent
 /// if download_timeout is Some, it will be used as the timeout in seconds
 /// if sample_size is Some, it will be used as the number of bytes to download
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub async fn download_file(
     url: &str,
     path: PathBuf,
@@ -1706,6 +1773,7 @@ pub fn to_lowercase_into(s: &str, buf: &mut String) {
 }
 
 /// load the first BUFFER*8 (1024k) bytes of the file and check if it is utf8
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn isutf8_file(path: &Path) -> Result<bool, CliError> {
     let metadata = std::fs::metadata(path)?;
     let buffer_len = config::DEFAULT_RDR_BUFFER_CAPACITY * 8;
@@ -1724,31 +1792,188 @@ pub fn isutf8_file(path: &Path) -> Result<bool, CliError> {
     Ok(simdutf8::basic::from_utf8(&buffer).is_ok())
 }
 
+/// detect the character encoding of a sample of bytes.
+///
+/// A BOM takes precedence. Otherwise, UTF-8 is preferred, followed by UTF-16 without a BOM
+/// (detected by the position of its NUL bytes) & Shift_JIS (if the sample is valid Shift_JIS
+/// with runs of multibyte characters). Finally, windows-1252 is returned, which WHATWG also
+/// uses for ISO-8859-1/Latin-1.
+#[cfg_attr(feature = "lib", allow(dead_code))]
+pub fn detect_encoding(sample: &[u8]) -> &'static encoding_rs::Encoding {
+    if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(sample) {
+        return encoding;
+    }
+
+    match simdutf8::compat::from_utf8(sample) {
+        // the sample may end in the middle of a multibyte sequence
+        Ok(_) => return encoding_rs::UTF_8,
+        Err(e) if e.error_len().is_none() => return encoding_rs::UTF_8,
+        Err(_) => {},
+    }
+
+    let (mut even_nuls, mut odd_nuls) = (0_usize, 0_usize);
+    for (i, _) in sample.iter().enumerate().filter(|(_, b)| **b == 0) {
+        if i % 2 == 0 {
+            even_nuls += 1;
+        } else {
+            odd_nuls += 1;
+        }
+    }
+    // ASCII characters in UTF-16LE have a NUL high byte at odd positions, and vice versa
+    let quarter_len = sample.len() / 4;
+    if odd_nuls > quarter_len && even_nuls < odd_nuls / 10 {
+        return encoding_rs::UTF_16LE;
+    }
+    if even_nuls > quarter_len && odd_nuls < even_nuls / 10 {
+        return encoding_rs::UTF_16BE;
+    }
+
+    // only check complete lines, so we don't end in the middle of a multibyte character
+    let complete_lines = sample
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(sample, |i| &sample[..=i]);
+    let (decoded, had_errors) = encoding_rs::SHIFT_JIS.decode_without_bom_handling(complete_lines);
+    if !had_errors {
+        // Latin text decoded as Shift_JIS mostly has isolated non-ASCII characters,
+        // while Japanese text has runs of them
+        let non_ascii: Vec<bool> = decoded.chars().map(|c| !c.is_ascii()).collect();
+        let non_ascii_count = non_ascii.iter().filter(|&&c| c).count();
+        let in_runs = (0..non_ascii.len())
+            .filter(|&i| {
+                non_ascii[i] && ((i > 0 && non_ascii[i - 1]) || non_ascii.get(i + 1) == Some(&true))
+            })
+            .count();
+        if non_ascii_count > 0 && in_runs * 2 > non_ascii_count {
+            return encoding_rs::SHIFT_JIS;
+        }
+    }
+
+    encoding_rs::WINDOWS_1252
+}
+
+/// A reader that transcodes its input from the given encoding to UTF-8.
+/// If the input starts with a BOM, the BOM is removed & its encoding is used instead.
+/// Malformed sequences are replaced with the U+FFFD (�) replacement character.
+pub struct TranscodingReader<R> {
+    rdr:       R,
+    decoder:   encoding_rs::Decoder,
+    inbuf:     Vec<u8>,
+    in_start:  usize,
+    in_end:    usize,
+    outbuf:    Vec<u8>,
+    out_start: usize,
+    out_end:   usize,
+    eof:       bool,
+    finished:  bool,
+}
+
+impl<R: Read> TranscodingReader<R> {
+    pub fn new(rdr: R, encoding: &'static encoding_rs::Encoding) -> Self {
+        Self {
+            rdr,
+            decoder: encoding.new_decoder(),
+            inbuf: vec![0; DEFAULT_RDR_BUFFER_CAPACITY],
+            in_start: 0,
+            in_end: 0,
+            outbuf: vec![0; DEFAULT_RDR_BUFFER_CAPACITY],
+            out_start: 0,
+            out_end: 0,
+            eof: false,
+            finished: false,
+        }
+    }
+}
+
+impl<R: Read> Read for TranscodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.out_start < self.out_end {
+                let n = min(buf.len(), self.out_end - self.out_start);
+                buf[..n].copy_from_slice(&self.outbuf[self.out_start..self.out_start + n]);
+                self.out_start += n;
+                return Ok(n);
+            }
+            if self.finished {
+                return Ok(0);
+            }
+            if self.in_start == self.in_end && !self.eof {
+                self.in_start = 0;
+                self.in_end = self.rdr.read(&mut self.inbuf)?;
+                self.eof = self.in_end == 0;
+            }
+            let (result, read, written, _) = self.decoder.decode_to_utf8(
+                &self.inbuf[self.in_start..self.in_end],
+                &mut self.outbuf,
+                self.eof,
+            );
+            self.in_start += read;
+            self.out_start = 0;
+            self.out_end = written;
+            if self.eof && result == encoding_rs::CoderResult::InputEmpty {
+                self.finished = true;
+            }
+        }
+    }
+}
+
 /// Process the input files and return a vector of paths to the input files.
 ///
 /// If the input is empty, try to copy stdin to a file named stdin in the passed temp directory.
 /// If the input is empty and stdin is empty, return an error.
 /// If it's not empty, check the input files if they exist, and return an error if they don't.
 ///
-/// If the input is a directory, add all the files in the directory to the input.
+/// Each input is expanded as follows:
+/// If the input is a directory, add all the files in the directory to the input, sorted by path.
+/// Hidden files & directories (names starting with a `.`) are skipped.
+/// Subdirectories are only traversed if QSV_INPUT_RECURSIVE is set.
+/// If the input does not exist and contains glob metacharacters (`*`, `?` or `[`), add all
+/// the files matching the glob pattern (e.g. `data/**/*.csv`) to the input, sorted by path.
 /// If the input is a file with the extension ".infile-list", read the file & add each line as a
 /// file to the input.
 /// If the input is a file, add the file to the input.
+/// Files found by expanding a directory or a glob pattern are filtered by the
+/// QSV_INPUT_INCLUDE and QSV_INPUT_EXCLUDE file name patterns.
 /// If the input are snappy compressed files, uncompress them before adding them to the input.
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn process_input(
     arg_input: Vec<PathBuf>,
     tmpdir: &tempfile::TempDir,
     custom_empty_stdin_errmsg: &str,
+) -> Result<Vec<PathBuf>, CliError> {
+    process_input_filtered(
+        arg_input,
+        tmpdir,
+        custom_empty_stdin_errmsg,
+        &InputFileFilter::new(None, None, None)?,
+    )
+}
+
+/// Same as `process_input`, but directories & glob patterns are expanded using `filter`,
+/// which a command can create from its own options.
+pub fn process_input_filtered(
+    arg_input: Vec<PathBuf>,
+    tmpdir: &tempfile::TempDir,
+    custom_empty_stdin_errmsg: &str,
+    filter: &InputFileFilter,
 ) -> Result<Vec<PathBuf>, CliError> {
     let mut processed_input = Vec::with_capacity(arg_input.len());
 
-    let work_input = if arg_input.len() == 1 {
-        let input_path = &arg_input[0];
+    let mut work_input = Vec::with_capacity(arg_input.len());
+    for input_path in arg_input {
         if input_path.is_dir() {
             // if the input is a directory, add all the files in the directory to the input
-            std::fs::read_dir(input_path)?
-                .map(|entry| entry.map(|e| e.path()))
-                .collect::<Result<Vec<_>, _>>()?
+            let recursive = filter.recursive;
+            let mut dir_files = Vec::new();
+            collect_dir_files(&input_path, recursive, &mut dir_files)?;
+            dir_files.retain(|path| filter.matches(path));
+            dir_files.sort_unstable();
+            log::info!(
+                "directory '{}' expanded to {} file/s. recursive: {recursive}",
+                input_path.display(),
+                dir_files.len()
+            );
+            work_input.extend(dir_files);
         } else if input_path.is_file() {
             // if the input is a file and has the extension "infile-list" case-insensitive,
             // read the file. Each line is a file path
@@ -1758,51 +1983,17 @@ pub fn process_input(
                 .map(str::to_lowercase)
                 == Some("infile-list".to_string())
             {
-                let mut input_file = std::fs::File::open(input_path)?;
-                let mut input_file_contents = String::new();
-                let mut canonical_invalid_path = PathBuf::new();
-                let mut invalid_files = 0_u32;
-                input_file.read_to_string(&mut input_file_contents)?;
-                let infile_list_vec = input_file_contents
-                    .lines()
-                    .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
-                    .map(PathBuf::from)
-                    .filter_map(|path| {
-                        if path.exists() {
-                            Some(path)
-                        } else {
-                            // note that we're warn logging if files do not exist for
-                            // each line in the infile-list file
-                            // even though we're returning an error on the FIRST file that
-                            // doesn't exist in the next section. This is because
-                            // we want to log ALL the invalid file paths in the infile-list
-                            // file, not just the first one.
-                            invalid_files += 1;
-                            canonical_invalid_path = path.canonicalize().unwrap_or_default();
-                            log::warn!(
-                                ".infile-list file '{}': '{}' does not exist",
-                                path.display(),
-                                canonical_invalid_path.display()
-                            );
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                log::info!(
-                    ".infile-list file parsed. Filecount - valid:{} invalid:{invalid_files}",
-                    infile_list_vec.len()
-                );
-                infile_list_vec
+                work_input.extend(read_infile_list(&input_path)?);
             } else {
                 // if the input is not an ".infile-list" file, add the file to the input
-                arg_input
+                work_input.push(input_path);
             }
+        } else if is_glob_pattern(&input_path) {
+            work_input.extend(expand_glob(&input_path, filter)?);
         } else {
-            arg_input
+            work_input.push(input_path);
         }
-    } else {
-        arg_input
-    };
+    }
 
     let mut stdin_path = PathBuf::new();
     let mut stdin_file_created = false;
@@ -1859,6 +2050,170 @@ pub fn process_input(
     Ok(processed_input)
 }
 
+/// Read an ".infile-list" file. Each non-empty line that doesn't start with `#` is a file path.
+/// Paths that do not exist are warn logged & skipped.
+fn read_infile_list(input_path: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let mut input_file = std::fs::File::open(input_path)?;
+    let mut input_file_contents = String::new();
+    let mut canonical_invalid_path = PathBuf::new();
+    let mut invalid_files = 0_u32;
+    input_file.read_to_string(&mut input_file_contents)?;
+    let infile_list_vec = input_file_contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .filter_map(|path| {
+            if path.exists() {
+                Some(path)
+            } else {
+                // note that we're warn logging if files do not exist for
+                // each line in the infile-list file
+                // even though we're returning an error on the FIRST file that
+                // doesn't exist in the next section. This is because
+                // we want to log ALL the invalid file paths in the infile-list
+                // file, not just the first one.
+                invalid_files += 1;
+                canonical_invalid_path = path.canonicalize().unwrap_or_default();
+                log::warn!(
+                    ".infile-list file '{}': '{}' does not exist",
+                    path.display(),
+                    canonical_invalid_path.display()
+                );
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    log::info!(
+        ".infile-list file parsed. Filecount - valid:{} invalid:{invalid_files}",
+        infile_list_vec.len()
+    );
+    Ok(infile_list_vec)
+}
+
+/// Collect the files in a directory, descending into subdirectories if `recursive` is set.
+/// Hidden files and directories (those starting with a `.`) are skipped.
+fn collect_dir_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> CliResult<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .and_then(std::ffi::OsStr::to_str)
+            .is_some_and(|name| name.starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            if recursive {
+                collect_dir_files(&path, recursive, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// check if an input argument names multiple files - a directory, a glob pattern
+/// or an `.infile-list` file - which `process_input` expands to the files they list.
+pub fn is_multi_file_input(input: &str) -> bool {
+    let input_path = Path::new(input);
+    input_path.is_dir()
+        || (!input_path.exists() && is_glob_pattern(input_path))
+        || input_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("infile-list"))
+}
+
+#[inline]
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_str().is_some_and(|p| p.contains(['*', '?', '[']))
+}
+
+/// Expand a glob pattern (e.g. `data/**/*.csv`) to the files it matches, sorted by path.
+/// Returns an error if the pattern is invalid or no file matches.
+fn expand_glob(pattern: &Path, filter: &InputFileFilter) -> Result<Vec<PathBuf>, CliError> {
+    let pattern_str = pattern.to_string_lossy();
+    let paths = match glob::glob(&pattern_str) {
+        Ok(paths) => paths,
+        Err(e) => {
+            return fail_incorrectusage_clierror!("Invalid glob pattern '{pattern_str}': {e}");
+        },
+    };
+    let mut matched = Vec::new();
+    for entry in paths {
+        let path = entry.map_err(|e| CliError::Other(format!("Cannot read glob match: {e}")))?;
+        if path.is_file() && filter.matches(&path) {
+            matched.push(path);
+        }
+    }
+    if matched.is_empty() {
+        return fail_clierror!("Glob pattern '{pattern_str}' did not match any files");
+    }
+    matched.sort_unstable();
+    log::info!(
+        "glob pattern '{pattern_str}' expanded to {} file/s",
+        matched.len()
+    );
+    Ok(matched)
+}
+
+/// File name filters for directory & glob expansion, set with comma-separated glob patterns
+/// (e.g. `*.csv,*.tsv`), and whether directories are traversed recursively.
+pub struct InputFileFilter {
+    include:   Vec<glob::Pattern>,
+    exclude:   Vec<glob::Pattern>,
+    recursive: bool,
+}
+
+impl InputFileFilter {
+    /// Creates the filter from a command's options. Options that are not set default to
+    /// the QSV_INPUT_INCLUDE, QSV_INPUT_EXCLUDE & QSV_INPUT_RECURSIVE environment variables.
+    pub fn new(
+        include: Option<&str>,
+        exclude: Option<&str>,
+        recursive: Option<bool>,
+    ) -> CliResult<Self> {
+        let include = include
+            .map(str::to_string)
+            .or_else(|| std::env::var("QSV_INPUT_INCLUDE").ok());
+        let exclude = exclude
+            .map(str::to_string)
+            .or_else(|| std::env::var("QSV_INPUT_EXCLUDE").ok());
+        Ok(Self {
+            include:   Self::parse_patterns("include", include.as_deref())?,
+            exclude:   Self::parse_patterns("exclude", exclude.as_deref())?,
+            recursive: recursive.unwrap_or_else(|| get_envvar_flag("QSV_INPUT_RECURSIVE")),
+        })
+    }
+
+    fn parse_patterns(kind: &str, value: Option<&str>) -> CliResult<Vec<glob::Pattern>> {
+        let Some(value) = value else {
+            return Ok(Vec::new());
+        };
+        let mut patterns = Vec::new();
+        for pattern in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match glob::Pattern::new(pattern) {
+                Ok(p) => patterns.push(p),
+                Err(e) => {
+                    return fail_incorrectusage_clierror!(
+                        "Invalid {kind} pattern '{pattern}': {e}"
+                    );
+                },
+            }
+        }
+        Ok(patterns)
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let Some(file_name) = path.file_name().and_then(std::ffi::OsStr::to_str) else {
+            return self.include.is_empty();
+        };
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(file_name)))
+            && !self.exclude.iter().any(|p| p.matches(file_name))
+    }
+}
+
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[inline]
 pub fn replace_column_value(
     record: &csv::StringRecord,
@@ -1873,6 +2228,7 @@ pub fn replace_column_value(
 }
 
 /// format a SystemTime from a file's metadata to a string using the format specifier
+#[cfg_attr(feature = "lib", allow(dead_code))]
 #[inline]
 pub fn format_systemtime(time: SystemTime, format_specifier: &str) -> String {
     // safety: we know the duration since UNIX EPOCH is always positive
@@ -1919,6 +2275,7 @@ pub fn create_json_writer(
 }
 
 /// iterate over the CSV ByteRecords and write them to the JSON file
+#[cfg_attr(feature = "lib", allow(dead_code))]
 pub fn write_json(
     output: Option<&String>,
     no_headers: bool,
@@ -2076,6 +2433,22 @@ pub fn write_json_record<W: std::io::Write>(
     Ok(write!(json_wtr, "}}")?)
 }
 
+/// returns true if the stats cache of the input file was created with `--infer-dates`
+/// and the same `--dates-whitelist`, per its <FILESTEM>.stats.csv.json file
+fn stats_cache_infers_dates(canonical_input_path: &Path, dates_whitelist: &str) -> bool {
+    let Ok(stats_args_json) = fs::read(canonical_input_path.with_extension("stats.csv.json"))
+    else {
+        return false;
+    };
+    let Ok(stats_args) = serde_json::from_slice::<serde_json::Value>(&stats_args_json) else {
+        return false;
+    };
+    stats_args["flag_infer_dates"].as_bool().unwrap_or_default()
+        && stats_args["flag_dates_whitelist"]
+            .as_str()
+            .is_some_and(|whitelist| whitelist.eq_ignore_ascii_case(dates_whitelist))
+}
+
 /// get stats records from stats.csv.data.jsonl file, or if its invalid, by running the stats
 /// command returns tuple (`csv_fields`, `csv_stats`, `stats_col_index_map`)
 pub fn get_stats_records(
@@ -2096,6 +2469,8 @@ pub fn get_stats_records(
 
     if requested_mode == StatsMode::None
         || env_mode == "none"
+        // the library API runs commands in-process, so it never spawns qsv to create the cache
+        || config::stdio_piped()
         || args.arg_input.is_none()
         || args.arg_input.as_ref() == Some(&"-".to_string())
     {
@@ -2126,6 +2501,12 @@ pub fn get_stats_records(
         false
     };
 
+    // schemas & stats-based selectors need the Date & DateTime types, which a stats cache
+    // created without date inference never has, so treat such a cache as stale
+    let stats_data_current = stats_data_current
+        && (requested_mode != StatsMode::Schema
+            || stats_cache_infers_dates(&canonical_input_path, &args.flag_dates_whitelist));
+
     if requested_mode == StatsMode::Frequency && env_mode != "auto" && !stats_data_current {
         // if the stats.data file is not current,
         // we're also doing frequency old school w/o cardinality
@@ -2133,8 +2514,11 @@ pub fn get_stats_records(
         return Ok((ByteRecord::new(), Vec::new(), HashMap::new()));
     }
 
-    // get the headers from the input file
-    let mut rdr = csv::Reader::from_path(args.arg_input.as_ref().ok_or("No input provided")?)?;
+    // get the headers from the input file, through Config so JSONL, Parquet & Arrow IPC
+    // inputs are converted to CSV just like when the stats command reads them
+    let mut rdr = Config::new(args.arg_input.as_ref())
+        .delimiter(args.flag_delimiter)
+        .reader()?;
     let csv_fields = rdr.byte_headers()?.clone();
     drop(rdr);
 
@@ -2366,6 +2750,51 @@ pub fn get_stats_records(
     ))
 }
 
+/// derive a Polars schema from the stats cache, using the smallest integer & float types
+/// that can hold each column's range. Used by `sqlp --cache-schema` & `schema --format polars`.
+#[cfg(feature = "polars")]
+pub fn polars_schema_from_stats(
+    csv_fields: &csv::ByteRecord,
+    csv_stats: &[StatsData],
+) -> polars::prelude::Schema {
+    use polars::{datatypes::DataType, prelude::PlSmallStr};
+
+    let mut schema = polars::prelude::Schema::with_capacity(csv_stats.len());
+    for (idx, stat) in csv_stats.iter().enumerate() {
+        let datatype = &stat.r#type;
+        #[allow(clippy::match_same_arms)]
+        let dtype = match datatype.as_str() {
+            "String" => DataType::String,
+            "Integer" => {
+                let min = stat.min.as_ref().unwrap();
+                let max = stat.max.as_ref().unwrap();
+                if min.parse::<i32>().is_ok() && max.parse::<i32>().is_ok() {
+                    DataType::Int32
+                } else {
+                    DataType::Int64
+                }
+            },
+            "Float" => {
+                let min = stat.min.as_ref().unwrap();
+                let max = stat.max.as_ref().unwrap();
+                if min.parse::<f32>().is_ok() && max.parse::<f32>().is_ok() {
+                    DataType::Float32
+                } else {
+                    DataType::Float64
+                }
+            },
+            "Boolean" => DataType::Boolean,
+            "Date" => DataType::Date,
+            _ => DataType::String,
+        };
+        schema.insert(
+            PlSmallStr::from_str(simdutf8::basic::from_utf8(csv_fields.get(idx).unwrap()).unwrap()),
+            dtype,
+        );
+    }
+    schema
+}
+
 pub fn csv_to_jsonl(
     input_csv: &str,
     csv_types: &phf::Map<&'static str, JsonTypes>,
@@ -2454,8 +2883,8 @@ pub fn optimal_batch_size(rconfig: &Config, batch_size: usize, num_jobs: usize)
         return DEFAULT_BATCH_SIZE;
     }
 
-    let num_rows = match ROW_COUNT.get() {
-        Some(count) => count.unwrap() as usize,
+    let num_rows = match cached_row_count() {
+        Some(count) => count as usize,
         None => match rconfig.indexed() {
             Ok(Some(idx)) => idx.count() as usize,
             _ => {
@@ -0,0 +1,209 @@
+use qsv::api::{
+    self, CsvOptions, DedupOptions, FrequencyOptions, SearchOptions, SelectOptions, SortOptions,
+    StatsOptions, ValidateOptions,
+};
+
+use crate::workdir::Workdir;
+
+const PEOPLE: &str = "name,age,city
+Alice,30,Paris
+Bob,12,Oslo
+Carol,45,Paris
+Bob,12,Oslo
+";
+
+fn run<F>(f: F) -> String
+where
+    F: FnOnce(&mut Vec<u8>) -> qsv::CliResult<()>,
+{
+    let mut output = Vec::new();
+    f(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn api_select() {
+    let got = run(|out| api::select(PEOPLE.as_bytes(), out, &SelectOptions::new("city,name")));
+    assert_eq!(
+        got,
+        "city,name\nParis,Alice\nOslo,Bob\nParis,Carol\nOslo,Bob\n"
+    );
+}
+
+#[test]
+fn api_select_delimiter() {
+    let options = SelectOptions {
+        csv: CsvOptions {
+            delimiter:  Some(';'),
+            no_headers: false,
+        },
+        ..SelectOptions::new("b")
+    };
+    let got = run(|out| api::select("a;b\n1;2\n".as_bytes(), out, &options));
+    assert_eq!(got, "b\n2\n");
+}
+
+#[test]
+fn api_select_stats_selector() {
+    // stats-based selectors need the stats cache, which the library API never creates
+    let err = api::select(
+        PEOPLE.as_bytes(),
+        Vec::new(),
+        &SelectOptions::new("type:Integer"),
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Stats-based selectors are not supported by the library API.")
+    );
+
+    // a column named like a stats-based selector is still selected by name
+    let got = run(|out| {
+        api::select(
+            "type:Integer,b\n1,2\n".as_bytes(),
+            out,
+            &SelectOptions::new("type:Integer"),
+        )
+    });
+    assert_eq!(got, "type:Integer\n1\n");
+}
+
+#[test]
+fn api_search() {
+    let options = SearchOptions {
+        select: Some("city".to_string()),
+        ..SearchOptions::new("^par")
+    };
+    let got = run(|out| api::search(PEOPLE.as_bytes(), out, &options));
+    assert_eq!(got, "name,age,city\n");
+
+    let options = SearchOptions {
+        ignore_case: true,
+        ..options
+    };
+    let got = run(|out| api::search(PEOPLE.as_bytes(), out, &options));
+    assert_eq!(got, "name,age,city\nAlice,30,Paris\nCarol,45,Paris\n");
+}
+
+#[test]
+fn api_dedup_sort() {
+    let got = run(|out| api::dedup(PEOPLE.as_bytes(), out, &DedupOptions::default()));
+    assert_eq!(
+        got,
+        "name,age,city\nAlice,30,Paris\nBob,12,Oslo\nCarol,45,Paris\n"
+    );
+
+    let options = SortOptions {
+        select: Some("age".to_string()),
+        numeric: true,
+        reverse: true,
+        ..Default::default()
+    };
+    let got = run(|out| api::sort(PEOPLE.as_bytes(), out, &options));
+    assert_eq!(
+        got,
+        "name,age,city\nCarol,45,Paris\nAlice,30,Paris\nBob,12,Oslo\nBob,12,Oslo\n"
+    );
+}
+
+#[test]
+fn api_stats_frequency() {
+    let options = StatsOptions {
+        select: Some("age".to_string()),
+        ..Default::default()
+    };
+    let got = run(|out| api::stats(PEOPLE.as_bytes(), out, &options));
+    let mut lines = got.lines();
+    assert!(lines.next().unwrap().starts_with("field,type,"));
+    assert!(lines.next().unwrap().starts_with("age,Integer,"));
+    assert_eq!(lines.next(), None);
+
+    // run again, to check that no state leaks from the previous run
+    let options = FrequencyOptions {
+        select: Some("city".to_string()),
+        ..Default::default()
+    };
+    let got = run(|out| api::frequency(PEOPLE.as_bytes(), out, &options));
+    assert!(got.starts_with("field,value,count,percentage\n"));
+    assert!(got.contains("\ncity,Oslo,2,"));
+    assert!(got.contains("\ncity,Paris,2,"));
+}
+
+#[test]
+fn api_validate() {
+    let wrk = Workdir::new("api_validate");
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+  "type": "object",
+  "properties": {
+    "name": { "type": "string" },
+    "age": { "type": "integer", "minimum": 18 },
+    "city": { "type": "string" }
+  }
+}"#,
+    );
+    let options = ValidateOptions {
+        schema: Some(wrk.path("schema.json").display().to_string()),
+        ..Default::default()
+    };
+
+    let report = api::validate("name,age,city\nAlice,30,Paris\n".as_bytes(), &options).unwrap();
+    assert!(report.valid);
+    assert!(report.errors.is_empty());
+
+    let report = api::validate(PEOPLE.as_bytes(), &options).unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.errors.len(), 2);
+    assert_eq!(report.errors[0].row_number, Some(2));
+    assert_eq!(report.errors[0].field, "age");
+    assert!(report.errors[0].error.contains("minimum"));
+    assert_eq!(report.errors[1].row_number, Some(4));
+
+    // without a schema, only RFC 4180 compliance is checked
+    let report = api::validate("a,b\n1,2\n3\n".as_bytes(), &ValidateOptions::default()).unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].field, "<RECORD>");
+
+    // a missing schema is an error, not an invalid input
+    let options = ValidateOptions {
+        schema: Some(wrk.path("missing.json").display().to_string()),
+        ..Default::default()
+    };
+    assert!(api::validate(PEOPLE.as_bytes(), &options).is_err());
+}
+
+#[test]
+fn api_streaming() {
+    // more input & output than the pipes buffer, so both copiers block on the command
+    let mut input = String::from("id,value\n");
+    for i in 0..200_000 {
+        input.push_str(&format!("{i},v{i}\n"));
+    }
+    let got = run(|out| api::select(input.as_bytes(), out, &SelectOptions::new("value")));
+    assert_eq!(got.lines().count(), 200_001);
+    assert_eq!(got.lines().last(), Some("v199999"));
+
+    // errors reading the input & writing the output are returned
+    struct FailingReader;
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("input failed"))
+        }
+    }
+    struct FailingWriter;
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("output failed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let err = api::select(FailingReader, Vec::new(), &SelectOptions::new("1")).unwrap_err();
+    assert!(err.to_string().contains("input failed"));
+    let err = api::select(input.as_bytes(), FailingWriter, &SelectOptions::new("1")).unwrap_err();
+    assert!(err.to_string().contains("output failed"));
+}
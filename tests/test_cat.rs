@@ -285,6 +285,97 @@ fn cat_rows_flexible_infile() {
     similar_asserts::assert_eq!(got, expected);
 }
 
+#[test]
+fn cat_rows_grouping_fname() {
+    let wrk = Workdir::new("cat_rows_grouping_fname");
+    wrk.create("in1.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+    wrk.create(
+        "in2.csv",
+        vec![svec!["a", "b"], svec!["3", "4"], svec!["5", "6"]],
+    );
+
+    let mut cmd = wrk.command("cat");
+    cmd.arg("rows")
+        .args(["--group", "fname"])
+        .args(["--group-name", "source_file"])
+        .arg("in1.csv")
+        .arg("in2.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["source_file", "a", "b"],
+        svec!["in1.csv", "1", "2"],
+        svec!["in2.csv", "3", "4"],
+        svec!["in2.csv", "5", "6"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn cat_rows_glob_recursive() {
+    let wrk = Workdir::new("cat_rows_glob_recursive");
+    let _ = wrk.create_subdir("data");
+    let _ = wrk.create_subdir("data/2024");
+    wrk.create("data/b.csv", vec![svec!["a", "b"], svec!["3", "4"]]);
+    wrk.create("data/2024/a.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+    wrk.create("data/2024/skip.csv", vec![svec!["a", "b"], svec!["x", "y"]]);
+    wrk.create("data/notes.txt", vec![svec!["not", "csv"]]);
+
+    let mut cmd = wrk.command("cat");
+    cmd.env("QSV_INPUT_EXCLUDE", "skip*")
+        .arg("rows")
+        .args(["--group", "fstem"])
+        .arg("data/**/*.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["file", "a", "b"],
+        svec!["a", "1", "2"],
+        svec!["b", "3", "4"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn cat_rows_dir_recursive_include() {
+    let wrk = Workdir::new("cat_rows_dir_recursive_include");
+    let _ = wrk.create_subdir("data");
+    let _ = wrk.create_subdir("data/sub");
+    wrk.create("data/a.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+    wrk.create("data/sub/b.csv", vec![svec!["a", "b"], svec!["3", "4"]]);
+    wrk.create("data/sub/c.txt", vec![svec!["a", "b"], svec!["x", "y"]]);
+
+    // without QSV_INPUT_RECURSIVE, only the files directly in the directory are read
+    let mut cmd = wrk.command("cat");
+    cmd.env("QSV_INPUT_INCLUDE", "*.csv")
+        .arg("rows")
+        .arg("data");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, vec![svec!["a", "b"], svec!["1", "2"]]);
+
+    let mut cmd = wrk.command("cat");
+    cmd.env("QSV_INPUT_INCLUDE", "*.csv")
+        .env("QSV_INPUT_RECURSIVE", "1")
+        .arg("rows")
+        .arg("data");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "b"], svec!["1", "2"], svec!["3", "4"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn cat_rows_glob_no_match() {
+    let wrk = Workdir::new("cat_rows_glob_no_match");
+    wrk.create("in1.csv", vec![svec!["a", "b"], svec!["1", "2"]]);
+
+    let mut cmd = wrk.command("cat");
+    cmd.arg("rows").arg("nodata/*.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("did not match any files"), "{got}");
+}
+
 #[test]
 fn cat_rowskey_grouping() {
     let wrk = Workdir::new("cat_rowskey_grouping");
@@ -0,0 +1,80 @@
+use crate::workdir::Workdir;
+
+fn completions(wrk: &Workdir, shell: &str) -> String {
+    let mut cmd = wrk.command("completions");
+    cmd.args([shell, "--bin-name", "qsv"]);
+    wrk.assert_success(&mut cmd);
+    wrk.stdout(&mut cmd)
+}
+
+#[test]
+fn completions_bash() {
+    let wrk = Workdir::new("completions_bash");
+    let got = completions(&wrk, "bash");
+
+    assert!(got.contains("_qsv() {"));
+    assert!(got.contains("complete -o filenames -o bashdefault -F _qsv qsv"));
+    // options of a command, with their valid values
+    assert!(got.contains("        sort)\n"));
+    assert!(got.contains(
+        "                --rng)\n                    COMPREPLY=( $(compgen -W \"standard faster \
+         cryptosecure\" -- \"${cur}\") )"
+    ));
+    assert!(got.contains("-s --select -N --numeric -R --reverse"));
+    // subcommands
+    assert!(got.contains("COMPREPLY=( $(compgen -W \"schema\" -- \"${cur}\")"));
+}
+
+#[test]
+fn completions_zsh() {
+    let wrk = Workdir::new("completions_zsh");
+    let got = completions(&wrk, "zsh");
+
+    assert!(got.starts_with("#compdef qsv\n"));
+    assert!(got.contains("'(-s --select)'{-s,--select=}'[Select a subset of columns to sort.]"));
+    assert!(got.contains(
+        "'--rng=[The RNG algorithm to use if --random is set.]:value:(standard faster \
+         cryptosecure)'"
+    ));
+    assert!(got.contains("(schema)"));
+    assert!(
+        got.contains("'sort:Sort CSV data in alphabetical, numerical, reverse or random order'")
+    );
+}
+
+#[test]
+fn completions_fish() {
+    let wrk = Workdir::new("completions_fish");
+    let got = completions(&wrk, "fish");
+
+    assert!(got.contains("complete -c qsv -n \"__fish_use_subcommand\" -f -a sort -d"));
+    assert!(got.contains(
+        "complete -c qsv -n \"__fish_seen_subcommand_from sort\" -l rng -x -a \"standard faster \
+         cryptosecure\""
+    ));
+    assert!(got.contains(
+        "complete -c qsv -n \"__fish_seen_subcommand_from validate; and not \
+         __fish_seen_subcommand_from schema\" -a \"schema\""
+    ));
+}
+
+#[test]
+fn completions_powershell() {
+    let wrk = Workdir::new("completions_powershell");
+    let got = completions(&wrk, "powershell");
+
+    assert!(got.contains("Register-ArgumentCompleter -Native -CommandName 'qsv'"));
+    assert!(got.contains("'sort --rng' = @('standard', 'faster', 'cryptosecure')"));
+    assert!(got.contains("'validate' = @('schema')"));
+}
+
+#[test]
+fn completions_unsupported_shell() {
+    let wrk = Workdir::new("completions_unsupported_shell");
+    let mut cmd = wrk.command("completions");
+    cmd.arg("tcsh");
+
+    wrk.assert_err(&mut cmd);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Unsupported shell \"tcsh\""));
+}
@@ -0,0 +1,96 @@
+use crate::workdir::Workdir;
+
+fn parse_events(ndjson: &str) -> Vec<serde_json::Value> {
+    ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn events_start_end() {
+    let wrk = Workdir::new("events_start_end");
+    let data = "letter,number\na,1\nb,2\n";
+    wrk.create_from_string("data.csv", data);
+
+    let mut cmd = wrk.command("select");
+    cmd.env("QSV_PROGRESS_FORMAT", "ndjson")
+        .arg("letter")
+        .arg("data.csv");
+
+    wrk.assert_success(&mut cmd);
+    let events = parse_events(&wrk.output_stderr(&mut cmd));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["event"], "start");
+    assert_eq!(events[0]["command"], "select");
+
+    let end = &events[1];
+    assert_eq!(end["event"], "end");
+    assert_eq!(end["command"], "select");
+    assert_eq!(end["status"], "ok");
+    assert_eq!(end["exit_code"], 0);
+    assert_eq!(end["bytes_read"], data.len());
+    assert!(end["elapsed_ms"].is_u64());
+}
+
+#[test]
+fn events_end_error() {
+    let wrk = Workdir::new("events_end_error");
+
+    let mut cmd = wrk.command("select");
+    cmd.env("QSV_PROGRESS_FORMAT", "ndjson")
+        .arg("letter")
+        .arg("nonexistent.csv");
+
+    wrk.assert_err(&mut cmd);
+    let stderr = wrk.output_stderr(&mut cmd);
+    let end = stderr
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|event| event["event"] == "end")
+        .unwrap();
+    assert_eq!(end["status"], "error");
+    assert_eq!(end["exit_code"], 1);
+}
+
+#[test]
+fn events_progress_to_file() {
+    let wrk = Workdir::new("events_progress_to_file");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["letter", "number"],
+            svec!["a", "1"],
+            svec!["b", "2"],
+            svec!["c", "3"],
+        ],
+    );
+
+    let mut cmd = wrk.command("search");
+    cmd.env("QSV_PROGRESS_FORMAT", "ndjson")
+        .env("QSV_PROGRESS_OUTPUT", "events.ndjson")
+        .arg("[ab]")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["letter", "number"], svec!["a", "1"], svec!["b", "2"]];
+    similar_asserts::assert_eq!(got, expected);
+
+    let events = parse_events(&wrk.read_to_string("events.ndjson").unwrap());
+    let event_names: Vec<&str> = events
+        .iter()
+        .map(|event| event["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(event_names.first(), Some(&"start"));
+    assert_eq!(event_names.last(), Some(&"end"));
+    assert!(event_names.contains(&"progress_start"));
+
+    let progress_end = events
+        .iter()
+        .find(|event| event["event"] == "progress_end")
+        .unwrap();
+    assert_eq!(progress_end["rows"], 3);
+    assert_eq!(progress_end["total_rows"], 3);
+    assert_eq!(progress_end["percent"], 100.0);
+    assert_eq!(events.last().unwrap()["rows"], 3);
+}
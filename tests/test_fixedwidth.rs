@@ -0,0 +1,214 @@
+use crate::workdir::Workdir;
+
+const CENSUS: &str = "\
+00001NY  8336817
+00002CA 39538223
+00003WY   576851
+";
+
+#[test]
+fn fixedwidth_layout_csv() {
+    let wrk = Workdir::new("fixedwidth_layout_csv");
+    wrk.create_from_string("census.txt", CENSUS);
+    wrk.create(
+        "layout.csv",
+        vec![
+            svec!["name", "start", "length"],
+            svec!["id", "1", "5"],
+            svec!["state", "6", "2"],
+            svec!["population", "8", "9"],
+        ],
+    );
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--layout").arg("layout.csv").arg("census.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "state", "population"],
+        svec!["00001", "NY", "8336817"],
+        svec!["00002", "CA", "39538223"],
+        svec!["00003", "WY", "576851"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_layout_json_no_trim() {
+    let wrk = Workdir::new("fixedwidth_layout_json_no_trim");
+    wrk.create_from_string("census.txt", CENSUS);
+    wrk.create_from_string(
+        "layout.json",
+        r#"{"fields": [{"name": "state", "start": 6, "length": 3}, {"name": "id", "start": 1, "length": 5}]}"#,
+    );
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--layout")
+        .arg("layout.json")
+        .arg("--no-trim")
+        .arg("census.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["state", "id"],
+        svec!["NY ", "00001"],
+        svec!["CA ", "00002"],
+        svec!["WY ", "00003"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_auto() {
+    let wrk = Workdir::new("fixedwidth_auto");
+    wrk.create_from_string(
+        "report.txt",
+        "name     city         age\r\nalice    New York      30\r\nbob      Boston         \
+         5\r\n\r\n",
+    );
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--auto").arg("report.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "city", "age"],
+        svec!["alice", "New York", "30"],
+        svec!["bob", "Boston", "5"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_auto_print_layout() {
+    let wrk = Workdir::new("fixedwidth_auto_print_layout");
+    wrk.create_from_string("census.txt", CENSUS);
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--auto")
+        .arg("--no-headers")
+        .arg("--print-layout")
+        .arg("census.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "start", "length"],
+        svec!["column_1", "1", "9"],
+        svec!["column_2", "10", "7"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+fn multi_record_layout(wrk: &Workdir) {
+    wrk.create_from_string(
+        "extract.txt",
+        "H2024-01-31\nD00001apples   12\nD00002pears     7\nXjunk\nT00002\n",
+    );
+    wrk.create(
+        "layout.csv",
+        vec![
+            svec!["name", "start", "length", "record_type"],
+            svec!["date", "2", "10", "H"],
+            svec!["id", "2", "5", "D"],
+            svec!["item", "7", "8", "D"],
+            svec!["qty", "15", "3", "D"],
+            svec!["count", "2", "5", "T"],
+        ],
+    );
+}
+
+#[test]
+fn fixedwidth_multi_record() {
+    let wrk = Workdir::new("fixedwidth_multi_record");
+    multi_record_layout(&wrk);
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--layout")
+        .arg("layout.csv")
+        .arg("--record-type")
+        .arg("1:1")
+        .arg("extract.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["record_type", "date", "id", "item", "qty", "count"],
+        svec!["H", "2024-01-31", "", "", "", ""],
+        svec!["D", "", "00001", "apples", "12", ""],
+        svec!["D", "", "00002", "pears", "7", ""],
+        svec!["T", "", "", "", "", "00002"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+
+    let got = wrk.output_stderr(&mut cmd);
+    similar_asserts::assert_eq!(
+        got,
+        "Skipped 1 records with a record type that's not in the layout.\n"
+    );
+}
+
+#[test]
+fn fixedwidth_multi_record_type_filter() {
+    let wrk = Workdir::new("fixedwidth_multi_record_type_filter");
+    multi_record_layout(&wrk);
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--layout")
+        .arg("layout.csv")
+        .arg("--record-type")
+        .arg("1:1")
+        .arg("--type")
+        .arg("D")
+        .arg("extract.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "item", "qty"],
+        svec!["00001", "apples", "12"],
+        svec!["00002", "pears", "7"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_multi_record_requires_position() {
+    let wrk = Workdir::new("fixedwidth_multi_record_requires_position");
+    multi_record_layout(&wrk);
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--layout").arg("layout.csv").arg("extract.txt");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn fixedwidth_ebcdic_record_length() {
+    let wrk = Workdir::new("fixedwidth_ebcdic_record_length");
+    // "AB 01" & "CD 02" in EBCDIC, as 5-byte records without terminators
+    std::fs::write(
+        wrk.path("extract.dat"),
+        [0xC1, 0xC2, 0x40, 0xF0, 0xF1, 0xC3, 0xC4, 0x40, 0xF0, 0xF2],
+    )
+    .unwrap();
+    wrk.create(
+        "layout.csv",
+        vec![
+            svec!["name", "start", "length"],
+            svec!["code", "1", "3"],
+            svec!["num", "4", "2"],
+        ],
+    );
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("--layout")
+        .arg("layout.csv")
+        .arg("--ebcdic")
+        .arg("--record-length")
+        .arg("5")
+        .arg("extract.dat");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["code", "num"], svec!["AB", "01"], svec!["CD", "02"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn fixedwidth_layout_or_auto_required() {
+    let wrk = Workdir::new("fixedwidth_layout_or_auto_required");
+    wrk.create_from_string("census.txt", CENSUS);
+    let mut cmd = wrk.command("fixedwidth");
+    cmd.arg("census.txt");
+
+    wrk.assert_err(&mut cmd);
+}
@@ -478,3 +478,208 @@ fn input_headertrim() {
     ];
     similar_asserts::assert_eq!(got, expected);
 }
+
+#[test]
+fn input_encoding_windows1252() {
+    let wrk = Workdir::new("input_encoding_windows1252");
+    std::fs::write(wrk.path("data.csv"), b"name,city\nJos\xe9,Z\xfcrich\n").unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--encoding").arg("windows-1252").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "city"], svec!["José", "Zürich"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_encoding_shift_jis() {
+    let wrk = Workdir::new("input_encoding_shift_jis");
+    // "id,name\n1,東京\n2,大阪\n" in Shift_JIS
+    std::fs::write(
+        wrk.path("data.csv"),
+        [
+            105, 100, 44, 110, 97, 109, 101, 10, 49, 44, 147, 140, 139, 158, 10, 50, 44, 145, 229,
+            141, 227, 10,
+        ],
+    )
+    .unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--encoding").arg("shift_jis").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "name"], svec!["1", "東京"], svec!["2", "大阪"]];
+    similar_asserts::assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--encoding").arg("auto").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_encoding_auto() {
+    let wrk = Workdir::new("input_encoding_auto");
+    std::fs::write(wrk.path("latin1.csv"), b"name,city\nJos\xe9,Z\xfcrich\n").unwrap();
+    // "a,b\n1,é\n" in UTF-16LE with a BOM
+    std::fs::write(
+        wrk.path("utf16.csv"),
+        [
+            255, 254, 97, 0, 44, 0, 98, 0, 10, 0, 49, 0, 44, 0, 233, 0, 10, 0,
+        ],
+    )
+    .unwrap();
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--encoding").arg("auto").arg("latin1.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name", "city"], svec!["José", "Zürich"]];
+    similar_asserts::assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--encoding").arg("auto").arg("utf16.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "b"], svec!["1", "é"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_encoding_invalid_label() {
+    let wrk = Workdir::new("input_encoding_invalid_label");
+    wrk.create("data.csv", vec![svec!["a"], svec!["1"]]);
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--encoding").arg("klingon").arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn input_multi_delimiter() {
+    let wrk = Workdir::new("input_multi_delimiter");
+    wrk.create_from_string(
+        "data.txt",
+        "id~|~name~|~motto\n1~|~Alice~|~a, b & \"c\"\n2~|~Bob~|~\n",
+    );
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--multi-delimiter").arg("~|~").arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "motto"],
+        svec!["1", "Alice", "a, b & \"c\""],
+        svec!["2", "Bob", ""],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_multi_delimiter_escaped_tabs() {
+    let wrk = Workdir::new("input_multi_delimiter_escaped_tabs");
+    wrk.create_from_string("data.txt", "a\t\tb\r\n1\t\t2\r\n");
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--multi-delimiter").arg(r"\t\t").arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "b"], svec!["1", "2"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_regex_delimiter() {
+    let wrk = Workdir::new("input_regex_delimiter");
+    wrk.create_from_string("data.txt", "a | b|c\n1  |2 |  3\n");
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--regex-delimiter")
+        .arg(r"\s*\|\s*")
+        .arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "b", "c"], svec!["1", "2", "3"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_record_terminator() {
+    let wrk = Workdir::new("input_record_terminator");
+    wrk.create_from_string("data.txt", "id||note|$|1||multi\nline|$|2||single|$|\n");
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--multi-delimiter")
+        .arg("||")
+        .arg("--record-terminator")
+        .arg("|$|")
+        .arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "note"],
+        svec!["1", "multi\nline"],
+        svec!["2", "single"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_record_terminator_newline_after_each() {
+    let wrk = Workdir::new("input_record_terminator_newline_after_each");
+    wrk.create_from_string(
+        "data.txt",
+        "id||note|$|\n1||first|$|\r\n#comment|$|\n2||multi\nline|$|\n3||last|$|\n",
+    );
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--multi-delimiter")
+        .arg("||")
+        .arg("--record-terminator")
+        .arg("|$|")
+        .args(["--comment", "#"])
+        .arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "note"],
+        svec!["1", "first"],
+        svec!["2", "multi\nline"],
+        svec!["3", "last"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_record_terminator_hex_escape() {
+    let wrk = Workdir::new("input_record_terminator_hex_escape");
+    wrk.create_from_string("data.txt", "a;b\x1e1;2\x1e");
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--delimiter")
+        .arg(";")
+        .arg("--record-terminator")
+        .arg(r"\x1e")
+        .arg("data.txt");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["a", "b"], svec!["1", "2"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn input_multi_delimiter_auto_skip_err() {
+    let wrk = Workdir::new("input_multi_delimiter_auto_skip_err");
+    wrk.create_from_string("data.txt", "a||b\n1||2\n");
+
+    let mut cmd = wrk.command("input");
+    cmd.arg("--multi-delimiter")
+        .arg("||")
+        .arg("--auto-skip")
+        .arg("data.txt");
+
+    wrk.assert_err(&mut cmd);
+}
@@ -329,6 +329,26 @@ fn join_inner_issue11() {
     similar_asserts::assert_eq!(got, expected);
 }
 
+#[test]
+fn join_key_error_json_error_format() {
+    let wrk = Workdir::new("join_key_error_json_error_format");
+    wrk.create("a.csv", vec![svec!["id", "name"], svec!["1", "a"]]);
+    wrk.create("b.csv", vec![svec!["key", "value"], svec!["1", "b"]]);
+
+    let mut cmd = wrk.command("join");
+    cmd.env("QSV_ERROR_FORMAT", "json")
+        .args(["id", "a.csv", "id", "b.csv"]);
+
+    wrk.assert_err(&mut cmd);
+    let got: serde_json::Value = serde_json::from_str(&wrk.output_stderr(&mut cmd)).unwrap();
+    let error = &got["error"];
+    assert_eq!(error["code"], "JOIN_KEY_ERROR");
+    assert_eq!(error["command"], "join");
+    assert_eq!(error["input"], "b.csv");
+    assert_eq!(error["column"], "id");
+    assert_eq!(error["row"], serde_json::Value::Null);
+}
+
 #[test]
 fn join_cross() {
     let wrk = Workdir::new("join_cross");
@@ -120,3 +120,37 @@ fn jsonl_nested() {
     ];
     similar_asserts::assert_eq!(got, expected);
 }
+
+#[test]
+fn jsonl_input_adapter_select() {
+    let wrk = Workdir::new("jsonl_input_adapter_select");
+    wrk.create_from_string(
+        "data.jsonl",
+        r#"{"id":1,"father":"Mark","mother":"Charlotte","oldest_child":"Tom"}
+{"id":2,"father":"John","mother":"Ann","oldest_child":"Jessika"}
+{"id":3,"father":"Bob","mother":"Monika","oldest_child":"Jerry"}
+"#,
+    );
+    let mut cmd = wrk.command("select");
+    cmd.arg("id,oldest_child").arg("data.jsonl");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "oldest_child"],
+        svec!["1", "Tom"],
+        svec!["2", "Jessika"],
+        svec!["3", "Jerry"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn jsonl_input_adapter_count() {
+    let wrk = Workdir::new("jsonl_input_adapter_count");
+    let test_file = wrk.load_test_file("boston311-10.jsonl");
+    let mut cmd = wrk.command("count");
+    cmd.arg(test_file);
+
+    let got: String = wrk.stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, "10");
+}
@@ -0,0 +1,208 @@
+use crate::workdir::Workdir;
+
+fn create_cities(wrk: &Workdir) {
+    wrk.create(
+        "cities.csv",
+        vec![
+            svec!["name", "state", "population"],
+            svec!["Buffalo", "NY", "278349"],
+            svec!["Dallas", "TX", "1304379"],
+            svec!["Fort Worth", "TX", "918915"],
+            svec!["Manhattan", "NY", "1694251"],
+        ],
+    );
+}
+
+#[test]
+fn pipeline_yaml() {
+    let wrk = Workdir::new("pipeline_yaml");
+    create_cities(&wrk);
+    wrk.create_from_string(
+        "pipeline.yaml",
+        r#"steps:
+  - name: pick
+    command: select
+    args: ["name,population", "cities.csv"]
+    output: picked
+  - name: sort
+    command: sort
+    args: ["--select", "population", "--numeric", "--reverse", "${picked}"]
+"#,
+    );
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.yaml").arg("--quiet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "population"],
+        svec!["Manhattan", "1694251"],
+        svec!["Dallas", "1304379"],
+        svec!["Fort Worth", "918915"],
+        svec!["Buffalo", "278349"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn pipeline_toml_timings() {
+    let wrk = Workdir::new("pipeline_toml_timings");
+    create_cities(&wrk);
+    wrk.create_from_string(
+        "pipeline.toml",
+        r#"[[steps]]
+name = "pick"
+command = "select"
+args = ["name,state", "cities.csv"]
+output = "picked"
+
+[[steps]]
+name = "slice"
+command = "slice"
+args = ["--len", 2, "${picked}"]
+output = "sliced"
+
+[[steps]]
+name = "again"
+command = "select"
+args = ["name", "${sliced}"]
+"#,
+    );
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.toml");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["name"], svec!["Buffalo"], svec!["Dallas"]];
+    similar_asserts::assert_eq!(got, expected);
+
+    let stderr = wrk.output_stderr(&mut cmd);
+    assert!(stderr.starts_with("step"));
+    assert!(stderr.contains("pick"));
+    assert!(stderr.contains("in-process"));
+    // the second use of select runs in a separate process
+    assert!(stderr.contains("subprocess"));
+    assert!(stderr.contains("total"));
+}
+
+#[test]
+fn pipeline_parallel() {
+    let wrk = Workdir::new("pipeline_parallel");
+    create_cities(&wrk);
+    wrk.create_from_string(
+        "pipeline.json",
+        r#"{"steps": [
+  {"name": "both", "parallel": [
+    {"name": "ny", "command": "search", "args": ["--select", "state", "NY", "cities.csv"],
+     "output": "ny"},
+    {"name": "tx", "command": "search", "args": ["--select", "state", "TX", "cities.csv"],
+     "output": "tx"}
+  ]},
+  {"name": "combine", "command": "cat", "args": ["rows", "${ny}", "${tx}"]}
+]}"#,
+    );
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.json").arg("--quiet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "state", "population"],
+        svec!["Buffalo", "NY", "278349"],
+        svec!["Manhattan", "NY", "1694251"],
+        svec!["Dallas", "TX", "1304379"],
+        svec!["Fort Worth", "TX", "918915"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn pipeline_resume() {
+    let wrk = Workdir::new("pipeline_resume");
+    create_cities(&wrk);
+    wrk.create_from_string(
+        "pipeline.yaml",
+        r#"steps:
+  - name: pick
+    command: select
+    args: ["name", "cities.csv"]
+    output: picked
+  - name: count
+    command: count
+    args: ["${picked}"]
+"#,
+    );
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.yaml")
+        .args(["--workdir", "work"])
+        .args(["--report", "report.json"]);
+    wrk.assert_success(&mut cmd);
+    assert!(wrk.path("work/picked.csv").exists());
+    assert!(wrk.path("work/pipeline.state.json").exists());
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.yaml")
+        .args(["--workdir", "work"])
+        .arg("--resume")
+        .args(["--report", "report.json"]);
+    let got: String = wrk.stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, "4");
+
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["status"], "ok");
+    assert_eq!(report["steps"][0]["name"], "pick");
+    assert_eq!(report["steps"][0]["mode"], "skipped");
+    assert_eq!(report["steps"][1]["name"], "count");
+    assert_eq!(report["steps"][1]["status"], "ok");
+}
+
+#[test]
+fn pipeline_unknown_output() {
+    let wrk = Workdir::new("pipeline_unknown_output");
+    create_cities(&wrk);
+    wrk.create_from_string(
+        "pipeline.yaml",
+        r#"steps:
+  - name: count
+    command: count
+    args: ["${nope}"]
+"#,
+    );
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.yaml");
+    wrk.assert_err(&mut cmd);
+
+    let stderr = wrk.output_stderr(&mut cmd);
+    assert!(stderr.contains("${nope} is not the output of an earlier step"));
+}
+
+#[test]
+fn pipeline_step_failed() {
+    let wrk = Workdir::new("pipeline_step_failed");
+    create_cities(&wrk);
+    wrk.create_from_string(
+        "pipeline.yaml",
+        r#"steps:
+  - name: pick
+    command: select
+    args: ["nonexistent_column", "cities.csv"]
+    output: picked
+  - name: count
+    command: count
+    args: ["${picked}"]
+"#,
+    );
+
+    let mut cmd = wrk.command("pipeline");
+    cmd.arg("pipeline.yaml").args(["--report", "report.json"]);
+    wrk.assert_err(&mut cmd);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&wrk.read_to_string("report.json").unwrap()).unwrap();
+    assert_eq!(report["status"], "failed");
+    assert_eq!(report["steps"].as_array().unwrap().len(), 1);
+    assert_eq!(report["steps"][0]["status"], "failed");
+}
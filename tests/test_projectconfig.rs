@@ -0,0 +1,195 @@
+use crate::workdir::Workdir;
+
+fn create_data(wrk: &Workdir) {
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["letter", "number"],
+            svec!["a", "1"],
+            svec!["b", "2"],
+            svec!["c", "3"],
+        ],
+    );
+}
+
+#[test]
+fn qsvtoml_command_defaults() {
+    let wrk = Workdir::new("qsvtoml_command_defaults");
+    create_data(&wrk);
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[commands.slice]
+len = 1
+"#,
+    );
+
+    let mut cmd = wrk.command("slice");
+    cmd.arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["letter", "number"], svec!["a", "1"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn qsvtoml_explicit_flags_win() {
+    let wrk = Workdir::new("qsvtoml_explicit_flags_win");
+    create_data(&wrk);
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[commands.slice]
+len = 1
+start = 1
+"#,
+    );
+
+    // both the long and the short forms of an option override the default
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--len", "2"]).args(["-s", "0"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["letter", "number"], svec!["a", "1"], svec!["b", "2"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn qsvtoml_found_in_parent_dir() {
+    let wrk = Workdir::new("qsvtoml_found_in_parent_dir");
+    let _ = wrk.create_subdir("project");
+    wrk.create_from_string("qsv.toml", "[commands.headers]\ndelimiter = \";\"\n");
+    wrk.create_from_string("project/data.ssv.txt", "letter;number\na;1\n");
+
+    let mut cmd = wrk.command("headers");
+    cmd.current_dir(wrk.path("project"))
+        .arg("--just-names")
+        .arg("data.ssv.txt");
+    let got: String = wrk.stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, "letter\nnumber");
+}
+
+#[test]
+fn qsvtoml_profile() {
+    let wrk = Workdir::new("qsvtoml_profile");
+    create_data(&wrk);
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[commands.slice]
+len = 1
+
+[profiles.ci.commands.slice]
+len = 2
+start = 1
+"#,
+    );
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--profile", "ci"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["letter", "number"], svec!["b", "2"], svec!["c", "3"]];
+    similar_asserts::assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("slice");
+    cmd.env("QSV_PROFILE", "ci")
+        .args(["--start", "2"])
+        .arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["letter", "number"], svec!["c", "3"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn qsvtoml_unknown_profile() {
+    let wrk = Workdir::new("qsvtoml_unknown_profile");
+    create_data(&wrk);
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[profiles.ci]
+jobs = 2
+"#,
+    );
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--profile", "nightly"]).arg("data.csv");
+    wrk.assert_err(&mut cmd);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.contains("Profile \"nightly\" not found") && got.contains("Available profiles: ci"),
+        "{got}"
+    );
+}
+
+#[test]
+fn qsvtoml_abbreviated_option_wins() {
+    let wrk = Workdir::new("qsvtoml_abbreviated_option_wins");
+    create_data(&wrk);
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[commands.slice]
+len = 1
+"#,
+    );
+
+    let mut cmd = wrk.command("slice");
+    cmd.args(["--le", "2"]).arg("data.csv");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["letter", "number"], svec!["a", "1"], svec!["b", "2"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn qsvtoml_delimiter_respects_extension() {
+    let wrk = Workdir::new("qsvtoml_delimiter_respects_extension");
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[commands.headers]
+delimiter = ";"
+"#,
+    );
+    wrk.create_from_string("data.tsv", "letter\tnumber\na\t1\n");
+
+    let mut cmd = wrk.command("headers");
+    cmd.arg("--just-names").arg("data.tsv");
+    let got: String = wrk.stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, "letter\nnumber");
+}
+
+#[test]
+fn qsvtoml_global_delimiter_rejected() {
+    let wrk = Workdir::new("qsvtoml_global_delimiter_rejected");
+    create_data(&wrk);
+    wrk.create_from_string("qsv.toml", "delimiter = \";\"\n");
+
+    let mut cmd = wrk.command("headers");
+    cmd.arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn qsvtoml_profile_false_overrides_flag() {
+    let wrk = Workdir::new("qsvtoml_profile_false_overrides_flag");
+    create_data(&wrk);
+    wrk.create_from_string(
+        "qsv.toml",
+        r#"
+[commands.headers]
+just-names = true
+
+[profiles.ci.commands.headers]
+just-names = false
+"#,
+    );
+
+    let mut cmd = wrk.command("headers");
+    cmd.arg("data.csv");
+    let got: String = wrk.stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, "letter\nnumber");
+
+    let mut cmd = wrk.command("headers");
+    cmd.args(["--profile", "ci"]).arg("data.csv");
+    let got: String = wrk.stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, "1   letter\n2   number");
+}
@@ -0,0 +1,128 @@
+use crate::workdir::Workdir;
+
+fn data() -> Vec<Vec<String>> {
+    vec![
+        svec!["id", "empty", "constant", "sparse", "name"],
+        svec!["1", "", "x", "", "alpha"],
+        svec!["2", "", "x", "", "beta"],
+        svec!["3", "", "x", "", "gamma"],
+        svec!["4", "", "x", "s", "delta"],
+    ]
+}
+
+#[test]
+fn prune_default() {
+    let wrk = Workdir::new("prune_default");
+    wrk.create("data.csv", data());
+    let mut cmd = wrk.command("prune");
+    cmd.arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "sparse", "name"],
+        svec!["1", "", "alpha"],
+        svec!["2", "", "beta"],
+        svec!["3", "", "gamma"],
+        svec!["4", "s", "delta"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn prune_sparsity_keep_constant() {
+    let wrk = Workdir::new("prune_sparsity_keep_constant");
+    wrk.create("data.csv", data());
+    let mut cmd = wrk.command("prune");
+    cmd.arg("--sparsity")
+        .arg("0.7")
+        .arg("--keep-constant")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "constant", "name"],
+        svec!["1", "x", "alpha"],
+        svec!["2", "x", "beta"],
+        svec!["3", "x", "gamma"],
+        svec!["4", "x", "delta"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn prune_report_dry_run() {
+    let wrk = Workdir::new("prune_report_dry_run");
+    wrk.create("data.csv", data());
+    let mut cmd = wrk.command("prune");
+    cmd.arg("--sparsity")
+        .arg("0.7")
+        .arg("--dry-run")
+        .arg("--report")
+        .arg("-")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "field",
+            "type",
+            "nullcount",
+            "sparsity",
+            "cardinality",
+            "reason"
+        ],
+        svec!["empty", "NULL", "4", "1", "1", "empty"],
+        svec!["constant", "String", "0", "0", "1", "constant"],
+        svec!["sparse", "String", "3", "0.75", "2", "sparse"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn prune_summary() {
+    let wrk = Workdir::new("prune_summary");
+    wrk.create("data.csv", data());
+    let mut cmd = wrk.command("prune");
+    cmd.arg("--output").arg("pruned.csv").arg("data.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    similar_asserts::assert_eq!(got, "Dropped 2 of 5 columns: [\"empty\", \"constant\"]\n");
+}
+
+#[test]
+fn prune_stdin_err() {
+    let wrk = Workdir::new("prune_stdin_err");
+    let mut cmd = wrk.command("prune");
+    cmd.arg("-");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[cfg(feature = "polars")]
+#[test]
+fn prune_arrow_input() {
+    let wrk = Workdir::new("prune_arrow_input");
+    wrk.create("data.csv", data());
+    let mut cmd = wrk.command("sqlp");
+    cmd.arg("data.csv").arg("select * from data").args([
+        "--format",
+        "arrow",
+        "--output",
+        "data.arrow",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    // the stats of the Arrow IPC file are computed from its CSV conversion
+    let mut cmd = wrk.command("prune");
+    cmd.arg("data.arrow");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "sparse", "name"],
+        svec!["1", "", "alpha"],
+        svec!["2", "", "beta"],
+        svec!["3", "", "gamma"],
+        svec!["4", "s", "delta"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
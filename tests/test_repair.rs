@@ -0,0 +1,86 @@
+use crate::workdir::Workdir;
+
+const BROKEN: &str = "id,name,comment
+1,Alice,\"say \"hi\" now\"
+2,Bob,\"unclosed
+3,Carol,fine
+4,Dan,\"multi
+line\"
+5,Eve
+Smith,comment
+6,Frank,cr\rhere
+";
+
+#[test]
+fn repair_broken() {
+    let wrk = Workdir::new("repair_broken");
+    wrk.create_from_string("broken.csv", BROKEN);
+    let mut cmd = wrk.command("repair");
+    cmd.arg("broken.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "name", "comment"],
+        svec!["1", "Alice", "say \"hi\" now"],
+        svec!["2", "Bob", "\"unclosed"],
+        svec!["3", "Carol", "fine"],
+        svec!["4", "Dan", "multi\nline"],
+        svec!["5", "Eve\nSmith", "comment"],
+        svec!["6", "Frank", "crhere"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn repair_log() {
+    let wrk = Workdir::new("repair_log");
+    wrk.create_from_string("broken.csv", BROKEN);
+    let mut cmd = wrk.command("repair");
+    cmd.arg("--log")
+        .arg("repairs.csv")
+        .arg("--output")
+        .arg("fixed.csv")
+        .arg("broken.csv");
+    wrk.assert_success(&mut cmd);
+
+    let got = wrk.read_to_string("repairs.csv").unwrap();
+    let expected = "line,repair,details
+2,unescaped_quote,escaped 2 unescaped quote(s)
+3,unbalanced_quote,the quote at character 7 was never closed & was treated as a literal quote
+3,unescaped_quote,escaped 1 unescaped quote(s)
+7,rejoined_row,re-joined lines 7-8 into one row
+9,stray_cr,removed 1 stray carriage return(s)
+";
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn repair_summary() {
+    let wrk = Workdir::new("repair_summary");
+    wrk.create_from_string("broken.csv", BROKEN);
+    let mut cmd = wrk.command("repair");
+    cmd.arg("--output").arg("fixed.csv").arg("broken.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    similar_asserts::assert_eq!(
+        got,
+        "Wrote 7 records. Repaired 1 stray carriage return(s), 3 unescaped quote(s), 1 unbalanced \
+         quote(s) & 1 split row(s).\n"
+    );
+}
+
+#[test]
+fn repair_valid_csv_unchanged() {
+    let wrk = Workdir::new("repair_valid_csv_unchanged");
+    let rows = vec![
+        svec!["h1", "h2"],
+        svec!["a, b", "line1\nline2"],
+        svec!["\"quoted\"", ""],
+    ];
+    wrk.create("data.csv", rows.clone());
+    let mut cmd = wrk.command("repair");
+    cmd.arg("--quiet").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    similar_asserts::assert_eq!(got, rows);
+}
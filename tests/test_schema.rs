@@ -380,3 +380,434 @@ fn generate_schema_with_const_and_enum_constraints() {
 
     similar_asserts::assert_eq!(output_schema_string, expected_schema);
 }
+
+#[test]
+#[file_serial]
+fn generate_tableschema_and_validate() {
+    let wrk = Workdir::new("generate_tableschema_and_validate").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    let csv = "id,name,score
+1,alpha,1.5
+2,beta,
+3,gamma,3.25
+";
+    wrk.create_from_string("data.csv", csv);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "tableschema"]);
+    wrk.assert_success(&mut cmd);
+
+    let output_schema_string: String = wrk.from_str(&wrk.path("data.csv.tableschema.json"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse table schema json");
+
+    let expected_schema_json: Value = serde_json::from_str(
+        r#"{
+  "fields": [
+    {
+      "name": "id",
+      "type": "integer",
+      "description": "id column from data.csv",
+      "constraints": {
+        "required": true,
+        "unique": true,
+        "minimum": 1,
+        "maximum": 3,
+        "enum": [1, 2, 3]
+      }
+    },
+    {
+      "name": "name",
+      "type": "string",
+      "description": "name column from data.csv",
+      "constraints": {
+        "required": true,
+        "unique": true,
+        "minLength": 4,
+        "maxLength": 5,
+        "enum": ["alpha", "beta", "gamma"]
+      }
+    },
+    {
+      "name": "score",
+      "type": "number",
+      "description": "score column from data.csv",
+      "constraints": {
+        "minimum": 1.5,
+        "maximum": 3.25
+      }
+    }
+  ],
+  "missingValues": [""]
+}"#,
+    )
+    .unwrap();
+    assert_json_eq!(expected_schema_json, output_schema_json);
+    assert!(!Path::new(&wrk.path("data.csv.schema.json")).exists());
+
+    // the generated Table Schema can be used directly by validate
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("data.csv.tableschema.json");
+    wrk.assert_success(&mut cmd);
+    assert!(!Path::new(&wrk.path("data.csv.validation-errors.tsv")).exists());
+
+    wrk.create_from_string("data2.csv", "id,name,score\n3,delta,9.5\n");
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data2.csv").arg("data.csv.tableschema.json");
+    wrk.assert_err(&mut cmd);
+    let validation_errors = wrk
+        .read_to_string("data2.csv.validation-errors.tsv")
+        .unwrap();
+    let mut flagged_fields: Vec<&str> = validation_errors
+        .lines()
+        .skip(1)
+        .map(|line| line.split('\t').nth(1).unwrap())
+        .collect();
+    flagged_fields.sort_unstable();
+    assert_eq!(flagged_fields, vec!["name", "score"]);
+}
+
+#[test]
+fn generate_schema_invalid_format() {
+    let wrk = Workdir::new("generate_schema_invalid_format");
+    wrk.create_from_string("data.csv", "id\n1\n");
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "xmlschema"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_detect_formats() {
+    let wrk = Workdir::new("generate_schema_detect_formats").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    let csv = "id,phone,iban,postcode,name
+f47ac10b-58cc-4372-a567-0e02b2c3d479,+14155552671,GB82WEST12345698765432,SW1A 1AA,alpha
+9b2e4c6a-1f3d-4e5b-8a7c-2d4f6b8a0c1e,+442079460958,DE89370400440532013000,,beta
+3fa85f64-5717-4562-b3fc-2c963f66afa6,n/a,GB82WEST12345698765432,BN15 8AG,gamma
+";
+    wrk.create_from_string("data.csv", csv);
+
+    // formats are only detected with --detect-formats
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv");
+    wrk.assert_success(&mut cmd);
+
+    let output_schema_string: String = wrk.from_str(&wrk.path("data.csv.schema.json"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse schema json");
+    assert!(
+        output_schema_json["properties"]["id"]
+            .get("format")
+            .is_none()
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").arg("--detect-formats");
+    wrk.assert_success(&mut cmd);
+
+    let output_schema_string: String = wrk.from_str(&wrk.path("data.csv.schema.json"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse schema json");
+    let properties = &output_schema_json["properties"];
+
+    assert_eq!(properties["id"]["format"], "uuid-v4");
+    assert_eq!(properties["iban"]["format"], "iban");
+    // empty values are ignored
+    assert_eq!(properties["postcode"]["format"], "uk-postcode");
+    // not all values are E.164 phone numbers
+    assert!(properties["phone"].get("format").is_none());
+    assert!(properties["name"].get("format").is_none());
+
+    // the generated schema validates the original data
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("data.csv.schema.json");
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_merged_from_directory() {
+    let wrk = Workdir::new("generate_schema_merged_from_directory").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    std::fs::create_dir_all(wrk.path("daily")).unwrap();
+    wrk.create_from_string(
+        "daily/day1.csv",
+        "id,amount,status,code\n1,10,open,A1\n2,20,closed,B2\n",
+    );
+    wrk.create_from_string(
+        "daily/day2.csv",
+        "id,amount,status,code,note\n3,15.5,open,C3,hi\n4,30,pending,,there\n",
+    );
+    wrk.create_from_string(
+        "daily/day3.csv",
+        "id,amount,status\n5,7,open\n6,40,closed\n",
+    );
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("daily");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("Widened field 'amount' from integer to number, forced by "));
+    assert!(got.contains("day2.csv"));
+    assert!(got.contains("Field 'code' is optional. Missing from: "));
+    assert!(got.contains("Merged schema of 3 files."));
+
+    let output_schema_string: String = wrk.from_str(&wrk.path("daily.schema.json"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse schema json");
+    let properties = &output_schema_json["properties"];
+
+    assert_eq!(properties["id"]["type"], serde_json::json!(["integer"]));
+    assert_eq!(
+        properties["id"]["enum"],
+        serde_json::json!([1, 2, 3, 4, 5, 6])
+    );
+    assert_eq!(properties["id"]["minimum"], 1);
+    assert_eq!(properties["id"]["maximum"], 6);
+
+    // widened to number, so the integer enum of day1.csv is dropped
+    assert_eq!(properties["amount"]["type"], serde_json::json!(["number"]));
+    assert!(properties["amount"].get("enum").is_none());
+    assert_eq!(properties["amount"]["minimum"], 7);
+    assert_eq!(properties["amount"]["maximum"], 40);
+
+    assert_eq!(
+        properties["status"]["enum"],
+        serde_json::json!(["closed", "open", "pending"])
+    );
+    assert_eq!(properties["status"]["minLength"], 4);
+    assert_eq!(properties["status"]["maxLength"], 7);
+
+    assert_eq!(
+        properties["code"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert_eq!(
+        properties["code"]["enum"],
+        serde_json::json!([null, "A1", "B2", "C3"])
+    );
+    assert_eq!(properties["note"]["description"], "note column from daily");
+
+    // only the fields present in all files are required
+    assert_eq!(
+        output_schema_json["required"],
+        serde_json::json!(["id", "amount", "status"])
+    );
+
+    // the merged schema validates all the files
+    for file in ["daily/day1.csv", "daily/day2.csv", "daily/day3.csv"] {
+        let mut cmd = wrk.command("validate");
+        cmd.arg(file).arg("daily.schema.json");
+        wrk.assert_success(&mut cmd);
+    }
+}
+
+fn create_ddl_test_file(wrk: &Workdir) {
+    wrk.create_from_string(
+        "data.csv",
+        "id,unit name,price,created_date,flag
+1,alpha,1.5,2024-01-15,
+2,beta,22.25,2024-02-01,
+3,gamma,-3.125,2024-03-10,x
+",
+    );
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_postgresql_ddl() {
+    let wrk = Workdir::new("generate_schema_postgresql_ddl").flexible(true);
+    wrk.clear_contents().unwrap();
+    create_ddl_test_file(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "postgresql"]);
+    wrk.assert_success(&mut cmd);
+
+    let got = wrk.read_to_string("data.csv.postgresql.sql").unwrap();
+    let expected = r#"CREATE TABLE "data" (
+  "id" INTEGER NOT NULL,
+  "unit name" VARCHAR(5) NOT NULL,
+  "price" NUMERIC(5,3) NOT NULL,
+  "created_date" DATE NOT NULL,
+  "flag" VARCHAR(1)
+);
+"#;
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_sqlite_duckdb_bigquery_ddl() {
+    let wrk = Workdir::new("generate_schema_sqlite_duckdb_bigquery_ddl").flexible(true);
+    wrk.clear_contents().unwrap();
+    create_ddl_test_file(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv")
+        .args(["--format", "sqlite"])
+        .arg("--stdout");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"CREATE TABLE "data" (
+  "id" INTEGER NOT NULL,
+  "unit name" TEXT NOT NULL,
+  "price" REAL NOT NULL,
+  "created_date" TEXT NOT NULL,
+  "flag" TEXT
+);"#;
+    similar_asserts::assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv")
+        .args(["--format", "duckdb"])
+        .arg("--stdout");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"CREATE TABLE "data" (
+  "id" INTEGER NOT NULL,
+  "unit name" VARCHAR NOT NULL,
+  "price" DECIMAL(5,3) NOT NULL,
+  "created_date" DATE NOT NULL,
+  "flag" VARCHAR
+);"#;
+    similar_asserts::assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv")
+        .args(["--format", "bigquery"])
+        .arg("--stdout");
+    let got: String = wrk.stdout(&mut cmd);
+    let expected = r#"CREATE TABLE `data` (
+  `id` INT64 NOT NULL,
+  `unit name` STRING(5) NOT NULL,
+  `price` NUMERIC(5,3) NOT NULL,
+  `created_date` DATE NOT NULL,
+  `flag` STRING(1)
+);"#;
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_avro() {
+    let wrk = Workdir::new("generate_schema_avro").flexible(true);
+    wrk.clear_contents().unwrap();
+    create_ddl_test_file(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "avro"]);
+    wrk.assert_success(&mut cmd);
+
+    let output_schema_string: String = wrk.from_str(&wrk.path("data.csv.avsc"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse avro schema json");
+    let expected_schema_json: Value = serde_json::from_str(
+        r#"{
+  "type": "record",
+  "name": "data",
+  "fields": [
+    {"name": "id", "type": "int", "doc": "id column from data.csv"},
+    {"name": "unit_name", "type": "string", "doc": "unit name column from data.csv"},
+    {"name": "price", "type": "double", "doc": "price column from data.csv"},
+    {
+      "name": "created_date",
+      "type": {"type": "int", "logicalType": "date"},
+      "doc": "created_date column from data.csv"
+    },
+    {
+      "name": "flag",
+      "type": ["null", "string"],
+      "default": null,
+      "doc": "flag column from data.csv"
+    }
+  ]
+}"#,
+    )
+    .unwrap();
+    assert_json_eq!(expected_schema_json, output_schema_json);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_arrow() {
+    let wrk = Workdir::new("generate_schema_arrow").flexible(true);
+    wrk.clear_contents().unwrap();
+    create_ddl_test_file(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "arrow"]);
+    wrk.assert_success(&mut cmd);
+
+    let output_schema_string: String = wrk.from_str(&wrk.path("data.csv.arrow-schema.json"));
+    let output_schema_json: Value =
+        serde_json::from_str(&output_schema_string).expect("parse arrow schema json");
+    let expected_schema_json: Value = serde_json::from_str(
+        r#"{
+  "fields": [
+    {
+      "name": "id",
+      "nullable": false,
+      "type": {"name": "int", "isSigned": true, "bitWidth": 32},
+      "children": []
+    },
+    {"name": "unit name", "nullable": false, "type": {"name": "utf8"}, "children": []},
+    {
+      "name": "price",
+      "nullable": false,
+      "type": {"name": "floatingpoint", "precision": "DOUBLE"},
+      "children": []
+    },
+    {
+      "name": "created_date",
+      "nullable": false,
+      "type": {"name": "date", "unit": "DAY"},
+      "children": []
+    },
+    {"name": "flag", "nullable": true, "type": {"name": "utf8"}, "children": []}
+  ]
+}"#,
+    )
+    .unwrap();
+    assert_json_eq!(expected_schema_json, output_schema_json);
+}
+
+#[test]
+#[file_serial]
+#[cfg(feature = "polars")]
+fn generate_schema_polars() {
+    let wrk = Workdir::new("generate_schema_polars").flexible(true);
+    wrk.clear_contents().unwrap();
+    create_ddl_test_file(&wrk);
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("data.csv").args(["--format", "polars"]);
+    wrk.assert_success(&mut cmd);
+
+    // the schema is written where `sqlp --cache-schema` looks for it
+    assert!(Path::new(&wrk.path("data.pschema.json")).exists());
+
+    let mut cmd = wrk.command("sqlp");
+    cmd.arg("data.csv")
+        .arg("--cache-schema")
+        .arg("select id, price from data where id = 2");
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "price"], svec!["2", "22.25"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+#[file_serial]
+fn generate_schema_ddl_multiple_inputs_not_supported() {
+    let wrk = Workdir::new("generate_schema_ddl_multiple_inputs_not_supported").flexible(true);
+    wrk.clear_contents().unwrap();
+
+    std::fs::create_dir_all(wrk.path("daily")).unwrap();
+    wrk.create_from_string("daily/day1.csv", "id\n1\n");
+
+    let mut cmd = wrk.command("schema");
+    cmd.arg("daily").args(["--format", "postgresql"]);
+    wrk.assert_err(&mut cmd);
+}
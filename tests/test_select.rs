@@ -204,6 +204,179 @@ select_test_err!(select_err_regex_invalid, "/?/");
 select_test_err!(select_err_regex_empty, "//");
 select_test_err!(select_err_regex_triple_slash, "///");
 
+fn typed_data() -> Vec<Vec<String>> {
+    vec![
+        svec!["id", "name", "amount", "empty", "constant"],
+        svec!["1", "alpha", "1.5", "", "x"],
+        svec!["2", "beta", "2.25", "", "x"],
+        svec!["3", "gamma", "3", "", "x"],
+    ]
+}
+
+#[test]
+fn select_stats_type() {
+    let wrk = Workdir::new("select_stats_type");
+    wrk.create("data.csv", typed_data());
+    let mut cmd = wrk.command("select");
+    cmd.arg("type:Float|Integer").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount"],
+        svec!["1", "1.5"],
+        svec!["2", "2.25"],
+        svec!["3", "3"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_inverted() {
+    let wrk = Workdir::new("select_stats_inverted");
+    wrk.create("data.csv", typed_data());
+    let mut cmd = wrk.command("select");
+    cmd.arg("!type:String").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["id", "amount", "empty"],
+        svec!["1", "1.5", ""],
+        svec!["2", "2.25", ""],
+        svec!["3", "3", ""],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_nulls() {
+    let wrk = Workdir::new("select_stats_nulls");
+    wrk.create("data.csv", typed_data());
+    let mut cmd = wrk.command("select");
+    cmd.arg("name,nulls>0.5").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["name", "empty"],
+        svec!["alpha", ""],
+        svec!["beta", ""],
+        svec!["gamma", ""],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_cardinality() {
+    let wrk = Workdir::new("select_stats_cardinality");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "constant", "name"],
+            svec!["1", "x", "alpha"],
+            svec!["2", "x", "beta"],
+        ],
+    );
+    let mut cmd = wrk.command("select");
+    cmd.arg("!cardinality=1").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["id", "name"], svec!["1", "alpha"], svec!["2", "beta"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_date() {
+    let wrk = Workdir::new("select_stats_date");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "opened", "name"],
+            svec!["1", "2024-01-15", "alpha"],
+            svec!["2", "2024-02-01", "beta"],
+        ],
+    );
+    let mut cmd = wrk.command("select");
+    cmd.arg("type:Date").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["opened"], svec!["2024-01-15"], svec!["2024-02-01"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_date_stale_cache() {
+    let wrk = Workdir::new("select_stats_date_stale_cache");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "opened", "name"],
+            svec!["1", "2024-01-15", "alpha"],
+            svec!["2", "2024-02-01", "beta"],
+        ],
+    );
+    // create a stats cache without date inference first
+    let mut cmd = wrk.command("stats");
+    cmd.arg("--stats-jsonl").arg("data.csv");
+    wrk.run(&mut cmd);
+
+    let mut cmd = wrk.command("select");
+    cmd.arg("type:Date").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["opened"], svec!["2024-01-15"], svec!["2024-02-01"]];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_header_name() {
+    let wrk = Workdir::new("select_stats_header_name");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["type:id", "name", "cardinality=1"],
+            svec!["1", "alpha", "x"],
+            svec!["2", "beta", "x"],
+        ],
+    );
+    let mut cmd = wrk.command("select");
+    cmd.arg("cardinality=1,type:id").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["cardinality=1", "type:id"],
+        svec!["x", "1"],
+        svec!["x", "2"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn select_stats_nomatch_msg() {
+    let wrk = Workdir::new("select_stats_nomatch_msg");
+    wrk.create("data.csv", typed_data());
+    let mut cmd = wrk.command("select");
+    cmd.arg("type:date|datetime").arg("data.csv");
+
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(
+        got.contains("Stats selector 'type:Date|DateTime' does not match any columns"),
+        "{got}"
+    );
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn select_stats_stdin_err() {
+    let wrk = Workdir::new("select_stats_stdin_err");
+    let mut cmd = wrk.command("select");
+    cmd.arg("type:Integer").stdin(std::process::Stdio::null());
+
+    wrk.assert_err(&mut cmd);
+}
+
+select_test_err!(select_err_stats_invalid_type, "type:Number");
+select_test_err!(select_err_stats_invalid_value, "nullcount>abc");
+select_test_err!(select_err_stats_nomatch, "type:Date");
+
 fn unsorted_data(headers: bool) -> Vec<Vec<String>> {
     let mut rows = vec![
         svec![
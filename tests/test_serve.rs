@@ -0,0 +1,293 @@
+use std::{
+    io::{BufRead, BufReader},
+    process::{self, Stdio},
+};
+
+use crate::workdir::Workdir;
+
+/// a `qsv serve` process listening on a random port, killed when dropped
+struct TestServer {
+    child:    process::Child,
+    base_url: String,
+}
+
+impl TestServer {
+    fn start(wrk: &Workdir, options: &[&str]) -> Self {
+        let mut cmd = wrk.command("serve");
+        cmd.args(["--port", "0"])
+            .args(options)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let mut child = cmd.spawn().unwrap();
+
+        let stderr = child.stderr.take().unwrap();
+        let mut line = String::new();
+        BufReader::new(stderr).read_line(&mut line).unwrap();
+        let base_url = line
+            .trim()
+            .strip_prefix("qsv serve listening on ")
+            .unwrap_or_else(|| panic!("unexpected serve output: {line}"))
+            .to_string();
+        Self { child, base_url }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+const CITIES: &str = "name,state,population
+Buffalo,NY,278349
+Dallas,TX,1304379
+Fort Worth,TX,918915
+Manhattan,NY,1694251
+";
+
+#[test]
+fn serve_health() {
+    let wrk = Workdir::new("serve_health");
+    let server = TestServer::start(&wrk, &["--max-concurrent", "3"]);
+
+    let resp = reqwest::blocking::get(server.url("/health")).unwrap();
+    assert_eq!(resp.status(), 200);
+    let health: serde_json::Value = resp.json().unwrap();
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["running"], 0);
+    assert_eq!(health["max_concurrent"], 3);
+}
+
+#[test]
+fn serve_run_stdin() {
+    let wrk = Workdir::new("serve_run_stdin");
+    let server = TestServer::start(&wrk, &[]);
+
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/sort?arg=--select&arg=population&arg=--numeric"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    similar_asserts::assert_eq!(
+        resp.text().unwrap(),
+        "name,state,population
+Buffalo,NY,278349
+Fort Worth,TX,918915
+Dallas,TX,1304379
+Manhattan,NY,1694251
+"
+    );
+}
+
+#[test]
+fn serve_run_uploaded_file() {
+    let wrk = Workdir::new("serve_run_uploaded_file");
+    let server = TestServer::start(&wrk, &[]);
+
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/select?arg=name&arg=cities.csv&filename=cities.csv"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    similar_asserts::assert_eq!(
+        resp.text().unwrap(),
+        "name\nBuffalo\nDallas\nFort Worth\nManhattan\n"
+    );
+
+    let resp = client
+        .post(server.url("/run/count"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().unwrap().trim(), "4");
+}
+
+#[test]
+fn serve_command_error() {
+    let wrk = Workdir::new("serve_command_error");
+    let server = TestServer::start(&wrk, &[]);
+
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/select?arg=nonexistent_column"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 422);
+    let error: serde_json::Value = resp.json().unwrap();
+    assert!(error["error"]["message"].is_string());
+}
+
+#[test]
+fn serve_unknown_and_denied_commands() {
+    let wrk = Workdir::new("serve_unknown_and_denied_commands");
+    let server = TestServer::start(&wrk, &["--deny", "count,luau"]);
+
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/nonexistent"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+
+    let resp = client
+        .post(server.url("/run/count"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 403);
+    let error: serde_json::Value = resp.json().unwrap();
+    assert_eq!(error["error"]["status"], 403);
+
+    let resp = client
+        .post(server.url("/run/headers?bogus=1"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[test]
+fn serve_max_body_size() {
+    let wrk = Workdir::new("serve_max_body_size");
+    let server = TestServer::start(&wrk, &["--max-body-size", "1"]);
+
+    let big_body = "a,b\n".repeat(300_000);
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/count"))
+        .body(big_body)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 413);
+}
+
+#[test]
+fn serve_default_denied_commands() {
+    let wrk = Workdir::new("serve_default_denied_commands");
+    let server = TestServer::start(&wrk, &[]);
+
+    let client = reqwest::blocking::Client::new();
+    for command in [
+        "fetch",
+        "fetchpost",
+        "sqlp",
+        "to",
+        "describegpt",
+        "luau",
+        "geocode",
+        "sniff",
+        "validate",
+    ] {
+        let resp = client
+            .post(server.url(&format!("/run/{command}")))
+            .body(CITIES)
+            .send()
+            .unwrap();
+        // the command may not be compiled in, so it is either unknown or denied
+        assert!(
+            resp.status() == 403 || resp.status() == 404,
+            "{command}: {}",
+            resp.status()
+        );
+    }
+}
+
+#[test]
+fn serve_paths_outside_workspace() {
+    let wrk = Workdir::new("serve_paths_outside_workspace");
+    let server = TestServer::start(&wrk, &[]);
+
+    let client = reqwest::blocking::Client::new();
+    for query in [
+        "arg=name&arg=--output&arg=/tmp/qsv_serve_out.csv",
+        "arg=name&arg=--output=/tmp/qsv_serve_out.csv",
+        "arg=name&arg=../cities.csv",
+        "arg=name&arg=data/../../cities.csv",
+        "arg=name&arg=-o/tmp/qsv_serve_out.csv",
+        "arg=name&arg=-o../qsv_serve_out.csv",
+        "arg=name&arg=https://example.com/cities.csv",
+        "arg=name&arg=--output=https://example.com/out.csv",
+        "arg=name&filename=qsv.toml",
+        "arg=name&filename=QSV.TOML",
+    ] {
+        let resp = client
+            .post(server.url(&format!("/run/select?{query}")))
+            .body(CITIES)
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 400, "{query}");
+    }
+
+    let resp = client
+        .post(server.url("/run/select?arg=name&arg=--output&arg=out.csv"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
+#[test]
+fn serve_auth_token() {
+    let wrk = Workdir::new("serve_auth_token");
+
+    let mut cmd = wrk.command("serve");
+    cmd.args(["--bind", "0.0.0.0", "--port", "0"]);
+    wrk.assert_err(&mut cmd);
+
+    let server = TestServer::start(&wrk, &["--auth-token", "s3cret"]);
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/count"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+
+    let resp = client
+        .post(server.url("/run/count"))
+        .bearer_auth("wrong")
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+
+    let resp = client
+        .post(server.url("/run/count"))
+        .bearer_auth("s3cret")
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().unwrap().trim(), "4");
+}
+
+#[test]
+fn serve_ignores_qsv_toml() {
+    let wrk = Workdir::new("serve_ignores_qsv_toml");
+    let _ = wrk.create_subdir("workspaces");
+    // the workspaces are created in a subdirectory, so commands would find this qsv.toml
+    wrk.create_from_string("qsv.toml", "[commands.count]\nno-headers = true\n");
+    let workdir = wrk.path("workspaces");
+    let server = TestServer::start(&wrk, &["--workdir", workdir.to_str().unwrap()]);
+
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .post(server.url("/run/count"))
+        .body(CITIES)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.text().unwrap().trim(), "4");
+}
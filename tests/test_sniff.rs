@@ -262,6 +262,7 @@ Preamble Rows: 0
 Quote Char: none
 Flexible: false
 Is UTF8: true
+Encoding: UTF-8
 Detected Mime Type: text/plain
 Detected Kind: Other
 Retrieved Size (bytes): 27
@@ -568,3 +569,30 @@ fn sniff_consistent_results_issue_956() {
     cmd.arg(test_file);
     wrk.assert_err(&mut cmd);
 }
+
+#[test]
+fn sniff_multichar_delimiter() {
+    let wrk = Workdir::new("sniff_multichar_delimiter");
+    wrk.create_from_string(
+        "data.txt",
+        "id||name||city\n1||Alice||Boston\n2||Bob||Denver\n3||Carol||Austin\n",
+    );
+
+    let mut cmd = wrk.command("sniff");
+    cmd.arg("--json").arg("data.txt");
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert!(got.contains(r#""multichar_delimiter":"||""#));
+}
+
+#[test]
+fn sniff_no_multichar_delimiter() {
+    let wrk = Workdir::new("sniff_no_multichar_delimiter");
+    wrk.create_with_delim("data.txt", data(), b'|');
+
+    let mut cmd = wrk.command("sniff");
+    cmd.arg("--json").arg("data.txt");
+
+    let got: String = wrk.stdout(&mut cmd);
+    assert!(got.contains(r#""multichar_delimiter":null"#));
+}
@@ -3180,3 +3180,39 @@ fn sqlp_string_function_joins() {
 
 //     similar_asserts::assert_eq!(got_dot, expected_dot);
 // }
+
+#[test]
+fn sqlp_parquet_input_adapter() {
+    let wrk = Workdir::new("sqlp_parquet_input_adapter");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["letter", "number"],
+            svec!["alpha", "13"],
+            svec!["beta", "24"],
+            svec!["gamma", "37"],
+        ],
+    );
+
+    let mut cmd = wrk.command("sqlp");
+    cmd.arg("data.csv").arg("select * from data").args([
+        "--format",
+        "parquet",
+        "--output",
+        "data.parquet",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    // the parquet file is read as CSV by the other commands
+    let mut cmd = wrk.command("search");
+    cmd.args(["--select", "letter", "^[ab]"])
+        .arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["letter", "number"],
+        svec!["alpha", "13"],
+        svec!["beta", "24"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
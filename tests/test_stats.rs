@@ -2457,3 +2457,37 @@ fn stats_percentiles_single_value() {
 
     similar_asserts::assert_eq!(got, expected);
 }
+
+#[cfg(feature = "polars")]
+#[test]
+fn stats_parquet_input() {
+    let wrk = Workdir::new("stats_parquet_input");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["letter", "number"],
+            svec!["alpha", "13"],
+            svec!["beta", "24"],
+            svec!["gamma", "37"],
+        ],
+    );
+    let mut cmd = wrk.command("sqlp");
+    cmd.arg("data.csv").arg("select * from data").args([
+        "--format",
+        "parquet",
+        "--output",
+        "data.parquet",
+    ]);
+    wrk.assert_success(&mut cmd);
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("--typesonly").arg("data.parquet");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "type"],
+        svec!["letter", "String"],
+        svec!["number", "Integer"],
+    ];
+    similar_asserts::assert_eq!(got, expected);
+}
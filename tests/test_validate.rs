@@ -128,6 +128,101 @@ fn validate_good_csv_json() {
     similar_asserts::assert_eq!(got, expected);
 }
 
+#[test]
+fn validate_glob_multiple_files() {
+    let wrk = Workdir::new("validate_glob_multiple_files").flexible(true);
+    let _ = wrk.create_subdir("data");
+    wrk.create(
+        "data/good.csv",
+        vec![svec!["title", "name"], svec!["Professor", "Xaviers"]],
+    );
+    wrk.create(
+        "data/bad.csv",
+        vec![svec!["title", "name", "age"], svec!["Magneto", "90"]],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data/*.csv");
+    wrk.assert_err(&mut cmd);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("1 of 2 files failed validation."), "{got}");
+
+    let mut cmd = wrk.command("validate");
+    cmd.env("QSV_INPUT_EXCLUDE", "bad.csv").arg("data");
+    wrk.assert_success(&mut cmd);
+}
+
+#[test]
+fn validate_dir_filter_options() {
+    let wrk = Workdir::new("validate_dir_filter_options").flexible(true);
+    let _ = wrk.create_subdir("data");
+    let _ = wrk.create_subdir("data/sub");
+    wrk.create(
+        "data/good.csv",
+        vec![svec!["title", "name"], svec!["Professor", "Xaviers"]],
+    );
+    wrk.create(
+        "data/bad.csv",
+        vec![svec!["title", "name", "age"], svec!["Magneto", "90"]],
+    );
+    wrk.create(
+        "data/sub/good2.csv",
+        vec![svec!["title", "name"], svec!["Student", "Iceman"]],
+    );
+    // hidden files are skipped
+    wrk.create(
+        "data/.hidden.csv",
+        vec![svec!["title", "name", "age"], svec!["Magneto", "90"]],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.args(["--recursive", "--exclude", "bad.csv"])
+        .arg("data");
+    wrk.assert_success(&mut cmd);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("good2.csv"), "{got}");
+    assert!(got.contains("All 2 files are valid."), "{got}");
+
+    let mut cmd = wrk.command("validate");
+    cmd.args(["--include", "b*.csv"]).arg("data");
+    wrk.assert_err(&mut cmd);
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("1 of 1 files failed validation."), "{got}");
+}
+
+#[test]
+fn validate_bad_csv_json_error_format() {
+    let wrk = Workdir::new("validate_bad_csv_json_error_format").flexible(true);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["title", "name", "age"],
+            svec!["Professor", "Xaviers", "60"],
+            svec!["Magneto", "90",],
+            svec!["First Class Student", "Iceman", "14"],
+        ],
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.env("QSV_ERROR_FORMAT", "json").arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+    let got: serde_json::Value = serde_json::from_str(&wrk.output_stderr(&mut cmd)).unwrap();
+    let error = &got["error"];
+    assert_eq!(error["code"], "RFC4180_VALIDATION_FAILED");
+    assert_eq!(error["kind"], "other");
+    assert_eq!(error["command"], "validate");
+    assert_eq!(error["input"], "data.csv");
+    assert_eq!(error["row"], 2);
+    assert_eq!(error["line"], 3);
+    assert_eq!(error["exit_code"], 1);
+    assert!(
+        error["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Validation error: CSV error: record 2")
+    );
+}
+
 #[test]
 fn validate_bad_csv() {
     let wrk = Workdir::new("validate").flexible(true);
@@ -750,3 +845,1129 @@ fn validate_lite_dynenum_combinations() {
         );
     }
 }
+
+#[test]
+fn validate_with_table_schema() {
+    let wrk = Workdir::new("validate_with_table_schema").flexible(true);
+
+    let schema = r#"{
+        "fields": [
+            { "name": "id", "type": "integer", "constraints": { "required": true, "minimum": 1 } },
+            { "name": "name", "type": "string", "constraints": { "maxLength": 5 } },
+            { "name": "active", "type": "boolean" },
+            { "name": "grade", "type": "string",
+              "constraints": { "enum": ["A", "B", "C"], "pattern": "[A-Z]" } },
+            { "name": "joined", "type": "date" }
+        ],
+        "missingValues": ["", "NA"]
+    }"#;
+    wrk.create_from_string("schema.tableschema.json", schema);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "name", "active", "grade", "joined"],
+            svec!["1", "alpha", "true", "A", "2024-01-31"],
+            svec!["2", "NA", "False", "NA", ""],
+            svec!["0", "bravo", "yes", "B", "2024-13-01"],
+            svec!["NA", "charlie", "1", "D", "2024-03-01"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.tableschema.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected = vec![
+        svec!["id", "name", "active", "grade", "joined"],
+        svec!["0", "bravo", "yes", "B", "2024-13-01"],
+        svec!["NA", "charlie", "1", "D", "2024-03-01"],
+    ];
+    similar_asserts::assert_eq!(invalid_output, expected);
+
+    // check the row numbers & fields flagged in the error report
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let mut flagged: Vec<(String, String)> = validation_errors
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let mut cols = line.splitn(3, '\t');
+            (
+                cols.next().unwrap().to_string(),
+                cols.next().unwrap().to_string(),
+            )
+        })
+        .collect();
+    flagged.sort();
+    let expected = vec![
+        ("3".to_string(), "active".to_string()),
+        ("3".to_string(), "id".to_string()),
+        ("3".to_string(), "joined".to_string()),
+        ("4".to_string(), "grade".to_string()),
+        ("4".to_string(), "id".to_string()),
+        ("4".to_string(), "name".to_string()),
+    ];
+    similar_asserts::assert_eq!(flagged, expected);
+}
+
+#[test]
+fn validate_schema_subcmd_with_table_schema() {
+    let wrk = Workdir::new("validate_schema_subcmd_with_table_schema");
+
+    wrk.create_from_string(
+        "schema.tableschema.json",
+        r#"{ "fields": [ { "name": "id", "type": "integer" } ] }"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("schema").arg("schema.tableschema.json");
+    let got = wrk.output_stderr(&mut cmd);
+    assert_eq!(got, "Valid Table Schema.\n");
+    wrk.assert_success(&mut cmd);
+
+    wrk.create_from_string(
+        "bad.tableschema.json",
+        r#"{ "fields": [ { "type": "integer" } ] }"#,
+    );
+    let mut cmd = wrk.command("validate");
+    cmd.arg("schema").arg("bad.tableschema.json");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn validate_unique_and_primary_keys() {
+    let wrk = Workdir::new("validate_unique_and_primary_keys").flexible(true);
+
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "id": { "type": ["string", "null"] },
+            "date": { "type": ["string", "null"] },
+            "store": { "type": ["string", "null"] }
+        },
+        "uniqueKey": "id",
+        "primaryKey": ["date", "store"]
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "date", "store"],
+            svec!["1", "2024-01-01", "s1"],
+            svec!["2", "2024-01-01", "s2"],
+            svec!["", "2024-01-02", "s1"],
+            svec!["1", "2024-01-03", "s1"],
+            svec!["", "2024-01-01", "s2"],
+            svec!["4", "2024-01-04", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected = vec![
+        svec!["id", "date", "store"],
+        svec!["1", "2024-01-03", "s1"],
+        svec!["", "2024-01-01", "s2"],
+        svec!["4", "2024-01-04", ""],
+    ];
+    similar_asserts::assert_eq!(invalid_output, expected);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = r#"row_number	field	error
+4	id	duplicate uniqueKey value ["1"], first seen in row 1
+5	date,store	duplicate primaryKey value ["2024-01-01", "s2"], first seen in row 2
+6	store	primaryKey value is missing
+"#;
+    similar_asserts::assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_composite_key_values_dont_collide() {
+    let wrk = Workdir::new("validate_composite_key_values_dont_collide").flexible(true);
+
+    wrk.create_from_string(
+        "schema.json",
+        r#"{ "type": "object", "uniqueKey": ["a", "b"] }"#,
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["a", "b"],
+            svec!["x\u{1f}y", "z"],
+            svec!["x", "y\u{1f}z"],
+            svec!["1:a", "b"],
+            svec!["1", "a1:b"],
+            svec!["1:a", "b"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    // only the last row is a duplicate, whatever characters the key values have
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = r#"row_number	field	error
+5	a,b	duplicate uniqueKey value ["1:a", "b"], first seen in row 3
+"#;
+    similar_asserts::assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_unique_key_invalid_column() {
+    let wrk = Workdir::new("validate_unique_key_invalid_column").flexible(true);
+
+    wrk.create_from_string(
+        "schema.json",
+        r#"{ "type": "object", "properties": { "id": { "type": "string" } }, "uniqueKey": "nope" }"#,
+    );
+    wrk.create("data.csv", vec![svec!["id"], svec!["1"]]);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("'uniqueKey' column 'nope' not found in the CSV."));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn validate_table_schema_unique_and_primary_key() {
+    let wrk = Workdir::new("validate_table_schema_unique_and_primary_key").flexible(true);
+
+    let schema = r#"{
+        "fields": [
+            { "name": "id", "type": "integer" },
+            { "name": "email", "type": "string", "constraints": { "unique": true } }
+        ],
+        "primaryKey": "id"
+    }"#;
+    wrk.create_from_string("schema.tableschema.json", schema);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "email"],
+            svec!["1", "a@example.com"],
+            svec!["2", "b@example.com"],
+            svec!["2", "a@example.com"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.tableschema.json");
+    wrk.assert_err(&mut cmd);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = r#"row_number	field	error
+3	id	duplicate primaryKey value ["2"], first seen in row 2
+3	email	duplicate uniqueKey value ["a@example.com"], first seen in row 1
+"#;
+    similar_asserts::assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_unique_key_many_duplicates() {
+    let wrk = Workdir::new("validate_unique_key_many_duplicates").flexible(true);
+
+    wrk.create_from_string(
+        "schema.json",
+        r#"{ "type": "object", "properties": { "id": { "type": "string" } }, "uniqueKey": "id" }"#,
+    );
+    // 10,005 duplicates of the first row's id
+    let mut rows = vec![svec!["id"]];
+    rows.extend((0..10_006).map(|_| svec!["1"]));
+    wrk.create("data.csv", rows);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "json"])
+        .args(["--report-examples", "2"]);
+    wrk.assert_err(&mut cmd);
+
+    // the report counts all the duplicates, and only has examples of the reported ones
+    let report: serde_json::Value = serde_json::from_str(
+        &wrk.read_to_string("data.csv.validation-report.json")
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["errors"], 10_005);
+    let group = &report["error_groups"][0];
+    assert_eq!(group["count"], 10_005);
+    assert_eq!(group["unreported"], 5);
+    assert_eq!(
+        group["examples"],
+        serde_json::json!([
+            {"row": 2, "error": "duplicate uniqueKey value [\"1\"], first seen in row 1"},
+            {"row": 3, "error": "duplicate uniqueKey value [\"1\"], first seen in row 1"}
+        ])
+    );
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let lines: Vec<&str> = validation_errors.lines().collect();
+    // the header, the 10,000 reported duplicates & the count of the others
+    assert_eq!(lines.len(), 10_002);
+    assert_eq!(
+        lines[1],
+        r#"2	id	duplicate uniqueKey value ["1"], first seen in row 1"#
+    );
+    assert_eq!(
+        lines[10_001],
+        "\tid\t5 more rows with a duplicate uniqueKey value, not reported individually"
+    );
+
+    // all the duplicates are invalid, reported individually or not
+    let invalid_records: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    assert_eq!(invalid_records.len(), 10_005);
+}
+
+fn foreign_key_schema(sorted: bool) -> String {
+    format!(
+        r#"{{
+        "type": "object",
+        "properties": {{
+            "store": {{ "type": ["string", "null"] }},
+            "region": {{ "type": ["string", "null"] }},
+            "amount": {{ "type": "string" }}
+        }},
+        "foreignKeys": [
+            {{ "fields": ["store", "region"],
+               "reference": {{ "resource": "stores.csv", "fields": ["id", "region"],
+                              "sorted": {sorted} }} }}
+        ]
+    }}"#
+    )
+}
+
+fn foreign_key_data(wrk: &Workdir) {
+    wrk.create(
+        "stores.csv",
+        vec![
+            svec!["id", "region", "name"],
+            svec!["s1", "east", "Main St"],
+            svec!["s1", "west", "Harbor"],
+            svec!["s2", "east", "Mall"],
+            svec!["s3", "north", "Airport"],
+        ],
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["store", "region", "amount"],
+            svec!["s1", "east", "10"],
+            svec!["s2", "west", "20"],
+            svec!["s3", "north", "30"],
+            svec!["s9", "east", "40"],
+            svec!["", "east", "50"],
+            svec!["s1", "west", "60"],
+        ],
+    );
+}
+
+#[test]
+fn validate_foreign_keys() {
+    let wrk = Workdir::new("validate_foreign_keys").flexible(true);
+    foreign_key_data(&wrk);
+    wrk.create_from_string("schema.json", &foreign_key_schema(false));
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected = vec![
+        svec!["store", "region", "amount"],
+        svec!["s2", "west", "20"],
+        svec!["s9", "east", "40"],
+    ];
+    similar_asserts::assert_eq!(invalid_output, expected);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = r#"row_number	field	error
+2	store,region	foreign key value ["s2", "west"] not found in stores.csv (id,region)
+4	store,region	foreign key value ["s9", "east"] not found in stores.csv (id,region)
+"#;
+    similar_asserts::assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_foreign_keys_sorted_reference() {
+    let wrk = Workdir::new("validate_foreign_keys_sorted_reference").flexible(true);
+    foreign_key_data(&wrk);
+    wrk.create_from_string("schema.json", &foreign_key_schema(true));
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    // the sorted reference file is indexed for binary search
+    assert!(std::path::Path::new(&wrk.path("stores.csv.idx")).exists());
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = r#"row_number	field	error
+2	store,region	foreign key value ["s2", "west"] not found in stores.csv (id,region)
+4	store,region	foreign key value ["s9", "east"] not found in stores.csv (id,region)
+"#;
+    similar_asserts::assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_foreign_keys_unsorted_reference() {
+    let wrk = Workdir::new("validate_foreign_keys_unsorted_reference").flexible(true);
+    foreign_key_data(&wrk);
+    wrk.create(
+        "stores.csv",
+        vec![
+            svec!["id", "region", "name"],
+            svec!["s1", "east", "Main St"],
+            svec!["s2", "east", "Mall"],
+            svec!["s1", "west", "Harbor"],
+        ],
+    );
+    wrk.create_from_string("schema.json", &foreign_key_schema(true));
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains(
+        "'foreignKeys' reference file stores.csv is not sorted on id,region (row 3 is out of \
+         order)."
+    ));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn validate_foreign_keys_trim_reference() {
+    let wrk = Workdir::new("validate_foreign_keys_trim_reference").flexible(true);
+    wrk.create("stores.csv", vec![svec!["id"], svec![" s1"], svec!["s2 "]]);
+    wrk.create(
+        "data.csv",
+        vec![svec!["store"], svec!["s1"], svec!["s2"], svec!["s3"]],
+    );
+
+    for sorted in [false, true] {
+        wrk.create_from_string(
+            "schema.json",
+            &format!(
+                r#"{{
+                "type": "object",
+                "foreignKeys": [
+                    {{ "fields": "store",
+                       "reference": {{ "resource": "stores.csv", "fields": "id",
+                                      "sorted": {sorted} }} }}
+                ]
+            }}"#
+            ),
+        );
+
+        // the reference values are trimmed like the input values
+        let mut cmd = wrk.command("validate");
+        cmd.arg("data.csv").arg("schema.json").arg("--trim");
+        wrk.assert_err(&mut cmd);
+
+        let validation_errors = wrk
+            .read_to_string("data.csv.validation-errors.tsv")
+            .unwrap();
+        let expected = r#"row_number	field	error
+3	store	foreign key value ["s3"] not found in stores.csv (id)
+"#;
+        similar_asserts::assert_eq!(validation_errors, expected);
+    }
+}
+
+#[test]
+fn validate_foreign_keys_self_reference() {
+    let wrk = Workdir::new("validate_foreign_keys_self_reference").flexible(true);
+
+    wrk.create_from_string(
+        "schema.json",
+        r#"{
+        "type": "object",
+        "properties": {},
+        "foreignKeys": [
+            { "fields": "manager", "reference": { "resource": "", "fields": "id" } }
+        ]
+    }"#,
+    );
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "manager"],
+            svec!["1", ""],
+            svec!["2", "1"],
+            svec!["3", "7"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let expected = r#"row_number	field	error
+3	manager	foreign key value ["7"] not found in data.csv (id)
+"#;
+    similar_asserts::assert_eq!(validation_errors, expected);
+}
+
+#[test]
+fn validate_row_rules() {
+    let wrk = Workdir::new("validate_row_rules").flexible(true);
+
+    let schema = r#"{
+        "type": "object",
+        "properties": {
+            "status": { "type": "string", "enum": ["open", "closed"] }
+        },
+        "rowRules": [
+            { "name": "closed_date_set",
+              "if": "status == 'closed'",
+              "assert": "!empty(closed_date) && closed_date >= opened_date" },
+            { "name": "total_matches",
+              "assert": "qty * price == total",
+              "message": "total is not qty * price" }
+        ]
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec![
+                "status",
+                "opened_date",
+                "closed_date",
+                "qty",
+                "price",
+                "total"
+            ],
+            svec!["closed", "2024-01-05", "2024-01-10", "2", "5", "10"],
+            svec!["open", "2024-01-05", "", "3", "1.5", "4.5"],
+            svec!["closed", "2024-01-05", "", "1", "1", "1"],
+            svec!["closed", "2024-01-05", "2024-01-01", "10", "2", "2"],
+            svec!["pending", "2024-01-05", "", "1", "1", "1"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let invalid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected = vec![
+        svec![
+            "status",
+            "opened_date",
+            "closed_date",
+            "qty",
+            "price",
+            "total"
+        ],
+        svec!["closed", "2024-01-05", "", "1", "1", "1"],
+        svec!["closed", "2024-01-05", "2024-01-01", "10", "2", "2"],
+        svec!["pending", "2024-01-05", "", "1", "1", "1"],
+    ];
+    similar_asserts::assert_eq!(invalid_output, expected);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let rule_errors: Vec<&str> = validation_errors
+        .lines()
+        .filter(|line| !line.contains("\tstatus\t"))
+        .collect();
+    let expected = vec![
+        "row_number\tfield\terror",
+        "3\tclosed_date_set\trow rule failed: !empty(closed_date) && closed_date >= opened_date",
+        "4\tclosed_date_set\trow rule failed: !empty(closed_date) && closed_date >= opened_date",
+        "4\ttotal_matches\ttotal is not qty * price",
+    ];
+    similar_asserts::assert_eq!(rule_errors, expected);
+    // the enum error for "pending" is still reported
+    assert!(validation_errors.contains("5\tstatus\t"));
+}
+
+#[test]
+fn validate_row_rules_invalid_expression() {
+    let wrk = Workdir::new("validate_row_rules_invalid_expression").flexible(true);
+
+    wrk.create_from_string(
+        "schema.json",
+        r#"{ "type": "object", "properties": {},
+             "rowRules": [{ "name": "bad", "assert": "missing_col > 1" }] }"#,
+    );
+    wrk.create("data.csv", vec![svec!["a"], svec!["1"]]);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    let got = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("'rowRules' rule 'bad': 'assert' unknown column 'missing_col'"));
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn validate_custom_formats() {
+    let wrk = Workdir::new("validate_custom_formats").flexible(true);
+
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "iban": { "type": "string", "format": "iban" },
+            "card": { "type": "string", "format": "credit-card" },
+            "phone": { "type": "string", "format": "e164" },
+            "country": { "type": "string", "format": "iso3166-1-alpha3" },
+            "region": { "type": "string", "format": "iso3166-2" },
+            "currency": { "type": "string", "format": "iso4217" },
+            "zip": { "type": "string", "format": "us-zip" },
+            "postcode": { "type": "string", "format": "uk-postcode" },
+            "id": { "type": "string", "format": "uuid-v4" },
+            "lat": { "type": "string", "format": "latitude" },
+            "isbn": { "type": "string", "format": "isbn" },
+            "issn": { "type": "string", "format": "issn" }
+        }
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec![
+                "iban", "card", "phone", "country", "region", "currency", "zip", "postcode", "id",
+                "lat", "isbn", "issn"
+            ],
+            svec![
+                "GB82 WEST 1234 5698 7654 32",
+                "4111-1111-1111-1111",
+                "+14155552671",
+                "PHL",
+                "US-NY",
+                "EUR",
+                "10001-1234",
+                "SW1A 1AA",
+                "f47ac10b-58cc-4372-a567-0e02b2c3d479",
+                "40.7128",
+                "978-0-306-40615-7",
+                "0317-8471"
+            ],
+            svec![
+                "GB82WEST12345698765431",
+                "4111111111111112",
+                "01903 221471",
+                "PHX",
+                "XX-NY",
+                "DOG",
+                "1000",
+                "10001",
+                "01890a5d-ac96-774b-bcce-b302099a8057",
+                "95.5",
+                "9780306406158",
+                "0317-8472"
+            ],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    let mut errors: Vec<&str> = validation_errors.lines().skip(1).collect();
+    errors.sort_unstable();
+    let expected = vec![
+        "2\tcard\t\"4111111111111112\" is not a \"credit-card\"",
+        "2\tcountry\t\"PHX\" is not a \"iso3166-1-alpha3\"",
+        "2\tcurrency\t\"DOG\" is not a \"iso4217\"",
+        "2\tiban\t\"GB82WEST12345698765431\" is not a \"iban\"",
+        "2\tid\t\"01890a5d-ac96-774b-bcce-b302099a8057\" is not a \"uuid-v4\"",
+        "2\tisbn\t\"9780306406158\" is not a \"isbn\"",
+        "2\tissn\t\"0317-8472\" is not a \"issn\"",
+        "2\tlat\t\"95.5\" is not a \"latitude\"",
+        "2\tphone\t\"01903 221471\" is not a \"e164\"",
+        "2\tpostcode\t\"10001\" is not a \"uk-postcode\"",
+        "2\tregion\t\"XX-NY\" is not a \"iso3166-2\"",
+        "2\tzip\t\"1000\" is not a \"us-zip\"",
+    ];
+    similar_asserts::assert_eq!(errors, expected);
+
+    let invalid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    assert_eq!(invalid_output.len(), 2);
+}
+
+fn create_report_test_files(wrk: &Workdir) {
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 2 },
+            "age": { "type": "integer", "minimum": 0 }
+        },
+        "rowRules": [
+            { "name": "adult_name", "if": "age >= 18", "assert": "len(name) > 2",
+              "message": "adults need a longer name" }
+        ]
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "age"],
+            svec!["Xaviers", "60"],
+            svec!["X", "-1"],
+            svec!["Y", "-2"],
+            svec!["Al", "30"],
+            svec!["Z", "10"],
+        ],
+    );
+}
+
+#[test]
+fn validate_report_format_json() {
+    let wrk = Workdir::new("validate_report_format_json").flexible(true);
+    create_report_test_files(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "json"])
+        .args(["--report-examples", "2"]);
+    wrk.assert_err(&mut cmd);
+
+    let report: serde_json::Value = serde_json::from_str(
+        &wrk.read_to_string("data.csv.validation-report.json")
+            .unwrap(),
+    )
+    .unwrap();
+    let expected = serde_json::json!({
+        "input": "data.csv",
+        "schema": "schema.json",
+        "verdict": "fail",
+        "records": 5,
+        "invalid_records": 4,
+        "errors": 6,
+        "error_groups": [
+            {
+                "field": "name",
+                "keyword": "minLength",
+                "count": 3,
+                "unreported": 0,
+                "examples": [
+                    {"row": 2, "error": "\"X\" is shorter than 2 characters"},
+                    {"row": 3, "error": "\"Y\" is shorter than 2 characters"}
+                ]
+            },
+            {
+                "field": "age",
+                "keyword": "minimum",
+                "count": 2,
+                "unreported": 0,
+                "examples": [
+                    {"row": 2, "error": "-1 is less than the minimum of 0"},
+                    {"row": 3, "error": "-2 is less than the minimum of 0"}
+                ]
+            },
+            {
+                "field": "adult_name",
+                "keyword": "rowRules",
+                "count": 1,
+                "unreported": 0,
+                "examples": [{"row": 4, "error": "adults need a longer name"}]
+            }
+        ]
+    });
+    similar_asserts::assert_eq!(report, expected);
+
+    // the error report is unchanged
+    let validation_errors = wrk
+        .read_to_string("data.csv.validation-errors.tsv")
+        .unwrap();
+    assert!(validation_errors.contains("4\tadult_name\tadults need a longer name\n"));
+    assert!(!validation_errors.contains("rowRules"));
+}
+
+#[test]
+fn validate_report_format_json_all_valid() {
+    let wrk = Workdir::new("validate_report_format_json_all_valid").flexible(true);
+    create_report_test_files(&wrk);
+    wrk.create(
+        "data.csv",
+        vec![svec!["name", "age"], svec!["Xaviers", "60"]],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "json"]);
+    wrk.assert_success(&mut cmd);
+
+    let report: serde_json::Value = serde_json::from_str(
+        &wrk.read_to_string("data.csv.validation-report.json")
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["verdict"], "pass");
+    assert_eq!(report["errors"], 0);
+    assert_eq!(report["error_groups"], serde_json::json!([]));
+}
+
+#[test]
+fn validate_report_format_junit() {
+    let wrk = Workdir::new("validate_report_format_junit").flexible(true);
+    create_report_test_files(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "junit"])
+        .args(["--report-examples", "1"]);
+    wrk.assert_err(&mut cmd);
+
+    let got = wrk
+        .read_to_string("data.csv.validation-report.xml")
+        .unwrap();
+    let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="qsv validate" tests="3" failures="3">
+  <testsuite name="data.csv" tests="3" failures="3" errors="0" skipped="0">
+    <properties>
+      <property name="schema" value="schema.json"/>
+      <property name="verdict" value="fail"/>
+      <property name="records" value="5"/>
+      <property name="invalid_records" value="4"/>
+      <property name="errors" value="6"/>
+    </properties>
+    <testcase classname="data.csv" name="name">
+      <failure type="minLength" message="3 errors: minLength (3)">row 2: &quot;X&quot; is shorter than 2 characters</failure>
+    </testcase>
+    <testcase classname="data.csv" name="age">
+      <failure type="minimum" message="2 errors: minimum (2)">row 2: -1 is less than the minimum of 0</failure>
+    </testcase>
+    <testcase classname="data.csv" name="adult_name">
+      <failure type="rowRules" message="1 error: rowRules (1)">row 4: adults need a longer name</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+    similar_asserts::assert_eq!(got, expected);
+}
+
+#[test]
+fn validate_report_format_sarif() {
+    let wrk = Workdir::new("validate_report_format_sarif").flexible(true);
+    create_report_test_files(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "sarif"])
+        .args(["--report-examples", "1"]);
+    wrk.assert_err(&mut cmd);
+
+    let report: serde_json::Value = serde_json::from_str(
+        &wrk.read_to_string("data.csv.validation-report.sarif")
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["version"], "2.1.0");
+    let run = &report["runs"][0];
+    let rule_ids: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(rule_ids, vec!["minLength", "minimum", "rowRules"]);
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[2]["ruleId"], "rowRules");
+    assert_eq!(results[2]["ruleIndex"], 2);
+    assert_eq!(
+        results[2]["message"]["text"],
+        "adult_name: adults need a longer name"
+    );
+    assert_eq!(
+        results[2]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "data.csv"
+    );
+    assert!(results[2]["locations"][0]["physicalLocation"]["region"].is_null());
+    assert_eq!(results[2]["properties"]["row"], 4);
+    assert_eq!(run["properties"]["verdict"], "fail");
+    assert_eq!(run["properties"]["errors"], 6);
+}
+
+#[test]
+fn validate_report_format_invalid() {
+    let wrk = Workdir::new("validate_report_format_invalid").flexible(true);
+    create_report_test_files(&wrk);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .args(["--report-format", "html"]);
+    wrk.assert_err(&mut cmd);
+
+    // a report needs a schema
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").args(["--report-format", "json"]);
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn validate_coerce() {
+    let wrk = Workdir::new("validate_coerce").flexible(true);
+
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer" },
+            "qty": { "type": "integer", "minimum": 0 },
+            "price": { "type": "number" },
+            "opened": { "type": "string", "format": "date" },
+            "status": { "type": "string", "enum": ["open", "closed"] }
+        }
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "qty", "price", "opened", "status"],
+            svec!["1", "10", "2.5", "2024-01-15", "open"],
+            svec!["2", "1,234", " 5.5", "01/15/2024", "OPEN"],
+            svec!["3", "1,000", "1,234.75", "2024-01-15", "Closed "],
+            svec!["4", "abc", "1", "2024-01-15", "open"],
+            svec!["5", "1,000", "1", "2024-01-15", "pending"],
+        ],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json").arg("--coerce");
+    wrk.assert_err(&mut cmd);
+
+    // coerced rows are written with their fixed values
+    let valid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.valid");
+    let expected = vec![
+        svec!["id", "qty", "price", "opened", "status"],
+        svec!["1", "10", "2.5", "2024-01-15", "open"],
+        svec!["2", "1234", "5.5", "2024-01-15", "open"],
+        svec!["3", "1000", "1234.75", "2024-01-15", "closed"],
+    ];
+    similar_asserts::assert_eq!(valid_output, expected);
+
+    // rows that can't be fully fixed keep their original values
+    let invalid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.invalid");
+    let expected = vec![
+        svec!["id", "qty", "price", "opened", "status"],
+        svec!["4", "abc", "1", "2024-01-15", "open"],
+        svec!["5", "1,000", "1", "2024-01-15", "pending"],
+    ];
+    similar_asserts::assert_eq!(invalid_output, expected);
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .from_path(wrk.path("data.csv.coercions.tsv"))
+        .unwrap();
+    let coercions: Vec<Vec<String>> = rdr
+        .records()
+        .map(|r| r.unwrap().iter().map(str::to_string).collect())
+        .collect();
+    let expected = vec![
+        svec!["row_number", "field", "old_value", "new_value"],
+        svec!["2", "qty", "1,234", "1234"],
+        svec!["2", "price", " 5.5", "5.5"],
+        svec!["2", "opened", "01/15/2024", "2024-01-15"],
+        svec!["2", "status", "OPEN", "open"],
+        svec!["3", "qty", "1,000", "1000"],
+        svec!["3", "price", "1,234.75", "1234.75"],
+        svec!["3", "status", "Closed ", "closed"],
+    ];
+    similar_asserts::assert_eq!(coercions, expected);
+}
+
+#[test]
+fn validate_coerce_all_fixed() {
+    let wrk = Workdir::new("validate_coerce_all_fixed").flexible(true);
+
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "amount": { "type": "number" }
+        }
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+    wrk.create(
+        "data.csv",
+        vec![svec!["amount"], svec!["1,500.25"], svec!["3"]],
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv").arg("schema.json").arg("--coerce");
+    wrk.assert_success(&mut cmd);
+
+    let valid_output: Vec<Vec<String>> = wrk.read_csv("data.csv.valid");
+    let expected = vec![svec!["amount"], svec!["1500.25"], svec!["3"]];
+    similar_asserts::assert_eq!(valid_output, expected);
+    assert!(!std::path::Path::new(&wrk.path("data.csv.invalid")).exists());
+    assert!(!std::path::Path::new(&wrk.path("data.csv.validation-errors.tsv")).exists());
+
+    // the logged coercions are also re-applied to the --valid-output
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.csv")
+        .arg("schema.json")
+        .arg("--coerce")
+        .args(["--valid-output", "-"]);
+    let output = wrk.output(&mut cmd);
+    similar_asserts::assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "amount\n1500.25\n3\n"
+    );
+}
+
+#[test]
+fn validate_jsonl_nested() {
+    let wrk = Workdir::new("validate_jsonl_nested").flexible(true);
+
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "address": {
+                "type": "object",
+                "properties": {
+                    "city": { "type": "string" },
+                    "zip": { "type": "string", "format": "us-zip" }
+                },
+                "required": ["city"]
+            },
+            "scores": { "type": "array", "items": { "type": "integer", "minimum": 0 } }
+        },
+        "required": ["name"]
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+
+    let data = r#"{"name": "Alice", "address": {"city": "Boston", "zip": "02134"}, "scores": [1, 2]}
+{"name": "Bob", "address": {"city": 42, "zip": "02134"}, "scores": [3]}
+
+{"name": "Carol", "address": {"city": "Austin"}, "scores": [5, -1]}
+{"name": "Dan", "address": {"city": "Miami", "zip": "33101"}}
+not json
+"#;
+    wrk.create_from_string("data.jsonl", data);
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.jsonl").arg("schema.json");
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.output_stderr(&mut cmd);
+    assert!(got.ends_with("3 out of 5 records invalid.\n"));
+
+    let validation_errors = wrk
+        .read_to_string("data.jsonl.validation-errors.tsv")
+        .unwrap();
+    let mut lines = validation_errors.lines();
+    assert_eq!(lines.next(), Some("row_number\tfield\terror"));
+    assert_eq!(
+        lines.next(),
+        Some("2\taddress/city\t42 is not of type \"string\"")
+    );
+    assert_eq!(
+        lines.next(),
+        Some("4\tscores/1\t-1 is less than the minimum of 0")
+    );
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with("6\t<RECORD>\tinvalid JSON: ")
+    );
+    assert_eq!(lines.next(), None);
+
+    let valid_output = wrk.read_to_string("data.jsonl.valid").unwrap();
+    similar_asserts::assert_eq!(
+        valid_output,
+        r#"{"name": "Alice", "address": {"city": "Boston", "zip": "02134"}, "scores": [1, 2]}
+{"name": "Dan", "address": {"city": "Miami", "zip": "33101"}}
+"#
+    );
+
+    let invalid_output = wrk.read_to_string("data.jsonl.invalid").unwrap();
+    similar_asserts::assert_eq!(
+        invalid_output,
+        r#"{"name": "Bob", "address": {"city": 42, "zip": "02134"}, "scores": [3]}
+{"name": "Carol", "address": {"city": "Austin"}, "scores": [5, -1]}
+not json
+"#
+    );
+}
+
+#[test]
+fn validate_jsonl_all_valid() {
+    let wrk = Workdir::new("validate_jsonl_all_valid").flexible(true);
+
+    let schema = r#"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer" },
+            "tags": { "type": "array", "items": { "type": "string" } }
+        }
+    }"#;
+    wrk.create_from_string("schema.json", schema);
+    wrk.create_from_string(
+        "data.jsonl",
+        "{\"id\": 1, \"tags\": [\"a\", \"b\"]}\n{\"id\": 2, \"tags\": []}\n",
+    );
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.jsonl").arg("schema.json");
+    wrk.assert_success(&mut cmd);
+
+    assert!(!std::path::Path::new(&wrk.path("data.jsonl.valid")).exists());
+    assert!(!std::path::Path::new(&wrk.path("data.jsonl.invalid")).exists());
+    assert!(!std::path::Path::new(&wrk.path("data.jsonl.validation-errors.tsv")).exists());
+}
+
+#[test]
+fn validate_jsonl_coerce_not_supported() {
+    let wrk = Workdir::new("validate_jsonl_coerce_not_supported").flexible(true);
+
+    wrk.create_from_string(
+        "schema.json",
+        r#"{"type": "object", "properties": {"id": {"type": "integer"}}}"#,
+    );
+    wrk.create_from_string("data.jsonl", "{\"id\": 1}\n");
+
+    let mut cmd = wrk.command("validate");
+    cmd.arg("data.jsonl").arg("schema.json").arg("--coerce");
+    wrk.assert_err(&mut cmd);
+
+    let got: String = wrk.output_stderr(&mut cmd);
+    assert!(got.contains("--coerce is not supported for JSONL, Parquet & Arrow inputs."));
+}
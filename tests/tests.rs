@@ -32,6 +32,8 @@ macro_rules! rassert_eq {
 mod workdir;
 
 mod test_100;
+#[cfg(feature = "lib")]
+mod test_api;
 #[cfg(feature = "apply")]
 mod test_apply;
 #[cfg(feature = "datapusher_plus")]
@@ -48,6 +50,7 @@ mod test_cat;
 mod test_clipboard;
 mod test_combos;
 mod test_comments;
+mod test_completions;
 mod test_count;
 mod test_datefmt;
 mod test_dedup;
@@ -57,6 +60,8 @@ mod test_diff;
 mod test_edit;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_enumerate;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_events;
 mod test_excel;
 mod test_exclude;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
@@ -69,6 +74,8 @@ mod test_fetch;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_fill;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_fixedwidth;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_fixlengths;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_flatten;
@@ -94,14 +101,21 @@ mod test_jsonl;
 mod test_luau;
 #[cfg(any(feature = "feature_capable", feature = "lite"))]
 mod test_partition;
+#[cfg(feature = "feature_capable")]
+mod test_pipeline;
 #[cfg(feature = "polars")]
 mod test_pivotp;
+mod test_projectconfig;
 #[cfg(feature = "prompt")]
 mod test_prompt;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_prune;
 mod test_pseudo;
 #[cfg(feature = "python")]
 mod test_py;
 mod test_rename;
+#[cfg(any(feature = "feature_capable", feature = "lite"))]
+mod test_repair;
 mod test_replace;
 mod test_reverse;
 mod test_safenames;
@@ -111,6 +125,8 @@ mod test_schema;
 mod test_search;
 mod test_searchset;
 mod test_select;
+#[cfg(feature = "serve")]
+mod test_serve;
 mod test_slice;
 mod test_snappy;
 mod test_sniff;